@@ -0,0 +1,46 @@
+//! Búsqueda de los archivos `.um` sobre los que trabajan `umbral fmt` y
+//! `umbral lint`.
+
+use std::fs;
+use std::path::{Path, PathBuf};
+use umbral_interpreter::paquetes::DIRECTORIO_MODULOS;
+
+/// Expande las rutas (por defecto, el directorio actual): los archivos se
+/// toman tal cual y los directorios se recorren en busca de `.um`.
+pub fn expandir(rutas: Vec<PathBuf>) -> Vec<PathBuf> {
+    let rutas = match rutas.is_empty() {
+        true => vec![PathBuf::from(".")],
+        false => rutas,
+    };
+
+    let mut archivos = Vec::new();
+    for ruta in rutas {
+        if ruta.is_dir() {
+            buscar_archivos(&ruta, &mut archivos);
+        } else {
+            archivos.push(ruta);
+        }
+    }
+    archivos
+}
+
+// Recorre el directorio en orden, sin entrar en directorios ocultos, en
+// `target` ni en las dependencias instaladas.
+fn buscar_archivos(directorio: &Path, archivos: &mut Vec<PathBuf>) {
+    let Ok(entradas) = fs::read_dir(directorio) else {
+        return;
+    };
+    let mut rutas: Vec<PathBuf> = entradas.flatten().map(|entrada| entrada.path()).collect();
+    rutas.sort();
+
+    for ruta in rutas {
+        let nombre = ruta.file_name().and_then(|n| n.to_str()).unwrap_or_default();
+        if ruta.is_dir() {
+            if !nombre.starts_with('.') && nombre != "target" && nombre != DIRECTORIO_MODULOS {
+                buscar_archivos(&ruta, archivos);
+            }
+        } else if ruta.extension().is_some_and(|extension| extension == "um") {
+            archivos.push(ruta);
+        }
+    }
+}
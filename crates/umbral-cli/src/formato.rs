@@ -0,0 +1,88 @@
+//! `umbral fmt`: formatea los archivos `.um` en su sitio o, con `--check`,
+//! lista los que no están formateados sin tocarlos.
+
+use crate::archivos;
+use std::fs;
+use std::io::{self, Read};
+use std::path::PathBuf;
+use std::process;
+use umbral_fmt::formatear;
+
+pub fn ejecutar(argumentos: &[String]) {
+    let mut comprobar = false;
+    let mut rutas = Vec::new();
+    for argumento in argumentos {
+        match argumento.as_str() {
+            "--check" => comprobar = true,
+            "-" => {
+                formatear_entrada_estandar();
+                return;
+            }
+            opcion if opcion.starts_with('-') => {
+                eprintln!("Opción desconocida para 'umbral fmt': {}", opcion);
+                eprintln!("Uso: umbral fmt [--check] [rutas...]");
+                process::exit(1);
+            }
+            ruta => rutas.push(PathBuf::from(ruta)),
+        }
+    }
+
+    let mut con_errores = false;
+    let mut sin_formato = 0;
+    for archivo in &archivos::expandir(rutas) {
+        let codigo = match fs::read_to_string(archivo) {
+            Ok(codigo) => codigo,
+            Err(e) => {
+                eprintln!("Error al leer el archivo '{}': {}", archivo.display(), e);
+                con_errores = true;
+                continue;
+            }
+        };
+        let formateado = match formatear(&codigo) {
+            Ok(formateado) => formateado,
+            Err(e) => {
+                eprintln!("Error en '{}':", archivo.display());
+                eprintln!("{}", e);
+                con_errores = true;
+                continue;
+            }
+        };
+        if formateado == codigo {
+            continue;
+        }
+
+        sin_formato += 1;
+        if comprobar {
+            println!("{}", archivo.display());
+        } else if let Err(e) = fs::write(archivo, formateado) {
+            eprintln!("Error al escribir el archivo '{}': {}", archivo.display(), e);
+            con_errores = true;
+        } else {
+            println!("Formateado: {}", archivo.display());
+        }
+    }
+
+    if comprobar && sin_formato > 0 {
+        eprintln!("{} archivo(s) sin formatear", sin_formato);
+    }
+    if con_errores || (comprobar && sin_formato > 0) {
+        process::exit(1);
+    }
+}
+
+// `umbral fmt -`: lee el código de la entrada estándar y escribe el
+// resultado en la salida, para integrarlo en editores.
+fn formatear_entrada_estandar() {
+    let mut codigo = String::new();
+    if let Err(e) = io::stdin().read_to_string(&mut codigo) {
+        eprintln!("Error al leer la entrada estándar: {}", e);
+        process::exit(1);
+    }
+    match formatear(&codigo) {
+        Ok(formateado) => print!("{}", formateado),
+        Err(e) => {
+            eprintln!("{}", e);
+            process::exit(1);
+        }
+    }
+}
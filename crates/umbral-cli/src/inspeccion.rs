@@ -0,0 +1,214 @@
+//! `umbral --inspect`: ejecuta un programa deteniéndose antes de su primera
+//! sentencia y lo controla con órdenes escritas en la consola.
+
+use std::collections::BTreeSet;
+use std::fs;
+use std::io::{self, BufRead, Write};
+use std::process;
+use std::thread;
+use tokio::sync::mpsc::{self, UnboundedReceiver};
+use umbral_interpreter::depuracion::{self, Cliente, Detencion, Motivo, Paso};
+use umbral_interpreter::{lineas_ejecutables, Interpreter, InterpreterError};
+
+const AYUDA: &str = "\
+Órdenes:
+    c, continuar       Sigue hasta el siguiente punto de ruptura
+    n, siguiente       Ejecuta la línea actual sin entrar en las llamadas
+    s, entrar          Avanza una sentencia, entrando en las llamadas
+    o, salir           Sigue hasta volver a quien llamó a la función actual
+    b <línea>          Pone o quita un punto de ruptura
+    bt, pila           Muestra la pila de llamadas
+    v, vars            Muestra las variables del ámbito actual
+    p <expresión>      Evalúa la expresión en el marco actual
+    l, lista           Muestra el código alrededor de la línea actual
+    q, terminar        Termina el programa
+    h, ayuda           Muestra esta ayuda";
+
+pub async fn ejecutar(ruta_archivo: &str, argumentos: Vec<String>) {
+    let codigo = crate::leer_archivo(ruta_archivo);
+    let ejecutables = match lineas_ejecutables(&codigo) {
+        Ok(lineas) => lineas,
+        Err(e) => {
+            eprintln!("{}", e);
+            process::exit(1);
+        }
+    };
+
+    let (depuracion, cliente, mut detenciones) = depuracion::crear(true);
+    let mut interprete = Interpreter::nuevo();
+    interprete.depurar(depuracion);
+    interprete.establecer_argumentos(argumentos);
+    if let Ok(ruta_abs) = fs::canonicalize(ruta_archivo) {
+        interprete.establecer_archivo(ruta_abs);
+    }
+    let fuente = codigo.clone();
+    let programa = tokio::spawn(async move { interprete.ejecutar(&fuente).await });
+
+    println!(
+        "Inspeccionando {}. Escribe 'h' para ver las órdenes.",
+        ruta_archivo
+    );
+    let lineas: Vec<&str> = codigo.lines().collect();
+    let mut consola = Consola {
+        cliente,
+        entrada: leer_entrada(),
+        lineas,
+        ejecutables,
+        puntos: BTreeSet::new(),
+    };
+    while let Some(detencion) = detenciones.recv().await {
+        consola.atender(detencion).await;
+    }
+
+    match programa.await {
+        Ok(Ok(())) => {}
+        Ok(Err(InterpreterError::Salida(codigo))) => process::exit(codigo),
+        Ok(Err(e)) => {
+            eprintln!("{}", e);
+            process::exit(1);
+        }
+        Err(e) => {
+            eprintln!("Error interno al ejecutar el programa: {}", e);
+            process::exit(1);
+        }
+    }
+}
+
+// Las líneas de la consola se leen en un hilo aparte para no bloquear el
+// programa, que sigue ejecutándose en el runtime.
+fn leer_entrada() -> UnboundedReceiver<String> {
+    let (enviar, recibir) = mpsc::unbounded_channel();
+    thread::spawn(move || {
+        for linea in io::stdin().lock().lines() {
+            let Ok(linea) = linea else { break };
+            if enviar.send(linea).is_err() {
+                break;
+            }
+        }
+    });
+    recibir
+}
+
+struct Consola<'a> {
+    cliente: Cliente,
+    entrada: UnboundedReceiver<String>,
+    lineas: Vec<&'a str>,
+    ejecutables: Vec<usize>,
+    puntos: BTreeSet<usize>,
+}
+
+impl Consola<'_> {
+    async fn atender(&mut self, detencion: Detencion) {
+        let motivo = match detencion.motivo {
+            Motivo::Entrada => "inicio",
+            Motivo::PuntoDeRuptura => "punto de ruptura",
+            Motivo::Paso => "paso",
+            Motivo::Pausa => "pausa",
+        };
+        println!("Detenido en la línea {} ({})", detencion.linea, motivo);
+        self.mostrar_lineas(detencion.linea, 0);
+
+        loop {
+            print!("(umbral) ");
+            let _ = io::stdout().flush();
+            let Some(orden) = self.entrada.recv().await else {
+                // Sin consola el programa termina sin volver a detenerse.
+                self.cliente.establecer_puntos([]);
+                self.cliente.reanudar(Paso::Continuar);
+                return;
+            };
+
+            let orden = orden.trim();
+            let (nombre, argumento) = orden.split_once(' ').unwrap_or((orden, ""));
+            let argumento = argumento.trim();
+            let paso = match nombre {
+                "c" | "continuar" => Paso::Continuar,
+                "n" | "siguiente" => Paso::Saltar,
+                "s" | "entrar" => Paso::Entrar,
+                "o" | "salir" => Paso::Salir,
+                "b" => {
+                    self.alternar_punto(argumento);
+                    continue;
+                }
+                "bt" | "pila" => {
+                    for (indice, marco) in self.cliente.pila().await.iter().enumerate() {
+                        println!("  #{} {} (línea {})", indice, marco.nombre, marco.linea);
+                    }
+                    continue;
+                }
+                "v" | "vars" => {
+                    self.mostrar_variables().await;
+                    continue;
+                }
+                "p" => {
+                    match self.cliente.evaluar(argumento).await {
+                        Ok(variable) => println!("{} ({})", variable.valor, variable.tipo),
+                        Err(mensaje) => println!("Error: {}", mensaje),
+                    }
+                    continue;
+                }
+                "l" | "lista" => {
+                    self.mostrar_lineas(detencion.linea, 4);
+                    continue;
+                }
+                "q" | "terminar" => process::exit(0),
+                "h" | "ayuda" => {
+                    println!("{}", AYUDA);
+                    continue;
+                }
+                "" => continue,
+                otra => {
+                    println!(
+                        "Orden desconocida: {}. Escribe 'h' para ver las órdenes.",
+                        otra
+                    );
+                    continue;
+                }
+            };
+            self.cliente.reanudar(paso);
+            return;
+        }
+    }
+
+    // Un punto en una línea sin sentencias se mueve a la siguiente que
+    // tenga alguna.
+    fn alternar_punto(&mut self, argumento: &str) {
+        let Ok(pedida) = argumento.parse::<usize>() else {
+            println!("Uso: b <línea>");
+            return;
+        };
+        let Some(&linea) = self.ejecutables.iter().find(|&&linea| linea >= pedida) else {
+            println!("No hay ninguna sentencia en la línea {} ni después", pedida);
+            return;
+        };
+
+        if self.puntos.remove(&linea) {
+            println!("Punto de ruptura quitado de la línea {}", linea);
+        } else {
+            self.puntos.insert(linea);
+            println!("Punto de ruptura en la línea {}", linea);
+        }
+        self.cliente.establecer_puntos(self.puntos.iter().copied());
+    }
+
+    async fn mostrar_variables(&self) {
+        let Some(ambito) = self.cliente.ambitos().await.into_iter().next() else {
+            return;
+        };
+        for variable in self.cliente.variables(ambito.referencia).await {
+            println!(
+                "  {}: {} = {}",
+                variable.nombre, variable.tipo, variable.valor
+            );
+        }
+    }
+
+    fn mostrar_lineas(&self, actual: usize, alrededor: usize) {
+        let desde = actual.saturating_sub(alrededor).max(1);
+        let hasta = (actual + alrededor).min(self.lineas.len());
+        for numero in desde..=hasta {
+            let marca = if numero == actual { "->" } else { "  " };
+            println!("{} {:>4} | {}", marca, numero, self.lineas[numero - 1]);
+        }
+    }
+}
@@ -0,0 +1,93 @@
+//! `umbral lint`: analiza los archivos `.um` sin ejecutarlos. El nivel de
+//! cada regla se configura en la tabla `lint` de `ump.json`.
+
+use crate::archivos;
+use std::env;
+use std::fs;
+use std::path::PathBuf;
+use std::process;
+use umbral_interpreter::paquetes::Proyecto;
+use umbral_lint::{analizar, Configuracion, Nivel, Regla};
+
+pub fn ejecutar(argumentos: &[String]) {
+    let mut rutas = Vec::new();
+    for argumento in argumentos {
+        match argumento.as_str() {
+            "--reglas" => {
+                mostrar_reglas();
+                return;
+            }
+            opcion if opcion.starts_with('-') => {
+                eprintln!("Opción desconocida para 'umbral lint': {}", opcion);
+                eprintln!("Uso: umbral lint [--reglas] [rutas...]");
+                process::exit(1);
+            }
+            ruta => rutas.push(PathBuf::from(ruta)),
+        }
+    }
+
+    let configuracion = cargar_configuracion();
+    let mut advertencias = 0;
+    let mut errores = 0;
+    let mut con_errores = false;
+
+    for archivo in &archivos::expandir(rutas) {
+        let codigo = match fs::read_to_string(archivo) {
+            Ok(codigo) => codigo,
+            Err(e) => {
+                eprintln!("Error al leer el archivo '{}': {}", archivo.display(), e);
+                con_errores = true;
+                continue;
+            }
+        };
+        match analizar(&codigo, &configuracion) {
+            Ok(diagnosticos) => {
+                for diagnostico in diagnosticos {
+                    println!("{}:{}", archivo.display(), diagnostico);
+                    match diagnostico.nivel {
+                        Nivel::Error => errores += 1,
+                        _ => advertencias += 1,
+                    }
+                }
+            }
+            Err(errores_sintaxis) => {
+                eprintln!("Error en '{}':", archivo.display());
+                for error in errores_sintaxis {
+                    eprintln!("{}", error.formatear_error());
+                }
+                con_errores = true;
+            }
+        }
+    }
+
+    if advertencias + errores > 0 {
+        eprintln!("{} advertencia(s), {} error(es)", advertencias, errores);
+    }
+    if con_errores || errores > 0 {
+        process::exit(1);
+    }
+}
+
+fn cargar_configuracion() -> Configuracion {
+    let directorio = env::current_dir().unwrap_or_else(|_| PathBuf::from("."));
+    let resultado = match Proyecto::buscar(&directorio) {
+        Ok(Some(proyecto)) => Configuracion::desde_tabla(&proyecto.manifiesto.lint),
+        Ok(None) => Ok(Configuracion::default()),
+        Err(e) => Err(e),
+    };
+    resultado.unwrap_or_else(|e| {
+        eprintln!("Error: {}", e);
+        process::exit(1);
+    })
+}
+
+fn mostrar_reglas() {
+    for regla in Regla::TODAS {
+        println!(
+            "{:<24} {:<12} {}",
+            regla.nombre(),
+            regla.nivel_por_defecto().to_string(),
+            regla.descripcion()
+        );
+    }
+}
@@ -1,7 +1,16 @@
+mod archivos;
+mod formato;
+mod inspeccion;
+mod lint;
+mod perfil;
+mod pruebas;
+
 use std::env;
 use std::fs;
+use std::path::PathBuf;
 use std::process;
-use umbral_interpreter::Interpreter;
+use umbral_interpreter::paquetes::{NodoDependencia, Proyecto};
+use umbral_interpreter::{Interpreter, InterpreterError};
 
 const VERSION: &str = env!("CARGO_PKG_VERSION");
 
@@ -21,9 +30,48 @@ async fn main() {
         "--version" | "-v" => {
             mostrar_version();
         }
+        "deps" => {
+            mostrar_dependencias(args.get(2));
+        }
+        "fmt" => {
+            formato::ejecutar(&args[2..]);
+        }
+        "lint" => {
+            lint::ejecutar(&args[2..]);
+        }
+        "test" => {
+            pruebas::ejecutar(&args[2..]).await;
+        }
+        "debug" => {
+            if let Err(e) = umbral_dap::ejecutar().await {
+                eprintln!("Error en el servidor de depuración: {}", e);
+                process::exit(1);
+            }
+        }
+        "--inspect" => match args.get(2) {
+            Some(ruta_archivo) => inspeccion::ejecutar(ruta_archivo, args[3..].to_vec()).await,
+            None => {
+                eprintln!("Uso: umbral --inspect <archivo.um> [argumentos...]");
+                process::exit(1);
+            }
+        },
+        opcion if opcion == "--profile" || opcion.starts_with("--profile=") => {
+            let destino = opcion
+                .strip_prefix("--profile=")
+                .unwrap_or(perfil::RUTA_POR_DEFECTO);
+            match args.get(2) {
+                Some(ruta_archivo) => {
+                    perfil::ejecutar(ruta_archivo, args[3..].to_vec(), destino).await
+                }
+                None => {
+                    eprintln!("Uso: umbral --profile[=ruta] <archivo.um> [argumentos...]");
+                    process::exit(1);
+                }
+            }
+        }
         ruta_archivo => {
             let codigo = leer_archivo(ruta_archivo);
-            ejecutar_codigo(&codigo, ruta_archivo).await;
+            ejecutar_codigo(&codigo, ruta_archivo, args[2..].to_vec()).await;
         }
     }
 }
@@ -36,19 +84,52 @@ fn mostrar_ayuda() {
     println!("Lenguaje de programación de propósito general");
     println!();
     println!("USO:");
-    println!("    umbral [OPCIONES] <archivo.um>");
+    println!("    umbral [OPCIONES] <archivo.um> [argumentos...]");
+    println!("    umbral --inspect <archivo.um> [argumentos...]");
+    println!("    umbral --profile[=ruta] <archivo.um> [argumentos...]");
+    println!("    umbral deps [directorio]");
+    println!("    umbral fmt [--check] [rutas...]");
+    println!("    umbral lint [--reglas] [rutas...]");
+    println!("    umbral test [--filtro <texto>] [-j <n>] [--formato texto|tap|junit] [--coverage[=ruta]] [rutas...]");
+    println!("    umbral debug");
     println!();
     println!("OPCIONES:");
     println!("    -h, --help       Muestra esta ayuda");
     println!("    -v, --version    Muestra la versión del intérprete");
+    println!("    --inspect        Ejecuta el archivo en la consola del depurador,");
+    println!("                     detenido antes de la primera sentencia");
+    println!("    --profile        Mide llamadas y tiempos por función y por línea;");
+    println!("                     escribe las pilas plegadas (por defecto, perfil.folded)");
     println!();
     println!("ARGUMENTOS:");
     println!("    <archivo.um>     Ruta al archivo .um a ejecutar");
+    println!("    [argumentos...]  Se pasan al script (ARGS o Std.proc.args())");
+    println!();
+    println!("COMANDOS:");
+    println!("    deps             Lista el árbol de dependencias instaladas en modules_ump");
+    println!("    fmt              Formatea los archivos .um (por defecto, el directorio actual)");
+    println!("                     --check no escribe: lista los archivos sin formatear");
+    println!("                     y termina con error si hay alguno");
+    println!("    lint             Analiza los archivos .um sin ejecutarlos (reglas en ump.json)");
+    println!("                     --reglas lista las reglas y su nivel por defecto");
+    println!("    test             Ejecuta las funciones test* de los archivos *_test.um");
+    println!("                     --filtro solo ejecuta las pruebas cuyo nombre lo contiene");
+    println!("                     -j limita las pruebas simultáneas (por defecto, una por núcleo)");
+    println!("                     --formato tap|junit cambia el informe");
+    println!("                     --coverage escribe la cobertura de líneas (por defecto, lcov.info)");
+    println!("    debug            Servidor de depuración (DAP) por stdio para los editores");
     println!();
     println!("EJEMPLOS:");
     println!("    umbral programa.um");
     println!("    umbral /ruta/completa/script.um");
+    println!("    umbral herramienta.um --input datos.csv");
     println!("    umbral ejemplos/01_variables_y_constantes.um");
+    println!("    umbral fmt --check src/");
+    println!("    umbral lint src/");
+    println!("    umbral test --filtro suma tests/");
+    println!("    umbral --inspect programa.um");
+    println!("    umbral --profile=perfil.folded programa.um");
+    println!("    umbral test --coverage tests/");
     println!();
     println!("Para usar el REPL interactivo, ejecuta:");
     println!("    umbral-repl");
@@ -60,6 +141,74 @@ fn mostrar_version() {
     println!("Umbral v{}", VERSION);
 }
 
+fn mostrar_dependencias(directorio: Option<&String>) {
+    let directorio = directorio
+        .map(PathBuf::from)
+        .or_else(|| env::current_dir().ok())
+        .unwrap_or_else(|| PathBuf::from("."));
+
+    let proyecto = match Proyecto::buscar(&directorio) {
+        Ok(Some(proyecto)) => proyecto,
+        Ok(None) => {
+            eprintln!(
+                "No se encontró ump.json en '{}' ni en sus directorios superiores.",
+                directorio.display()
+            );
+            process::exit(1);
+        }
+        Err(e) => {
+            eprintln!("Error: {}", e);
+            process::exit(1);
+        }
+    };
+
+    println!(
+        "{}@{}",
+        proyecto.manifiesto.nombre, proyecto.manifiesto.version
+    );
+
+    let arbol = proyecto.arbol_dependencias();
+    let hay_conflictos = imprimir_nodos(&arbol, "");
+
+    if hay_conflictos {
+        process::exit(1);
+    }
+}
+
+fn imprimir_nodos(nodos: &[NodoDependencia], prefijo: &str) -> bool {
+    let mut hay_conflictos = false;
+
+    for (i, nodo) in nodos.iter().enumerate() {
+        let es_ultimo = i + 1 == nodos.len();
+        let rama = if es_ultimo {
+            "└── "
+        } else {
+            "├── "
+        };
+        let version = nodo
+            .instalada
+            .as_ref()
+            .map(|v| v.to_string())
+            .unwrap_or_else(|| "?".to_string());
+
+        let mut linea = format!(
+            "{}{}{}@{} ({})",
+            prefijo, rama, nodo.nombre, version, nodo.requerida
+        );
+        if let Some(conflicto) = &nodo.conflicto {
+            linea.push_str(&format!(" ✗ {}", conflicto));
+            hay_conflictos = true;
+        }
+        println!("{}", linea);
+
+        let continuacion = if es_ultimo { "    " } else { "│   " };
+        let sub_prefijo = format!("{}{}", prefijo, continuacion);
+        hay_conflictos |= imprimir_nodos(&nodo.dependencias, &sub_prefijo);
+    }
+
+    hay_conflictos
+}
+
 fn leer_archivo(ruta: &str) -> String {
     fs::read_to_string(ruta).unwrap_or_else(|e| {
         eprintln!("Error al leer el archivo '{}': {}", ruta, e);
@@ -70,18 +219,25 @@ fn leer_archivo(ruta: &str) -> String {
     })
 }
 
-async fn ejecutar_codigo(codigo: &str, ruta_archivo: &str) {
+async fn ejecutar_codigo(codigo: &str, ruta_archivo: &str, argumentos: Vec<String>) {
     let mut interprete = Interpreter::nuevo();
+    interprete.establecer_argumentos(argumentos);
 
     if let Ok(ruta_abs) = fs::canonicalize(ruta_archivo) {
-        if let Some(parent) = ruta_abs.parent() {
-            interprete.establecer_directorio_base(parent.to_path_buf());
-        }
+        interprete.establecer_archivo(ruta_abs);
     }
 
     if let Err(e) = interprete.ejecutar(codigo).await {
-        eprintln!("Error de ejecución:");
-        eprintln!("{}", e);
-        process::exit(1);
+        terminar_con_error(e);
+    }
+}
+
+// Si el programa pidió terminar, se sale con su código y sin mensaje.
+fn terminar_con_error(error: InterpreterError) -> ! {
+    if let InterpreterError::Salida(codigo) = error {
+        process::exit(codigo);
     }
+    eprintln!("Error de ejecución:");
+    eprintln!("{}", error);
+    process::exit(1)
 }
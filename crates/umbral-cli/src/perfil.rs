@@ -0,0 +1,99 @@
+//! `umbral --profile`: ejecuta un programa midiendo sus funciones y líneas.
+//! Las pilas se escriben en formato plegado, listas para `flamegraph.pl` o
+//! `inferno-flamegraph`, y el resumen se muestra en la salida de errores.
+
+use std::fs;
+use std::path::Path;
+use std::process;
+use std::time::Duration;
+use umbral_interpreter::perfil::Perfil;
+use umbral_interpreter::Interpreter;
+
+pub const RUTA_POR_DEFECTO: &str = "perfil.folded";
+const FUNCIONES_MOSTRADAS: usize = 20;
+const LINEAS_MOSTRADAS: usize = 10;
+
+pub async fn ejecutar(ruta_archivo: &str, argumentos: Vec<String>, destino: &str) {
+    let codigo = crate::leer_archivo(ruta_archivo);
+    let perfil = Perfil::nuevo();
+
+    let mut interprete = Interpreter::nuevo();
+    interprete.establecer_argumentos(argumentos);
+    if let Ok(ruta_abs) = fs::canonicalize(ruta_archivo) {
+        interprete.establecer_archivo(ruta_abs);
+    }
+    interprete.perfilar(perfil.clone());
+    let resultado = interprete.ejecutar(&codigo).await;
+    interprete.terminar_perfil();
+
+    // El perfil se guarda aunque el programa termine con error.
+    if let Err(e) = fs::write(destino, perfil.pilas_plegadas()) {
+        eprintln!("Error al escribir '{}': {}", destino, e);
+        process::exit(1);
+    }
+    imprimir_resumen(&perfil, destino);
+
+    if let Err(e) = resultado {
+        crate::terminar_con_error(e);
+    }
+}
+
+fn imprimir_resumen(perfil: &Perfil, destino: &str) {
+    eprintln!();
+    eprintln!(
+        "{:>10} {:>10} {:>10}  función",
+        "llamadas", "total", "propio"
+    );
+    for (nombre, estadistica) in perfil.funciones().iter().take(FUNCIONES_MOSTRADAS) {
+        eprintln!(
+            "{:>10} {:>10} {:>10}  {}",
+            estadistica.llamadas,
+            duracion(estadistica.total),
+            duracion(estadistica.propio),
+            nombre
+        );
+    }
+
+    let mut lineas = Vec::new();
+    for archivo in perfil.archivos() {
+        let nombre = archivo
+            .ruta
+            .file_name()
+            .map(|nombre| nombre.to_string_lossy().into_owned())
+            .unwrap_or_else(|| archivo.ruta.display().to_string());
+        for (linea, estadistica) in archivo.lineas {
+            if estadistica.veces > 0 {
+                lineas.push((format!("{}:{}", nombre, linea), estadistica));
+            }
+        }
+    }
+    lineas.sort_by(|a, b| b.1.tiempo.cmp(&a.1.tiempo).then_with(|| a.0.cmp(&b.0)));
+
+    eprintln!();
+    eprintln!("{:>10} {:>10}  línea", "veces", "tiempo");
+    for (ubicacion, estadistica) in lineas.iter().take(LINEAS_MOSTRADAS) {
+        eprintln!(
+            "{:>10} {:>10}  {}",
+            estadistica.veces,
+            duracion(estadistica.tiempo),
+            ubicacion
+        );
+    }
+
+    eprintln!();
+    eprintln!(
+        "Pilas plegadas escritas en {}",
+        Path::new(destino).display()
+    );
+}
+
+fn duracion(tiempo: Duration) -> String {
+    let micros = tiempo.as_micros();
+    if micros < 1_000 {
+        format!("{} µs", micros)
+    } else if micros < 1_000_000 {
+        format!("{:.2} ms", tiempo.as_secs_f64() * 1_000.0)
+    } else {
+        format!("{:.2} s", tiempo.as_secs_f64())
+    }
+}
@@ -0,0 +1,418 @@
+//! `umbral test`: busca los archivos `*_test.um`, ejecuta sus pruebas en
+//! paralelo y muestra el resultado como texto, TAP o JUnit. Con `--coverage`
+//! escribe además la cobertura de líneas en formato lcov.
+
+use crate::archivos;
+use std::fs;
+use std::fmt::Write as _;
+use std::path::PathBuf;
+use std::process;
+use std::sync::Arc;
+use std::thread;
+use std::time::{Duration, Instant};
+use tokio::sync::Semaphore;
+use umbral_interpreter::perfil::Perfil;
+use umbral_interpreter::pruebas::{self, Resultado};
+
+const USO: &str = "Uso: umbral test [--filtro <texto>] [-j <n>] [--formato texto|tap|junit] [--coverage[=ruta]] [rutas...]";
+const COBERTURA_POR_DEFECTO: &str = "lcov.info";
+
+#[derive(Clone, Copy, PartialEq)]
+enum Formato {
+    Texto,
+    Tap,
+    Junit,
+}
+
+struct Prueba {
+    archivo: PathBuf,
+    nombre: String,
+    resultado: Resultado,
+}
+
+impl Prueba {
+    fn identificador(&self) -> String {
+        format!("{}::{}", self.archivo.display(), self.nombre)
+    }
+}
+
+pub async fn ejecutar(argumentos: &[String]) {
+    let mut rutas = Vec::new();
+    let mut filtro = None;
+    let mut trabajos = thread::available_parallelism().map_or(1, |n| n.get());
+    let mut formato = Formato::Texto;
+    let mut cobertura = None;
+
+    let mut argumentos = argumentos.iter();
+    while let Some(argumento) = argumentos.next() {
+        match argumento.as_str() {
+            "--filtro" => filtro = Some(valor_opcion(argumento, argumentos.next())),
+            "-j" | "--trabajos" => {
+                let valor = valor_opcion(argumento, argumentos.next());
+                trabajos = match valor.parse::<usize>() {
+                    Ok(n) if n > 0 => n,
+                    _ => error_de_uso(&format!("'{}' no es un número de trabajos válido", valor)),
+                };
+            }
+            "--formato" => {
+                formato = match valor_opcion(argumento, argumentos.next()).as_str() {
+                    "texto" => Formato::Texto,
+                    "tap" => Formato::Tap,
+                    "junit" => Formato::Junit,
+                    otro => error_de_uso(&format!("formato desconocido: {}", otro)),
+                };
+            }
+            "--coverage" => cobertura = Some(PathBuf::from(COBERTURA_POR_DEFECTO)),
+            opcion if opcion.starts_with("--coverage=") => {
+                cobertura = Some(PathBuf::from(&opcion["--coverage=".len()..]))
+            }
+            opcion if opcion.starts_with('-') => {
+                error_de_uso(&format!("Opción desconocida para 'umbral test': {}", opcion))
+            }
+            ruta => rutas.push(PathBuf::from(ruta)),
+        }
+    }
+
+    let inicio = Instant::now();
+    let mut con_errores = false;
+    let semaforo = Arc::new(Semaphore::new(trabajos));
+    // Todas las pruebas acumulan sus líneas en el mismo perfil.
+    let perfil = cobertura.as_ref().map(|_| Perfil::nuevo());
+    let mut tareas = Vec::new();
+
+    for archivo in archivos_de_prueba(rutas) {
+        let codigo = match fs::read_to_string(&archivo) {
+            Ok(codigo) => Arc::new(codigo),
+            Err(e) => {
+                eprintln!("Error al leer el archivo '{}': {}", archivo.display(), e);
+                con_errores = true;
+                continue;
+            }
+        };
+        let nombres = match pruebas::descubrir(&codigo) {
+            Ok(nombres) => nombres,
+            Err(e) => {
+                eprintln!("Error en '{}':", archivo.display());
+                eprintln!("{}", e);
+                con_errores = true;
+                continue;
+            }
+        };
+
+        for nombre in nombres {
+            let identificador = format!("{}::{}", archivo.display(), nombre);
+            if filtro.as_ref().is_some_and(|filtro| !identificador.contains(filtro.as_str())) {
+                continue;
+            }
+
+            let archivo = archivo.clone();
+            let codigo = codigo.clone();
+            let semaforo = semaforo.clone();
+            let perfil = perfil.clone();
+            tareas.push(tokio::spawn(async move {
+                let _permiso = semaforo.acquire_owned().await.unwrap();
+                let ruta = fs::canonicalize(&archivo).unwrap_or_else(|_| archivo.clone());
+                let resultado = pruebas::ejecutar(&ruta, &codigo, &nombre, perfil).await;
+                Prueba {
+                    archivo,
+                    nombre,
+                    resultado,
+                }
+            }));
+        }
+    }
+
+    let mut resultados = Vec::with_capacity(tareas.len());
+    for tarea in tareas {
+        match tarea.await {
+            Ok(prueba) => resultados.push(prueba),
+            Err(e) => {
+                eprintln!("Error interno al ejecutar una prueba: {}", e);
+                con_errores = true;
+            }
+        }
+    }
+
+    let duracion = inicio.elapsed();
+    match formato {
+        Formato::Texto => imprimir_texto(&resultados, duracion),
+        Formato::Tap => imprimir_tap(&resultados),
+        Formato::Junit => imprimir_junit(&resultados, duracion),
+    }
+
+    if let (Some(ruta), Some(perfil)) = (cobertura, perfil) {
+        let (lcov, cubiertas, totales) = informe_lcov(&perfil);
+        if let Err(e) = fs::write(&ruta, lcov) {
+            eprintln!("Error al escribir '{}': {}", ruta.display(), e);
+            process::exit(1);
+        }
+        let porcentaje = match totales {
+            0 => 100.0,
+            _ => cubiertas as f64 * 100.0 / totales as f64,
+        };
+        eprintln!(
+            "Cobertura: {:.1}% ({} de {} líneas) en {}",
+            porcentaje,
+            cubiertas,
+            totales,
+            ruta.display()
+        );
+    }
+
+    let codigo = codigo_de_salida(con_errores, &resultados);
+    if codigo != 0 {
+        process::exit(codigo);
+    }
+}
+
+// 1 si algún archivo no pudo ejecutarse o alguna prueba falló, aunque lo
+// hiciera llamando a `Std.proc.exit(0)`.
+fn codigo_de_salida(con_errores: bool, resultados: &[Prueba]) -> i32 {
+    match con_errores || resultados.iter().any(|prueba| !prueba.resultado.paso()) {
+        true => 1,
+        false => 0,
+    }
+}
+
+fn valor_opcion(opcion: &str, valor: Option<&String>) -> String {
+    match valor {
+        Some(valor) => valor.clone(),
+        None => error_de_uso(&format!("falta el valor de {}", opcion)),
+    }
+}
+
+fn error_de_uso(mensaje: &str) -> ! {
+    eprintln!("{}", mensaje);
+    eprintln!("{}", USO);
+    process::exit(1);
+}
+
+// Un registro por archivo ejecutado, salvo los propios archivos de pruebas.
+// Devuelve también las líneas cubiertas y el total.
+fn informe_lcov(perfil: &Perfil) -> (String, usize, usize) {
+    let mut informe = String::new();
+    let (mut cubiertas, mut totales) = (0, 0);
+    for archivo in perfil.archivos() {
+        if pruebas::es_archivo_de_pruebas(&archivo.ruta) || archivo.lineas.is_empty() {
+            continue;
+        }
+        let _ = writeln!(informe, "TN:");
+        let _ = writeln!(informe, "SF:{}", archivo.ruta.display());
+        for (linea, estadistica) in &archivo.lineas {
+            let _ = writeln!(informe, "DA:{},{}", linea, estadistica.veces);
+        }
+        let ejecutadas = archivo.lineas.values().filter(|l| l.veces > 0).count();
+        let _ = writeln!(informe, "LF:{}", archivo.lineas.len());
+        let _ = writeln!(informe, "LH:{}", ejecutadas);
+        let _ = writeln!(informe, "end_of_record");
+        cubiertas += ejecutadas;
+        totales += archivo.lineas.len();
+    }
+    (informe, cubiertas, totales)
+}
+
+// Los archivos indicados se toman tal cual; en los directorios solo cuentan
+// los que terminan en `_test.um`.
+fn archivos_de_prueba(rutas: Vec<PathBuf>) -> Vec<PathBuf> {
+    let rutas = match rutas.is_empty() {
+        true => vec![PathBuf::from(".")],
+        false => rutas,
+    };
+
+    let mut archivos = Vec::new();
+    for ruta in rutas {
+        if ruta.is_dir() {
+            archivos.extend(
+                archivos::expandir(vec![ruta])
+                    .into_iter()
+                    .filter(|archivo| pruebas::es_archivo_de_pruebas(archivo)),
+            );
+        } else {
+            archivos.push(ruta);
+        }
+    }
+    archivos
+}
+
+fn imprimir_texto(resultados: &[Prueba], duracion: Duration) {
+    for prueba in resultados {
+        let estado = match prueba.resultado.paso() {
+            true => "ok   ",
+            false => "FALLO",
+        };
+        println!(
+            "{} {} ({} ms)",
+            estado,
+            prueba.identificador(),
+            prueba.resultado.duracion.as_millis()
+        );
+    }
+
+    let fallidas: Vec<&Prueba> = resultados.iter().filter(|p| !p.resultado.paso()).collect();
+    for prueba in &fallidas {
+        println!();
+        println!("--- {} ---", prueba.identificador());
+        if let Some(fallo) = &prueba.resultado.fallo {
+            println!("{}", fallo);
+        }
+        if !prueba.resultado.salida.is_empty() {
+            println!("salida:");
+            for linea in prueba.resultado.salida.lines() {
+                println!("  {}", linea);
+            }
+        }
+    }
+
+    println!();
+    println!(
+        "{} prueba(s): {} correcta(s), {} fallida(s) en {:.2} s",
+        resultados.len(),
+        resultados.len() - fallidas.len(),
+        fallidas.len(),
+        duracion.as_secs_f64()
+    );
+}
+
+// TAP 13: el detalle de los fallos va en un bloque YAML y la salida
+// capturada como comentarios.
+fn imprimir_tap(resultados: &[Prueba]) {
+    println!("TAP version 13");
+    println!("1..{}", resultados.len());
+    for (indice, prueba) in resultados.iter().enumerate() {
+        let estado = match prueba.resultado.paso() {
+            true => "ok",
+            false => "not ok",
+        };
+        println!("{} {} - {}", estado, indice + 1, prueba.identificador());
+
+        for linea in prueba.resultado.salida.lines() {
+            println!("# {}", linea);
+        }
+        if let Some(fallo) = &prueba.resultado.fallo {
+            println!("  ---");
+            println!("  message: |");
+            for linea in fallo.lines() {
+                println!("    {}", linea);
+            }
+            println!("  duration_ms: {}", prueba.resultado.duracion.as_millis());
+            println!("  ...");
+        }
+    }
+}
+
+// Un `testsuite` por archivo, en el orden en que se encontraron.
+fn imprimir_junit(resultados: &[Prueba], duracion: Duration) {
+    let fallidas = resultados.iter().filter(|p| !p.resultado.paso()).count();
+    println!(r#"<?xml version="1.0" encoding="UTF-8"?>"#);
+    println!(
+        r#"<testsuites name="umbral" tests="{}" failures="{}" time="{:.3}">"#,
+        resultados.len(),
+        fallidas,
+        duracion.as_secs_f64()
+    );
+
+    let mut inicio = 0;
+    while inicio < resultados.len() {
+        let archivo = &resultados[inicio].archivo;
+        let fin = resultados[inicio..]
+            .iter()
+            .position(|prueba| &prueba.archivo != archivo)
+            .map_or(resultados.len(), |desplazamiento| inicio + desplazamiento);
+        let suite = &resultados[inicio..fin];
+        let nombre = escapar_xml(&archivo.display().to_string());
+
+        println!(
+            r#"  <testsuite name="{}" tests="{}" failures="{}" time="{:.3}">"#,
+            nombre,
+            suite.len(),
+            suite.iter().filter(|p| !p.resultado.paso()).count(),
+            suite
+                .iter()
+                .map(|p| p.resultado.duracion)
+                .sum::<Duration>()
+                .as_secs_f64()
+        );
+        for prueba in suite {
+            let etiqueta = format!(
+                r#"<testcase name="{}" classname="{}" time="{:.3}""#,
+                escapar_xml(&prueba.nombre),
+                nombre,
+                prueba.resultado.duracion.as_secs_f64()
+            );
+            if prueba.resultado.paso() && prueba.resultado.salida.is_empty() {
+                println!("    {}/>", etiqueta);
+                continue;
+            }
+
+            println!("    {}>", etiqueta);
+            if let Some(fallo) = &prueba.resultado.fallo {
+                let resumen = fallo.lines().next().unwrap_or_default();
+                println!(
+                    r#"      <failure message="{}">{}</failure>"#,
+                    escapar_xml(resumen),
+                    escapar_xml(fallo)
+                );
+            }
+            if !prueba.resultado.salida.is_empty() {
+                println!(
+                    "      <system-out>{}</system-out>",
+                    escapar_xml(&prueba.resultado.salida)
+                );
+            }
+            println!("    </testcase>");
+        }
+        println!("  </testsuite>");
+
+        inicio = fin;
+    }
+    println!("</testsuites>");
+}
+
+fn escapar_xml(texto: &str) -> String {
+    let mut resultado = String::with_capacity(texto.len());
+    for caracter in texto.chars() {
+        match caracter {
+            '&' => resultado.push_str("&amp;"),
+            '<' => resultado.push_str("&lt;"),
+            '>' => resultado.push_str("&gt;"),
+            '"' => resultado.push_str("&quot;"),
+            '\'' => resultado.push_str("&apos;"),
+            otro => resultado.push(otro),
+        }
+    }
+    resultado
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_una_prueba_fallida_da_un_codigo_distinto_de_cero() {
+        let codigo = r#"
+            equip { eq } origin "std:assert";
+            f: test_pasa() { eq(1, 1); }
+            f: test_falla() { eq(1, 2); }
+            f: test_sale_con_cero() { Std.proc.exit(0); }
+            f: test_sigue_despues() { eq(2, 2); }
+        "#;
+        let archivo = PathBuf::from("ejemplo_test.um");
+        let mut resultados = Vec::new();
+        for nombre in pruebas::descubrir(codigo).unwrap() {
+            let resultado = pruebas::ejecutar(&archivo, codigo, &nombre, None).await;
+            resultados.push(Prueba {
+                archivo: archivo.clone(),
+                nombre,
+                resultado,
+            });
+        }
+
+        let pasan: Vec<bool> = resultados.iter().map(|p| p.resultado.paso()).collect();
+        assert_eq!(pasan, [true, false, false, true]);
+        assert_ne!(codigo_de_salida(false, &resultados), 0);
+        assert_ne!(codigo_de_salida(false, &resultados[2..3]), 0);
+        assert_eq!(codigo_de_salida(false, &resultados[..1]), 0);
+        assert_ne!(codigo_de_salida(true, &resultados[..1]), 0);
+        assert_eq!(codigo_de_salida(false, &[]), 0);
+    }
+}
@@ -0,0 +1,180 @@
+//! Adaptador de depuración (DAP) para Umbral.
+//!
+//! `umbral debug` lo sirve por stdio para que los editores puedan lanzar un
+//! programa con `launch` (`program`, `args`, `cwd`, `stopOnEntry`), poner
+//! puntos de ruptura, avanzar paso a paso, ver la pila y las variables y
+//! evaluar expresiones mientras está detenido.
+
+mod protocolo;
+mod sesion;
+
+use sesion::Sesion;
+use std::io;
+use tokio::io::{AsyncRead, AsyncWrite, BufReader};
+use tokio::sync::mpsc;
+
+pub async fn ejecutar() -> io::Result<()> {
+    servir(tokio::io::stdin(), tokio::io::stdout()).await
+}
+
+/// Atiende una sesión completa: hasta `disconnect` o hasta que se cierra
+/// la entrada.
+pub async fn servir<R, W>(entrada: R, salida: W) -> io::Result<()>
+where
+    R: AsyncRead + Unpin + Send + 'static,
+    W: AsyncWrite + Unpin,
+{
+    // La lectura va en su propia tarea: un mensaje a medio leer no puede
+    // cancelarse cuando llega un aviso del programa.
+    let (enviar_peticion, mut peticiones) = mpsc::unbounded_channel();
+    tokio::spawn(async move {
+        let mut entrada = BufReader::new(entrada);
+        while let Ok(Some(mensaje)) = protocolo::leer_mensaje(&mut entrada).await {
+            if enviar_peticion.send(mensaje).is_err() {
+                break;
+            }
+        }
+    });
+
+    let (enviar_aviso, mut avisos) = mpsc::unbounded_channel();
+    let mut sesion = Sesion::nueva(salida, enviar_aviso);
+    loop {
+        tokio::select! {
+            peticion = peticiones.recv() => match peticion {
+                Some(peticion) if peticion["type"] == "request" => {
+                    if !sesion.atender(peticion).await? {
+                        break;
+                    }
+                }
+                Some(_) => {}
+                None => break,
+            },
+            Some(aviso) = avisos.recv() => sesion.avisar(aviso).await?,
+        }
+    }
+
+    sesion.detener();
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::{json, Value};
+    use tokio::io::{AsyncBufRead, AsyncWrite};
+
+    async fn pedir<W: AsyncWrite + Unpin>(
+        salida: &mut W,
+        seq: u64,
+        comando: &str,
+        argumentos: Value,
+    ) {
+        let peticion = json!({
+            "seq": seq,
+            "type": "request",
+            "command": comando,
+            "arguments": argumentos,
+        });
+        protocolo::escribir_mensaje(salida, &peticion)
+            .await
+            .unwrap();
+    }
+
+    // Descarta mensajes hasta el primero que cumple `condicion`.
+    async fn esperar<R: AsyncBufRead + Unpin>(
+        entrada: &mut R,
+        condicion: impl Fn(&Value) -> bool,
+    ) -> Value {
+        loop {
+            let mensaje = protocolo::leer_mensaje(entrada).await.unwrap().unwrap();
+            if condicion(&mensaje) {
+                return mensaje;
+            }
+        }
+    }
+
+    fn respuesta(comando: &'static str) -> impl Fn(&Value) -> bool {
+        move |mensaje| mensaje["type"] == "response" && mensaje["command"] == comando
+    }
+
+    fn evento(nombre: &'static str) -> impl Fn(&Value) -> bool {
+        move |mensaje| mensaje["type"] == "event" && mensaje["event"] == nombre
+    }
+
+    #[tokio::test]
+    async fn test_sesion_con_punto_de_ruptura() {
+        let directorio = tempfile::tempdir().unwrap();
+        let ruta = directorio.path().join("sesion.um");
+        std::fs::write(&ruta, "v: a = 1;\n\nv: b = a + 1;\ntprint(b);\n").unwrap();
+
+        let (cliente, servidor) = tokio::io::duplex(1 << 16);
+        let (lectura_servidor, escritura_servidor) = tokio::io::split(servidor);
+        tokio::spawn(servir(lectura_servidor, escritura_servidor));
+        let (lectura, mut escritura) = tokio::io::split(cliente);
+        let mut lectura = BufReader::new(lectura);
+
+        pedir(&mut escritura, 1, "initialize", json!({})).await;
+        esperar(&mut lectura, evento("initialized")).await;
+        pedir(&mut escritura, 2, "launch", json!({ "program": ruta })).await;
+        assert_eq!(
+            esperar(&mut lectura, respuesta("launch")).await["success"],
+            true
+        );
+
+        let puntos = json!({ "source": { "path": ruta }, "breakpoints": [{ "line": 2 }] });
+        pedir(&mut escritura, 3, "setBreakpoints", puntos).await;
+        let puntos = esperar(&mut lectura, respuesta("setBreakpoints")).await;
+        assert_eq!(
+            puntos["body"]["breakpoints"][0],
+            json!({ "verified": true, "line": 3 })
+        );
+
+        pedir(&mut escritura, 4, "configurationDone", json!({})).await;
+        let detenido = esperar(&mut lectura, evento("stopped")).await;
+        assert_eq!(detenido["body"]["reason"], "breakpoint");
+
+        pedir(&mut escritura, 5, "stackTrace", json!({ "threadId": 1 })).await;
+        let pila = esperar(&mut lectura, respuesta("stackTrace")).await;
+        assert_eq!(pila["body"]["stackFrames"][0]["line"], 3);
+
+        pedir(&mut escritura, 6, "scopes", json!({ "frameId": 0 })).await;
+        let ambitos = esperar(&mut lectura, respuesta("scopes")).await;
+        let globales = &ambitos["body"]["scopes"][0];
+        assert_eq!(globales["name"], "Globales");
+
+        let referencia = json!({ "variablesReference": globales["variablesReference"] });
+        pedir(&mut escritura, 7, "variables", referencia).await;
+        let variables = esperar(&mut lectura, respuesta("variables")).await;
+        let variables = variables["body"]["variables"].as_array().unwrap().clone();
+        let variable = |nombre: &str| variables.iter().find(|v| v["name"] == nombre).cloned();
+        assert_eq!(
+            variable("a"),
+            Some(json!({ "name": "a", "value": "1", "type": "Int", "variablesReference": 0 }))
+        );
+        // `b` aún no se ha declarado al detenerse en su línea.
+        assert_eq!(variable("b"), None);
+
+        // Los valores compuestos se expanden con su propia referencia.
+        let std = variable("Std").unwrap();
+        assert_ne!(std["variablesReference"], 0);
+        let referencia = json!({ "variablesReference": std["variablesReference"] });
+        pedir(&mut escritura, 8, "variables", referencia).await;
+        let miembros = esperar(&mut lectura, respuesta("variables")).await;
+        assert!(miembros["body"]["variables"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .any(|miembro| miembro["name"] == "math" && miembro["type"] == "Dict"));
+
+        let expresion = json!({ "expression": "a + 41", "frameId": 0 });
+        pedir(&mut escritura, 9, "evaluate", expresion).await;
+        let evaluada = esperar(&mut lectura, respuesta("evaluate")).await;
+        assert_eq!(evaluada["body"]["result"], "42");
+
+        pedir(&mut escritura, 10, "continue", json!({ "threadId": 1 })).await;
+        let salida = esperar(&mut lectura, evento("output")).await;
+        assert_eq!(salida["body"]["output"], "2\n");
+        let fin = esperar(&mut lectura, evento("exited")).await;
+        assert_eq!(fin["body"]["exitCode"], 0);
+    }
+}
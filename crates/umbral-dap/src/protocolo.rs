@@ -0,0 +1,93 @@
+//! Mensajes DAP: JSON precedido de la cabecera `Content-Length`.
+
+use serde_json::{json, Value};
+use std::io;
+use tokio::io::{AsyncBufRead, AsyncBufReadExt, AsyncReadExt, AsyncWrite, AsyncWriteExt};
+
+/// Lee el siguiente mensaje; `None` si la entrada terminó.
+pub async fn leer_mensaje<R: AsyncBufRead + Unpin>(entrada: &mut R) -> io::Result<Option<Value>> {
+    let mut longitud = None;
+    loop {
+        let mut linea = String::new();
+        if entrada.read_line(&mut linea).await? == 0 {
+            return Ok(None);
+        }
+        let linea = linea.trim_end();
+        if linea.is_empty() {
+            if longitud.is_some() {
+                break;
+            }
+            continue;
+        }
+        if let Some((nombre, valor)) = linea.split_once(':') {
+            if nombre.eq_ignore_ascii_case("Content-Length") {
+                longitud = valor.trim().parse::<usize>().ok();
+            }
+        }
+    }
+
+    let mut cuerpo = vec![0; longitud.unwrap_or_default()];
+    entrada.read_exact(&mut cuerpo).await?;
+    serde_json::from_slice(&cuerpo)
+        .map(Some)
+        .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))
+}
+
+pub async fn escribir_mensaje<W: AsyncWrite + Unpin>(
+    salida: &mut W,
+    mensaje: &Value,
+) -> io::Result<()> {
+    let cuerpo = mensaje.to_string();
+    let cabecera = format!("Content-Length: {}\r\n\r\n", cuerpo.len());
+    salida.write_all(cabecera.as_bytes()).await?;
+    salida.write_all(cuerpo.as_bytes()).await?;
+    salida.flush().await
+}
+
+/// Escribe los mensajes del adaptador numerándolos con `seq`.
+pub struct Conexion<W> {
+    salida: W,
+    secuencia: u64,
+}
+
+impl<W: AsyncWrite + Unpin> Conexion<W> {
+    pub fn nueva(salida: W) -> Self {
+        Self {
+            salida,
+            secuencia: 0,
+        }
+    }
+
+    pub async fn responder(&mut self, peticion: &Value, cuerpo: Value) -> io::Result<()> {
+        self.enviar(json!({
+            "type": "response",
+            "request_seq": peticion["seq"],
+            "command": peticion["command"],
+            "success": true,
+            "body": cuerpo,
+        }))
+        .await
+    }
+
+    pub async fn rechazar(&mut self, peticion: &Value, mensaje: &str) -> io::Result<()> {
+        self.enviar(json!({
+            "type": "response",
+            "request_seq": peticion["seq"],
+            "command": peticion["command"],
+            "success": false,
+            "message": mensaje,
+        }))
+        .await
+    }
+
+    pub async fn evento(&mut self, evento: &str, cuerpo: Value) -> io::Result<()> {
+        self.enviar(json!({ "type": "event", "event": evento, "body": cuerpo }))
+            .await
+    }
+
+    async fn enviar(&mut self, mut mensaje: Value) -> io::Result<()> {
+        self.secuencia += 1;
+        mensaje["seq"] = json!(self.secuencia);
+        escribir_mensaje(&mut self.salida, &mensaje).await
+    }
+}
@@ -0,0 +1,418 @@
+//! Estado de una sesión de depuración y respuesta a cada petición.
+
+use crate::protocolo::Conexion;
+use serde_json::{json, Value};
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+use tokio::io::AsyncWrite;
+use tokio::sync::mpsc::{self, UnboundedSender};
+use tokio::task::AbortHandle;
+use umbral_interpreter::depuracion::{self, Cliente, Detencion, Motivo, Paso};
+use umbral_interpreter::{lineas_ejecutables, Interpreter, InterpreterError};
+
+// El intérprete solo tiene un hilo visible para el depurador.
+const HILO: u64 = 1;
+
+/// Lo que ocurre en el programa mientras la sesión espera peticiones.
+pub enum Aviso {
+    Detenido(Detencion),
+    Salida(String),
+    /// El programa terminó, quizá con un error sin capturar.
+    Fin(Result<(), InterpreterError>),
+}
+
+struct Programa {
+    ruta: PathBuf,
+    codigo: String,
+    argumentos: Vec<String>,
+    detener_al_entrar: bool,
+}
+
+pub struct Sesion<W> {
+    conexion: Conexion<W>,
+    avisos: UnboundedSender<Aviso>,
+    programa: Option<Programa>,
+    puntos: Vec<usize>,
+    cliente: Option<Cliente>,
+    ejecucion: Option<AbortHandle>,
+}
+
+impl<W: AsyncWrite + Unpin> Sesion<W> {
+    pub fn nueva(salida: W, avisos: UnboundedSender<Aviso>) -> Self {
+        Self {
+            conexion: Conexion::nueva(salida),
+            avisos,
+            programa: None,
+            puntos: Vec::new(),
+            cliente: None,
+            ejecucion: None,
+        }
+    }
+
+    /// Responde a una petición. Devuelve `false` cuando la sesión termina.
+    pub async fn atender(&mut self, peticion: Value) -> io::Result<bool> {
+        let argumentos = &peticion["arguments"];
+        match peticion["command"].as_str().unwrap_or_default() {
+            "initialize" => {
+                let capacidades = json!({
+                    "supportsConfigurationDoneRequest": true,
+                    "supportsEvaluateForHovers": true,
+                    "supportsTerminateRequest": true,
+                });
+                self.conexion.responder(&peticion, capacidades).await?;
+                self.conexion.evento("initialized", json!({})).await?;
+            }
+            "launch" => match leer_programa(argumentos) {
+                Ok(programa) => {
+                    self.programa = Some(programa);
+                    self.conexion.responder(&peticion, json!({})).await?;
+                }
+                Err(mensaje) => self.conexion.rechazar(&peticion, &mensaje).await?,
+            },
+            "setBreakpoints" => {
+                let puntos = self.establecer_puntos(argumentos);
+                self.conexion
+                    .responder(&peticion, json!({ "breakpoints": puntos }))
+                    .await?;
+            }
+            "configurationDone" => {
+                self.conexion.responder(&peticion, json!({})).await?;
+                self.iniciar();
+            }
+            "threads" => {
+                let hilos = json!({ "threads": [{ "id": HILO, "name": "principal" }] });
+                self.conexion.responder(&peticion, hilos).await?;
+            }
+            "stackTrace" => {
+                let marcos = self.marcos().await;
+                let cuerpo = json!({ "stackFrames": marcos, "totalFrames": marcos.len() });
+                self.conexion.responder(&peticion, cuerpo).await?;
+            }
+            "scopes" => {
+                let ambitos = self.ambitos(argumentos["frameId"].as_u64()).await;
+                self.conexion
+                    .responder(&peticion, json!({ "scopes": ambitos }))
+                    .await?;
+            }
+            "variables" => {
+                let referencia = argumentos["variablesReference"]
+                    .as_u64()
+                    .unwrap_or_default();
+                let variables = self.variables(referencia as usize).await;
+                self.conexion
+                    .responder(&peticion, json!({ "variables": variables }))
+                    .await?;
+            }
+            "evaluate" => {
+                let expresion = argumentos["expression"].as_str().unwrap_or_default();
+                match self.evaluar(expresion).await {
+                    Ok(resultado) => self.conexion.responder(&peticion, resultado).await?,
+                    Err(mensaje) => self.conexion.rechazar(&peticion, &mensaje).await?,
+                }
+            }
+            "continue" => {
+                self.reanudar(Paso::Continuar);
+                self.conexion
+                    .responder(&peticion, json!({ "allThreadsContinued": true }))
+                    .await?;
+            }
+            "next" | "stepIn" | "stepOut" => {
+                self.reanudar(match peticion["command"].as_str() {
+                    Some("next") => Paso::Saltar,
+                    Some("stepIn") => Paso::Entrar,
+                    _ => Paso::Salir,
+                });
+                self.conexion.responder(&peticion, json!({})).await?;
+            }
+            "pause" => {
+                if let Some(cliente) = &self.cliente {
+                    cliente.pausar();
+                }
+                self.conexion.responder(&peticion, json!({})).await?;
+            }
+            "terminate" => {
+                // Al abortar la ejecución llega `Aviso::Fin` y con él el
+                // evento `terminated`.
+                let en_ejecucion = self.ejecucion.is_some();
+                self.detener();
+                self.conexion.responder(&peticion, json!({})).await?;
+                if !en_ejecucion {
+                    self.conexion.evento("terminated", json!({})).await?;
+                }
+            }
+            "disconnect" => {
+                self.detener();
+                self.conexion.responder(&peticion, json!({})).await?;
+                return Ok(false);
+            }
+            otro => {
+                let mensaje = format!("Petición no soportada: {}", otro);
+                self.conexion.rechazar(&peticion, &mensaje).await?;
+            }
+        }
+        Ok(true)
+    }
+
+    pub async fn avisar(&mut self, aviso: Aviso) -> io::Result<()> {
+        match aviso {
+            Aviso::Detenido(detencion) => {
+                let motivo = match detencion.motivo {
+                    Motivo::Entrada => "entry",
+                    Motivo::PuntoDeRuptura => "breakpoint",
+                    Motivo::Paso => "step",
+                    Motivo::Pausa => "pause",
+                };
+                let cuerpo = json!({
+                    "reason": motivo,
+                    "threadId": HILO,
+                    "allThreadsStopped": true,
+                });
+                self.conexion.evento("stopped", cuerpo).await
+            }
+            Aviso::Salida(linea) => {
+                let cuerpo = json!({ "category": "stdout", "output": format!("{}\n", linea) });
+                self.conexion.evento("output", cuerpo).await
+            }
+            Aviso::Fin(resultado) => {
+                self.cliente = None;
+                self.ejecucion = None;
+                let codigo = match resultado {
+                    Ok(()) => 0,
+                    Err(InterpreterError::Salida(codigo)) => codigo,
+                    Err(error) => {
+                        let cuerpo =
+                            json!({ "category": "stderr", "output": format!("{}\n", error) });
+                        self.conexion.evento("output", cuerpo).await?;
+                        1
+                    }
+                };
+                self.conexion
+                    .evento("exited", json!({ "exitCode": codigo }))
+                    .await?;
+                self.conexion.evento("terminated", json!({})).await
+            }
+        }
+    }
+
+    pub fn detener(&mut self) {
+        if let Some(ejecucion) = self.ejecucion.take() {
+            ejecucion.abort();
+        }
+        self.cliente = None;
+    }
+
+    // Solo el archivo principal admite puntos de ruptura; cada uno se mueve
+    // a la primera línea con una sentencia a partir de la pedida.
+    fn establecer_puntos(&mut self, argumentos: &Value) -> Vec<Value> {
+        let pedidas: Vec<u64> = match argumentos["breakpoints"].as_array() {
+            Some(puntos) => puntos.iter().filter_map(|p| p["line"].as_u64()).collect(),
+            None => argumentos["lines"]
+                .as_array()
+                .map(|lineas| lineas.iter().filter_map(Value::as_u64).collect())
+                .unwrap_or_default(),
+        };
+
+        let ruta = argumentos["source"]["path"].as_str().map(PathBuf::from);
+        let es_principal = match (&ruta, &self.programa) {
+            (Some(ruta), Some(programa)) => {
+                fs::canonicalize(ruta).ok().as_ref() == Some(&programa.ruta)
+            }
+            (Some(_), None) => true,
+            (None, _) => false,
+        };
+        if !es_principal {
+            return pedidas
+                .into_iter()
+                .map(|linea| {
+                    json!({
+                        "verified": false,
+                        "line": linea,
+                        "message": "Solo se puede depurar el archivo principal",
+                    })
+                })
+                .collect();
+        }
+
+        let ejecutables = ruta
+            .and_then(|ruta| fs::read_to_string(ruta).ok())
+            .and_then(|codigo| lineas_ejecutables(&codigo).ok())
+            .unwrap_or_default();
+        let mut puntos = Vec::new();
+        let respuesta = pedidas
+            .into_iter()
+            .map(
+                |pedida| match ejecutables.iter().find(|&&linea| linea as u64 >= pedida) {
+                    Some(&linea) => {
+                        puntos.push(linea);
+                        json!({ "verified": true, "line": linea })
+                    }
+                    None => json!({
+                        "verified": false,
+                        "line": pedida,
+                        "message": "No hay ninguna sentencia en esta línea ni después",
+                    }),
+                },
+            )
+            .collect();
+
+        if let Some(cliente) = &self.cliente {
+            cliente.establecer_puntos(puntos.iter().copied());
+        }
+        self.puntos = puntos;
+        respuesta
+    }
+
+    fn iniciar(&mut self) {
+        let Some(programa) = &self.programa else {
+            return;
+        };
+        if self.cliente.is_some() {
+            return;
+        }
+
+        let (depuracion, cliente, mut detenciones) = depuracion::crear(programa.detener_al_entrar);
+        cliente.establecer_puntos(self.puntos.iter().copied());
+        let (enviar_salida, mut salida) = mpsc::unbounded_channel();
+
+        let mut interprete = Interpreter::nuevo();
+        interprete.depurar(depuracion);
+        interprete.redirigir_salida(enviar_salida);
+        interprete.establecer_argumentos(programa.argumentos.clone());
+        interprete.establecer_archivo(programa.ruta.clone());
+        let codigo = programa.codigo.clone();
+        let ejecucion = tokio::spawn(async move { interprete.ejecutar(&codigo).await });
+        self.ejecucion = Some(ejecucion.abort_handle());
+        self.cliente = Some(cliente);
+
+        // La salida tiene prioridad para que una línea escrita justo antes
+        // de detenerse aparezca antes que la detención.
+        let avisos = self.avisos.clone();
+        tokio::spawn(async move {
+            loop {
+                tokio::select! {
+                    biased;
+                    Some(linea) = salida.recv() => {
+                        let _ = avisos.send(Aviso::Salida(linea));
+                    }
+                    Some(detencion) = detenciones.recv() => {
+                        let _ = avisos.send(Aviso::Detenido(detencion));
+                    }
+                    else => break,
+                }
+            }
+            // Si la ejecución se abortó, el programa termina sin error.
+            let resultado = ejecucion.await.unwrap_or(Ok(()));
+            let _ = avisos.send(Aviso::Fin(resultado));
+        });
+    }
+
+    fn reanudar(&self, paso: Paso) {
+        if let Some(cliente) = &self.cliente {
+            cliente.reanudar(paso);
+        }
+    }
+
+    async fn marcos(&self) -> Vec<Value> {
+        let (Some(cliente), Some(programa)) = (&self.cliente, &self.programa) else {
+            return Vec::new();
+        };
+        let fuente = json!({
+            "name": programa.ruta.file_name().map(|nombre| nombre.to_string_lossy()),
+            "path": programa.ruta,
+        });
+        cliente
+            .pila()
+            .await
+            .into_iter()
+            .enumerate()
+            .map(|(id, marco)| {
+                json!({
+                    "id": id,
+                    "name": marco.nombre,
+                    "line": marco.linea,
+                    "column": 1,
+                    "source": fuente,
+                })
+            })
+            .collect()
+    }
+
+    // Las variables solo se pueden ver en el marco actual (el 0): los
+    // entornos de los demás no son accesibles mientras el programa espera.
+    async fn ambitos(&self, marco: Option<u64>) -> Vec<Value> {
+        let Some(cliente) = self.cliente.as_ref().filter(|_| marco == Some(0)) else {
+            return Vec::new();
+        };
+        cliente
+            .ambitos()
+            .await
+            .into_iter()
+            .map(|ambito| {
+                json!({
+                    "name": ambito.nombre,
+                    "variablesReference": ambito.referencia,
+                    "expensive": false,
+                })
+            })
+            .collect()
+    }
+
+    async fn variables(&self, referencia: usize) -> Vec<Value> {
+        let Some(cliente) = &self.cliente else {
+            return Vec::new();
+        };
+        cliente
+            .variables(referencia)
+            .await
+            .into_iter()
+            .map(|variable| {
+                json!({
+                    "name": variable.nombre,
+                    "value": variable.valor,
+                    "type": variable.tipo,
+                    "variablesReference": variable.referencia,
+                })
+            })
+            .collect()
+    }
+
+    async fn evaluar(&self, expresion: &str) -> Result<Value, String> {
+        let Some(cliente) = &self.cliente else {
+            return Err("El programa no está en ejecución".to_string());
+        };
+        let variable = cliente.evaluar(expresion).await?;
+        Ok(json!({
+            "result": variable.valor,
+            "type": variable.tipo,
+            "variablesReference": variable.referencia,
+        }))
+    }
+}
+
+fn leer_programa(argumentos: &Value) -> Result<Programa, String> {
+    let Some(ruta) = argumentos["program"].as_str() else {
+        return Err("Falta el argumento 'program' con el archivo a depurar".to_string());
+    };
+    let mut ruta = PathBuf::from(ruta);
+    if let Some(directorio) = argumentos["cwd"].as_str().filter(|_| ruta.is_relative()) {
+        ruta = Path::new(directorio).join(ruta);
+    }
+
+    let codigo = fs::read_to_string(&ruta)
+        .map_err(|e| format!("Error al leer el archivo '{}': {}", ruta.display(), e))?;
+    Ok(Programa {
+        ruta: fs::canonicalize(&ruta).unwrap_or(ruta),
+        codigo,
+        argumentos: argumentos["args"]
+            .as_array()
+            .map(|args| {
+                args.iter()
+                    .filter_map(Value::as_str)
+                    .map(String::from)
+                    .collect()
+            })
+            .unwrap_or_default(),
+        detener_al_entrar: argumentos["stopOnEntry"].as_bool().unwrap_or(false),
+    })
+}
@@ -0,0 +1,386 @@
+//! Documento intermedio del formateador: fragmentos de texto (hojas) con
+//! saltos de línea opcionales agrupados, al estilo de Wadler. Un grupo se
+//! imprime en una sola línea si cabe en el ancho; si no, rompe todos sus
+//! saltos.
+
+pub const ANCHO: usize = 100;
+pub const SANGRIA: usize = 4;
+
+/// Fragmento de código indivisible. Las hojas son la unidad con la que se
+/// alinean los tokens del código original para recuperar comentarios.
+#[derive(Debug, Clone, Default)]
+pub struct Hoja {
+    pub texto: String,
+    /// Primera hoja de una sentencia, miembro o caso: puede llevar una
+    /// línea en blanco delante.
+    pub inicio_sentencia: bool,
+    /// `}` que cierra un bloque: sus comentarios van sangrados dentro.
+    pub cierre: bool,
+    /// Cadena que el original pudo escribir con cualquier comilla o como
+    /// identificador (rutas de módulos y claves de objetos).
+    pub cadena_libre: bool,
+}
+
+#[derive(Debug)]
+pub enum Doc {
+    Hoja(usize),
+    Lista(Vec<Doc>),
+    /// Espacio si el grupo cabe en la línea; salto si no.
+    Linea,
+    /// Nada si el grupo cabe en la línea; salto si no.
+    LineaSuave,
+    LineaDura,
+    Sangria(Vec<Doc>),
+    Grupo(Grupo),
+}
+
+#[derive(Debug)]
+pub struct Grupo {
+    pub partes: Vec<Doc>,
+    pub romper: bool,
+    /// Hoja que abre un array u objeto: si en el original había un salto
+    /// de línea tras ella, el grupo se mantiene desplegado.
+    pub apertura: Option<usize>,
+}
+
+impl Doc {
+    pub fn grupo(partes: Vec<Doc>) -> Doc {
+        Doc::Grupo(Grupo {
+            partes,
+            romper: false,
+            apertura: None,
+        })
+    }
+
+    pub fn primera_hoja(&self) -> Option<usize> {
+        match self {
+            Doc::Hoja(id) => Some(*id),
+            Doc::Lista(partes) | Doc::Sangria(partes) => partes.iter().find_map(Doc::primera_hoja),
+            Doc::Grupo(grupo) => grupo.partes.iter().find_map(Doc::primera_hoja),
+            _ => None,
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct ComentarioPropio {
+    pub texto: String,
+    pub linea_en_blanco: bool,
+}
+
+/// Lo que el código original tenía alrededor de una hoja y que el árbol
+/// sintáctico no conserva.
+#[derive(Debug, Clone, Default)]
+pub struct Trivia {
+    /// Comentarios en líneas propias justo antes de la hoja.
+    pub antes: Vec<ComentarioPropio>,
+    /// Comentarios al final de la línea de la hoja.
+    pub despues: Vec<String>,
+    pub linea_en_blanco: bool,
+    pub salto_despues: bool,
+}
+
+#[derive(Debug, Default)]
+pub struct Anotaciones {
+    pub hojas: Vec<Trivia>,
+    /// Comentarios tras la última hoja del archivo.
+    pub finales: Vec<ComentarioPropio>,
+}
+
+/// Imprime el documento respetando el ancho y los comentarios anotados.
+pub fn renderizar(doc: &mut Doc, hojas: &[Hoja], anotaciones: &Anotaciones) -> String {
+    marcar(doc, &anotaciones.hojas);
+
+    let mut escritor = Escritor {
+        hojas,
+        anotaciones,
+        salida: String::new(),
+        inicio_linea: 0,
+        pendientes: Vec::new(),
+    };
+    escritor.escribir(doc);
+    escritor.terminar()
+}
+
+// Resumen de un subdocumento para decidir qué grupos deben romperse.
+#[derive(Default)]
+struct Resumen {
+    duro: bool,
+    hojas: bool,
+    antes_primera: bool,
+    antes_resto: bool,
+    despues_ultima: bool,
+    despues_resto: bool,
+}
+
+impl Resumen {
+    fn unir(partes: impl Iterator<Item = Resumen>) -> Resumen {
+        let mut total = Resumen::default();
+        for parte in partes {
+            total.duro |= parte.duro;
+            if !parte.hojas {
+                continue;
+            }
+            if total.hojas {
+                total.antes_resto |= parte.antes_primera || parte.antes_resto;
+                total.despues_resto |= total.despues_ultima || parte.despues_resto;
+            } else {
+                total.antes_primera = parte.antes_primera;
+                total.antes_resto = parte.antes_resto;
+                total.despues_resto = parte.despues_resto;
+            }
+            total.despues_ultima = parte.despues_ultima;
+            total.hojas = true;
+        }
+        total
+    }
+}
+
+// Fuerza a romperse los grupos con saltos duros dentro o con comentarios
+// que no caben en una sola línea: los que van antes de una hoja que no es
+// la primera del grupo y los de final de línea tras una que no es la última.
+fn marcar(doc: &mut Doc, trivia: &[Trivia]) -> Resumen {
+    match doc {
+        Doc::Hoja(id) => Resumen {
+            hojas: true,
+            antes_primera: !trivia[*id].antes.is_empty(),
+            despues_ultima: !trivia[*id].despues.is_empty(),
+            ..Resumen::default()
+        },
+        Doc::LineaDura => Resumen {
+            duro: true,
+            ..Resumen::default()
+        },
+        Doc::Linea | Doc::LineaSuave => Resumen::default(),
+        Doc::Lista(partes) | Doc::Sangria(partes) => {
+            Resumen::unir(partes.iter_mut().map(|parte| marcar(parte, trivia)))
+        }
+        Doc::Grupo(grupo) => {
+            let resumen = Resumen::unir(grupo.partes.iter_mut().map(|parte| marcar(parte, trivia)));
+            grupo.romper = resumen.duro
+                || resumen.antes_resto
+                || resumen.despues_resto
+                || grupo.apertura.is_some_and(|hoja| trivia[hoja].salto_despues);
+            resumen
+        }
+    }
+}
+
+#[derive(Clone, Copy, PartialEq)]
+enum Modo {
+    Plano,
+    Roto,
+}
+
+struct Orden<'d> {
+    sangria: usize,
+    modo: Modo,
+    doc: &'d Doc,
+}
+
+struct Escritor<'a> {
+    hojas: &'a [Hoja],
+    anotaciones: &'a Anotaciones,
+    salida: String,
+    inicio_linea: usize,
+    /// Comentarios de final de línea que esperan al próximo salto.
+    pendientes: Vec<String>,
+}
+
+impl Escritor<'_> {
+    fn escribir(&mut self, doc: &Doc) {
+        let mut pila = vec![Orden {
+            sangria: 0,
+            modo: Modo::Roto,
+            doc,
+        }];
+
+        while let Some(Orden { sangria, modo, doc }) = pila.pop() {
+            match doc {
+                Doc::Hoja(id) => self.hoja(*id, sangria),
+                Doc::Lista(partes) => {
+                    for parte in partes.iter().rev() {
+                        pila.push(Orden { sangria, modo, doc: parte });
+                    }
+                }
+                Doc::Linea if modo == Modo::Plano => self.salida.push(' '),
+                Doc::LineaSuave if modo == Modo::Plano => {}
+                Doc::Linea | Doc::LineaSuave | Doc::LineaDura => self.salto(sangria),
+                Doc::Sangria(partes) => {
+                    for parte in partes.iter().rev() {
+                        pila.push(Orden {
+                            sangria: sangria + SANGRIA,
+                            modo,
+                            doc: parte,
+                        });
+                    }
+                }
+                Doc::Grupo(grupo) => {
+                    let modo = if grupo.romper {
+                        Modo::Roto
+                    } else if modo == Modo::Plano {
+                        Modo::Plano
+                    } else {
+                        let restante = ANCHO as isize - self.columna() as isize;
+                        if self.cabe(doc, &pila, restante) {
+                            Modo::Plano
+                        } else {
+                            Modo::Roto
+                        }
+                    };
+                    for parte in grupo.partes.iter().rev() {
+                        pila.push(Orden { sangria, modo, doc: parte });
+                    }
+                }
+            }
+        }
+    }
+
+    // Comprueba si `doc` en modo plano, seguido de lo que queda en la pila
+    // hasta el próximo salto, cabe en `restante` columnas.
+    fn cabe(&self, doc: &Doc, resto: &[Orden], mut restante: isize) -> bool {
+        let mut pila = vec![(Modo::Plano, doc)];
+        let mut siguiente = resto.len();
+
+        loop {
+            let (modo, doc) = match pila.pop() {
+                Some(orden) => orden,
+                None if siguiente == 0 => return true,
+                None => {
+                    siguiente -= 1;
+                    (resto[siguiente].modo, resto[siguiente].doc)
+                }
+            };
+
+            match doc {
+                Doc::Hoja(id) => {
+                    let texto = &self.hojas[*id].texto;
+                    match texto.split_once('\n') {
+                        Some((primera, _)) => return restante >= primera.chars().count() as isize,
+                        None => restante -= texto.chars().count() as isize,
+                    }
+                }
+                Doc::Linea if modo == Modo::Plano => restante -= 1,
+                Doc::LineaSuave if modo == Modo::Plano => {}
+                Doc::Linea | Doc::LineaSuave | Doc::LineaDura => return true,
+                Doc::Lista(partes) | Doc::Sangria(partes) => {
+                    pila.extend(partes.iter().rev().map(|parte| (modo, parte)));
+                }
+                Doc::Grupo(grupo) => {
+                    let modo = if grupo.romper { Modo::Roto } else { modo };
+                    pila.extend(grupo.partes.iter().rev().map(|parte| (modo, parte)));
+                }
+            }
+
+            if restante < 0 {
+                return false;
+            }
+        }
+    }
+
+    fn hoja(&mut self, id: usize, sangria: usize) {
+        let hoja = &self.hojas[id];
+        let trivia = &self.anotaciones.hojas[id];
+
+        if !trivia.antes.is_empty() {
+            let interna = if hoja.cierre { sangria + SANGRIA } else { sangria };
+            if self.en_inicio_de_linea() {
+                self.sangrar(interna);
+            } else {
+                self.salto(interna);
+            }
+            for comentario in &trivia.antes {
+                if comentario.linea_en_blanco {
+                    self.linea_en_blanco();
+                }
+                self.salida.push_str(&comentario.texto);
+                self.salto(interna);
+            }
+            self.sangrar(sangria);
+        }
+
+        if hoja.inicio_sentencia && trivia.linea_en_blanco && self.en_inicio_de_linea() {
+            self.linea_en_blanco();
+        }
+
+        self.salida.push_str(&hoja.texto);
+        if let Some(ultimo_salto) = hoja.texto.rfind('\n') {
+            self.inicio_linea = self.salida.len() - (hoja.texto.len() - ultimo_salto - 1);
+        }
+        self.pendientes.extend(trivia.despues.iter().cloned());
+    }
+
+    fn salto(&mut self, sangria: usize) {
+        self.volcar_pendientes(sangria);
+        self.recortar_linea();
+        self.salida.push('\n');
+        self.inicio_linea = self.salida.len();
+        self.salida.push_str(&" ".repeat(sangria));
+    }
+
+    fn volcar_pendientes(&mut self, sangria: usize) {
+        let pendientes = std::mem::take(&mut self.pendientes);
+        for (indice, comentario) in pendientes.iter().enumerate() {
+            self.recortar_linea();
+            if indice == 0 && !self.en_inicio_de_linea() {
+                self.salida.push(' ');
+            } else {
+                self.salida.push('\n');
+                self.inicio_linea = self.salida.len();
+                self.salida.push_str(&" ".repeat(sangria));
+            }
+            self.salida.push_str(comentario);
+        }
+    }
+
+    // Inserta una línea en blanco antes de la línea actual, que debe estar
+    // vacía, salvo al principio del archivo o justo tras abrir un bloque.
+    fn linea_en_blanco(&mut self) {
+        let anterior = &self.salida[..self.inicio_linea];
+        let previo = anterior.trim_end();
+        if previo.is_empty() || previo.ends_with(['{', '[', '(']) || anterior.ends_with("\n\n") {
+            return;
+        }
+        self.salida.insert(self.inicio_linea, '\n');
+        self.inicio_linea += 1;
+    }
+
+    fn sangrar(&mut self, sangria: usize) {
+        self.salida.truncate(self.inicio_linea);
+        self.salida.push_str(&" ".repeat(sangria));
+    }
+
+    fn recortar_linea(&mut self) {
+        let fin = self.salida.trim_end_matches([' ', '\t']).len().max(self.inicio_linea);
+        self.salida.truncate(fin);
+    }
+
+    fn en_inicio_de_linea(&self) -> bool {
+        self.salida[self.inicio_linea..].trim().is_empty()
+    }
+
+    fn columna(&self) -> usize {
+        self.salida[self.inicio_linea..].chars().count()
+    }
+
+    fn terminar(mut self) -> String {
+        self.volcar_pendientes(0);
+        for comentario in &self.anotaciones.finales {
+            if !self.en_inicio_de_linea() {
+                self.salto(0);
+            }
+            self.sangrar(0);
+            if comentario.linea_en_blanco {
+                self.linea_en_blanco();
+            }
+            self.salida.push_str(&comentario.texto);
+            self.salto(0);
+        }
+
+        let mut salida = self.salida.trim_end().to_string();
+        if !salida.is_empty() {
+            salida.push('\n');
+        }
+        salida
+    }
+}
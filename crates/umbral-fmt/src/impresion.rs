@@ -0,0 +1,778 @@
+//! Traducción del árbol sintáctico al documento intermedio con la forma
+//! canónica de cada construcción.
+
+use crate::documento::{Doc, Grupo, Hoja};
+use umbral_lexer::{es_caracter_identificador, es_inicio_identificador};
+use umbral_parser::ast::*;
+
+#[derive(Default)]
+pub struct Impresora {
+    pub hojas: Vec<Hoja>,
+}
+
+impl Impresora {
+    pub fn programa(&mut self, programa: &Programa) -> Doc {
+        let mut partes = Vec::new();
+        for (indice, sentencia) in programa.sentencias.iter().enumerate() {
+            if indice > 0 {
+                partes.push(Doc::LineaDura);
+            }
+            partes.push(self.sentencia(sentencia));
+        }
+        Doc::Lista(partes)
+    }
+
+    fn texto(&mut self, texto: impl Into<String>) -> Doc {
+        self.hoja(Hoja {
+            texto: texto.into(),
+            ..Hoja::default()
+        })
+    }
+
+    fn hoja(&mut self, hoja: Hoja) -> Doc {
+        self.hojas.push(hoja);
+        Doc::Hoja(self.hojas.len() - 1)
+    }
+
+    // Marca la primera hoja como comienzo de sentencia.
+    fn iniciar(&mut self, doc: Doc) -> Doc {
+        if let Some(id) = doc.primera_hoja() {
+            self.hojas[id].inicio_sentencia = true;
+        }
+        doc
+    }
+
+    fn cadena_libre(&mut self, valor: &str) -> Doc {
+        self.hoja(Hoja {
+            texto: format!("\"{}\"", escapar(valor, '"')),
+            cadena_libre: true,
+            ..Hoja::default()
+        })
+    }
+
+    fn bloque(&mut self, sentencias: &[Sentencia]) -> Doc {
+        let abre = self.texto("{");
+        let mut interior = Vec::new();
+        for sentencia in sentencias {
+            interior.push(Doc::LineaDura);
+            interior.push(self.sentencia(sentencia));
+        }
+        self.cuerpo(abre, interior)
+    }
+
+    // `{`, miembros sangrados y `}` en su propia línea; `{}` si está vacío.
+    fn cuerpo(&mut self, abre: Doc, interior: Vec<Doc>) -> Doc {
+        let cierra = self.hoja(Hoja {
+            texto: "}".to_string(),
+            cierre: true,
+            ..Hoja::default()
+        });
+        if interior.is_empty() {
+            return Doc::Lista(vec![abre, cierra]);
+        }
+        Doc::Lista(vec![abre, Doc::Sangria(interior), Doc::LineaDura, cierra])
+    }
+
+    // Elementos separados por comas que se despliegan uno por línea cuando
+    // no caben.
+    fn delimitada(&mut self, abre: &str, elementos: Vec<Doc>, cierra: &str, apertura: bool) -> Doc {
+        let abre = self.texto(abre);
+        if elementos.is_empty() {
+            let cierra = self.texto(cierra);
+            return Doc::Lista(vec![abre, cierra]);
+        }
+
+        let id_apertura = abre.primera_hoja();
+        let mut interior = vec![Doc::LineaSuave];
+        let total = elementos.len();
+        for (indice, elemento) in elementos.into_iter().enumerate() {
+            interior.push(elemento);
+            if indice + 1 < total {
+                interior.push(self.texto(","));
+                interior.push(Doc::Linea);
+            }
+        }
+        let cierra = self.texto(cierra);
+        Doc::Grupo(Grupo {
+            partes: vec![abre, Doc::Sangria(interior), Doc::LineaSuave, cierra],
+            romper: false,
+            apertura: if apertura { id_apertura } else { None },
+        })
+    }
+
+    fn argumentos(&mut self, argumentos: &[Expresion]) -> Doc {
+        let elementos = argumentos.iter().map(|a| self.expresion(a)).collect();
+        self.delimitada("(", elementos, ")", false)
+    }
+
+    fn parametros(&mut self, parametros: &[Parametro], retorno: &Option<Tipo>) -> Doc {
+        let elementos = parametros
+            .iter()
+            .map(|p| self.texto(tipado(&p.nombre, p.tipo.as_ref())))
+            .collect();
+        let lista = self.delimitada("(", elementos, ")", false);
+        match retorno {
+            Some(tipo) => Doc::Lista(vec![lista, self.texto(format!("->{}", tipo.nombre))]),
+            None => lista,
+        }
+    }
+
+    fn exportado(&mut self, exportado: bool, partes: Vec<Doc>) -> Doc {
+        let mut todas = Vec::new();
+        if exportado {
+            todas.push(self.texto("out "));
+        }
+        todas.extend(partes);
+        Doc::Lista(todas)
+    }
+
+    fn sentencia(&mut self, sentencia: &Sentencia) -> Doc {
+        let doc = match sentencia {
+            Sentencia::DeclaracionVariable(d) => {
+                let partes = vec![
+                    self.texto("v: "),
+                    self.texto(tipado(&d.nombre, d.tipo.as_ref())),
+                    self.texto(" = "),
+                    self.expresion(&d.valor),
+                    self.texto(";"),
+                ];
+                self.exportado(d.exportado, partes)
+            }
+            Sentencia::DeclaracionConstante(d) => {
+                let tipo = if d.tipo_inferido { None } else { d.tipo.as_ref() };
+                let partes = vec![
+                    self.texto("c: "),
+                    self.texto(tipado(&d.nombre, tipo)),
+                    self.texto(" = "),
+                    self.expresion(&d.valor),
+                    self.texto(";"),
+                ];
+                self.exportado(d.exportado, partes)
+            }
+            Sentencia::Asignacion(a) => {
+                let mut partes = match &a.objetivo {
+                    ObjetivoAsignacion::Variable(nombre) => vec![self.texto(nombre.clone())],
+                    ObjetivoAsignacion::Propiedad { objeto, propiedad } => vec![
+                        self.expresion(objeto),
+                        self.texto("."),
+                        self.texto(propiedad.clone()),
+                    ],
+                };
+                partes.push(self.texto(" = "));
+                partes.push(self.expresion(&a.valor));
+                partes.push(self.texto(";"));
+                Doc::Lista(partes)
+            }
+            Sentencia::LlamadoTPrint(t) => Doc::Lista(vec![
+                self.texto("tprint("),
+                self.expresion(&t.valor),
+                self.texto(")"),
+                self.texto(";"),
+            ]),
+            Sentencia::Return(valor) => Doc::Lista(vec![
+                self.texto("r: ("),
+                self.expresion(valor),
+                self.texto(")"),
+                self.texto(";"),
+            ]),
+            Sentencia::If(si) => {
+                let mut partes = vec![
+                    self.texto("i: ("),
+                    self.expresion(&si.condicion),
+                    self.texto(") "),
+                    self.bloque(&si.bloque_entonces),
+                ];
+                for rama in &si.else_ifs {
+                    partes.push(self.texto(" ie: ("));
+                    partes.push(self.expresion(&rama.condicion));
+                    partes.push(self.texto(") "));
+                    partes.push(self.bloque(&rama.bloque));
+                }
+                if let Some(bloque) = &si.bloque_else {
+                    partes.push(self.texto(" e: "));
+                    partes.push(self.bloque(bloque));
+                }
+                Doc::Lista(partes)
+            }
+            Sentencia::Switch(s) => {
+                let cabecera = Doc::Lista(vec![
+                    self.texto("sw: ("),
+                    self.expresion(&s.expresion),
+                    self.texto(") {"),
+                ]);
+                let mut interior = Vec::new();
+                for caso in &s.casos {
+                    let etiqueta = vec![
+                        self.texto("ca: "),
+                        self.expresion(&caso.valor),
+                        self.texto(" =>"),
+                    ];
+                    interior.push(Doc::LineaDura);
+                    interior.push(self.caso(etiqueta, &caso.bloque));
+                }
+                if let Some(bloque) = &s.default {
+                    let etiqueta = vec![self.texto("def: =>")];
+                    interior.push(Doc::LineaDura);
+                    interior.push(self.caso(etiqueta, bloque));
+                }
+                self.cuerpo(cabecera, interior)
+            }
+            Sentencia::For(f) => Doc::Lista(vec![
+                self.texto("fo: ("),
+                self.sentencia(&f.inicializacion),
+                self.texto(" "),
+                self.expresion(&f.condicion),
+                self.texto("; "),
+                self.expresion(&f.incremento),
+                self.texto(") "),
+                self.bloque(&f.bloque),
+            ]),
+            Sentencia::ForEach(f) => Doc::Lista(vec![
+                self.texto("fe: ("),
+                self.texto("v: "),
+                self.texto(tipado(&f.variable, f.tipo.as_ref())),
+                self.texto(" <= "),
+                self.expresion(&f.iterable),
+                self.texto(") "),
+                self.bloque(&f.bloque),
+            ]),
+            Sentencia::While(w) => Doc::Lista(vec![
+                self.texto("wh: ("),
+                self.expresion(&w.condicion),
+                self.texto(") "),
+                self.bloque(&w.bloque),
+            ]),
+            Sentencia::DoWhile(d) => Doc::Lista(vec![
+                self.texto("dw: "),
+                self.bloque(&d.bloque),
+                self.texto(" ("),
+                self.expresion(&d.condicion),
+                self.texto(")"),
+            ]),
+            Sentencia::Expresion(expresion) => {
+                Doc::Lista(vec![self.expresion(expresion), self.texto(";")])
+            }
+            Sentencia::Funcion(f) => {
+                let mut partes = Vec::new();
+                if f.es_async {
+                    partes.push(self.texto("asy "));
+                }
+                partes.push(self.texto("f: "));
+                partes.push(self.texto(f.nombre.clone()));
+                partes.push(self.parametros(&f.parametros, &f.tipo_retorno));
+                partes.push(self.texto(" "));
+                partes.push(self.bloque(&f.cuerpo));
+                self.exportado(f.exportado, partes)
+            }
+            Sentencia::Clase(c) => self.clase(c),
+            Sentencia::Interfaz(i) => {
+                let mut cabecera = Vec::new();
+                if i.exportado {
+                    cabecera.push(self.texto("out "));
+                }
+                cabecera.push(self.texto("in: "));
+                cabecera.push(self.texto(i.nombre.clone()));
+                cabecera.push(self.texto(" {"));
+                let mut interior = Vec::new();
+                for metodo in &i.metodos {
+                    let firma = Doc::Lista(vec![
+                        self.texto("f: "),
+                        self.texto(metodo.nombre.clone()),
+                        self.parametros(&metodo.parametros, &metodo.tipo_retorno),
+                        self.texto(";"),
+                    ]);
+                    interior.push(Doc::LineaDura);
+                    interior.push(self.iniciar(firma));
+                }
+                self.cuerpo(Doc::Lista(cabecera), interior)
+            }
+            Sentencia::Enum(e) => {
+                let mut cabecera = Vec::new();
+                if e.exportado {
+                    cabecera.push(self.texto("out "));
+                }
+                cabecera.push(self.texto("em: "));
+                cabecera.push(self.texto(e.nombre.clone()));
+                cabecera.push(self.texto(" {"));
+                let mut interior = Vec::new();
+                for (indice, variante) in e.variantes.iter().enumerate() {
+                    let mut partes = vec![self.texto(variante.nombre.clone())];
+                    if let Some(valor) = &variante.valor {
+                        partes.push(self.texto(" = "));
+                        partes.push(self.expresion(valor));
+                    }
+                    if indice + 1 < e.variantes.len() {
+                        partes.push(self.texto(","));
+                    }
+                    interior.push(Doc::LineaDura);
+                    interior.push(self.iniciar(Doc::Lista(partes)));
+                }
+                self.cuerpo(Doc::Lista(cabecera), interior)
+            }
+            Sentencia::LlamadoFuncion(llamado) => Doc::Lista(vec![
+                self.texto(llamado.nombre.clone()),
+                self.argumentos(&llamado.argumentos),
+                self.texto(";"),
+            ]),
+            Sentencia::Importacion(importacion) => {
+                // Se respeta el orden de las cláusulas tal como se escribió.
+                let mut equip = vec![self.texto("equip ")];
+                for item in &importacion.items {
+                    equip.push(self.item_importacion(item));
+                }
+                let origin = vec![self.texto("origin "), self.cadena_libre(&importacion.ruta)];
+                let (primera, segunda) = if importacion.origen_primero {
+                    (origin, equip)
+                } else {
+                    (equip, origin)
+                };
+                let mut partes = primera;
+                partes.push(self.texto(" "));
+                partes.extend(segunda);
+                partes.push(self.texto(";"));
+                Doc::Lista(partes)
+            }
+            Sentencia::TryCatch(t) => {
+                let mut partes = vec![self.texto("tr: "), self.bloque(&t.bloque_try)];
+                if let Some(captura) = &t.bloque_catch {
+                    let variable = match &captura.tipo {
+                        Some(tipo) => format!("c: {}->{}", captura.variable, tipo),
+                        None => format!("v: {}", captura.variable),
+                    };
+                    partes.push(self.texto(" ct: ("));
+                    partes.push(self.texto(variable));
+                    partes.push(self.texto(") "));
+                    partes.push(self.bloque(&captura.bloque));
+                }
+                if let Some(bloque) = &t.bloque_finally {
+                    partes.push(self.texto(" fy: "));
+                    partes.push(self.bloque(bloque));
+                }
+                Doc::Lista(partes)
+            }
+            Sentencia::Throw(t) => Doc::Lista(vec![
+                self.texto("tw: "),
+                self.expresion(&t.valor),
+                self.texto(";"),
+            ]),
+            Sentencia::Exportacion(nombre) => Doc::Lista(vec![
+                self.texto("out "),
+                self.texto(nombre.clone()),
+                self.texto(";"),
+            ]),
+            Sentencia::ListaExportacion(lista) => {
+                let mut partes = Vec::new();
+                if matches!(lista.items.as_slice(), [ItemExportacion::Todo]) {
+                    partes.push(self.texto("out: *"));
+                } else {
+                    partes.push(self.texto("out "));
+                    let nombres = lista
+                        .items
+                        .iter()
+                        .map(|item| match item {
+                            ItemExportacion::Todo => self.texto("*"),
+                            ItemExportacion::Nombre(nombre, alias) => {
+                                self.texto(con_alias(nombre, alias.as_deref()))
+                            }
+                        })
+                        .collect();
+                    partes.push(self.llaves(nombres));
+                }
+                if let Some(origen) = &lista.origen {
+                    partes.push(self.texto(" origin "));
+                    partes.push(self.cadena_libre(origen));
+                }
+                partes.push(self.texto(";"));
+                Doc::Lista(partes)
+            }
+            Sentencia::ExportacionPorDefecto(interna) => {
+                Doc::Lista(vec![self.texto("out def: "), self.sentencia(interna)])
+            }
+            Sentencia::Ubicada(_, interna) => return self.sentencia(interna),
+        };
+        self.iniciar(doc)
+    }
+
+    fn caso(&mut self, mut etiqueta: Vec<Doc>, bloque: &[Sentencia]) -> Doc {
+        let mut interior = Vec::new();
+        for sentencia in bloque {
+            interior.push(Doc::LineaDura);
+            interior.push(self.sentencia(sentencia));
+        }
+        etiqueta.push(Doc::Sangria(interior));
+        self.iniciar(Doc::Lista(etiqueta))
+    }
+
+    fn clase(&mut self, clase: &DeclaracionClase) -> Doc {
+        let mut cabecera = Vec::new();
+        if clase.exportado {
+            cabecera.push(self.texto("out "));
+        }
+        cabecera.push(self.texto("cs: "));
+        cabecera.push(self.texto(clase.nombre.clone()));
+        if !clase.extensiones.is_empty() {
+            cabecera.push(self.texto(format!(" ext: {}", clase.extensiones.join(", "))));
+        }
+        if !clase.implementaciones.is_empty() {
+            cabecera.push(self.texto(format!(" imp: {}", clase.implementaciones.join(", "))));
+        }
+        cabecera.push(self.texto(" {"));
+
+        let mut interior = Vec::new();
+        for propiedad in &clase.propiedades {
+            let visibilidad = if propiedad.publico { "pu: " } else { "pr: " };
+            let miembro = Doc::Lista(vec![
+                self.texto(visibilidad),
+                self.texto(tipado(&propiedad.nombre, propiedad.tipo.as_ref())),
+                self.texto(";"),
+            ]);
+            interior.push(Doc::LineaDura);
+            interior.push(self.iniciar(miembro));
+        }
+        for metodo in &clase.metodos {
+            let mut partes = vec![self.texto(if metodo.publico { "pu " } else { "pr " })];
+            if metodo.es_async {
+                partes.push(self.texto("asy "));
+            }
+            partes.push(self.texto("f: "));
+            partes.push(self.texto(metodo.nombre.clone()));
+            partes.push(self.parametros(&metodo.parametros, &metodo.tipo_retorno));
+            partes.push(self.texto(" "));
+            partes.push(self.bloque(&metodo.cuerpo));
+            interior.push(Doc::LineaDura);
+            interior.push(self.iniciar(Doc::Lista(partes)));
+        }
+        self.cuerpo(Doc::Lista(cabecera), interior)
+    }
+
+    fn item_importacion(&mut self, item: &ItemImportacion) -> Doc {
+        match item {
+            ItemImportacion::Todo(alias) => self.texto(con_alias("*", alias.as_deref())),
+            ItemImportacion::Nombre(nombre, alias) | ItemImportacion::Modulo(nombre, alias) => {
+                self.texto(con_alias(nombre, alias.as_deref()))
+            }
+            ItemImportacion::ListaNombres(items) => {
+                let nombres = items.iter().map(|item| self.item_importacion(item)).collect();
+                self.llaves(nombres)
+            }
+        }
+    }
+
+    // `{ a, b as c }` en listas de importación y exportación.
+    fn llaves(&mut self, nombres: Vec<Doc>) -> Doc {
+        if nombres.is_empty() {
+            return self.texto("{}");
+        }
+        let abre = self.texto("{");
+        let mut interior = vec![Doc::Linea];
+        let total = nombres.len();
+        for (indice, nombre) in nombres.into_iter().enumerate() {
+            interior.push(nombre);
+            if indice + 1 < total {
+                interior.push(self.texto(","));
+                interior.push(Doc::Linea);
+            }
+        }
+        let cierra = self.texto("}");
+        Doc::grupo(vec![abre, Doc::Sangria(interior), Doc::Linea, cierra])
+    }
+
+    pub fn expresion(&mut self, expresion: &Expresion) -> Doc {
+        match expresion {
+            Expresion::LiteralEntero(valor) => self.texto(valor.to_string()),
+            Expresion::LiteralFloat(valor) => self.texto(flotante(*valor)),
+            Expresion::LiteralDecimal(valor) => self.texto(format!("{}d", valor)),
+            Expresion::LiteralCadena(valor) => {
+                self.texto(plantilla(&[ParteCadena::Texto(valor.clone())]))
+            }
+            Expresion::LiteralCadenaLiteral(valor) => {
+                self.texto(format!("'{}'", escapar(valor, '\'')))
+            }
+            Expresion::Plantilla(partes) => self.texto(plantilla(partes)),
+            Expresion::LiteralBool(valor) => self.texto(valor.to_string()),
+            Expresion::LiteralNulo => self.texto("null"),
+            Expresion::Identificador(nombre) => self.texto(nombre.clone()),
+            Expresion::Binaria { .. } => self.binaria(expresion),
+            Expresion::Unaria { operador, expresion } => {
+                if operador == "++" || operador == "--" {
+                    let interna = self.expresion(expresion);
+                    return Doc::Lista(vec![interna, self.texto(operador.clone())]);
+                }
+                self.prefijo(operador, expresion)
+            }
+            Expresion::Await(interna) => Doc::Lista(vec![self.texto("awa "), self.expresion(interna)]),
+            Expresion::Spread(interna) => self.prefijo("&", interna),
+            Expresion::This => self.texto("th"),
+            Expresion::Agrupada(interna) => Doc::Lista(vec![
+                self.texto("("),
+                self.expresion(interna),
+                self.texto(")"),
+            ]),
+            Expresion::Array(elementos) => {
+                let elementos = elementos.iter().map(|e| self.expresion(e)).collect();
+                self.delimitada("{", elementos, "}", true)
+            }
+            Expresion::Objeto(pares) => {
+                let elementos = pares
+                    .iter()
+                    .map(|(clave, valor)| {
+                        Doc::Lista(vec![
+                            self.cadena_libre(clave),
+                            self.texto(" => "),
+                            self.expresion(valor),
+                        ])
+                    })
+                    .collect();
+                self.delimitada("[", elementos, "]", true)
+            }
+            Expresion::Instanciacion { tipo, argumentos } => Doc::Lista(vec![
+                self.texto("n: "),
+                self.texto(tipo.clone()),
+                self.argumentos(argumentos),
+            ]),
+            Expresion::AccesoPropiedad { objeto, propiedad } => Doc::Lista(vec![
+                self.expresion(objeto),
+                self.texto("."),
+                self.texto(propiedad.clone()),
+            ]),
+            Expresion::AccesoIndice { objeto, indice } => Doc::Lista(vec![
+                self.expresion(objeto),
+                self.texto("["),
+                self.expresion(indice),
+                self.texto("]"),
+            ]),
+            Expresion::Rebanada { objeto, inicio, fin } => {
+                let mut partes = vec![self.expresion(objeto), self.texto("[")];
+                let mut separador = ":";
+                if let Some(inicio) = inicio {
+                    let inicio = self.expresion(inicio);
+                    // `nombre:` se leería como una palabra clave.
+                    if self.ultimo_caracter(&inicio).is_some_and(es_caracter_identificador) {
+                        separador = " :";
+                    }
+                    partes.push(inicio);
+                }
+                partes.push(self.texto(separador));
+                if let Some(fin) = fin {
+                    partes.push(self.expresion(fin));
+                }
+                partes.push(self.texto("]"));
+                Doc::Lista(partes)
+            }
+            Expresion::LlamadoMetodo {
+                objeto,
+                metodo,
+                argumentos,
+            } => Doc::Lista(vec![
+                self.expresion(objeto),
+                self.texto("."),
+                self.texto(metodo.clone()),
+                self.argumentos(argumentos),
+            ]),
+            Expresion::LlamadoFuncion { nombre, argumentos } => {
+                Doc::Lista(vec![self.texto(nombre.clone()), self.argumentos(argumentos)])
+            }
+        }
+    }
+
+    // `!!` abre un comentario, `--` es un decremento y `&&` un «y» lógico:
+    // el operador se separa si la expresión empieza por el mismo carácter.
+    fn prefijo(&mut self, operador: &str, expresion: &Expresion) -> Doc {
+        let interna = self.expresion(expresion);
+        let pegado = interna
+            .primera_hoja()
+            .is_some_and(|id| self.hojas[id].texto.starts_with(operador));
+        let operador = if pegado {
+            format!("{} ", operador)
+        } else {
+            operador.to_string()
+        };
+        Doc::Lista(vec![self.texto(operador), interna])
+    }
+
+    // Encadena los operandos de una misma precedencia (`a + b - c`) para
+    // que se rompan juntos, con el operador al final de la línea.
+    fn binaria(&mut self, expresion: &Expresion) -> Doc {
+        let Expresion::Binaria { operador, .. } = expresion else {
+            return self.expresion(expresion);
+        };
+        let nivel = precedencia(operador);
+
+        let mut resto = Vec::new();
+        let mut actual = expresion;
+        while let Expresion::Binaria {
+            izquierda,
+            operador,
+            derecha,
+        } = actual
+        {
+            if precedencia(operador) != nivel {
+                break;
+            }
+            resto.push((operador, derecha));
+            actual = izquierda;
+        }
+
+        let primero = self.expresion(actual);
+        let mut interior = Vec::new();
+        for (operador, derecha) in resto.into_iter().rev() {
+            interior.push(self.texto(format!(" {}", operador)));
+            interior.push(Doc::Linea);
+            interior.push(self.expresion(derecha));
+        }
+        Doc::grupo(vec![primero, Doc::Sangria(interior)])
+    }
+
+    fn ultimo_caracter(&self, doc: &Doc) -> Option<char> {
+        match doc {
+            Doc::Hoja(id) => self.hojas[*id].texto.chars().last(),
+            Doc::Lista(partes) | Doc::Sangria(partes) => {
+                partes.iter().rev().find_map(|parte| self.ultimo_caracter(parte))
+            }
+            Doc::Grupo(grupo) => grupo
+                .partes
+                .iter()
+                .rev()
+                .find_map(|parte| self.ultimo_caracter(parte)),
+            _ => None,
+        }
+    }
+
+    // Texto de una expresión en una sola línea, para interpolaciones.
+    fn plano(&self, doc: &Doc, salida: &mut String) {
+        match doc {
+            Doc::Hoja(id) => salida.push_str(&self.hojas[*id].texto),
+            Doc::Linea | Doc::LineaDura => salida.push(' '),
+            Doc::LineaSuave => {}
+            Doc::Lista(partes) | Doc::Sangria(partes) => {
+                partes.iter().for_each(|parte| self.plano(parte, salida))
+            }
+            Doc::Grupo(grupo) => grupo.partes.iter().for_each(|parte| self.plano(parte, salida)),
+        }
+    }
+}
+
+fn precedencia(operador: &str) -> u8 {
+    match operador {
+        "||" => 1,
+        "&&" => 2,
+        "==" | "!=" => 3,
+        "<" | "<=" | ">" | ">=" => 4,
+        "+" | "-" => 5,
+        _ => 6,
+    }
+}
+
+fn tipado(nombre: &str, tipo: Option<&Tipo>) -> String {
+    match tipo {
+        Some(tipo) => format!("{}->{}", nombre, tipo.nombre),
+        None => nombre.to_string(),
+    }
+}
+
+fn con_alias(nombre: &str, alias: Option<&str>) -> String {
+    match alias {
+        Some(alias) => format!("{} as {}", nombre, alias),
+        None => nombre.to_string(),
+    }
+}
+
+fn flotante(valor: f64) -> String {
+    let texto = valor.to_string();
+    if texto.contains('.') {
+        texto
+    } else {
+        format!("{}.0", texto)
+    }
+}
+
+// Escapa el contenido de una cadena para el lexer.
+fn escapar(valor: &str, comilla: char) -> String {
+    let mut salida = String::new();
+    for caracter in valor.chars() {
+        match caracter {
+            '\\' => salida.push_str("\\\\"),
+            '\n' => salida.push_str("\\n"),
+            '\t' => salida.push_str("\\t"),
+            '\r' => salida.push_str("\\r"),
+            c if c == comilla => {
+                salida.push('\\');
+                salida.push(c);
+            }
+            c => salida.push(c),
+        }
+    }
+    salida
+}
+
+// Cadena con comillas dobles: el texto literal protege los `&` que se
+// leerían como interpolación y cada expresión usa la forma corta `&a.b`
+// cuando se puede leer sin ambigüedad, o `&{ ... }` si no.
+fn plantilla(partes: &[ParteCadena]) -> String {
+    let mut contenido = String::new();
+    for (indice, parte) in partes.iter().enumerate() {
+        match parte {
+            ParteCadena::Texto(texto) => contenido.push_str(&proteger_ampersands(texto)),
+            ParteCadena::Expresion(expresion) => {
+                let mut impresora = Impresora::default();
+                let doc = impresora.expresion(expresion);
+                let mut fuente = String::new();
+                impresora.plano(&doc, &mut fuente);
+
+                let siguiente = match partes.get(indice + 1) {
+                    Some(ParteCadena::Texto(texto)) => texto.as_str(),
+                    _ => "",
+                };
+                if es_encadenamiento(expresion) && !continua_encadenamiento(siguiente) {
+                    contenido.push('&');
+                    contenido.push_str(&fuente);
+                } else {
+                    contenido.push_str("&{");
+                    contenido.push_str(&fuente);
+                    contenido.push('}');
+                }
+            }
+        }
+    }
+    format!("\"{}\"", escapar(&contenido, '"'))
+}
+
+fn proteger_ampersands(texto: &str) -> String {
+    let caracteres: Vec<char> = texto.chars().collect();
+    let mut salida = String::new();
+    for (indice, &caracter) in caracteres.iter().enumerate() {
+        if caracter == '&' {
+            let anterior = indice.checked_sub(1).map(|i| caracteres[i]);
+            let siguiente = caracteres.get(indice + 1).copied();
+            if anterior == Some('\\')
+                || siguiente.is_some_and(|c| c == '{' || es_inicio_identificador(c))
+            {
+                salida.push('\\');
+            }
+        }
+        salida.push(caracter);
+    }
+    salida
+}
+
+fn es_encadenamiento(expresion: &Expresion) -> bool {
+    match expresion {
+        Expresion::Identificador(_) | Expresion::This | Expresion::LlamadoFuncion { .. } => true,
+        Expresion::AccesoPropiedad { objeto, .. }
+        | Expresion::AccesoIndice { objeto, .. }
+        | Expresion::Rebanada { objeto, .. }
+        | Expresion::LlamadoMetodo { objeto, .. } => es_encadenamiento(objeto),
+        _ => false,
+    }
+}
+
+// El texto que sigue a `&a.b` no debe prolongar la cadena de accesos.
+fn continua_encadenamiento(siguiente: &str) -> bool {
+    let mut caracteres = siguiente.chars();
+    match caracteres.next() {
+        Some('(' | '[') => true,
+        Some('.') => caracteres.next().is_some_and(es_inicio_identificador),
+        Some(c) => es_caracter_identificador(c),
+        None => false,
+    }
+}
@@ -0,0 +1,147 @@
+//! Formateador de código Umbral.
+//!
+//! `formatear` reimprime el programa en su forma canónica conservando los
+//! comentarios `!!`, una línea en blanco donde el original tenía alguna y
+//! la escritura original de números y cadenas. Formatear un resultado ya
+//! formateado lo deja igual.
+
+mod documento;
+mod impresion;
+mod trivia;
+
+use impresion::Impresora;
+use std::fmt;
+use umbral_lexer::{tokenizar_con_comentarios, Comentario, TokenConPosicion};
+use umbral_parser::{ParseError, Parser, Programa};
+
+#[derive(Debug)]
+pub enum ErrorFormato {
+    /// El código tiene errores léxicos o de sintaxis y no se formatea.
+    Sintaxis(Vec<ParseError>),
+    /// El resultado no reproduce el programa original.
+    Interno(String),
+}
+
+impl fmt::Display for ErrorFormato {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ErrorFormato::Sintaxis(errores) => {
+                let mensajes: Vec<String> = errores.iter().map(|e| e.formatear_error()).collect();
+                write!(f, "{}", mensajes.join("\n"))
+            }
+            ErrorFormato::Interno(mensaje) => write!(f, "Error interno del formateador: {}", mensaje),
+        }
+    }
+}
+
+impl std::error::Error for ErrorFormato {}
+
+/// Devuelve `codigo` formateado, terminado en un único salto de línea.
+pub fn formatear(codigo: &str) -> Result<String, ErrorFormato> {
+    let (tokens, comentarios, programa) = analizar(codigo)?;
+
+    let mut impresora = Impresora::default();
+    let mut doc = impresora.programa(&programa);
+    let anotaciones = trivia::anotar(codigo, &tokens, &comentarios, &doc, &mut impresora.hojas);
+    let resultado = documento::renderizar(&mut doc, &impresora.hojas, &anotaciones);
+
+    verificar(&programa, &comentarios, &resultado)?;
+    Ok(resultado)
+}
+
+fn analizar(
+    codigo: &str,
+) -> Result<(Vec<TokenConPosicion>, Vec<Comentario>, Programa), ErrorFormato> {
+    let (tokens, comentarios) = tokenizar_con_comentarios(codigo).map_err(|errores| {
+        let errores = errores.iter().map(|e| {
+            let posicion = codigo[..e.rango.start].chars().count();
+            ParseError::con_contexto(e.mensaje(), posicion, codigo)
+        });
+        ErrorFormato::Sintaxis(errores.collect())
+    })?;
+    let programa = Parser::nuevo_con_posiciones(tokens.clone(), codigo.to_string())
+        .parsear_programa()
+        .map_err(ErrorFormato::Sintaxis)?;
+    Ok((tokens, comentarios, programa))
+}
+
+// El resultado debe leerse como el mismo programa y con los mismos
+// comentarios; si no, es un fallo del formateador y el código no se toca.
+fn verificar(
+    programa: &Programa,
+    comentarios: &[Comentario],
+    resultado: &str,
+) -> Result<(), ErrorFormato> {
+    let (_, nuevos, reformateado) = analizar(resultado).map_err(|error| {
+        ErrorFormato::Interno(format!("el resultado no se puede analizar:\n{}", error))
+    })?;
+
+    if format!("{:?}", programa) != format!("{:?}", reformateado) {
+        return Err(ErrorFormato::Interno(
+            "el resultado no equivale al programa original".to_string(),
+        ));
+    }
+
+    let textos = |comentarios: &[Comentario]| {
+        let mut textos: Vec<String> = comentarios.iter().map(|c| c.texto.clone()).collect();
+        textos.sort();
+        textos
+    };
+    if textos(comentarios) != textos(&nuevos) {
+        return Err(ErrorFormato::Interno(
+            "el resultado no conserva todos los comentarios".to_string(),
+        ));
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::path::Path;
+
+    #[test]
+    fn formatea_los_ejemplos_de_forma_idempotente() {
+        let raiz = Path::new(env!("CARGO_MANIFEST_DIR")).join("../../ejemplos");
+        let mut archivos = Vec::new();
+        for directorio in [raiz.clone(), raiz.join("modulos")] {
+            for entrada in std::fs::read_dir(directorio).unwrap() {
+                let ruta = entrada.unwrap().path();
+                if ruta.extension().is_some_and(|e| e == "um") {
+                    archivos.push(ruta);
+                }
+            }
+        }
+        assert!(!archivos.is_empty());
+
+        for ruta in archivos {
+            let codigo = std::fs::read_to_string(&ruta).unwrap();
+            let formateado = formatear(&codigo)
+                .unwrap_or_else(|error| panic!("{}: {}", ruta.display(), error));
+            let otra_vez = formatear(&formateado).unwrap();
+            assert_eq!(formateado, otra_vez, "{} no es idempotente", ruta.display());
+        }
+    }
+
+    #[test]
+    fn conserva_comentarios_y_lineas_en_blanco() {
+        let codigo = "!! cabecera\nv:   x=1;   !! uno\n\n\n\nf: doble(n->Int)->Int {\n    !! dentro\n  r: (n*2);\n}\n!! fin\n";
+        let esperado = "!! cabecera\nv: x = 1; !! uno\n\nf: doble(n->Int)->Int {\n    !! dentro\n    r: (n * 2);\n}\n!! fin\n";
+        assert_eq!(formatear(codigo).unwrap(), esperado);
+    }
+
+    #[test]
+    fn rompe_las_listas_largas_y_respeta_los_literales() {
+        let codigo = "v: datos = [\"nombre\" => 'Ana', \"edad\" => 0x1F];\nv: lista = {1, 2, 3,};\nv: largo = {\"aaaaaaaaaaaaaaaa\", \"bbbbbbbbbbbbbbbbbbbb\", \"cccccccccccccccccccc\", \"dddddddddddddddddddd\"};\n";
+        let esperado = "v: datos = [\"nombre\" => 'Ana', \"edad\" => 0x1F];\nv: lista = {1, 2, 3};\nv: largo = {\n    \"aaaaaaaaaaaaaaaa\",\n    \"bbbbbbbbbbbbbbbbbbbb\",\n    \"cccccccccccccccccccc\",\n    \"dddddddddddddddddddd\"\n};\n";
+        assert_eq!(formatear(codigo).unwrap(), esperado);
+    }
+
+    #[test]
+    fn informa_errores_de_sintaxis() {
+        assert!(matches!(
+            formatear("v: x = ;"),
+            Err(ErrorFormato::Sintaxis(_))
+        ));
+    }
+}
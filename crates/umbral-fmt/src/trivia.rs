@@ -0,0 +1,331 @@
+//! Recupera lo que el árbol sintáctico no guarda: comentarios, líneas en
+//! blanco y la escritura original de los literales. Los tokens del código
+//! original se alinean con los de las hojas impresas (diff de Myers) y
+//! cada comentario se ancla a la hoja del token que lo rodea.
+
+use crate::documento::{Anotaciones, ComentarioPropio, Doc, Hoja, Trivia};
+use std::mem::discriminant;
+use umbral_lexer::{tokenizar, Comentario, Token, TokenConPosicion};
+
+// Más allá de esta distancia de edición se renuncia a alinear.
+const MAXIMA_DISTANCIA: usize = 4096;
+
+pub fn anotar(
+    codigo: &str,
+    tokens: &[TokenConPosicion],
+    comentarios: &[Comentario],
+    doc: &Doc,
+    hojas: &mut [Hoja],
+) -> Anotaciones {
+    let caracteres: Vec<char> = codigo.chars().collect();
+
+    // Tokens impresos, cada uno con la hoja de la que sale.
+    let mut orden = Vec::new();
+    recorrer(doc, &mut orden);
+    let mut impresos = Vec::new();
+    let mut hoja_de = Vec::new();
+    let mut primer_token = vec![None; hojas.len()];
+    let mut ultimo_token = vec![None; hojas.len()];
+    let mut cantidad = vec![0; hojas.len()];
+    for id in orden {
+        for token in tokenizar(&hojas[id].texto).unwrap_or_default() {
+            primer_token[id].get_or_insert(impresos.len());
+            ultimo_token[id] = Some(impresos.len());
+            cantidad[id] += 1;
+            impresos.push(token.token);
+            hoja_de.push(id);
+        }
+    }
+
+    let originales: Vec<Token> = tokens.iter().map(|t| t.token.clone()).collect();
+    let pareja = emparejar(&originales, &impresos);
+    let mut original_de = vec![None; impresos.len()];
+    for (i, j) in pareja.iter().enumerate() {
+        if let Some(j) = j {
+            original_de[*j] = Some(i);
+        }
+    }
+
+    let fuente = Fuente::nueva(&caracteres, tokens, comentarios);
+    let mut anotaciones = Anotaciones {
+        hojas: vec![Trivia::default(); hojas.len()],
+        finales: Vec::new(),
+    };
+
+    for (id, hoja) in hojas.iter_mut().enumerate() {
+        let trivia = &mut anotaciones.hojas[id];
+        if let Some(i) = primer_token[id].and_then(|j| original_de[j]) {
+            trivia.linea_en_blanco = fuente.blanco_antes_de_token[i];
+        }
+        if let Some(i) = ultimo_token[id].and_then(|j| original_de[j]) {
+            trivia.salto_despues = fuente.salto_despues_de_token[i];
+        }
+        // Un literal con el mismo valor conserva su escritura original.
+        if cantidad[id] == 1 {
+            let j = primer_token[id].unwrap_or_default();
+            if let Some(i) = original_de[j] {
+                if mismo_literal(&originales[i], &impresos[j], hoja.cadena_libre) {
+                    hoja.texto = fuente.texto_de_token(i);
+                }
+            }
+        }
+    }
+
+    for (indice, comentario) in comentarios.iter().enumerate() {
+        let previos = fuente.tokens_antes_de_comentario[indice];
+        let linea_en_blanco = fuente.blanco_antes_de_comentario[indice];
+
+        if fuente.comentario_final_de_linea[indice] {
+            let anterior = (0..previos).rev().find_map(|i| pareja[i]);
+            if let Some(j) = anterior {
+                anotaciones.hojas[hoja_de[j]].despues.push(comentario.texto.clone());
+                continue;
+            }
+        }
+
+        let propio = ComentarioPropio {
+            texto: comentario.texto.clone(),
+            linea_en_blanco,
+        };
+        match (previos..originales.len()).find_map(|i| pareja[i]) {
+            Some(j) => anotaciones.hojas[hoja_de[j]].antes.push(propio),
+            None => anotaciones.finales.push(propio),
+        }
+    }
+
+    anotaciones
+}
+
+fn recorrer(doc: &Doc, orden: &mut Vec<usize>) {
+    match doc {
+        Doc::Hoja(id) => orden.push(*id),
+        Doc::Lista(partes) | Doc::Sangria(partes) => {
+            partes.iter().for_each(|parte| recorrer(parte, orden))
+        }
+        Doc::Grupo(grupo) => grupo.partes.iter().for_each(|parte| recorrer(parte, orden)),
+        _ => {}
+    }
+}
+
+// Datos del código original calculados a partir de las posiciones de
+// tokens y comentarios.
+struct Fuente<'a> {
+    caracteres: &'a [char],
+    inicios: Vec<usize>,
+    fines: Vec<usize>,
+    blanco_antes_de_token: Vec<bool>,
+    salto_despues_de_token: Vec<bool>,
+    tokens_antes_de_comentario: Vec<usize>,
+    blanco_antes_de_comentario: Vec<bool>,
+    comentario_final_de_linea: Vec<bool>,
+}
+
+impl<'a> Fuente<'a> {
+    fn nueva(
+        caracteres: &'a [char],
+        tokens: &[TokenConPosicion],
+        comentarios: &[Comentario],
+    ) -> Self {
+        // Tokens y comentarios en orden de aparición: (inicio, fin, es_token).
+        let mut elementos: Vec<(usize, usize, bool)> = Vec::new();
+        let mut c = 0;
+        for (i, token) in tokens.iter().enumerate() {
+            while c < comentarios.len() && comentarios[c].posicion < token.posicion {
+                let inicio = comentarios[c].posicion;
+                elementos.push((inicio, inicio + comentarios[c].texto.chars().count(), false));
+                c += 1;
+            }
+            let siguiente = tokens.get(i + 1).map_or(caracteres.len(), |t| t.posicion);
+            let siguiente = comentarios
+                .get(c)
+                .map_or(siguiente, |comentario| comentario.posicion.min(siguiente));
+            let mut fin = siguiente.max(token.posicion + 1).min(caracteres.len());
+            while fin > token.posicion + 1 && caracteres[fin - 1].is_whitespace() {
+                fin -= 1;
+            }
+            elementos.push((token.posicion, fin, true));
+        }
+        for comentario in &comentarios[c..] {
+            let inicio = comentario.posicion;
+            elementos.push((inicio, inicio + comentario.texto.chars().count(), false));
+        }
+
+        let saltos = |desde: usize, hasta: usize| {
+            caracteres[desde.min(hasta)..hasta.min(caracteres.len())]
+                .iter()
+                .filter(|c| **c == '\n')
+                .count()
+        };
+
+        let mut fuente = Fuente {
+            caracteres,
+            inicios: Vec::new(),
+            fines: Vec::new(),
+            blanco_antes_de_token: Vec::new(),
+            salto_despues_de_token: Vec::new(),
+            tokens_antes_de_comentario: Vec::new(),
+            blanco_antes_de_comentario: Vec::new(),
+            comentario_final_de_linea: Vec::new(),
+        };
+
+        let mut previo: Option<(usize, bool)> = None;
+        for &(inicio, fin, es_token) in &elementos {
+            let blanco = previo.is_some_and(|(fin_previo, _)| saltos(fin_previo, inicio) >= 2);
+            if es_token {
+                fuente.inicios.push(inicio);
+                fuente.fines.push(fin);
+                fuente.blanco_antes_de_token.push(blanco);
+            } else {
+                let final_de_linea = previo
+                    .is_some_and(|(fin_previo, token)| token && saltos(fin_previo, inicio) == 0);
+                fuente.tokens_antes_de_comentario.push(fuente.inicios.len());
+                fuente.blanco_antes_de_comentario.push(blanco);
+                fuente.comentario_final_de_linea.push(final_de_linea);
+            }
+            previo = Some((fin, es_token));
+        }
+
+        for i in 0..fuente.inicios.len() {
+            let siguiente = fuente.inicios.get(i + 1).copied().unwrap_or(caracteres.len());
+            fuente.salto_despues_de_token.push(saltos(fuente.fines[i], siguiente) > 0);
+        }
+        fuente
+    }
+
+    fn texto_de_token(&self, indice: usize) -> String {
+        self.caracteres[self.inicios[indice]..self.fines[indice]]
+            .iter()
+            .collect()
+    }
+}
+
+// Dos tokens se emparejan si son del mismo tipo; los literales, además,
+// deben tener el mismo valor.
+fn equivalentes(original: &Token, impreso: &Token) -> bool {
+    use Token::*;
+    match (original, impreso) {
+        (Identificador(a) | Tipo(a), Identificador(b) | Tipo(b)) => a == b,
+        (Numero(a), Numero(b)) => valor_numerico(a).is_some() && valor_numerico(a) == valor_numerico(b),
+        (Cadena(a) | CadenaMultilinea(a), Cadena(b) | CadenaMultilinea(b)) => a == b,
+        // Rutas y claves de objeto admiten comillas simples o ningunas.
+        (Identificador(a) | CadenaLiteral(a), Cadena(b) | CadenaLiteral(b)) => a == b,
+        _ => discriminant(original) == discriminant(impreso),
+    }
+}
+
+// Solo una cadena libre puede cambiar de comillas al recuperar su escritura.
+fn mismo_literal(original: &Token, impreso: &Token, libre: bool) -> bool {
+    use Token::*;
+    match (original, impreso) {
+        (Numero(_), Numero(_)) | (Cadena(_) | CadenaMultilinea(_), Cadena(_)) => true,
+        (CadenaLiteral(_), CadenaLiteral(_)) => true,
+        (Identificador(_) | CadenaLiteral(_), Cadena(_)) => libre,
+        _ => false,
+    }
+}
+
+#[derive(PartialEq)]
+enum ValorNumerico {
+    Entero(i64),
+    Flotante(u64),
+    Decimal(String),
+}
+
+// Mismas reglas que el parser para leer un literal numérico.
+fn valor_numerico(numero: &str) -> Option<ValorNumerico> {
+    let limpio = numero.replace('_', "");
+    if let Some(decimal) = limpio.strip_suffix('d') {
+        return Some(ValorNumerico::Decimal(decimal.to_string()));
+    }
+    let base = match limpio.get(..2) {
+        Some("0x" | "0X") => 16,
+        Some("0b" | "0B") => 2,
+        Some("0o" | "0O") => 8,
+        _ => 10,
+    };
+    if base == 10 && limpio.contains('.') {
+        return limpio
+            .parse::<f64>()
+            .ok()
+            .map(|valor| ValorNumerico::Flotante(valor.to_bits()));
+    }
+    let digitos = if base == 10 { &limpio[..] } else { &limpio[2..] };
+    i64::from_str_radix(digitos, base)
+        .ok()
+        .map(ValorNumerico::Entero)
+}
+
+// Diff de Myers entre los tokens originales y los impresos: para cada
+// token original, el índice del impreso con el que se empareja.
+fn emparejar(originales: &[Token], impresos: &[Token]) -> Vec<Option<usize>> {
+    let n = originales.len() as isize;
+    let m = impresos.len() as isize;
+    let maximo = (n + m) as usize;
+    let centro = maximo as isize + 1;
+    let mut v = vec![0isize; 2 * maximo + 3];
+    // Para cada paso d, los valores de v en las diagonales -d..=d.
+    let mut traza: Vec<Vec<isize>> = Vec::new();
+    let mut pareja = vec![None; originales.len()];
+
+    for d in 0..=maximo.min(MAXIMA_DISTANCIA) as isize {
+        let mut k = -d;
+        while k <= d {
+            let mut x = if k == -d || (k != d && v[(centro + k - 1) as usize] < v[(centro + k + 1) as usize]) {
+                v[(centro + k + 1) as usize]
+            } else {
+                v[(centro + k - 1) as usize] + 1
+            };
+            let mut y = x - k;
+            while x < n && y < m && equivalentes(&originales[x as usize], &impresos[y as usize]) {
+                x += 1;
+                y += 1;
+            }
+            v[(centro + k) as usize] = x;
+
+            if x >= n && y >= m {
+                retroceder(&traza, d, k, n, m, &mut pareja);
+                return pareja;
+            }
+            k += 2;
+        }
+        traza.push(((centro - d)..=(centro + d)).map(|i| v[i as usize]).collect());
+    }
+    pareja
+}
+
+fn retroceder(
+    traza: &[Vec<isize>],
+    d_final: isize,
+    k_final: isize,
+    n: isize,
+    m: isize,
+    pareja: &mut [Option<usize>],
+) {
+    let (mut x, mut y) = (n, m);
+    let mut k = k_final;
+    for d in (0..=d_final).rev() {
+        let (previo_x, previo_y, previo_k) = if d == 0 {
+            (0, 0, 0)
+        } else {
+            let anterior = &traza[d as usize - 1];
+            let valor = |k: isize| anterior[(k + d - 1) as usize];
+            let previo_k = if k == -d || (k != d && valor(k - 1) < valor(k + 1)) {
+                k + 1
+            } else {
+                k - 1
+            };
+            let previo_x = valor(previo_k);
+            (previo_x, previo_x - previo_k, previo_k)
+        };
+
+        // La diagonal final de este paso son emparejamientos.
+        while x > previo_x && y > previo_y {
+            x -= 1;
+            y -= 1;
+            pareja[x as usize] = Some(y as usize);
+        }
+        x = previo_x;
+        y = previo_y;
+        k = previo_k;
+    }
+}
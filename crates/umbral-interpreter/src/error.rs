@@ -6,15 +6,20 @@ pub enum InterpreterError {
     ParserError(String),
     RuntimeError(String),
     IoError(String),
+    /// El programa pidió terminar (`Std.proc.exit`) con este código.
+    Salida(i32),
 }
 
 impl fmt::Display for InterpreterError {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         match self {
-            InterpreterError::LexerError(msg) => write!(f, "Error de Lexer: {}", msg),
+            InterpreterError::LexerError(msg) => write!(f, "{}", msg),
             InterpreterError::ParserError(msg) => write!(f, "{}", msg),
             InterpreterError::RuntimeError(msg) => write!(f, "Error de Runtime: {}", msg),
             InterpreterError::IoError(msg) => write!(f, "Error de I/O: {}", msg),
+            InterpreterError::Salida(codigo) => {
+                write!(f, "El programa terminó con código {}", codigo)
+            }
         }
     }
 }
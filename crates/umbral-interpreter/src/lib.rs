@@ -1,10 +1,21 @@
 use std::path::PathBuf;
-use umbral_lexer::{analizar_con_posiciones, TokenConPosicion};
-use umbral_parser::Parser;
+use std::sync::{Arc, Mutex};
+use umbral_lexer::TokenConPosicion;
+use umbral_parser::ast::Programa;
+use umbral_parser::{ParseError, Parser};
+use tokio::sync::mpsc::UnboundedSender;
+use umbral_runtime::runtime::depuracion::Depuracion;
+use umbral_runtime::runtime::describir_excepcion;
+use umbral_runtime::runtime::interpretador::Salida;
+use umbral_runtime::runtime::perfil::Perfil;
+use umbral_runtime::runtime::stdlib::errores;
+use umbral_runtime::runtime::valores::Valor;
 use umbral_runtime::Runtime;
 
 pub mod error;
+pub mod pruebas;
 pub use error::{InterpreterError, InterpreterResult};
+pub use umbral_runtime::runtime::{depuracion, paquetes, perfil};
 
 pub struct Interpreter {
     runtime: Runtime,
@@ -21,11 +32,63 @@ impl Interpreter {
         self.runtime.establecer_directorio_base(ruta);
     }
 
+    pub fn establecer_archivo(&mut self, ruta: PathBuf) {
+        self.runtime.establecer_archivo(ruta);
+    }
+
+    pub fn establecer_argumentos(&mut self, argumentos: Vec<String>) {
+        self.runtime.establecer_argumentos(argumentos);
+    }
+
+    /// Ejecuta bajo el depurador: el código se parsea anotando las líneas
+    /// para que el programa pueda detenerse en ellas.
+    pub fn depurar(&mut self, depuracion: Depuracion) {
+        self.runtime.interpretador.activar_depuracion(depuracion);
+    }
+
+    /// Mide las llamadas y las líneas de lo que se ejecute a partir de aquí,
+    /// también en los módulos que se importen.
+    pub fn perfilar(&mut self, perfil: Perfil) {
+        self.runtime.interpretador.activar_perfil(perfil);
+    }
+
+    /// Cierra las llamadas que el perfil tenga abiertas, incluida la
+    /// principal, para que su tiempo quede registrado.
+    pub fn terminar_perfil(&mut self) {
+        if let Some(perfil) = &mut self.runtime.interpretador.perfil {
+            perfil.salir_de_todo();
+        }
+    }
+
+    /// Ejecuta el código; una excepción no capturada se devuelve como
+    /// `InterpreterError::RuntimeError` y el fin pedido por el programa, como
+    /// `InterpreterError::Salida`.
     pub async fn ejecutar(&mut self, codigo: &str) -> InterpreterResult<()> {
-        let tokens = self.tokenizar(codigo)?;
-        let ast = self.parsear(tokens, codigo)?;
-        self.evaluar(ast).await?;
-        Ok(())
+        let ast = self.analizar(codigo)?;
+        self.evaluar(ast).await
+    }
+
+    /// Llama sin argumentos a la función global `nombre`, esperándola si
+    /// es asíncrona.
+    pub async fn llamar(&mut self, nombre: &str) -> InterpreterResult<()> {
+        self.runtime
+            .llamar_funcion(nombre)
+            .await
+            .map(|_| ())
+            .map_err(|excepcion| excepcion_no_capturada(&excepcion))
+    }
+
+    /// A partir de aquí `tprint` escribe en el búfer devuelto en lugar de
+    /// en la salida estándar.
+    pub fn capturar_salida(&mut self) -> Arc<Mutex<String>> {
+        let salida = Arc::new(Mutex::new(String::new()));
+        self.runtime.interpretador.salida = Some(Salida::Bufer(salida.clone()));
+        salida
+    }
+
+    /// A partir de aquí cada línea de `tprint` se envía por `canal`.
+    pub fn redirigir_salida(&mut self, canal: UnboundedSender<String>) {
+        self.runtime.interpretador.salida = Some(Salida::Canal(canal));
     }
 
     pub async fn ejecutar_con_resultado(&mut self, codigo: &str) -> InterpreterResult<String> {
@@ -37,34 +100,101 @@ impl Interpreter {
         self.runtime = Runtime::nuevo();
     }
 
-    fn tokenizar(&self, codigo: &str) -> InterpreterResult<Vec<TokenConPosicion>> {
-        let tokens = analizar_con_posiciones(codigo);
+    // El depurador solo sigue el archivo principal; el perfil identifica
+    // cada archivo por su ruta.
+    fn analizar(&self, codigo: &str) -> InterpreterResult<Programa> {
+        let interpretador = &self.runtime.interpretador;
+        let Some(perfil) = &interpretador.perfil else {
+            let archivo = interpretador.depuracion.is_some().then_some(0);
+            return analizar_con_lineas(codigo, archivo).map(|(programa, _)| programa);
+        };
 
-        if tokens.is_empty() {
-            return Err(InterpreterError::LexerError(
-                "No se generaron tokens del código fuente".to_string(),
-            ));
+        let ruta = interpretador
+            .archivo_actual
+            .clone()
+            .unwrap_or_else(|| PathBuf::from("<entrada>"));
+        let archivo = perfil.registrar_archivo(&ruta);
+        let (programa, lineas) = analizar_con_lineas(codigo, Some(archivo))?;
+        perfil.registrar_lineas(archivo, &lineas);
+        Ok(programa)
+    }
+
+    async fn evaluar(&mut self, programa: Programa) -> InterpreterResult<()> {
+        match self.runtime.ejecutar_programa(programa).await {
+            Some(excepcion) => Err(excepcion_no_capturada(&excepcion)),
+            None => Ok(()),
         }
+    }
+}
+
+fn analizar(codigo: &str) -> InterpreterResult<Programa> {
+    analizar_con_lineas(codigo, None).map(|(programa, _)| programa)
+}
+
+/// Líneas donde empieza alguna sentencia, es decir, donde el depurador
+/// puede detenerse.
+pub fn lineas_ejecutables(codigo: &str) -> InterpreterResult<Vec<usize>> {
+    let (_, mut lineas) = analizar_con_lineas(codigo, Some(0))?;
+    lineas.sort_unstable();
+    lineas.dedup();
+    Ok(lineas)
+}
+
+// Con `archivo` las sentencias se anotan con su línea y ese identificador.
+fn analizar_con_lineas(
+    codigo: &str,
+    archivo: Option<usize>,
+) -> InterpreterResult<(Programa, Vec<usize>)> {
+    let tokens = tokenizar(codigo)?;
+    parsear(tokens, codigo, archivo)
+}
 
-        Ok(tokens)
+fn tokenizar(codigo: &str) -> InterpreterResult<Vec<TokenConPosicion>> {
+    let tokens = umbral_lexer::tokenizar(codigo).map_err(|errores| {
+        let mensajes: Vec<String> = errores
+            .iter()
+            .map(|e| {
+                let posicion = codigo[..e.rango.start].chars().count();
+                ParseError::con_contexto(e.mensaje(), posicion, codigo).formatear_error()
+            })
+            .collect();
+        InterpreterError::LexerError(mensajes.join("\n"))
+    })?;
+
+    if tokens.is_empty() {
+        return Err(InterpreterError::LexerError(
+            "Error de Lexer: No se generaron tokens del código fuente".to_string(),
+        ));
     }
 
-    fn parsear(
-        &self,
-        tokens: Vec<TokenConPosicion>,
-        codigo: &str,
-    ) -> InterpreterResult<umbral_parser::ast::Programa> {
-        let mut parser = Parser::nuevo_con_posiciones(tokens, codigo.to_string());
+    Ok(tokens)
+}
 
-        parser
-            .parsear_programa()
-            .map_err(|e| InterpreterError::ParserError(e.formatear_error()))
+fn parsear(
+    tokens: Vec<TokenConPosicion>,
+    codigo: &str,
+    archivo: Option<usize>,
+) -> InterpreterResult<(Programa, Vec<usize>)> {
+    let mut parser = Parser::nuevo_con_posiciones(tokens, codigo.to_string());
+    if let Some(archivo) = archivo {
+        parser.anotar_lineas(archivo);
     }
 
-    async fn evaluar(&mut self, programa: umbral_parser::ast::Programa) -> InterpreterResult<()> {
-        self.runtime.ejecutar(programa).await;
-        Ok(())
+    let programa = parser.parsear_programa().map_err(|errores| {
+        let mensajes: Vec<String> = errores.iter().map(|e| e.formatear_error()).collect();
+        InterpreterError::ParserError(mensajes.join("\n"))
+    })?;
+    Ok((programa, parser.lineas_sentencias))
+}
+
+fn excepcion_no_capturada(excepcion: &Valor) -> InterpreterError {
+    if let Some(codigo) = errores::codigo_salida(excepcion) {
+        return InterpreterError::Salida(codigo);
     }
+    InterpreterError::RuntimeError(format!(
+        "Excepción no capturada: {}",
+        describir_excepcion(excepcion)
+    ))
 }
 
 impl Default for Interpreter {
@@ -76,6 +206,30 @@ impl Default for Interpreter {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use std::path::Path;
+    use tempfile::TempDir;
+
+    // Directorio propio de cada prueba, que se borra al soltar el `TempDir`.
+    // La ruta es canónica, como las que resuelven los módulos.
+    fn directorio_temporal() -> (TempDir, PathBuf) {
+        let temporal = tempfile::tempdir().unwrap();
+        let raiz = temporal.path().canonicalize().unwrap();
+        (temporal, raiz)
+    }
+
+    fn escribir(raiz: &Path, archivo: &str, contenido: &str) -> PathBuf {
+        let ruta = raiz.join(archivo);
+        std::fs::create_dir_all(ruta.parent().unwrap()).unwrap();
+        std::fs::write(&ruta, contenido).unwrap();
+        ruta
+    }
+
+    async fn ejecutar_con_salida(interprete: &mut Interpreter, codigo: &str) -> String {
+        let salida = interprete.capturar_salida();
+        interprete.ejecutar(codigo).await.unwrap();
+        let texto = salida.lock().unwrap().clone();
+        texto
+    }
 
     #[tokio::test]
     async fn test_declaracion_variable_simple() {
@@ -117,6 +271,605 @@ mod tests {
         assert!(resultado.is_err());
     }
 
+    #[tokio::test]
+    async fn test_excepcion_no_capturada_es_error() {
+        let mut interprete = Interpreter::nuevo();
+        let salida = interprete.capturar_salida();
+        let resultado = interprete
+            .ejecutar(r#"tprint("antes"); tw: "fallo"; tprint("después");"#)
+            .await;
+
+        match resultado {
+            Err(InterpreterError::RuntimeError(mensaje)) => {
+                assert!(mensaje.contains("fallo"), "{}", mensaje)
+            }
+            otro => panic!("se esperaba RuntimeError, se obtuvo {:?}", otro),
+        }
+        assert_eq!(salida.lock().unwrap().as_str(), "antes\n");
+    }
+
+    #[tokio::test]
+    async fn test_modulos_comparten_estado_y_resuelven_rutas_propias() {
+        let (_temporal, raiz) = directorio_temporal();
+        escribir(&raiz, "lib/util/texto.um", "out f: prefijo() { r: (\"n=\"); }");
+        let contador = escribir(
+            &raiz,
+            "lib/contador.um",
+            r#"
+            equip { prefijo } origin "./util/texto.um";
+            v: contador = 0;
+            out f: inc() {
+                contador = contador + 1;
+                r: ("&{ prefijo() }&contador");
+            }
+            out f: ubicacion() { r: ({__file__, __dir__}); }
+            "#,
+        );
+        let principal = escribir(&raiz, "main.um", "");
+
+        let mut interprete = Interpreter::nuevo();
+        interprete.establecer_archivo(principal.clone());
+        let codigo = r#"
+            equip { inc, ubicacion } origin "./lib/contador.um";
+            tprint(inc());
+            tprint(inc());
+            v: lugar = ubicacion();
+            tprint(lugar[0]);
+            tprint(lugar[1]);
+            tprint(__file__);
+            tprint(__dir__);
+        "#;
+        let salida = ejecutar_con_salida(&mut interprete, codigo).await;
+        let esperada = format!(
+            "n=1\nn=2\n{}\n{}\n{}\n{}\n",
+            contador.display(),
+            raiz.join("lib").display(),
+            principal.display(),
+            raiz.display()
+        );
+        assert_eq!(salida, esperada);
+    }
+
+    #[tokio::test]
+    async fn test_argumentos_en_modulos_y_salida_con_ayuda() {
+        let (_temporal, raiz) = directorio_temporal();
+        escribir(&raiz, "lib/args.um", "out f: primero() { r: (ARGS[0]); }");
+        let principal = escribir(&raiz, "main.um", "");
+
+        let mut interprete = Interpreter::nuevo();
+        interprete.establecer_archivo(principal);
+        interprete.establecer_argumentos(vec!["-5".to_string()]);
+        let salida = interprete.capturar_salida();
+        let codigo = r#"
+            equip { primero } origin "./lib/args.um";
+            c: espec = ["positionals" => {["name" => "n", "type" => "int"]}];
+            tprint(primero());
+            tprint(Std.proc.args()[0]);
+            tprint(Std.cli.parse(espec).n + 1);
+            tr: {
+                Std.cli.parse(espec, {"--help"});
+            } ct: (v: e) {
+                tprint("capturada");
+            }
+            tprint("no llega");
+        "#;
+
+        let resultado = interprete.ejecutar(codigo).await;
+        assert!(matches!(resultado, Err(InterpreterError::Salida(0))));
+        assert_eq!(salida.lock().unwrap().as_str(), "-5\n-5\n-4\n");
+    }
+
+    #[tokio::test]
+    async fn test_importacion_stdlib_con_espacio_de_nombres() {
+        let mut interprete = Interpreter::nuevo();
+        let codigo = r#"
+            equip { split, trim as recortar } origin "std:str";
+            v: partes = split(recortar(" a,b "), ",");
+            tprint(partes[1]);
+            tprint(Std.str.to_upper("umbral"));
+            tprint(Std.len({1, 2, 3}));
+            tprint(Std.reverse({1, 2})[0]);
+        "#;
+        let salida = ejecutar_con_salida(&mut interprete, codigo).await;
+        assert_eq!(salida, "b\nUMBRAL\n3\n2\n");
+    }
+
+    #[tokio::test]
+    async fn test_lista_exportacion_y_por_defecto() {
+        let (_temporal, raiz) = directorio_temporal();
+        escribir(
+            &raiz,
+            "base.um",
+            r#"
+            f: sumar(a, b) { r: (a + b); }
+            c: PI = 3;
+            out: { sumar as suma, PI }
+            out def: f: principal() { r: (10); }
+            "#,
+        );
+        escribir(&raiz, "medio.um", "out: * origin \"./base.um\";");
+
+        let mut interprete = Interpreter::nuevo();
+        interprete.establecer_archivo(raiz.join("main.um"));
+        let codigo = r#"
+            equip { suma, PI } origin "./medio.um";
+            equip { def as principal } origin "./base.um";
+            tprint(PI);
+            tprint(suma(PI, 1));
+            tprint(principal());
+            tprint(suma(PI, principal()));
+        "#;
+        let salida = ejecutar_con_salida(&mut interprete, codigo).await;
+        assert_eq!(salida, "3\n4\n10\n13\n");
+    }
+
+    #[tokio::test]
+    async fn test_indices_y_rebanadas_de_textos_y_listas() {
+        let mut interprete = Interpreter::nuevo();
+        let codigo = r#"
+            v: palabra = "Ñandú";
+            v: numeros = {10, 20, 30, 40};
+            tprint(palabra[0] + palabra[-1]);
+            tprint(palabra[1:-1]);
+            tprint(palabra[-3:]);
+            tprint(numeros[-1]);
+            tprint(numeros[:-2]);
+            tprint(palabra[9]);
+            tprint(numeros[-5]);
+            tprint(Std.len(palabra));
+            tprint(palabra.length);
+            tprint("añadir".to_upper().substring(0, 3));
+        "#;
+        let salida = ejecutar_con_salida(&mut interprete, codigo).await;
+        assert_eq!(
+            salida,
+            "Ñú\nand\nndú\n40\n[10, 20]\nnull\nnull\n5\n5\nAÑA\n"
+        );
+    }
+
+    #[tokio::test]
+    async fn test_rebanadas_con_variables_de_una_letra() {
+        let mut interprete = Interpreter::nuevo();
+        let codigo = r#"
+            v: lista = {10, 20, 30, 40, 50};
+            v: i = 1;
+            v: j = 3;
+            v: n = 2;
+            v: v = 4;
+            tprint(lista[i:j]);
+            tprint(lista[n:]);
+            tprint(lista[v:]);
+            tprint(lista[j - i:v]);
+            tprint("abcdef"[n:v]);
+        "#;
+        let salida = ejecutar_con_salida(&mut interprete, codigo).await;
+        assert_eq!(salida, "[20, 30]\n[30, 40, 50]\n[50]\n[30, 40]\ncd\n");
+    }
+
+    #[tokio::test]
+    async fn test_metodos_de_tipos_primitivos_y_extend() {
+        let mut interprete = Interpreter::nuevo();
+        let codigo = r#"
+            cs: Punto {
+                pr: x;
+            }
+            tprint("hola".to_upper());
+            tprint(3.7.round());
+            tprint(["a" => 1, "b" => 2].keys());
+            tprint(true.to_string());
+
+            f: gritar(texto) { r: (texto.to_upper() + "!"); }
+            Std.extend("Str", "gritar", gritar);
+            tprint("hola".gritar());
+
+            v: leer = Std.parse;
+            tprint(leer("{\"x\": 3}", Punto).x);
+            v: modulo_json = Std.json;
+            tprint(modulo_json.stringify({1, 2}));
+        "#;
+        let salida = ejecutar_con_salida(&mut interprete, codigo).await;
+        assert_eq!(salida, "HOLA\n4\n[a, b]\ntrue\nHOLA!\n3\n[1,2]\n");
+    }
+
+    #[tokio::test]
+    async fn test_enteros_comprobados_literales_y_decimales() {
+        let mut interprete = Interpreter::nuevo();
+        let codigo = r#"
+            tprint(0xFF + 0b1010 + 0o17 + 1_000_000);
+            f: tipo_error(a, b) {
+                v: tipo = "sin error";
+                tr: {
+                    v: x = a * b;
+                } ct: (v: e) {
+                    tipo = e.data.kind;
+                }
+                r: (tipo);
+            }
+            tprint(tipo_error(9223372036854775807, 2));
+            tprint(tipo_error(0x7FFF_FFFF, 0x7FFF_FFFF));
+            tprint(tipo_error(1.5d, 1.5));
+
+            tprint(0.1d + 0.2d == 0.3d);
+            tprint(Std.decimal.new("19.99") * 3 - 0.97d);
+            Std.decimal.set_context(2, "half_up");
+            tprint(10d / 3d);
+            tprint(7.50d % 2d);
+            tprint(7.5d % 2);
+            tprint(8 % 2.5d);
+
+            f: tipo_error_resto(a, b) {
+                v: tipo = "sin error";
+                tr: {
+                    v: x = a % b;
+                } ct: (v: e) {
+                    tipo = e.data.kind;
+                }
+                r: (tipo);
+            }
+            tprint(tipo_error_resto(7.5d, 0));
+            tprint(tipo_error_resto(7.5d, 0d));
+            tprint(tipo_error_resto(1, 0d));
+            tprint(tipo_error_resto(1.5d, 1.5));
+        "#;
+        let salida = ejecutar_con_salida(&mut interprete, codigo).await;
+        assert_eq!(
+            salida,
+            "1000280\nOverflow\nsin error\nInvalidInput\ntrue\n59.00\n3.33\n1.50\n\
+             1.5\n0.5\nDivisionByZero\nDivisionByZero\nDivisionByZero\nInvalidInput\n"
+        );
+
+        assert!(interprete
+            .ejecutar("v: grande = 9223372036854775808;")
+            .await
+            .is_err());
+
+        // El contexto decimal es de cada intérprete.
+        let mut otro = Interpreter::nuevo();
+        let salida = ejecutar_con_salida(&mut otro, "tprint(2d / 3d);").await;
+        assert_eq!(salida, "0.6666666666666666666666666667\n");
+    }
+
+    #[tokio::test]
+    async fn test_regex_reemplazo_con_funcion_y_metodos() {
+        let mut interprete = Interpreter::nuevo();
+        let codigo = r#"
+            f: marcar(m) {
+                r: ("[&{m.text}@&{m.start}]");
+            }
+            tprint(Std.regex.replace("\\d+", "ñ1 b20 c3", marcar, 2));
+
+            v: palabra = Std.regex.compile("(?P<w>[a-z]+)", "i");
+            tprint(palabra.find("  Hola mundo").named.w);
+            tprint(palabra.replace("Hola mundo", "<$w>"));
+        "#;
+        let salida = ejecutar_con_salida(&mut interprete, codigo).await;
+        assert_eq!(salida, "ñ[1@1] b[20@4] c3\nHola\n<Hola> <mundo>\n");
+    }
+
+    #[tokio::test]
+    async fn test_sleep_y_desbordamiento_de_duraciones() {
+        let mut interprete = Interpreter::nuevo();
+        let codigo = r#"
+            v: inicio = Std.time.monotonic();
+            tprint(awa Std.time.sleep(Std.time.millis(20)));
+            tprint(Std.time.elapsed(inicio) >= Std.time.millis(20));
+
+            v: largo = Std.time.weeks(10000);
+            tr: {
+                v: total = largo * 2;
+            } ct: (v: e) {
+                tprint(e.data.kind);
+            }
+            tr: {
+                v: fecha = Std.time.date(262142, 12, 1) + largo;
+            } ct: (v: e) {
+                tprint(e.data.kind);
+            }
+        "#;
+        let salida = ejecutar_con_salida(&mut interprete, codigo).await;
+        assert_eq!(salida, "null\ntrue\nOverflow\nOverflow\n");
+    }
+
+    #[tokio::test]
+    async fn test_json_con_clases() {
+        let mut interprete = Interpreter::nuevo();
+        let codigo = r#"
+            cs: Punto {
+                pr: x;
+                pr: y;
+                pu f: to_json() { r: ({th.x, th.y}); }
+                pu f: from_json(d) { th.x = d[0]; th.y = d[1]; }
+            }
+            cs: Nombre {
+                pr: valor;
+            }
+            em: Color { Rojo='rojo', Verde='verde' }
+
+            v: puntos = Std.json.parse("[[1, 2], [3, 4]]", Punto);
+            tprint(puntos[1].x + puntos[1].y);
+            tprint(Std.json.stringify(puntos));
+            v: nombre = Std.json.parse("{\"valor\": \"ana\"}", Nombre);
+            tprint(nombre.valor);
+            tprint(Std.json.stringify(["nombre" => nombre, "color" => Color.Verde]));
+        "#;
+        let salida = ejecutar_con_salida(&mut interprete, codigo).await;
+        assert_eq!(
+            salida,
+            "7\n[[1,2],[3,4]]\nana\n{\"nombre\":{\"valor\":\"ana\"},\"color\":\"verde\"}\n"
+        );
+    }
+
+    #[tokio::test]
+    async fn test_interpolacion_se_analiza_al_parsear() {
+        let mut interprete = Interpreter::nuevo();
+        let codigo = r#"
+            v: lista = {1, 2, 3};
+            tprint("total &{ lista.len() * 2 }, primero &lista[0] \& más");
+        "#;
+        assert_eq!(
+            ejecutar_con_salida(&mut interprete, codigo).await,
+            "total 6, primero 1 & más\n"
+        );
+
+        // Los nombres de entradas anteriores siguen disponibles.
+        let salida = ejecutar_con_salida(&mut interprete, r#"tprint("&lista");"#).await;
+        assert_eq!(salida, "[1, 2, 3]\n");
+
+        for codigo in [r#"v: mal = "x &{ 1 + }";"#, r#"v: mal = "x &{ 1 # 2 }";"#] {
+            let error = interprete.ejecutar(codigo).await.unwrap_err();
+            assert!(
+                matches!(error, InterpreterError::ParserError(_)),
+                "{}: {:?}",
+                codigo,
+                error
+            );
+        }
+
+        // Un nombre que no existe se resuelve al ejecutar, como siempre.
+        let salida = ejecutar_con_salida(&mut interprete, r#"tprint("hola &desconocido");"#).await;
+        assert_eq!(salida, "hola null\n");
+    }
+
+    #[tokio::test]
+    async fn test_interpolacion_de_nombres_importados_con_prefijo() {
+        let (_temporal, raiz) = directorio_temporal();
+        escribir(&raiz, "lib.um", "out v: numero = 5;");
+        let principal = escribir(&raiz, "main.um", "");
+
+        let mut interprete = Interpreter::nuevo();
+        interprete.establecer_archivo(principal);
+        let codigo = r#"
+            equip * as m origin "./lib.um";
+            tprint("valor &m_numero");
+        "#;
+        assert_eq!(
+            ejecutar_con_salida(&mut interprete, codigo).await,
+            "valor 5\n"
+        );
+    }
+
+    #[tokio::test]
+    async fn test_errores_lexicos() {
+        let mut interprete = Interpreter::nuevo();
+        assert!(interprete.ejecutar("v: a = \"sin cerrar;").await.is_err());
+        assert!(interprete.ejecutar("v: b = 1.2.3;").await.is_err());
+        assert!(interprete.ejecutar("v: c = 0xFG;").await.is_err());
+        assert!(interprete.ejecutar("v: d = 1 # 2;").await.is_err());
+
+        // Se informan todos los errores léxicos, no solo el primero.
+        let codigo = "v: a = 1.2.3;\nv: b = 0xFG;\nv: c = 1 # 2;\nv: d = \"sin cerrar;";
+        let Err(InterpreterError::LexerError(mensaje)) = interprete.ejecutar(codigo).await else {
+            panic!("se esperaba un error léxico");
+        };
+        for linea in ["línea 1", "línea 2", "línea 3", "línea 4"] {
+            assert!(mensaje.contains(linea), "falta {}: {}", linea, mensaje);
+        }
+    }
+
+    #[tokio::test]
+    async fn test_identificadores_unicode() {
+        let mut interprete = Interpreter::nuevo();
+        let codigo = "
+            v: año = 2024;
+            f: tamaño(lista) { r: (lista.len()); }
+            v: n\u{303}andú = tamaño({1, 2});
+            an\u{303}o = año + 1;
+            tprint(\"&año &{ ñandú + 1 } &an\u{303}o\");
+            tprint(taman\u{303}o({1}) + ñandu\u{301});
+        ";
+        // `ñ` y `n` + tilde combinante son la misma variable, igual que `ú`
+        // y `u` + acento agudo combinante.
+        assert_eq!(
+            ejecutar_con_salida(&mut interprete, codigo).await,
+            "2025 3 2025\n3\n"
+        );
+    }
+
+    #[tokio::test]
+    async fn test_recuperacion_de_errores_de_sintaxis() {
+        let codigo = "
+            v: a = (1 + ;
+            f: demo(x) {
+                v: b = x + * 3;
+                r: (x);
+            }
+            v: c = 2;
+        ";
+        let tokens = umbral_lexer::tokenizar(codigo).unwrap();
+        let mut parser = Parser::nuevo_con_posiciones(tokens, codigo.to_string());
+        let (programa, errores) = parser.parsear_programa_parcial();
+        assert_eq!(errores.len(), 2);
+        assert_eq!(programa.sentencias.len(), 2);
+    }
+
+    #[tokio::test]
+    async fn test_pruebas_descubre_y_ejecuta() {
+        let codigo = r#"
+            equip { eq, throws } origin "std:assert";
+            f: fallar() { tw: n: Error("boom"); }
+            f: auxiliar(x) { r: (x); }
+            f: test_pasa() { throws(fallar, "boom"); }
+            asy f: test_falla() {
+                tprint("dentro");
+                eq({1, ["a" => 2]}, {1, ["a" => 3]});
+                tprint("no llega");
+            }
+        "#;
+        let nombres = pruebas::descubrir(codigo).unwrap();
+        assert_eq!(nombres, vec!["test_pasa", "test_falla"]);
+
+        let ruta = PathBuf::from("ejemplo_test.um");
+        let pasa = pruebas::ejecutar(&ruta, codigo, "test_pasa", None).await;
+        assert!(pasa.paso(), "{:?}", pasa.fallo);
+
+        let falla = pruebas::ejecutar(&ruta, codigo, "test_falla", None).await;
+        assert!(falla.fallo.unwrap().contains("en [1][\"a\"]: se esperaba 3, se obtuvo 2"));
+        assert_eq!(falla.salida, "dentro\n");
+    }
+
+    #[tokio::test]
+    async fn test_depurador_puntos_pasos_y_evaluacion() {
+        use depuracion::{Detencion, Motivo, Paso};
+
+        let codigo = "f: doble(x) {\n    v: y = x * 2;\n    r: (y);\n}\nv: a = 1;\nv: b = doble(a);\ntprint(b);\n";
+        assert_eq!(lineas_ejecutables(codigo).unwrap(), vec![1, 2, 3, 5, 6, 7]);
+
+        let (depuracion, cliente, mut detenciones) = depuracion::crear(false);
+        cliente.establecer_puntos([6]);
+        let mut interprete = Interpreter::nuevo();
+        interprete.depurar(depuracion);
+        let salida = interprete.capturar_salida();
+        let programa = tokio::spawn(async move { interprete.ejecutar(codigo).await });
+
+        let detencion = |motivo, linea| Some(Detencion { motivo, linea });
+        assert_eq!(detenciones.recv().await, detencion(Motivo::PuntoDeRuptura, 6));
+        cliente.reanudar(Paso::Entrar);
+        assert_eq!(detenciones.recv().await, detencion(Motivo::Paso, 2));
+
+        let pila: Vec<(String, usize)> = cliente
+            .pila()
+            .await
+            .into_iter()
+            .map(|marco| (marco.nombre, marco.linea))
+            .collect();
+        assert_eq!(pila, vec![("doble".to_string(), 2), ("<principal>".to_string(), 6)]);
+
+        let ambitos = cliente.ambitos().await;
+        assert_eq!(ambitos[0].nombre, "Locales");
+        let locales = cliente.variables(ambitos[0].referencia).await;
+        assert!(locales.iter().any(|v| v.nombre == "x" && v.valor == "1" && v.tipo == "Int"));
+        assert_eq!(cliente.evaluar("x + 10").await.unwrap().valor, "11");
+
+        cliente.reanudar(Paso::Salir);
+        assert_eq!(detenciones.recv().await, detencion(Motivo::Paso, 7));
+        assert_eq!(cliente.evaluar("b").await.unwrap().valor, "2");
+        assert!(cliente.evaluar("tw: n: Error(\"no\")").await.is_err());
+
+        cliente.reanudar(Paso::Continuar);
+        assert!(programa.await.unwrap().is_ok());
+        assert_eq!(*salida.lock().unwrap(), "2\n");
+    }
+
+    #[tokio::test]
+    async fn test_perfil_funciones_lineas_y_pilas() {
+        let codigo = "f: doble(x) {\n    r: (x * 2);\n}\nv: a = doble(1);\ni: (a > 5) {\n    tprint(a);\n}\nv: b = doble(a);\n";
+        let perfil = Perfil::nuevo();
+        let mut interprete = Interpreter::nuevo();
+        interprete.perfilar(perfil.clone());
+        interprete.ejecutar(codigo).await.unwrap();
+        interprete.terminar_perfil();
+
+        let funciones = perfil.funciones();
+        let doble = funciones.iter().find(|(nombre, _)| nombre == "doble").unwrap();
+        assert_eq!(doble.1.llamadas, 2);
+        assert!(funciones.iter().any(|(nombre, e)| nombre == "<principal>" && e.llamadas == 1));
+
+        let archivos = perfil.archivos();
+        let veces: Vec<(usize, u64)> = archivos[0]
+            .lineas
+            .iter()
+            .map(|(linea, estadistica)| (*linea, estadistica.veces))
+            .collect();
+        assert_eq!(veces, vec![(1, 1), (2, 2), (4, 1), (5, 1), (6, 0), (8, 1)]);
+
+        let pilas = perfil.pilas_plegadas();
+        assert!(pilas.lines().any(|linea| linea.starts_with("<principal>;doble ")));
+    }
+
+    #[tokio::test]
+    async fn test_chdir_y_exit_no_afectan_al_proceso() {
+        let (_temporal, raiz) = directorio_temporal();
+        escribir(&raiz, "datos/entrada.txt", "hola");
+        let del_proceso = std::env::current_dir().unwrap();
+
+        let mut interprete = Interpreter::nuevo();
+        let salida = interprete.capturar_salida();
+        let codigo = format!(
+            r#"
+            Std.proc.chdir("{}");
+            Std.proc.chdir("datos");
+            tprint(Std.fs.read_file("entrada.txt"));
+            Std.fs.write_file("salida.txt", "adiós");
+            tprint(Std.fs.walk(".").len());
+            tr: {{
+                Std.proc.exit(4);
+            }} ct: (v: e) {{
+                tprint("capturada");
+            }}
+            tprint("no llega");
+            "#,
+            raiz.display()
+        );
+
+        let resultado = interprete.ejecutar(&codigo).await;
+        assert!(matches!(resultado, Err(InterpreterError::Salida(4))));
+        assert_eq!(salida.lock().unwrap().as_str(), "hola\n2\n");
+        assert!(raiz.join("datos/salida.txt").exists());
+        assert_eq!(std::env::current_dir().unwrap(), del_proceso);
+    }
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn test_proc_se_espera_con_awa() {
+        let mut interprete = Interpreter::nuevo();
+        let codigo = r#"
+            v: r = awa Std.proc.exec("sh", "-c", "printf hola");
+            tprint(r.stdout);
+            v: p = Std.proc.spawn("cat");
+            Std.proc.write(p, "uno\n");
+            Std.proc.close_stdin(p);
+            tprint(awa Std.proc.read_line(p));
+            tprint((awa Std.proc.wait(p)).code);
+            tr: {
+                awa Std.proc.exec("sleep", "5", ["timeout" => 20]);
+                tprint("no llega");
+            } ct: (v: e) {
+                tprint("capturada");
+            }
+        "#;
+        let salida = ejecutar_con_salida(&mut interprete, codigo).await;
+        assert_eq!(salida, "hola\nuno\n0\ncapturada\n");
+    }
+
+    #[tokio::test]
+    async fn test_clase_salida_del_usuario_se_captura() {
+        let mut interprete = Interpreter::nuevo();
+        let codigo = r#"
+            cs: Salida {
+                pr: codigo;
+                pu f: Salida(codigo) { th.codigo = codigo; }
+            }
+            tr: {
+                tw: n: Salida(2);
+            } ct: (v: e) {
+                tprint("capturada &e.codigo");
+            }
+            tprint("sigue");
+        "#;
+        let salida = ejecutar_con_salida(&mut interprete, codigo).await;
+        assert_eq!(salida, "capturada 2\nsigue\n");
+    }
+
     #[tokio::test]
     async fn test_reiniciar_interprete() {
         let mut interprete = Interpreter::nuevo();
@@ -0,0 +1,89 @@
+//! Pruebas de `umbral test`.
+//!
+//! Un archivo de pruebas termina en `_test.um` y sus pruebas son las
+//! funciones de nivel superior sin parámetros cuyo nombre empieza por
+//! `test`. Cada prueba se ejecuta en un intérprete nuevo: primero el archivo
+//! completo y después la función, así que no comparten estado.
+
+use crate::{analizar, Interpreter, InterpreterError, InterpreterResult};
+use std::path::Path;
+use std::time::{Duration, Instant};
+use umbral_parser::ast::Sentencia;
+use umbral_runtime::runtime::perfil::Perfil;
+
+pub const SUFIJO_ARCHIVO: &str = "_test.um";
+pub const PREFIJO_PRUEBA: &str = "test";
+
+pub fn es_archivo_de_pruebas(ruta: &Path) -> bool {
+    ruta.file_name()
+        .and_then(|nombre| nombre.to_str())
+        .is_some_and(|nombre| nombre.ends_with(SUFIJO_ARCHIVO))
+}
+
+/// Nombres de las pruebas del archivo, en el orden en que se declaran.
+pub fn descubrir(codigo: &str) -> InterpreterResult<Vec<String>> {
+    let programa = analizar(codigo)?;
+
+    let mut nombres = Vec::new();
+    for sentencia in &programa.sentencias {
+        let sentencia = match sentencia {
+            Sentencia::ExportacionPorDefecto(sentencia) => sentencia.as_ref(),
+            otra => otra,
+        };
+        if let Sentencia::Funcion(funcion) = sentencia {
+            if funcion.nombre.starts_with(PREFIJO_PRUEBA) && funcion.parametros.is_empty() {
+                nombres.push(funcion.nombre.clone());
+            }
+        }
+    }
+    Ok(nombres)
+}
+
+#[derive(Debug, Clone)]
+pub struct Resultado {
+    /// Mensaje de la excepción que hizo fallar la prueba.
+    pub fallo: Option<String>,
+    /// Lo que la prueba escribió con `tprint`.
+    pub salida: String,
+    pub duracion: Duration,
+}
+
+impl Resultado {
+    pub fn paso(&self) -> bool {
+        self.fallo.is_none()
+    }
+}
+
+/// Ejecuta la prueba `nombre` del archivo `ruta`, cuyo contenido es `codigo`.
+/// Con `perfil` se acumulan en él las líneas ejecutadas, para la cobertura.
+pub async fn ejecutar(
+    ruta: &Path,
+    codigo: &str,
+    nombre: &str,
+    perfil: Option<Perfil>,
+) -> Resultado {
+    let inicio = Instant::now();
+
+    let mut interprete = Interpreter::nuevo();
+    interprete.establecer_archivo(ruta.to_path_buf());
+    interprete.establecer_argumentos(Vec::new());
+    let salida = interprete.capturar_salida();
+    if let Some(perfil) = perfil {
+        interprete.perfilar(perfil);
+    }
+
+    let resultado = match interprete.ejecutar(codigo).await {
+        Ok(()) => interprete.llamar(nombre).await,
+        Err(error) => Err(error),
+    };
+
+    let salida = salida.lock().unwrap().clone();
+    Resultado {
+        fallo: resultado.err().map(|error| match error {
+            InterpreterError::RuntimeError(mensaje) => mensaje,
+            otro => otro.to_string(),
+        }),
+        salida,
+        duracion: inicio.elapsed(),
+    }
+}
@@ -0,0 +1,64 @@
+use std::fmt;
+use std::ops::Range;
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum TipoErrorLexico {
+    CadenaSinCerrar,
+    NumeroInvalido(String),
+    CaracterDesconocido(char),
+}
+
+#[derive(Debug, Clone)]
+pub struct LexError {
+    pub tipo: TipoErrorLexico,
+    /// Rango en bytes del fragmento erróneo dentro del código fuente.
+    pub rango: Range<usize>,
+    pub linea: usize,
+    pub columna: usize,
+}
+
+impl LexError {
+    pub fn nuevo(tipo: TipoErrorLexico, rango: Range<usize>, codigo_fuente: &str) -> Self {
+        let (linea, columna) = calcular_linea_columna(codigo_fuente, rango.start);
+        LexError {
+            tipo,
+            rango,
+            linea,
+            columna,
+        }
+    }
+
+    pub fn mensaje(&self) -> String {
+        match &self.tipo {
+            TipoErrorLexico::CadenaSinCerrar => {
+                "Cadena sin cerrar: falta la comilla final".to_string()
+            }
+            TipoErrorLexico::NumeroInvalido(numero) => format!("Número mal formado '{}'", numero),
+            TipoErrorLexico::CaracterDesconocido(caracter) => {
+                format!("Carácter inesperado '{}'", caracter)
+            }
+        }
+    }
+}
+
+impl fmt::Display for LexError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{} en línea {}, columna {}",
+            self.mensaje(),
+            self.linea,
+            self.columna
+        )
+    }
+}
+
+impl std::error::Error for LexError {}
+
+fn calcular_linea_columna(codigo: &str, byte: usize) -> (usize, usize) {
+    let anterior = &codigo[..byte.min(codigo.len())];
+    let linea = anterior.matches('\n').count() + 1;
+    let inicio_linea = anterior.rfind('\n').map_or(0, |indice| indice + 1);
+    let columna = anterior[inicio_linea..].chars().count() + 1;
+    (linea, columna)
+}
@@ -1,5 +1,9 @@
-use std::iter::Peekable;
 use std::str::Chars;
+use unicode_normalization::UnicodeNormalization;
+use unicode_xid::UnicodeXID;
+
+pub mod error;
+pub use error::{LexError, TipoErrorLexico};
 
 #[derive(Debug, Clone)]
 pub struct TokenConPosicion {
@@ -7,6 +11,14 @@ pub struct TokenConPosicion {
     pub posicion: usize,
 }
 
+/// Comentario `!!` tal como aparece en el código, sin el salto de línea
+/// final. `posicion` es el índice de carácter de su primer `!`.
+#[derive(Debug, Clone)]
+pub struct Comentario {
+    pub texto: String,
+    pub posicion: usize,
+}
+
 #[derive(Debug, Clone)]
 pub enum Token {
     DeclararVariable,
@@ -90,6 +102,45 @@ pub enum Token {
     Desconocido(char),
 }
 
+/// Recorre el texto como un `Peekable<Chars>` sabiendo en qué byte y en qué
+/// carácter está, para dar posiciones exactas a tokens y errores.
+#[derive(Clone)]
+struct Cursor<'a> {
+    resto: Chars<'a>,
+    siguiente: Option<char>,
+    byte: usize,
+    caracter: usize,
+}
+
+impl<'a> Cursor<'a> {
+    fn nuevo(texto: &'a str) -> Self {
+        let mut resto = texto.chars();
+        let siguiente = resto.next();
+        Self {
+            resto,
+            siguiente,
+            byte: 0,
+            caracter: 0,
+        }
+    }
+
+    fn peek(&self) -> Option<&char> {
+        self.siguiente.as_ref()
+    }
+}
+
+impl Iterator for Cursor<'_> {
+    type Item = char;
+
+    fn next(&mut self) -> Option<char> {
+        let actual = self.siguiente?;
+        self.byte += actual.len_utf8();
+        self.caracter += 1;
+        self.siguiente = self.resto.next();
+        Some(actual)
+    }
+}
+
 fn procesar_escape(caracter: char) -> char {
     match caracter {
         'n' => '\n',
@@ -102,7 +153,7 @@ fn procesar_escape(caracter: char) -> char {
     }
 }
 
-fn agregar_caracter_escapado(texto: &mut String, iter: &mut Peekable<Chars>) {
+fn agregar_caracter_escapado(texto: &mut String, iter: &mut Cursor) {
     let siguiente = iter.next();
 
     if siguiente.is_none() {
@@ -121,12 +172,13 @@ fn agregar_caracter_escapado(texto: &mut String, iter: &mut Peekable<Chars>) {
     texto.push(caracter);
 }
 
-fn leer_cadena_simple(iter: &mut Peekable<Chars>) -> String {
+// Las cadenas devuelven su contenido y si se encontró la comilla final.
+fn leer_cadena_simple(iter: &mut Cursor) -> (String, bool) {
     let mut texto = String::new();
 
     while let Some(caracter) = iter.next() {
         if caracter == '\'' {
-            break;
+            return (texto, true);
         }
 
         if caracter == '\\' {
@@ -137,15 +189,15 @@ fn leer_cadena_simple(iter: &mut Peekable<Chars>) -> String {
         texto.push(caracter);
     }
 
-    texto
+    (texto, false)
 }
 
-fn leer_cadena_doble(iter: &mut Peekable<Chars>) -> String {
+fn leer_cadena_doble(iter: &mut Cursor) -> (String, bool) {
     let mut texto = String::new();
 
     while let Some(caracter) = iter.next() {
         if caracter == '"' {
-            break;
+            return (texto, true);
         }
 
         if caracter == '\\' {
@@ -156,25 +208,25 @@ fn leer_cadena_doble(iter: &mut Peekable<Chars>) -> String {
         texto.push(caracter);
     }
 
-    texto
+    (texto, false)
 }
 
-fn es_fin_triple_comilla(iter: &mut Peekable<Chars>) -> bool {
+fn es_fin_triple_comilla(iter: &Cursor) -> bool {
     iter.peek().copied() == Some('\'') && iter.clone().nth(1) == Some('\'')
 }
 
-fn consumir_triple_comilla(iter: &mut Peekable<Chars>) {
+fn consumir_triple_comilla(iter: &mut Cursor) {
     iter.next();
     iter.next();
 }
 
-fn leer_triple_comilla_simple(iter: &mut Peekable<Chars>) -> String {
+fn leer_triple_comilla_simple(iter: &mut Cursor) -> (String, bool) {
     let mut texto = String::new();
 
     while let Some(caracter) = iter.next() {
         if caracter == '\'' && es_fin_triple_comilla(iter) {
             consumir_triple_comilla(iter);
-            break;
+            return (texto, true);
         }
 
         if caracter == '\\' {
@@ -185,15 +237,33 @@ fn leer_triple_comilla_simple(iter: &mut Peekable<Chars>) -> String {
         texto.push(caracter);
     }
 
-    texto
+    (texto, false)
 }
 
-fn leer_numero(iter: &mut Peekable<Chars>, primero: char) -> String {
+fn leer_numero(iter: &mut Cursor, primero: char) -> String {
     let mut numero = primero.to_string();
+
+    // Enteros en hexadecimal (`0x`), binario (`0b`) u octal (`0o`).
+    if primero == '0' {
+        if let Some(&prefijo @ ('x' | 'X' | 'b' | 'B' | 'o' | 'O')) = iter.peek() {
+            numero.push(prefijo);
+            iter.next();
+            while let Some(&s) = iter.peek() {
+                if !s.is_ascii_hexdigit() && s != '_' {
+                    break;
+                }
+                numero.push(s);
+                iter.next();
+            }
+            return numero;
+        }
+    }
+
     let mut punto = false;
     while let Some(&s) = iter.peek() {
-        let es_digito = s.is_ascii_digit();
-        let es_punto = s == '.' && !punto;
+        let es_digito = s.is_ascii_digit() || s == '_';
+        // `3.round()`: el punto solo es decimal si le sigue un dígito.
+        let es_punto = s == '.' && !punto && siguiente_es_digito(iter);
         if !es_digito && !es_punto {
             break;
         }
@@ -203,71 +273,220 @@ fn leer_numero(iter: &mut Peekable<Chars>, primero: char) -> String {
         numero.push(s);
         iter.next();
     }
+
+    // El sufijo `d` marca un literal decimal exacto: `0.1d`.
+    if iter.peek() == Some(&'d') {
+        let mut adelante = iter.clone();
+        adelante.next();
+        if !adelante
+            .peek()
+            .is_some_and(|c| es_caracter_identificador(*c))
+        {
+            numero.push('d');
+            iter.next();
+        }
+    }
     numero
 }
 
-fn leer_palabra(iter: &mut Peekable<Chars>, primero: char) -> String {
+fn siguiente_es_digito(iter: &Cursor) -> bool {
+    let mut adelante = iter.clone();
+    adelante.next();
+    adelante.peek().is_some_and(|c| c.is_ascii_digit())
+}
+
+// `1.2.3`, `12abc`, `0x` o `0b102`: consume el resto pegado al número para
+// informar del literal completo y devuelve si es válido.
+fn validar_numero(numero: &mut String, iter: &mut Cursor) -> bool {
+    let es_resto = |c: char, iter: &Cursor| {
+        es_caracter_identificador(c) || (c == '.' && siguiente_es_digito(iter))
+    };
+
+    let mut valido = true;
+    while let Some(&c) = iter.peek() {
+        if !es_resto(c, iter) {
+            break;
+        }
+        valido = false;
+        numero.push(c);
+        iter.next();
+    }
+
+    let digitos_validos = |digitos: &str, base: u32| {
+        let digitos = digitos.replace('_', "");
+        !digitos.is_empty() && digitos.chars().all(|c| c.is_digit(base))
+    };
+
+    valido
+        && match numero.get(..2) {
+            Some("0x" | "0X") => digitos_validos(&numero[2..], 16),
+            Some("0b" | "0B") => digitos_validos(&numero[2..], 2),
+            Some("0o" | "0O") => digitos_validos(&numero[2..], 8),
+            _ => true,
+        }
+}
+
+fn registrar_error(
+    errores: &mut Vec<LexError>,
+    tipo: TipoErrorLexico,
+    rango: std::ops::Range<usize>,
+    texto: &str,
+) {
+    errores.push(LexError::nuevo(tipo, rango, texto));
+}
+
+/// Un identificador empieza por `_` o por un carácter XID_Start: letras de
+/// cualquier escritura, incluidas las acentuadas y la `ñ`.
+pub fn es_inicio_identificador(caracter: char) -> bool {
+    caracter == '_' || caracter.is_xid_start()
+}
+
+/// Caracteres XID_Continue: letras, dígitos, `_` y marcas combinantes.
+pub fn es_caracter_identificador(caracter: char) -> bool {
+    caracter.is_xid_continue()
+}
+
+/// Forma NFC del identificador, para que `año` escrito con `ñ` precompuesta
+/// o con `n` + tilde combinante sea el mismo nombre.
+pub fn normalizar_identificador(nombre: &str) -> String {
+    nombre.nfc().collect()
+}
+
+fn leer_palabra(iter: &mut Cursor, primero: char) -> String {
     let mut palabra = primero.to_string();
     while let Some(&s) = iter.peek() {
-        if !s.is_ascii_alphanumeric() && s != '_' {
+        if !es_caracter_identificador(s) {
             break;
         }
         palabra.push(s);
         iter.next();
     }
-    palabra
+    normalizar_identificador(&palabra)
 }
 
+/// Analiza el texto sin detenerse en errores: una cadena sin cerrar termina
+/// al final del archivo y los caracteres desconocidos se emiten como
+/// `Token::Desconocido`. Usa `tokenizar` para obtener el error.
 pub fn analizar(texto: &str) -> Vec<Token> {
-    let mut lista = Vec::new();
-    let mut iterador = texto.chars().peekable();
+    analizar_texto(texto)
+        .0
+        .into_iter()
+        .map(|token| token.token)
+        .collect()
+}
+
+pub fn analizar_con_posiciones(texto: &str) -> Vec<TokenConPosicion> {
+    analizar_texto(texto).0
+}
+
+/// Tokens con su posición, o todos los errores léxicos del texto en orden.
+pub fn tokenizar(texto: &str) -> Result<Vec<TokenConPosicion>, Vec<LexError>> {
+    tokenizar_con_comentarios(texto).map(|(tokens, _)| tokens)
+}
+
+/// Como `tokenizar`, pero conserva también los comentarios, que el resto de
+/// fases ignora. Lo usan las herramientas que reescriben el código.
+pub fn tokenizar_con_comentarios(
+    texto: &str,
+) -> Result<(Vec<TokenConPosicion>, Vec<Comentario>), Vec<LexError>> {
+    match analizar_texto(texto) {
+        (tokens, comentarios, errores) if errores.is_empty() => Ok((tokens, comentarios)),
+        (_, _, errores) => Err(errores),
+    }
+}
 
-    while let Some(ch) = iterador.next() {
+// Las posiciones de los tokens son índices de carácter; el error usa bytes.
+fn analizar_texto(texto: &str) -> (Vec<TokenConPosicion>, Vec<Comentario>, Vec<LexError>) {
+    let mut lista = Vec::new();
+    let mut posiciones = Vec::new();
+    let mut comentarios = Vec::new();
+    let mut errores = Vec::new();
+    let mut iterador = Cursor::nuevo(texto);
+    let mut inicio = 0;
+    // Paréntesis, llaves y corchetes abiertos; `true` marca los corchetes de
+    // índice (`lista[`), donde `i:` o `n:` son una variable y una rebanada.
+    let mut agrupaciones: Vec<bool> = Vec::new();
+
+    loop {
+        // Los tokens emitidos en la vuelta anterior empiezan donde empezó ella.
+        posiciones.resize(lista.len(), inicio);
+        inicio = iterador.caracter;
+        let inicio_byte = iterador.byte;
+        let Some(ch) = iterador.next() else {
+            break;
+        };
         let doble = iterador.peek().copied();
 
         if ch == '!' && doble == Some('!') {
-            iterador.next();
-            while let Some(n) = iterador.next() {
+            let mut comentario = String::from("!");
+            for n in iterador.by_ref() {
                 if n == '\n' {
                     break;
                 }
+                comentario.push(n);
             }
+            comentarios.push(Comentario {
+                texto: comentario.trim_end().to_string(),
+                posicion: inicio,
+            });
             continue;
         }
 
         if ch == '\'' && doble == Some('\'') && iterador.clone().nth(1) == Some('\'') {
             iterador.next();
             iterador.next();
-            let val = leer_triple_comilla_simple(&mut iterador);
+            let (val, cerrada) = leer_triple_comilla_simple(&mut iterador);
+            if !cerrada {
+                let rango = inicio_byte..iterador.byte;
+                registrar_error(&mut errores, TipoErrorLexico::CadenaSinCerrar, rango, texto);
+            }
             lista.push(Token::CadenaMultilinea(val));
             continue;
         }
 
-        if ch == '\'' {
-            let val = leer_cadena_simple(&mut iterador);
-            lista.push(Token::CadenaLiteral(val));
-            continue;
-        }
-
-        if ch == '"' {
-            let val = leer_cadena_doble(&mut iterador);
-            lista.push(Token::Cadena(val));
+        if ch == '\'' || ch == '"' {
+            let (val, cerrada) = if ch == '"' {
+                leer_cadena_doble(&mut iterador)
+            } else {
+                leer_cadena_simple(&mut iterador)
+            };
+            if !cerrada {
+                let rango = inicio_byte..iterador.byte;
+                registrar_error(&mut errores, TipoErrorLexico::CadenaSinCerrar, rango, texto);
+            }
+            lista.push(if ch == '"' {
+                Token::Cadena(val)
+            } else {
+                Token::CadenaLiteral(val)
+            });
             continue;
         }
 
         if ch.is_ascii_digit() {
-            let numero = leer_numero(&mut iterador, ch);
+            let mut numero = leer_numero(&mut iterador, ch);
+            if !validar_numero(&mut numero, &mut iterador) {
+                let tipo = TipoErrorLexico::NumeroInvalido(numero.clone());
+                registrar_error(&mut errores, tipo, inicio_byte..iterador.byte, texto);
+            }
             lista.push(Token::Numero(numero));
             continue;
         }
 
-        if ch.is_ascii_alphabetic() || ch == '_' {
+        if es_inicio_identificador(ch) {
             let palabra = leer_palabra(&mut iterador, ch);
             let prox = iterador.peek().copied();
 
             if prox == Some(':') {
                 iterador.next();
+                let en_indice = agrupaciones.last() == Some(&true);
                 match palabra.as_str() {
+                    _ if en_indice => {
+                        lista.push(Token::Identificador(palabra.clone()));
+                        lista.push(Token::DosPuntos);
+                        posiciones.push(inicio);
+                        posiciones.push(iterador.caracter - 1);
+                        continue;
+                    }
                     "v" => {
                         lista.push(Token::DeclararVariable);
                         continue;
@@ -376,9 +595,15 @@ pub fn analizar(texto: &str) -> Vec<Token> {
                         lista.push(Token::DeclararClase);
                         continue;
                     }
+                    "out" => {
+                        lista.push(Token::Out);
+                        continue;
+                    }
                     _ => {
                         lista.push(Token::Identificador(palabra.clone()));
                         lista.push(Token::DosPuntos);
+                        posiciones.push(inicio);
+                        posiciones.push(iterador.caracter - 1);
                         continue;
                     }
                 }
@@ -445,11 +670,14 @@ pub fn analizar(texto: &str) -> Vec<Token> {
             }
 
             lista.push(Token::Identificador(palabra.clone()));
+            posiciones.push(inicio);
 
             if iterador.peek().copied() == Some('-') && iterador.clone().nth(1) == Some('>') {
+                posiciones.push(iterador.caracter);
                 iterador.next();
                 iterador.next();
                 lista.push(Token::OperadorTipo);
+                posiciones.push(iterador.caracter);
 
                 let mut prefijo = String::new();
                 while iterador.peek().copied() == Some('[') && iterador.clone().nth(1) == Some(']')
@@ -461,13 +689,14 @@ pub fn analizar(texto: &str) -> Vec<Token> {
 
                 let mut tipo_base = String::new();
                 while let Some(&c) = iterador.peek() {
-                    if c.is_ascii_alphanumeric() || c == '_' {
+                    if es_caracter_identificador(c) {
                         tipo_base.push(iterador.next().unwrap());
                     } else {
                         break;
                     }
                 }
 
+                let tipo_base = normalizar_identificador(&tipo_base);
                 lista.push(Token::Tipo(format!("{}{}", prefijo, tipo_base)));
             }
 
@@ -554,6 +783,8 @@ pub fn analizar(texto: &str) -> Vec<Token> {
                     lista.push(Token::Or);
                     continue;
                 }
+                let tipo = TipoErrorLexico::CaracterDesconocido(ch);
+                registrar_error(&mut errores, tipo, inicio_byte..iterador.byte, texto);
                 continue;
             }
             '.' => {
@@ -579,26 +810,43 @@ pub fn analizar(texto: &str) -> Vec<Token> {
                 continue;
             }
             '(' => {
+                agrupaciones.push(false);
                 lista.push(Token::ParentesisIzq);
                 continue;
             }
             ')' => {
+                agrupaciones.pop();
                 lista.push(Token::ParentesisDer);
                 continue;
             }
             '{' => {
+                agrupaciones.push(false);
                 lista.push(Token::LlaveIzq);
                 continue;
             }
             '}' => {
+                agrupaciones.pop();
                 lista.push(Token::LlaveDer);
                 continue;
             }
             '[' => {
+                agrupaciones.push(matches!(
+                    lista.last(),
+                    Some(
+                        Token::Identificador(_)
+                            | Token::This
+                            | Token::ParentesisDer
+                            | Token::CorcheteDer
+                            | Token::Cadena(_)
+                            | Token::CadenaLiteral(_)
+                            | Token::CadenaMultilinea(_)
+                    )
+                ));
                 lista.push(Token::CorcheteIzq);
                 continue;
             }
             ']' => {
+                agrupaciones.pop();
                 lista.push(Token::CorcheteDer);
                 continue;
             }
@@ -619,110 +867,19 @@ pub fn analizar(texto: &str) -> Vec<Token> {
                 continue;
             }
             ch if ch.is_whitespace() => continue,
-            _ => lista.push(Token::Desconocido(ch)),
-        }
-    }
-
-    lista
-}
-
-pub fn analizar_con_posiciones(texto: &str) -> Vec<TokenConPosicion> {
-    let tokens = analizar(texto);
-    let mut resultado = Vec::new();
-
-    let chars: Vec<char> = texto.chars().collect();
-    let mut idx_char = 0;
-
-    for token in tokens {
-        while idx_char < chars.len() {
-            if chars[idx_char].is_whitespace() {
-                idx_char += 1;
-                continue;
+            _ => {
+                let tipo = TipoErrorLexico::CaracterDesconocido(ch);
+                registrar_error(&mut errores, tipo, inicio_byte..iterador.byte, texto);
+                lista.push(Token::Desconocido(ch));
             }
-
-            if idx_char + 1 < chars.len() && chars[idx_char] == '!' && chars[idx_char + 1] == '!' {
-                while idx_char < chars.len() && chars[idx_char] != '\n' {
-                    idx_char += 1;
-                }
-                continue;
-            }
-
-            break;
-        }
-
-        resultado.push(TokenConPosicion {
-            token: token.clone(),
-            posicion: idx_char,
-        });
-
-        if idx_char < chars.len() {
-            idx_char += estimar_longitud_token_directo(&token, &chars[idx_char..]);
         }
     }
 
-    resultado
-}
-
-fn estimar_longitud_token_directo(token: &Token, _resto: &[char]) -> usize {
-    use Token::*;
-    match token {
-        Numero(s) | Identificador(s) | Tipo(s) => s.len(),
-        Cadena(s) => s.len() + 2,
-        CadenaLiteral(s) => s.len() + 2,
-        CadenaMultilinea(s) => s.len() + 6,
-        DeclararVariable | DeclararConstante | DeclararFuncion => 2,
-        Instanciar => 2,
-        DeclararClase => 3,
-        DeclararInterfaz => 3,
-        DeclararEnum => 3,
-        PropPrivada => 3,
-        PropPublica => 3,
-        Equip => 5,
-        Origin => 6,
-        As => 2,
-        Out => 3,
-        Implementacion => 4,
-        Extension => 4,
-        If => 2,
-        ElseIf => 3,
-        Else => 2,
-        Switch => 3,
-        Case => 3,
-        Default => 4,
-        For => 3,
-        ForEach => 3,
-        While => 3,
-        DoWhile => 3,
-        Return => 2,
-        This => 2,
-        TPrint => 6,
-        Try => 2,
-        Catch => 2,
-        Finally => 2,
-        Throw => 2,
-        Asy => 3,
-        Awa => 3,
-        FlechaDoble => 2,
-        Asignacion => 1,
-        IgualIgual => 2,
-        Diferente => 2,
-        MenorIgual => 2,
-        MayorIgual => 2,
-        And => 2,
-        Or => 2,
-        Incremento => 2,
-        Decremento => 2,
-        OperadorTipo => 2,
-        Rango => 2,
-        RangoIncluyente => 3,
-        Spread => 1,
-        Verdadero => 4,
-        Falso => 5,
-        Nulo => 4,
-        ParentesisIzq | ParentesisDer | LlaveIzq | LlaveDer | CorcheteIzq | CorcheteDer
-        | PuntoYComa | Coma | DosPuntos | Punto | Flecha | Suma | Resta | Multiplicacion
-        | Division | Modulo | Menor | Mayor | Not | Asterisco => 1,
-        Interpolacion => 2,
-        Desconocido(_) => 1,
-    }
+    posiciones.resize(lista.len(), inicio);
+    let tokens = lista
+        .into_iter()
+        .zip(posiciones)
+        .map(|(token, posicion)| TokenConPosicion { token, posicion })
+        .collect();
+    (tokens, comentarios, errores)
 }
@@ -0,0 +1,851 @@
+//! Recorrido del AST que produce los hallazgos de todas las reglas.
+//!
+//! El AST no guarda posiciones: cada sentencia se ubica con
+//! `Parser::inicios_sentencias`, que el parser llena en preorden, así que
+//! este recorrido consume las sentencias exactamente en ese orden. Los
+//! cuerpos de funciones y métodos se analizan al cerrar el bloque que los
+//! declara, cuando ya se conocen todos los nombres de ese bloque (una
+//! función puede usar una variable global declarada después de ella).
+
+use crate::reglas::Regla;
+use std::collections::HashMap;
+use umbral_lexer::{Token, TokenConPosicion};
+use umbral_parser::ast::*;
+
+const FUNCIONES_INTEGRADAS: [&str; 1] = ["tprint"];
+const VARIABLES_PREDEFINIDAS: [&str; 1] = ["Std"];
+const CONSTANTES_PREDEFINIDAS: [&str; 3] = ["ARGS", "__file__", "__dir__"];
+
+pub struct Hallazgo {
+    pub regla: Regla,
+    pub mensaje: String,
+    /// Índice de carácter en el código.
+    pub posicion: usize,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum TipoSimbolo {
+    Variable,
+    Constante,
+    Parametro,
+    Captura,
+    Importacion,
+    Funcion { es_async: bool },
+    Tipo,
+    Predefinido { constante: bool },
+}
+
+struct Simbolo {
+    nombre: String,
+    tipo: TipoSimbolo,
+    posicion: usize,
+    usado: bool,
+    exportado: bool,
+}
+
+enum Diferido<'p> {
+    Funcion(&'p DeclaracionFuncion),
+    Clase(&'p DeclaracionClase),
+}
+
+/// Cuerpo que se analiza al cerrar el bloque, junto con el punto de
+/// `inicios_sentencias` donde empiezan sus sentencias.
+struct Pendiente<'p> {
+    cuerpo: Diferido<'p>,
+    siguiente: usize,
+    inicio: usize,
+}
+
+pub struct Analizador<'a> {
+    tokens: &'a [TokenConPosicion],
+    inicios: &'a [usize],
+    /// Próxima entrada de `inicios` por consumir.
+    siguiente: usize,
+    /// Primer token de la sentencia que se está analizando.
+    actual: usize,
+    simbolos: Vec<Simbolo>,
+    ambitos: Vec<Vec<usize>>,
+    enums: HashMap<String, Vec<String>>,
+    /// Métodos de las clases que se están analizando y si son asíncronos.
+    clases: Vec<HashMap<String, bool>>,
+    /// Hay un `equip * origin ...` sin alias: cualquier nombre puede venir
+    /// de ahí.
+    importa_todo: bool,
+    hallazgos: Vec<Hallazgo>,
+}
+
+impl<'a> Analizador<'a> {
+    pub fn nuevo(tokens: &'a [TokenConPosicion], inicios: &'a [usize]) -> Self {
+        Self {
+            tokens,
+            inicios,
+            siguiente: 0,
+            actual: 0,
+            simbolos: Vec::new(),
+            ambitos: Vec::new(),
+            enums: HashMap::new(),
+            clases: Vec::new(),
+            importa_todo: false,
+            hallazgos: Vec::new(),
+        }
+    }
+
+    pub fn analizar(mut self, programa: &'a Programa) -> Vec<Hallazgo> {
+        self.abrir_ambito();
+        for nombre in VARIABLES_PREDEFINIDAS {
+            self.agregar(nombre, TipoSimbolo::Predefinido { constante: false }, 0, false);
+        }
+        for nombre in CONSTANTES_PREDEFINIDAS {
+            self.agregar(nombre, TipoSimbolo::Predefinido { constante: true }, 0, false);
+        }
+
+        self.abrir_ambito();
+        self.bloque(&programa.sentencias);
+        self.cerrar_ambito();
+        self.hallazgos
+    }
+
+    // --- Ámbitos y símbolos ---
+
+    fn abrir_ambito(&mut self) {
+        self.ambitos.push(Vec::new());
+    }
+
+    // Al cerrar un ámbito se informa lo que se declaró en él y nunca se leyó.
+    fn cerrar_ambito(&mut self) {
+        let Some(ambito) = self.ambitos.pop() else {
+            return;
+        };
+        for indice in ambito {
+            let simbolo = &self.simbolos[indice];
+            if simbolo.usado || simbolo.exportado || simbolo.nombre.starts_with('_') {
+                continue;
+            }
+            let (regla, mensaje) = match simbolo.tipo {
+                TipoSimbolo::Variable => (
+                    Regla::VariableSinUsar,
+                    format!("La variable '{}' no se usa", simbolo.nombre),
+                ),
+                TipoSimbolo::Constante => (
+                    Regla::VariableSinUsar,
+                    format!("La constante '{}' no se usa", simbolo.nombre),
+                ),
+                TipoSimbolo::Parametro => (
+                    Regla::ParametroSinUsar,
+                    format!("El parámetro '{}' no se usa", simbolo.nombre),
+                ),
+                TipoSimbolo::Importacion => (
+                    Regla::ImportacionSinUsar,
+                    format!("'{}' se importa pero no se usa", simbolo.nombre),
+                ),
+                _ => continue,
+            };
+            let posicion = simbolo.posicion;
+            self.reportar(regla, mensaje, posicion);
+        }
+    }
+
+    fn agregar(&mut self, nombre: &str, tipo: TipoSimbolo, posicion: usize, exportado: bool) {
+        let indice = self.simbolos.len();
+        self.simbolos.push(Simbolo {
+            nombre: nombre.to_string(),
+            tipo,
+            posicion,
+            usado: false,
+            exportado,
+        });
+        if let Some(ambito) = self.ambitos.last_mut() {
+            ambito.push(indice);
+        }
+    }
+
+    // Declara una variable, constante o variable de `fe:` comprobando antes
+    // si oculta otra declaración.
+    fn declarar(&mut self, nombre: &str, tipo: TipoSimbolo, exportado: bool) {
+        let posicion = self.posicion_token(self.ubicar(nombre, self.actual));
+        let en_este_ambito = self
+            .ambitos
+            .last()
+            .is_some_and(|ambito| ambito.iter().any(|&i| self.simbolos[i].nombre == nombre));
+        if en_este_ambito {
+            self.reportar(
+                Regla::Sombreado,
+                format!("'{}' ya está declarada en este ámbito", nombre),
+                posicion,
+            );
+        } else if self.resolver(nombre).is_some() {
+            self.reportar(
+                Regla::Sombreado,
+                format!("'{}' oculta una declaración de un ámbito superior", nombre),
+                posicion,
+            );
+        }
+        self.agregar(nombre, tipo, posicion, exportado);
+    }
+
+    fn resolver(&self, nombre: &str) -> Option<usize> {
+        self.ambitos.iter().rev().find_map(|ambito| {
+            ambito
+                .iter()
+                .rev()
+                .copied()
+                .find(|&indice| self.simbolos[indice].nombre == nombre)
+        })
+    }
+
+    fn usar(&mut self, nombre: &str) {
+        if let Some(indice) = self.resolver(nombre) {
+            self.simbolos[indice].usado = true;
+        }
+    }
+
+    fn es_constante(&self, nombre: &str) -> bool {
+        self.resolver(nombre).is_some_and(|indice| {
+            matches!(
+                self.simbolos[indice].tipo,
+                TipoSimbolo::Constante | TipoSimbolo::Predefinido { constante: true }
+            )
+        })
+    }
+
+    // Funciones, clases, interfaces y enums se conocen en todo su bloque,
+    // también antes de la línea que los declara.
+    fn declarar_elevadas(&mut self, sentencias: &[Sentencia]) {
+        for sentencia in sentencias {
+            let sentencia = match sentencia {
+                Sentencia::ExportacionPorDefecto(interna) => interna.as_ref(),
+                otra => otra,
+            };
+            match sentencia {
+                Sentencia::Funcion(funcion) => self.agregar(
+                    &funcion.nombre,
+                    TipoSimbolo::Funcion {
+                        es_async: funcion.es_async,
+                    },
+                    0,
+                    funcion.exportado,
+                ),
+                Sentencia::Clase(clase) => {
+                    self.agregar(&clase.nombre, TipoSimbolo::Tipo, 0, clase.exportado)
+                }
+                Sentencia::Interfaz(interfaz) => {
+                    self.agregar(&interfaz.nombre, TipoSimbolo::Tipo, 0, interfaz.exportado)
+                }
+                Sentencia::Enum(decl_enum) => {
+                    self.agregar(&decl_enum.nombre, TipoSimbolo::Tipo, 0, decl_enum.exportado);
+                    let variantes = decl_enum.variantes.iter().map(|v| v.nombre.clone());
+                    self.enums
+                        .insert(decl_enum.nombre.clone(), variantes.collect());
+                }
+                _ => {}
+            }
+        }
+    }
+
+    // --- Posiciones ---
+
+    fn tomar_inicio(&mut self) -> usize {
+        let inicio = self.inicios.get(self.siguiente).copied().unwrap_or(self.actual);
+        self.siguiente += 1;
+        self.actual = inicio;
+        inicio
+    }
+
+    fn posicion_token(&self, indice: usize) -> usize {
+        self.tokens
+            .get(indice)
+            .or(self.tokens.last())
+            .map(|token| token.posicion)
+            .unwrap_or(0)
+    }
+
+    // Primer identificador `nombre` desde el token `desde`.
+    fn ubicar(&self, nombre: &str, desde: usize) -> usize {
+        self.buscar_identificador(nombre, desde, false)
+            .unwrap_or(desde)
+    }
+
+    fn buscar_identificador(&self, nombre: &str, desde: usize, llamada: bool) -> Option<usize> {
+        (desde..self.tokens.len()).find(|&i| {
+            matches!(&self.tokens[i].token, Token::Identificador(n) if n == nombre)
+                && (!llamada
+                    || matches!(
+                        self.tokens.get(i + 1).map(|t| &t.token),
+                        Some(Token::ParentesisIzq)
+                    ))
+        })
+    }
+
+    fn buscar_parentesis(&self, desde: usize) -> usize {
+        (desde..self.tokens.len())
+            .find(|&i| matches!(self.tokens[i].token, Token::ParentesisIzq))
+            .unwrap_or(desde)
+    }
+
+    fn reportar(&mut self, regla: Regla, mensaje: String, posicion: usize) {
+        self.hallazgos.push(Hallazgo {
+            regla,
+            mensaje,
+            posicion,
+        });
+    }
+
+    // --- Sentencias ---
+
+    fn bloque(&mut self, sentencias: &'a [Sentencia]) {
+        let actual = self.actual;
+        self.declarar_elevadas(sentencias);
+        let mut pendientes = Vec::new();
+        let mut terminado = false;
+        let mut avisado = false;
+
+        for sentencia in sentencias {
+            let inicio = self.tomar_inicio();
+            if terminado && !avisado {
+                let posicion = self.posicion_token(inicio);
+                self.reportar(
+                    Regla::CodigoInalcanzable,
+                    "Código inalcanzable: el bloque ya terminó con `r:` o `tw:`".to_string(),
+                    posicion,
+                );
+                avisado = true;
+            }
+            self.sentencia(sentencia, inicio, &mut pendientes);
+            terminado |= termina(sentencia);
+        }
+
+        self.analizar_pendientes(pendientes);
+        self.actual = actual;
+    }
+
+    fn bloque_con_ambito(&mut self, sentencias: &'a [Sentencia]) {
+        self.abrir_ambito();
+        self.bloque(sentencias);
+        self.cerrar_ambito();
+    }
+
+    fn sentencia(
+        &mut self,
+        sentencia: &'a Sentencia,
+        inicio: usize,
+        pendientes: &mut Vec<Pendiente<'a>>,
+    ) {
+        match sentencia {
+            Sentencia::DeclaracionVariable(decl) => {
+                self.expresion(&decl.valor);
+                self.declarar(&decl.nombre, TipoSimbolo::Variable, decl.exportado);
+            }
+            Sentencia::DeclaracionConstante(decl) => {
+                self.expresion(&decl.valor);
+                self.declarar(&decl.nombre, TipoSimbolo::Constante, decl.exportado);
+            }
+            Sentencia::Asignacion(asignacion) => {
+                self.expresion(&asignacion.valor);
+                match &asignacion.objetivo {
+                    ObjetivoAsignacion::Variable(nombre) => self.asignar(nombre),
+                    ObjetivoAsignacion::Propiedad { objeto, .. } => self.expresion(objeto),
+                }
+            }
+            Sentencia::LlamadoTPrint(llamado) => self.expresion(&llamado.valor),
+            Sentencia::Return(valor) => self.expresion(valor),
+            Sentencia::Throw(throw) => self.expresion(&throw.valor),
+            Sentencia::Expresion(expresion) => self.expresion(expresion),
+            Sentencia::LlamadoFuncion(llamado) => {
+                self.llamada(&llamado.nombre, &llamado.argumentos)
+            }
+            Sentencia::If(si) => {
+                self.expresion(&si.condicion);
+                self.bloque_con_ambito(&si.bloque_entonces);
+                for else_if in &si.else_ifs {
+                    self.expresion(&else_if.condicion);
+                    self.bloque_con_ambito(&else_if.bloque);
+                }
+                if let Some(bloque) = &si.bloque_else {
+                    self.bloque_con_ambito(bloque);
+                }
+            }
+            Sentencia::Switch(switch) => self.switch(switch, inicio),
+            Sentencia::For(bucle) => {
+                self.abrir_ambito();
+                self.sentencia_suelta(&bucle.inicializacion);
+                self.expresion(&bucle.condicion);
+                self.expresion(&bucle.incremento);
+                self.bloque_con_ambito(&bucle.bloque);
+                self.cerrar_ambito();
+            }
+            Sentencia::ForEach(bucle) => {
+                self.expresion(&bucle.iterable);
+                self.abrir_ambito();
+                self.declarar(&bucle.variable, TipoSimbolo::Variable, false);
+                self.bloque_con_ambito(&bucle.bloque);
+                self.cerrar_ambito();
+            }
+            Sentencia::While(bucle) => {
+                self.expresion(&bucle.condicion);
+                self.bloque_con_ambito(&bucle.bloque);
+            }
+            Sentencia::DoWhile(bucle) => {
+                self.bloque_con_ambito(&bucle.bloque);
+                self.expresion(&bucle.condicion);
+            }
+            Sentencia::TryCatch(intento) => {
+                self.bloque_con_ambito(&intento.bloque_try);
+                if let Some(captura) = &intento.bloque_catch {
+                    self.abrir_ambito();
+                    self.agregar(&captura.variable, TipoSimbolo::Captura, 0, false);
+                    self.bloque_con_ambito(&captura.bloque);
+                    self.cerrar_ambito();
+                }
+                if let Some(bloque) = &intento.bloque_finally {
+                    self.bloque_con_ambito(bloque);
+                }
+            }
+            Sentencia::Funcion(funcion) => {
+                pendientes.push(Pendiente {
+                    cuerpo: Diferido::Funcion(funcion),
+                    siguiente: self.siguiente,
+                    inicio,
+                });
+                self.siguiente += contar_bloque(&funcion.cuerpo);
+            }
+            Sentencia::Clase(clase) => {
+                for nombre in clase.extensiones.iter().chain(&clase.implementaciones) {
+                    self.usar(nombre);
+                }
+                for propiedad in &clase.propiedades {
+                    if let Some(valor) = &propiedad.valor_inicial {
+                        self.expresion(valor);
+                    }
+                }
+                pendientes.push(Pendiente {
+                    cuerpo: Diferido::Clase(clase),
+                    siguiente: self.siguiente,
+                    inicio,
+                });
+                self.siguiente += clase
+                    .metodos
+                    .iter()
+                    .map(|metodo| contar_bloque(&metodo.cuerpo))
+                    .sum::<usize>();
+            }
+            Sentencia::Interfaz(_) => {}
+            Sentencia::Enum(decl_enum) => {
+                for variante in &decl_enum.variantes {
+                    if let Some(valor) = &variante.valor {
+                        self.expresion(valor);
+                    }
+                }
+            }
+            Sentencia::Importacion(importacion) => {
+                for item in &importacion.items {
+                    self.importar(item);
+                }
+            }
+            Sentencia::Exportacion(nombre) => self.usar(nombre),
+            Sentencia::ListaExportacion(lista) => {
+                if lista.origen.is_none() {
+                    for item in &lista.items {
+                        if let ItemExportacion::Nombre(nombre, _) = item {
+                            self.usar(nombre);
+                        }
+                    }
+                }
+            }
+            Sentencia::ExportacionPorDefecto(interna) => {
+                self.sentencia(interna, inicio, pendientes);
+                if let Sentencia::DeclaracionVariable(DeclaracionVariable { nombre, .. })
+                | Sentencia::DeclaracionConstante(DeclaracionConstante { nombre, .. }) =
+                    interna.as_ref()
+                {
+                    self.usar(nombre);
+                }
+            }
+            Sentencia::Ubicada(_, interna) => self.sentencia(interna, inicio, pendientes),
+        }
+    }
+
+    // Sentencia única con su propio ámbito (cada `ca:` de un `sw:`).
+    fn sentencia_suelta(&mut self, sentencia: &'a Sentencia) {
+        let actual = self.actual;
+        let inicio = self.tomar_inicio();
+        let mut pendientes = Vec::new();
+        self.sentencia(sentencia, inicio, &mut pendientes);
+        self.analizar_pendientes(pendientes);
+        self.actual = actual;
+    }
+
+    fn switch(&mut self, switch: &'a Switch, inicio: usize) {
+        self.expresion(&switch.expresion);
+        for caso in &switch.casos {
+            self.expresion(&caso.valor);
+        }
+        self.comprobar_exhaustivo(switch, inicio);
+
+        // `def:` puede escribirse antes que algún `ca:`: se recorren en el
+        // orden del código, que es el de `inicios_sentencias`.
+        let mut casos = switch.casos.iter();
+        let mut default = switch.default.as_ref();
+        loop {
+            let es_default = default.is_some() && self.siguiente_es_default();
+            let bloque = match es_default {
+                true => default.take(),
+                false => casos.next().map(|caso| &caso.bloque).or_else(|| default.take()),
+            };
+            let Some(bloque) = bloque else {
+                break;
+            };
+            self.abrir_ambito();
+            for sentencia in bloque {
+                self.sentencia_suelta(sentencia);
+            }
+            self.cerrar_ambito();
+        }
+    }
+
+    fn siguiente_es_default(&self) -> bool {
+        let Some(&inicio) = self.inicios.get(self.siguiente) else {
+            return false;
+        };
+        inicio >= 2 && matches!(self.tokens[inicio - 2].token, Token::Default)
+    }
+
+    fn comprobar_exhaustivo(&mut self, switch: &Switch, inicio: usize) {
+        if switch.default.is_some() || switch.casos.is_empty() {
+            return;
+        }
+
+        let mut nombre_enum = None;
+        let mut cubiertas = Vec::new();
+        for caso in &switch.casos {
+            let Expresion::AccesoPropiedad { objeto, propiedad } = &caso.valor else {
+                return;
+            };
+            let Expresion::Identificador(nombre) = objeto.as_ref() else {
+                return;
+            };
+            if nombre_enum.is_some_and(|anterior: &String| anterior != nombre) {
+                return;
+            }
+            nombre_enum = Some(nombre);
+            cubiertas.push(propiedad.as_str());
+        }
+
+        let Some(nombre_enum) = nombre_enum else {
+            return;
+        };
+        let Some(variantes) = self.enums.get(nombre_enum) else {
+            return;
+        };
+        let faltantes: Vec<&str> = variantes
+            .iter()
+            .map(String::as_str)
+            .filter(|variante| !cubiertas.contains(variante))
+            .collect();
+        if faltantes.is_empty() {
+            return;
+        }
+
+        let mensaje = format!(
+            "El `sw:` sobre '{}' no cubre {} y no tiene `def:`",
+            nombre_enum,
+            faltantes.join(", ")
+        );
+        let posicion = self.posicion_token(inicio);
+        self.reportar(Regla::SwitchNoExhaustivo, mensaje, posicion);
+    }
+
+    fn importar(&mut self, item: &ItemImportacion) {
+        let nombre = match item {
+            ItemImportacion::Todo(None) => {
+                self.importa_todo = true;
+                return;
+            }
+            ItemImportacion::ListaNombres(items) => {
+                for item in items {
+                    self.importar(item);
+                }
+                return;
+            }
+            ItemImportacion::Todo(Some(alias)) => alias,
+            ItemImportacion::Nombre(nombre, alias) | ItemImportacion::Modulo(nombre, alias) => {
+                alias.as_ref().unwrap_or(nombre)
+            }
+        };
+        let posicion = self.posicion_token(self.ubicar(nombre, self.actual));
+        self.agregar(nombre, TipoSimbolo::Importacion, posicion, false);
+    }
+
+    fn asignar(&mut self, nombre: &str) {
+        if !self.es_constante(nombre) {
+            return;
+        }
+        let posicion = self.posicion_token(self.ubicar(nombre, self.actual));
+        self.reportar(
+            Regla::AsignacionAConstante,
+            format!("No se puede asignar a la constante '{}'", nombre),
+            posicion,
+        );
+    }
+
+    fn analizar_pendientes(&mut self, pendientes: Vec<Pendiente<'a>>) {
+        for pendiente in pendientes {
+            let siguiente = self.siguiente;
+            self.siguiente = pendiente.siguiente;
+            self.actual = pendiente.inicio;
+
+            match pendiente.cuerpo {
+                Diferido::Funcion(funcion) => {
+                    let parametros = self.buscar_parentesis(pendiente.inicio);
+                    self.funcion(&funcion.parametros, &funcion.cuerpo, parametros);
+                }
+                Diferido::Clase(clase) => {
+                    let metodos = clase
+                        .metodos
+                        .iter()
+                        .map(|metodo| (metodo.nombre.clone(), metodo.es_async))
+                        .collect();
+                    self.clases.push(metodos);
+                    let mut desde = pendiente.inicio;
+                    for metodo in &clase.metodos {
+                        desde = self
+                            .buscar_identificador(&metodo.nombre, desde, true)
+                            .unwrap_or(desde);
+                        self.actual = pendiente.inicio;
+                        self.funcion(&metodo.parametros, &metodo.cuerpo, desde);
+                    }
+                    self.clases.pop();
+                }
+            }
+
+            self.siguiente = siguiente;
+        }
+    }
+
+    // Los parámetros y el cuerpo comparten ámbito; `parametros` es el token
+    // desde el que se buscan sus nombres.
+    fn funcion(&mut self, parametros: &[Parametro], cuerpo: &'a [Sentencia], desde: usize) {
+        self.abrir_ambito();
+        for parametro in parametros {
+            let posicion = self.posicion_token(self.ubicar(&parametro.nombre, desde));
+            self.agregar(&parametro.nombre, TipoSimbolo::Parametro, posicion, false);
+        }
+        self.bloque(cuerpo);
+        self.cerrar_ambito();
+    }
+
+    // --- Expresiones ---
+
+    fn llamada(&mut self, nombre: &str, argumentos: &[Expresion]) {
+        for argumento in argumentos {
+            self.expresion(argumento);
+        }
+        if FUNCIONES_INTEGRADAS.contains(&nombre) {
+            return;
+        }
+        if self.resolver(nombre).is_some() {
+            self.usar(nombre);
+            return;
+        }
+        if self.importa_todo {
+            return;
+        }
+        let token = self
+            .buscar_identificador(nombre, self.actual, true)
+            .unwrap_or(self.actual);
+        let posicion = self.posicion_token(token);
+        self.reportar(
+            Regla::FuncionNoDefinida,
+            format!("La función '{}' no está definida", nombre),
+            posicion,
+        );
+    }
+
+    fn esperar(&mut self, expresion: &Expresion) {
+        let nombre = match expresion {
+            Expresion::LlamadoFuncion { nombre, .. } => {
+                let sincrona = self.resolver(nombre).is_some_and(|indice| {
+                    self.simbolos[indice].tipo == TipoSimbolo::Funcion { es_async: false }
+                });
+                if !sincrona {
+                    return;
+                }
+                nombre
+            }
+            Expresion::LlamadoMetodo {
+                objeto, metodo, ..
+            } if matches!(objeto.as_ref(), Expresion::This) => {
+                let sincrono = self
+                    .clases
+                    .last()
+                    .and_then(|metodos| metodos.get(metodo))
+                    .is_some_and(|es_async| !es_async);
+                if !sincrono {
+                    return;
+                }
+                metodo
+            }
+            _ => return,
+        };
+
+        let token = self
+            .buscar_identificador(nombre, self.actual, true)
+            .unwrap_or(self.actual);
+        let posicion = self.posicion_token(token);
+        self.reportar(
+            Regla::AwaitInnecesario,
+            format!("'{}' no es asíncrona: `awa` no espera nada", nombre),
+            posicion,
+        );
+    }
+
+    fn expresion(&mut self, expresion: &Expresion) {
+        match expresion {
+            Expresion::Identificador(nombre) => self.usar(nombre),
+            Expresion::Binaria {
+                izquierda, derecha, ..
+            } => {
+                self.expresion(izquierda);
+                self.expresion(derecha);
+            }
+            Expresion::Unaria {
+                operador,
+                expresion,
+            } => {
+                if let ("++" | "--", Expresion::Identificador(nombre)) =
+                    (operador.as_str(), expresion.as_ref())
+                {
+                    self.asignar(nombre);
+                }
+                self.expresion(expresion);
+            }
+            Expresion::Await(interna) => {
+                self.esperar(interna);
+                self.expresion(interna);
+            }
+            Expresion::Spread(interna) | Expresion::Agrupada(interna) => self.expresion(interna),
+            Expresion::Array(elementos) => {
+                for elemento in elementos {
+                    self.expresion(elemento);
+                }
+            }
+            Expresion::Objeto(pares) => {
+                for (_, valor) in pares {
+                    self.expresion(valor);
+                }
+            }
+            Expresion::Plantilla(partes) => {
+                for parte in partes {
+                    if let ParteCadena::Expresion(interna) = parte {
+                        self.expresion(interna);
+                    }
+                }
+            }
+            Expresion::Instanciacion { tipo, argumentos } => {
+                self.usar(tipo);
+                for argumento in argumentos {
+                    self.expresion(argumento);
+                }
+            }
+            Expresion::AccesoPropiedad { objeto, .. } => self.expresion(objeto),
+            Expresion::AccesoIndice { objeto, indice } => {
+                self.expresion(objeto);
+                self.expresion(indice);
+            }
+            Expresion::Rebanada {
+                objeto,
+                inicio,
+                fin,
+            } => {
+                self.expresion(objeto);
+                for limite in [inicio, fin].into_iter().flatten() {
+                    self.expresion(limite);
+                }
+            }
+            Expresion::LlamadoMetodo {
+                objeto, argumentos, ..
+            } => {
+                self.expresion(objeto);
+                for argumento in argumentos {
+                    self.expresion(argumento);
+                }
+            }
+            Expresion::LlamadoFuncion { nombre, argumentos } => self.llamada(nombre, argumentos),
+            Expresion::LiteralEntero(_)
+            | Expresion::LiteralFloat(_)
+            | Expresion::LiteralDecimal(_)
+            | Expresion::LiteralCadena(_)
+            | Expresion::LiteralCadenaLiteral(_)
+            | Expresion::LiteralBool(_)
+            | Expresion::LiteralNulo
+            | Expresion::This => {}
+        }
+    }
+}
+
+// Un bloque termina si alguna de sus sentencias siempre sale de él.
+fn termina(sentencia: &Sentencia) -> bool {
+    let bloque_termina = |bloque: &[Sentencia]| bloque.iter().any(termina);
+    match sentencia {
+        Sentencia::Return(_) | Sentencia::Throw(_) => true,
+        Sentencia::If(si) => {
+            si.bloque_else.as_deref().is_some_and(bloque_termina)
+                && bloque_termina(&si.bloque_entonces)
+                && si.else_ifs.iter().all(|else_if| bloque_termina(&else_if.bloque))
+        }
+        _ => false,
+    }
+}
+
+// Entradas de `inicios_sentencias` que ocupa una sentencia con todo lo que
+// contiene. La declaración dentro de `out def:` no tiene entrada propia.
+fn contar(sentencia: &Sentencia) -> usize {
+    match sentencia {
+        Sentencia::ExportacionPorDefecto(interna) => contar(interna),
+        otra => 1 + contar_internas(otra),
+    }
+}
+
+fn contar_bloque(sentencias: &[Sentencia]) -> usize {
+    sentencias.iter().map(contar).sum()
+}
+
+fn contar_internas(sentencia: &Sentencia) -> usize {
+    match sentencia {
+        Sentencia::If(si) => {
+            contar_bloque(&si.bloque_entonces)
+                + si
+                    .else_ifs
+                    .iter()
+                    .map(|else_if| contar_bloque(&else_if.bloque))
+                    .sum::<usize>()
+                + si.bloque_else.as_deref().map_or(0, contar_bloque)
+        }
+        Sentencia::Switch(switch) => {
+            switch
+                .casos
+                .iter()
+                .map(|caso| contar_bloque(&caso.bloque))
+                .sum::<usize>()
+                + switch.default.as_deref().map_or(0, contar_bloque)
+        }
+        Sentencia::For(bucle) => contar(&bucle.inicializacion) + contar_bloque(&bucle.bloque),
+        Sentencia::ForEach(bucle) => contar_bloque(&bucle.bloque),
+        Sentencia::While(bucle) => contar_bloque(&bucle.bloque),
+        Sentencia::DoWhile(bucle) => contar_bloque(&bucle.bloque),
+        Sentencia::Funcion(funcion) => contar_bloque(&funcion.cuerpo),
+        Sentencia::Clase(clase) => clase
+            .metodos
+            .iter()
+            .map(|metodo| contar_bloque(&metodo.cuerpo))
+            .sum(),
+        Sentencia::TryCatch(intento) => {
+            contar_bloque(&intento.bloque_try)
+                + intento
+                    .bloque_catch
+                    .as_ref()
+                    .map_or(0, |captura| contar_bloque(&captura.bloque))
+                + intento.bloque_finally.as_deref().map_or(0, contar_bloque)
+        }
+        _ => 0,
+    }
+}
@@ -0,0 +1,89 @@
+//! Supresiones escritas en comentarios:
+//!
+//! - `!! lint: ignorar regla, otra` al final de una línea la silencia; en
+//!   una línea propia silencia la siguiente línea con código.
+//! - `!! lint: ignorar-archivo regla` silencia la regla en todo el archivo.
+//!
+//! Sin nombres de reglas, la directiva se aplica a todas.
+
+use crate::reglas::Regla;
+use crate::Lineas;
+use std::collections::HashMap;
+use umbral_lexer::{Comentario, TokenConPosicion};
+
+const PREFIJO: &str = "lint:";
+
+/// `None` significa todas las reglas.
+type Filtro = Option<Vec<Regla>>;
+
+#[derive(Debug, Default)]
+pub struct Supresiones {
+    archivo: Vec<Filtro>,
+    lineas: HashMap<usize, Vec<Filtro>>,
+}
+
+impl Supresiones {
+    pub fn desde_comentarios(
+        comentarios: &[Comentario],
+        tokens: &[TokenConPosicion],
+        lineas: &Lineas,
+    ) -> Self {
+        let mut supresiones = Self::default();
+        for comentario in comentarios {
+            let Some((todo_el_archivo, filtro)) = leer_directiva(&comentario.texto) else {
+                continue;
+            };
+            if todo_el_archivo {
+                supresiones.archivo.push(filtro);
+                continue;
+            }
+
+            let linea = lineas.linea(comentario.posicion);
+            let tiene_codigo_antes = tokens.iter().any(|token| {
+                token.posicion < comentario.posicion && lineas.linea(token.posicion) == linea
+            });
+            let objetivo = match tiene_codigo_antes {
+                true => Some(linea),
+                false => tokens
+                    .iter()
+                    .find(|token| token.posicion > comentario.posicion)
+                    .map(|token| lineas.linea(token.posicion)),
+            };
+            if let Some(objetivo) = objetivo {
+                supresiones.lineas.entry(objetivo).or_default().push(filtro);
+            }
+        }
+        supresiones
+    }
+
+    pub fn suprime(&self, regla: Regla, linea: usize) -> bool {
+        let aplica = |filtro: &Filtro| filtro.as_ref().is_none_or(|reglas| reglas.contains(&regla));
+        self.archivo.iter().any(aplica)
+            || self
+                .lineas
+                .get(&linea)
+                .is_some_and(|filtros| filtros.iter().any(aplica))
+    }
+}
+
+fn leer_directiva(texto: &str) -> Option<(bool, Filtro)> {
+    let resto = texto.trim_start_matches('!').trim().strip_prefix(PREFIJO)?.trim();
+    let (todo_el_archivo, resto) = if let Some(resto) = resto.strip_prefix("ignorar-archivo") {
+        (true, resto)
+    } else {
+        (false, resto.strip_prefix("ignorar")?)
+    };
+    if !resto.is_empty() && !resto.starts_with(char::is_whitespace) {
+        return None;
+    }
+
+    let reglas: Vec<Regla> = resto
+        .split(|c: char| c == ',' || c.is_whitespace())
+        .filter_map(Regla::desde_nombre)
+        .collect();
+    let filtro = match resto.trim().is_empty() {
+        true => None,
+        false => Some(reglas),
+    };
+    Some((todo_el_archivo, filtro))
+}
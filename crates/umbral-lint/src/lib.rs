@@ -0,0 +1,211 @@
+//! Linter de Umbral.
+//!
+//! `analizar` recorre el AST sin ejecutarlo y devuelve los avisos de cada
+//! regla activa (variables, importaciones y parámetros sin usar, sombreado,
+//! código inalcanzable, asignaciones a constantes, funciones no definidas,
+//! `sw:` no exhaustivos sobre enums y `awa` innecesarios). Las reglas se
+//! configuran con `Configuracion` y se silencian con comentarios
+//! `!! lint: ignorar ...`.
+
+mod analisis;
+mod directivas;
+mod reglas;
+
+pub use reglas::{Configuracion, Nivel, Regla};
+
+use analisis::Analizador;
+use directivas::Supresiones;
+use std::fmt;
+use umbral_lexer::tokenizar_con_comentarios;
+use umbral_parser::{ParseError, Parser};
+
+#[derive(Debug, Clone)]
+pub struct Diagnostico {
+    pub regla: Regla,
+    pub nivel: Nivel,
+    pub mensaje: String,
+    /// Índice de carácter en el código.
+    pub posicion: usize,
+    pub linea: usize,
+    pub columna: usize,
+}
+
+impl fmt::Display for Diagnostico {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{}:{}: {}[{}]: {}",
+            self.linea, self.columna, self.nivel, self.regla, self.mensaje
+        )
+    }
+}
+
+/// Analiza `codigo` y devuelve los diagnósticos ordenados por posición. Si
+/// el código no se puede parsear devuelve los errores de sintaxis.
+pub fn analizar(
+    codigo: &str,
+    configuracion: &Configuracion,
+) -> Result<Vec<Diagnostico>, Vec<ParseError>> {
+    let (tokens, comentarios) = tokenizar_con_comentarios(codigo).map_err(|errores| {
+        errores
+            .iter()
+            .map(|e| {
+                let posicion = codigo[..e.rango.start].chars().count();
+                ParseError::con_contexto(e.mensaje(), posicion, codigo)
+            })
+            .collect::<Vec<_>>()
+    })?;
+    let mut parser = Parser::nuevo_con_posiciones(tokens.clone(), codigo.to_string());
+    let programa = parser.parsear_programa()?;
+
+    let hallazgos = Analizador::nuevo(&tokens, &parser.inicios_sentencias).analizar(&programa);
+
+    let lineas = Lineas::nuevo(codigo);
+    let supresiones = Supresiones::desde_comentarios(&comentarios, &tokens, &lineas);
+    let mut diagnosticos: Vec<Diagnostico> = hallazgos
+        .into_iter()
+        .filter_map(|hallazgo| {
+            let nivel = configuracion.nivel(hallazgo.regla);
+            let linea = lineas.linea(hallazgo.posicion);
+            if nivel == Nivel::Desactivada || supresiones.suprime(hallazgo.regla, linea) {
+                return None;
+            }
+            Some(Diagnostico {
+                regla: hallazgo.regla,
+                nivel,
+                mensaje: hallazgo.mensaje,
+                posicion: hallazgo.posicion,
+                linea,
+                columna: hallazgo.posicion - lineas.inicios[linea - 1] + 1,
+            })
+        })
+        .collect();
+    diagnosticos.sort_by_key(|diagnostico| diagnostico.posicion);
+    Ok(diagnosticos)
+}
+
+/// Inicio (índice de carácter) de cada línea, para pasar de posiciones a
+/// líneas numeradas desde 1.
+pub(crate) struct Lineas {
+    inicios: Vec<usize>,
+}
+
+impl Lineas {
+    fn nuevo(codigo: &str) -> Self {
+        let mut inicios = vec![0];
+        for (indice, caracter) in codigo.chars().enumerate() {
+            if caracter == '\n' {
+                inicios.push(indice + 1);
+            }
+        }
+        Self { inicios }
+    }
+
+    pub(crate) fn linea(&self, posicion: usize) -> usize {
+        self.inicios.partition_point(|inicio| *inicio <= posicion)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn reglas(codigo: &str, configuracion: &Configuracion) -> Vec<(usize, Regla)> {
+        analizar(codigo, configuracion)
+            .expect("el código debería parsear")
+            .into_iter()
+            .map(|diagnostico| (diagnostico.linea, diagnostico.regla))
+            .collect()
+    }
+
+    const PROGRAMA: &str = "\
+em: Color { Rojo, Verde, Azul }
+c: LIMITE = 10;
+f: sincrona() { r: (1); }
+f: principal(usado, sobrante) {
+    v: color = Color.Rojo;
+    sw: (color) {
+        def: => tprint(usado);
+        ca: Color.Rojo => tprint(\"rojo\");
+    }
+    sw: (color) {
+        ca: Color.Rojo => tprint(\"rojo\");
+    }
+    LIMITE = LIMITE + 5;
+    v: LIMITE = awa sincrona();
+    tprint(noExiste());
+    r: (1);
+    tprint(\"nunca\");
+}
+principal(1, 2);
+equip { sumar } origin \"./otro.um\";
+";
+
+    #[test]
+    fn detecta_cada_regla_en_su_linea() {
+        assert_eq!(
+            reglas(PROGRAMA, &Configuracion::default()),
+            vec![
+                (4, Regla::ParametroSinUsar),
+                (10, Regla::SwitchNoExhaustivo),
+                (13, Regla::AsignacionAConstante),
+                (14, Regla::Sombreado),
+                (14, Regla::VariableSinUsar),
+                (14, Regla::AwaitInnecesario),
+                (15, Regla::FuncionNoDefinida),
+                (17, Regla::CodigoInalcanzable),
+                (20, Regla::ImportacionSinUsar),
+            ]
+        );
+    }
+
+    #[test]
+    fn respeta_directivas_y_configuracion() {
+        let codigo = "\
+!! lint: ignorar-archivo sombreado
+v: x = 1;
+f: f1() { v: x = 2; r: (x); }
+f1(); !! lint: ignorar
+!! lint: ignorar funcion-no-definida
+f2();
+f3();
+";
+        assert_eq!(
+            reglas(codigo, &Configuracion::default()),
+            vec![(2, Regla::VariableSinUsar), (7, Regla::FuncionNoDefinida)]
+        );
+
+        let tabla = [("variable-sin-usar".to_string(), "off".to_string())];
+        let configuracion =
+            Configuracion::desde_tabla(tabla.iter().map(|(regla, nivel)| (regla, nivel))).unwrap();
+        assert_eq!(
+            reglas(codigo, &configuracion),
+            vec![(7, Regla::FuncionNoDefinida)]
+        );
+
+        let invalida = [("sombreado".to_string(), "nada".to_string())];
+        assert!(Configuracion::desde_tabla(invalida.iter().map(|(r, n)| (r, n))).is_err());
+    }
+
+    #[test]
+    fn los_ejemplos_no_tienen_errores() {
+        let raiz = std::path::Path::new(env!("CARGO_MANIFEST_DIR")).join("../../ejemplos");
+        for directorio in [raiz.clone(), raiz.join("modulos")] {
+            for entrada in std::fs::read_dir(&directorio).unwrap().flatten() {
+                let ruta = entrada.path();
+                if ruta.extension().is_none_or(|extension| extension != "um") {
+                    continue;
+                }
+                let codigo = std::fs::read_to_string(&ruta).unwrap();
+                let diagnosticos = analizar(&codigo, &Configuracion::default())
+                    .unwrap_or_else(|_| panic!("{} no parsea", ruta.display()));
+                let errores: Vec<String> = diagnosticos
+                    .iter()
+                    .filter(|diagnostico| diagnostico.nivel == Nivel::Error)
+                    .map(|diagnostico| diagnostico.to_string())
+                    .collect();
+                assert!(errores.is_empty(), "{}: {:?}", ruta.display(), errores);
+            }
+        }
+    }
+}
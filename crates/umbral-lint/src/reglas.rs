@@ -0,0 +1,153 @@
+use std::collections::HashMap;
+use std::fmt;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Regla {
+    VariableSinUsar,
+    ImportacionSinUsar,
+    ParametroSinUsar,
+    Sombreado,
+    CodigoInalcanzable,
+    AsignacionAConstante,
+    FuncionNoDefinida,
+    SwitchNoExhaustivo,
+    AwaitInnecesario,
+}
+
+impl Regla {
+    pub const TODAS: [Regla; 9] = [
+        Regla::VariableSinUsar,
+        Regla::ImportacionSinUsar,
+        Regla::ParametroSinUsar,
+        Regla::Sombreado,
+        Regla::CodigoInalcanzable,
+        Regla::AsignacionAConstante,
+        Regla::FuncionNoDefinida,
+        Regla::SwitchNoExhaustivo,
+        Regla::AwaitInnecesario,
+    ];
+
+    /// Nombre con el que se configura y se suprime la regla.
+    pub fn nombre(self) -> &'static str {
+        match self {
+            Regla::VariableSinUsar => "variable-sin-usar",
+            Regla::ImportacionSinUsar => "importacion-sin-usar",
+            Regla::ParametroSinUsar => "parametro-sin-usar",
+            Regla::Sombreado => "sombreado",
+            Regla::CodigoInalcanzable => "codigo-inalcanzable",
+            Regla::AsignacionAConstante => "asignacion-a-constante",
+            Regla::FuncionNoDefinida => "funcion-no-definida",
+            Regla::SwitchNoExhaustivo => "switch-no-exhaustivo",
+            Regla::AwaitInnecesario => "await-innecesario",
+        }
+    }
+
+    pub fn desde_nombre(nombre: &str) -> Option<Regla> {
+        Regla::TODAS.into_iter().find(|regla| regla.nombre() == nombre)
+    }
+
+    pub fn descripcion(self) -> &'static str {
+        match self {
+            Regla::VariableSinUsar => "Variable o constante que se declara y nunca se lee",
+            Regla::ImportacionSinUsar => "Nombre importado con `equip` que no se usa",
+            Regla::ParametroSinUsar => "Parámetro que el cuerpo de la función no usa",
+            Regla::Sombreado => "Declaración que oculta otra del mismo nombre",
+            Regla::CodigoInalcanzable => "Sentencias después de `r:` o `tw:`",
+            Regla::AsignacionAConstante => "Asignación a una constante `c:`",
+            Regla::FuncionNoDefinida => "Llamada a una función que no está declarada",
+            Regla::SwitchNoExhaustivo => "`sw:` sobre un enum sin todas sus variantes ni `def:`",
+            Regla::AwaitInnecesario => "`awa` sobre una llamada que no es asíncrona",
+        }
+    }
+
+    pub fn nivel_por_defecto(self) -> Nivel {
+        match self {
+            Regla::AsignacionAConstante | Regla::FuncionNoDefinida => Nivel::Error,
+            _ => Nivel::Advertencia,
+        }
+    }
+}
+
+impl fmt::Display for Regla {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.nombre())
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Nivel {
+    Desactivada,
+    Advertencia,
+    Error,
+}
+
+impl Nivel {
+    /// Interpreta los valores de la tabla `lint` de `ump.json`.
+    pub fn desde_texto(texto: &str) -> Option<Nivel> {
+        match texto {
+            "off" => Some(Nivel::Desactivada),
+            "warn" => Some(Nivel::Advertencia),
+            "error" => Some(Nivel::Error),
+            _ => None,
+        }
+    }
+}
+
+impl fmt::Display for Nivel {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let texto = match self {
+            Nivel::Desactivada => "desactivada",
+            Nivel::Advertencia => "advertencia",
+            Nivel::Error => "error",
+        };
+        write!(f, "{}", texto)
+    }
+}
+
+/// Nivel de cada regla. Por defecto todas están activas con su nivel
+/// propio; la tabla `lint` de `ump.json` lo cambia regla a regla.
+#[derive(Debug, Clone)]
+pub struct Configuracion {
+    niveles: HashMap<Regla, Nivel>,
+}
+
+impl Default for Configuracion {
+    fn default() -> Self {
+        let niveles = Regla::TODAS
+            .into_iter()
+            .map(|regla| (regla, regla.nivel_por_defecto()))
+            .collect();
+        Self { niveles }
+    }
+}
+
+impl Configuracion {
+    pub fn desde_tabla<'a>(
+        tabla: impl IntoIterator<Item = (&'a String, &'a String)>,
+    ) -> Result<Self, String> {
+        let mut configuracion = Self::default();
+        for (nombre, nivel) in tabla {
+            let regla = Regla::desde_nombre(nombre)
+                .ok_or_else(|| format!("regla de lint desconocida: '{}'", nombre))?;
+            let nivel = Nivel::desde_texto(nivel).ok_or_else(|| {
+                format!(
+                    "nivel '{}' inválido para '{}' (se espera \"off\", \"warn\" o \"error\")",
+                    nivel, nombre
+                )
+            })?;
+            configuracion.establecer(regla, nivel);
+        }
+        Ok(configuracion)
+    }
+
+    pub fn establecer(&mut self, regla: Regla, nivel: Nivel) {
+        self.niveles.insert(regla, nivel);
+    }
+
+    pub fn nivel(&self, regla: Regla) -> Nivel {
+        self.niveles
+            .get(&regla)
+            .copied()
+            .unwrap_or_else(|| regla.nivel_por_defecto())
+    }
+}
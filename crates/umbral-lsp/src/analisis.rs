@@ -0,0 +1,756 @@
+use std::ops::Range;
+use umbral_lexer::{Token, TokenConPosicion};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TipoSimbolo {
+    Variable,
+    Constante,
+    Funcion,
+    Parametro,
+    Clase,
+    Interfaz,
+    Enum,
+    Variante,
+    Propiedad,
+    Metodo,
+    Importacion,
+}
+
+/// Módulo del que viene un símbolo importado.
+#[derive(Debug, Clone)]
+pub struct Origen {
+    pub ruta: String,
+    /// Nombre exportado; `None` cuando se importa el módulo completo
+    /// (`equip M origin ...` o `equip * as M origin ...`).
+    pub nombre: Option<String>,
+}
+
+#[derive(Debug, Clone)]
+pub struct Simbolo {
+    pub nombre: String,
+    pub tipo: TipoSimbolo,
+    /// Posición del nombre en la declaración, en índices de carácter.
+    pub rango: Range<usize>,
+    /// Zona del documento donde el nombre es visible.
+    pub ambito: Range<usize>,
+    /// Llaves del cuerpo de funciones, métodos, clases, interfaces y enums.
+    pub cuerpo: Option<Range<usize>>,
+    pub contenedor: Option<usize>,
+    /// Tipo declarado con `->`; en funciones y métodos, el de retorno.
+    pub anotacion: Option<String>,
+    pub parametros: Vec<(String, Option<String>)>,
+    /// Clase instanciada en el valor inicial: `v: p = n: Persona(...)`.
+    pub instancia: Option<String>,
+    pub exportado: bool,
+    pub origen: Option<Origen>,
+    /// Profundidad de llaves de la declaración; 0 es el nivel superior.
+    pub nivel: usize,
+}
+
+/// Aparición de un identificador en el código.
+#[derive(Debug, Clone)]
+pub struct Referencia {
+    pub nombre: String,
+    pub rango: Range<usize>,
+    /// Identificadores que preceden a un acceso con `.`: `Std.str.upper`
+    /// guarda `["Std", "str"]` y `th.nombre` guarda `["th"]`.
+    pub camino: Vec<String>,
+    pub miembro: bool,
+}
+
+/// Entrada de `out { local as publico }`, `out def: ...` u `out: * origin ...`
+/// (esta última con `publico` igual a `*`).
+#[derive(Debug, Clone)]
+pub struct Exportacion {
+    pub publico: String,
+    pub local: String,
+    pub origen: Option<String>,
+}
+
+#[derive(Debug, Default)]
+pub struct Analisis {
+    pub simbolos: Vec<Simbolo>,
+    pub referencias: Vec<Referencia>,
+    pub exportaciones: Vec<Exportacion>,
+    /// Rutas importadas con `equip * origin ...`, cuyos nombres quedan en el
+    /// ámbito global del módulo.
+    pub importaciones_totales: Vec<String>,
+}
+
+impl Analisis {
+    pub fn referencia_en(&self, indice: usize) -> Option<&Referencia> {
+        self.referencias
+            .iter()
+            .find(|referencia| referencia.rango.start <= indice && indice <= referencia.rango.end)
+    }
+
+    /// Declaración más interna de `nombre` visible en `indice`.
+    pub fn visible(&self, nombre: &str, indice: usize) -> Option<usize> {
+        self.simbolos
+            .iter()
+            .enumerate()
+            .filter(|(_, simbolo)| {
+                simbolo.nombre == nombre
+                    && !es_miembro(simbolo.tipo)
+                    && simbolo.ambito.start <= indice
+                    && indice <= simbolo.ambito.end
+            })
+            .max_by_key(|(_, simbolo)| (simbolo.nivel, simbolo.rango.start))
+            .map(|(indice, _)| indice)
+    }
+
+    /// Clase o interfaz cuyo cuerpo contiene `indice`.
+    pub fn clase_en(&self, indice: usize) -> Option<usize> {
+        self.simbolos
+            .iter()
+            .enumerate()
+            .filter(|(_, simbolo)| matches!(simbolo.tipo, TipoSimbolo::Clase))
+            .filter(|(_, simbolo)| {
+                simbolo
+                    .cuerpo
+                    .as_ref()
+                    .is_some_and(|cuerpo| cuerpo.start <= indice && indice < cuerpo.end)
+            })
+            .max_by_key(|(_, simbolo)| simbolo.nivel)
+            .map(|(indice, _)| indice)
+    }
+
+    pub fn miembros_de(&self, contenedor: usize) -> impl Iterator<Item = (usize, &Simbolo)> {
+        self.simbolos
+            .iter()
+            .enumerate()
+            .filter(move |(_, simbolo)| simbolo.contenedor == Some(contenedor))
+    }
+
+    pub fn nivel_superior(&self, nombre: &str) -> Option<usize> {
+        self.simbolos.iter().position(|simbolo| {
+            simbolo.nivel == 0 && simbolo.nombre == nombre && !es_miembro(simbolo.tipo)
+        })
+    }
+}
+
+pub fn es_miembro(tipo: TipoSimbolo) -> bool {
+    matches!(
+        tipo,
+        TipoSimbolo::Propiedad | TipoSimbolo::Metodo | TipoSimbolo::Variante
+    )
+}
+
+#[derive(Clone, Copy, PartialEq)]
+enum Cuerpo {
+    Bloque,
+    Clase,
+    Interfaz,
+    Enum,
+}
+
+struct Marco {
+    inicio: usize,
+    simbolos: Vec<usize>,
+    dueno: Option<usize>,
+    cuerpo: Cuerpo,
+}
+
+/// Recorre los tokens una vez y reconstruye declaraciones, ámbitos y
+/// referencias. Trabaja sobre tokens y no sobre el AST porque el AST no
+/// guarda posiciones y porque así sigue funcionando con código incompleto.
+pub fn analizar(tokens: &[TokenConPosicion], longitud: usize) -> Analisis {
+    let mut recorrido = Recorrido {
+        tokens,
+        longitud,
+        analisis: Analisis::default(),
+        marcos: vec![Marco {
+            inicio: 0,
+            simbolos: Vec::new(),
+            dueno: None,
+            cuerpo: Cuerpo::Bloque,
+        }],
+        pendientes: Vec::new(),
+        siguiente_cuerpo: None,
+        parentesis: 0,
+        cabecera: None,
+        exportar: false,
+        por_defecto: false,
+    };
+    recorrido.recorrer();
+    recorrido.analisis
+}
+
+struct Recorrido<'a> {
+    tokens: &'a [TokenConPosicion],
+    longitud: usize,
+    analisis: Analisis,
+    marcos: Vec<Marco>,
+    /// Parámetros y variables de cabeceras (`fo:`, `fe:`, `ct:`) que
+    /// pertenecen al bloque que se abre a continuación.
+    pendientes: Vec<usize>,
+    siguiente_cuerpo: Option<(Cuerpo, usize)>,
+    parentesis: usize,
+    cabecera: Option<usize>,
+    exportar: bool,
+    por_defecto: bool,
+}
+
+impl Recorrido<'_> {
+    fn token(&self, indice: usize) -> Option<&Token> {
+        self.tokens.get(indice).map(|token| &token.token)
+    }
+
+    fn posicion(&self, indice: usize) -> usize {
+        self.tokens
+            .get(indice)
+            .map_or(self.longitud, |token| token.posicion)
+    }
+
+    fn identificador(&self, indice: usize) -> Option<&str> {
+        match self.token(indice) {
+            Some(Token::Identificador(nombre)) => Some(nombre),
+            _ => None,
+        }
+    }
+
+    fn rango(&self, indice: usize, nombre: &str) -> Range<usize> {
+        let inicio = self.posicion(indice);
+        inicio..inicio + nombre.chars().count()
+    }
+
+    fn marco(&self) -> &Marco {
+        self.marcos.last().expect("siempre hay un marco raíz")
+    }
+
+    fn recorrer(&mut self) {
+        let mut indice = 0;
+        while indice < self.tokens.len() {
+            indice = self.procesar(indice);
+        }
+    }
+
+    // Procesa el token `indice` y devuelve el índice del siguiente.
+    fn procesar(&mut self, indice: usize) -> usize {
+        let Some(token) = self.token(indice).cloned() else {
+            return indice + 1;
+        };
+
+        match token {
+            Token::LlaveIzq => self.abrir_marco(indice),
+            Token::LlaveDer => self.cerrar_marco(indice),
+            Token::ParentesisIzq => self.parentesis += 1,
+            Token::ParentesisDer => {
+                self.parentesis = self.parentesis.saturating_sub(1);
+                if self.cabecera == Some(self.parentesis) {
+                    self.cabecera = None;
+                }
+            }
+            Token::PuntoYComa if self.parentesis == 0 => {
+                self.pendientes.clear();
+                self.siguiente_cuerpo = None;
+            }
+            Token::Out => return self.procesar_exportacion(indice),
+            Token::Equip | Token::Origin => return self.procesar_importacion(indice),
+            Token::DeclararVariable | Token::DeclararConstante => {
+                let tipo = match token {
+                    Token::DeclararVariable => TipoSimbolo::Variable,
+                    _ => TipoSimbolo::Constante,
+                };
+                if self.identificador(indice + 1).is_some() {
+                    self.declarar_variable(indice + 1, tipo);
+                }
+            }
+            Token::DeclararFuncion if self.identificador(indice + 1).is_some() => {
+                self.declarar_funcion(indice + 1);
+            }
+            Token::DeclararClase | Token::DeclararInterfaz | Token::DeclararEnum => {
+                let (tipo, cuerpo) = match token {
+                    Token::DeclararClase => (TipoSimbolo::Clase, Cuerpo::Clase),
+                    Token::DeclararInterfaz => (TipoSimbolo::Interfaz, Cuerpo::Interfaz),
+                    _ => (TipoSimbolo::Enum, Cuerpo::Enum),
+                };
+                if self.identificador(indice + 1).is_some() {
+                    let simbolo = self.declarar(indice + 1, tipo, None);
+                    self.siguiente_cuerpo = Some((cuerpo, simbolo));
+                }
+            }
+            Token::PropPrivada | Token::PropPublica
+                if self.marco().cuerpo == Cuerpo::Clase
+                    && self.identificador(indice + 1).is_some() =>
+            {
+                let clase = self.marco().dueno;
+                let simbolo = self.declarar(indice + 1, TipoSimbolo::Propiedad, clase);
+                self.analisis.simbolos[simbolo].anotacion = self.anotacion(indice + 2);
+            }
+            Token::For | Token::ForEach | Token::Catch => {
+                self.cabecera = Some(self.parentesis);
+                let declara_sin_prefijo = !matches!(token, Token::For)
+                    && matches!(self.token(indice + 1), Some(Token::ParentesisIzq))
+                    && self.identificador(indice + 2).is_some();
+                if declara_sin_prefijo {
+                    self.declarar_variable(indice + 2, TipoSimbolo::Variable);
+                }
+            }
+            Token::Identificador(nombre) => {
+                let es_variante = self.marco().cuerpo == Cuerpo::Enum
+                    && matches!(
+                        self.token(indice.wrapping_sub(1)),
+                        Some(Token::LlaveIzq | Token::Coma)
+                    );
+                if es_variante {
+                    let enumeracion = self.marco().dueno;
+                    self.declarar(indice, TipoSimbolo::Variante, enumeracion);
+                }
+                self.referenciar(indice, &nombre);
+            }
+            Token::Tipo(nombre) => {
+                let nombre = nombre.trim_start_matches("[]").to_string();
+                if !nombre.is_empty() {
+                    let inicio = self.posicion(indice) + self.token_tipo_prefijo(indice);
+                    self.analisis.referencias.push(Referencia {
+                        rango: inicio..inicio + nombre.chars().count(),
+                        nombre,
+                        camino: Vec::new(),
+                        miembro: false,
+                    });
+                }
+            }
+            _ => {}
+        }
+
+        indice + 1
+    }
+
+    fn token_tipo_prefijo(&self, indice: usize) -> usize {
+        match self.token(indice) {
+            Some(Token::Tipo(nombre)) => nombre.len() - nombre.trim_start_matches("[]").len(),
+            _ => 0,
+        }
+    }
+
+    fn abrir_marco(&mut self, indice: usize) {
+        let inicio = self.posicion(indice);
+        let (cuerpo, dueno) = match self.siguiente_cuerpo.take() {
+            Some((cuerpo, dueno)) => (cuerpo, Some(dueno)),
+            None => (Cuerpo::Bloque, None),
+        };
+
+        let simbolos = std::mem::take(&mut self.pendientes);
+        for simbolo in &simbolos {
+            self.analisis.simbolos[*simbolo].ambito.start = inicio;
+        }
+
+        self.marcos.push(Marco {
+            inicio,
+            simbolos,
+            dueno,
+            cuerpo,
+        });
+    }
+
+    fn cerrar_marco(&mut self, indice: usize) {
+        if self.marcos.len() == 1 {
+            return;
+        }
+        let marco = self.marcos.pop().expect("hay más de un marco");
+        let fin = self.posicion(indice) + 1;
+
+        for simbolo in marco.simbolos {
+            self.analisis.simbolos[simbolo].ambito.end = fin;
+        }
+        if let Some(dueno) = marco.dueno {
+            self.analisis.simbolos[dueno].cuerpo = Some(marco.inicio..fin);
+        }
+    }
+
+    fn declarar(&mut self, indice: usize, tipo: TipoSimbolo, contenedor: Option<usize>) -> usize {
+        let nombre = self.identificador(indice).unwrap_or_default().to_string();
+        let rango = self.rango(indice, &nombre);
+        let pendiente = self.cabecera.is_some() || tipo == TipoSimbolo::Parametro;
+
+        // Variables y parámetros son visibles desde su declaración; funciones,
+        // clases y demás, en todo su bloque.
+        let ambito = match tipo {
+            TipoSimbolo::Variable | TipoSimbolo::Constante if pendiente => rango.start..rango.start,
+            TipoSimbolo::Parametro => rango.start..rango.start,
+            TipoSimbolo::Variable | TipoSimbolo::Constante => rango.start..self.longitud,
+            _ => self.marco().inicio..self.longitud,
+        };
+
+        let nivel = self.marcos.len() - 1 + usize::from(pendiente);
+        let exportado = self.exportar && nivel == 0 && contenedor.is_none();
+        if exportado && self.por_defecto {
+            self.analisis.exportaciones.push(Exportacion {
+                publico: "def".to_string(),
+                local: nombre.clone(),
+                origen: None,
+            });
+        }
+        if contenedor.is_none() {
+            self.exportar = false;
+            self.por_defecto = false;
+        }
+
+        self.analisis.simbolos.push(Simbolo {
+            nombre,
+            tipo,
+            rango,
+            ambito,
+            cuerpo: None,
+            contenedor,
+            anotacion: None,
+            parametros: Vec::new(),
+            instancia: None,
+            exportado,
+            origen: None,
+            nivel,
+        });
+
+        let simbolo = self.analisis.simbolos.len() - 1;
+        if pendiente {
+            self.pendientes.push(simbolo);
+        } else {
+            self.marcos
+                .last_mut()
+                .expect("siempre hay un marco raíz")
+                .simbolos
+                .push(simbolo);
+        }
+        simbolo
+    }
+
+    fn declarar_variable(&mut self, indice: usize, tipo: TipoSimbolo) {
+        let simbolo = self.declarar(indice, tipo, None);
+        self.analisis.simbolos[simbolo].anotacion = self.anotacion(indice + 1);
+
+        let mut siguiente = indice + 1;
+        while matches!(
+            self.token(siguiente),
+            Some(Token::OperadorTipo | Token::Tipo(_) | Token::Identificador(_))
+        ) && siguiente <= indice + 3
+        {
+            siguiente += 1;
+        }
+        let instancia = matches!(self.token(siguiente), Some(Token::Asignacion))
+            && matches!(self.token(siguiente + 1), Some(Token::Instanciar));
+        if instancia {
+            self.analisis.simbolos[simbolo].instancia =
+                self.identificador(siguiente + 2).map(str::to_string);
+        }
+    }
+
+    fn declarar_funcion(&mut self, indice: usize) {
+        let (tipo, contenedor) = match self.marco().cuerpo {
+            Cuerpo::Clase | Cuerpo::Interfaz => (TipoSimbolo::Metodo, self.marco().dueno),
+            _ => (TipoSimbolo::Funcion, None),
+        };
+        let funcion = self.declarar(indice, tipo, contenedor);
+
+        let mut parametros = Vec::new();
+        let mut actual = indice + 2;
+        if matches!(self.token(indice + 1), Some(Token::ParentesisIzq)) {
+            let mut nivel = 1;
+            while nivel > 0 && actual < self.tokens.len() {
+                match self.token(actual) {
+                    Some(Token::ParentesisIzq) => nivel += 1,
+                    Some(Token::ParentesisDer) => nivel -= 1,
+                    Some(Token::Identificador(_))
+                        if nivel == 1
+                            && matches!(
+                                self.token(actual - 1),
+                                Some(Token::ParentesisIzq | Token::Coma)
+                            ) =>
+                    {
+                        let parametro = self.declarar(actual, TipoSimbolo::Parametro, None);
+                        let anotacion = self.anotacion(actual + 1);
+                        self.analisis.simbolos[parametro].anotacion = anotacion.clone();
+                        parametros
+                            .push((self.analisis.simbolos[parametro].nombre.clone(), anotacion));
+                    }
+                    _ => {}
+                }
+                actual += 1;
+            }
+        }
+
+        let anotacion = self.anotacion(actual);
+        let simbolo = &mut self.analisis.simbolos[funcion];
+        simbolo.parametros = parametros;
+        simbolo.anotacion = anotacion;
+        self.siguiente_cuerpo = Some((Cuerpo::Bloque, funcion));
+    }
+
+    // Tipo declarado tras `->` a partir del token `indice`.
+    fn anotacion(&self, indice: usize) -> Option<String> {
+        if !matches!(self.token(indice), Some(Token::OperadorTipo)) {
+            return None;
+        }
+        match self.token(indice + 1) {
+            Some(Token::Tipo(tipo) | Token::Identificador(tipo)) => Some(tipo.clone()),
+            _ => None,
+        }
+    }
+
+    fn referenciar(&mut self, indice: usize, nombre: &str) {
+        let mut camino = Vec::new();
+        let mut anterior = indice;
+        let miembro = matches!(self.token(indice.wrapping_sub(1)), Some(Token::Punto));
+        while anterior >= 2 && matches!(self.token(anterior - 1), Some(Token::Punto)) {
+            match self.token(anterior - 2) {
+                Some(Token::Identificador(objeto)) => camino.insert(0, objeto.clone()),
+                Some(Token::This) => camino.insert(0, "th".to_string()),
+                _ => {
+                    camino.clear();
+                    break;
+                }
+            }
+            anterior -= 2;
+        }
+
+        self.analisis.referencias.push(Referencia {
+            nombre: nombre.to_string(),
+            rango: self.rango(indice, nombre),
+            camino,
+            miembro,
+        });
+    }
+
+    fn procesar_exportacion(&mut self, indice: usize) -> usize {
+        match self.token(indice + 1) {
+            Some(Token::LlaveIzq) => self.procesar_lista_exportacion(indice + 2),
+            Some(Token::Multiplicacion | Token::Asterisco) => {
+                let origen = self.leer_origen(indice + 2);
+                if let Some((ruta, _)) = &origen {
+                    self.analisis.exportaciones.push(Exportacion {
+                        publico: "*".to_string(),
+                        local: "*".to_string(),
+                        origen: Some(ruta.clone()),
+                    });
+                }
+                origen.map_or(indice + 2, |(_, siguiente)| siguiente)
+            }
+            Some(Token::Default) => {
+                self.exportar = true;
+                self.por_defecto = true;
+                indice + 2
+            }
+            Some(Token::Identificador(nombre)) => {
+                let nombre = nombre.clone();
+                self.referenciar(indice + 1, &nombre);
+                self.analisis.exportaciones.push(Exportacion {
+                    publico: nombre.clone(),
+                    local: nombre,
+                    origen: None,
+                });
+                indice + 2
+            }
+            _ => {
+                self.exportar = true;
+                indice + 1
+            }
+        }
+    }
+
+    fn procesar_lista_exportacion(&mut self, mut indice: usize) -> usize {
+        let mut items = Vec::new();
+        while let Some(Token::Identificador(nombre)) = self.token(indice).cloned() {
+            self.referenciar(indice, &nombre);
+            let mut publico = nombre.clone();
+            indice += 1;
+            if matches!(self.token(indice), Some(Token::As)) {
+                if let Some(alias) = self.identificador(indice + 1) {
+                    publico = alias.to_string();
+                }
+                indice += 2;
+            }
+            items.push((nombre, publico));
+            if !matches!(self.token(indice), Some(Token::Coma)) {
+                break;
+            }
+            indice += 1;
+        }
+        if matches!(self.token(indice), Some(Token::LlaveDer)) {
+            indice += 1;
+        }
+
+        let origen = self.leer_origen(indice);
+        if let Some((_, siguiente)) = &origen {
+            indice = *siguiente;
+        }
+        for (local, publico) in items {
+            self.analisis.exportaciones.push(Exportacion {
+                publico,
+                local,
+                origen: origen.as_ref().map(|(ruta, _)| ruta.clone()),
+            });
+        }
+        indice
+    }
+
+    fn leer_origen(&self, indice: usize) -> Option<(String, usize)> {
+        if !matches!(self.token(indice), Some(Token::Origin)) {
+            return None;
+        }
+        match self.token(indice + 1) {
+            Some(Token::Cadena(ruta) | Token::CadenaLiteral(ruta)) => {
+                Some((ruta.clone(), indice + 2))
+            }
+            _ => None,
+        }
+    }
+
+    // `equip ... origin '...'` en cualquier orden. Los nombres importados
+    // se declaran en el ámbito actual con el módulo de origen.
+    fn procesar_importacion(&mut self, mut indice: usize) -> usize {
+        let mut items: Vec<(usize, Option<String>)> = Vec::new();
+        let mut todo = false;
+        let mut ruta = None;
+
+        loop {
+            match self.token(indice) {
+                Some(Token::Equip) => {
+                    indice = self.leer_items_importacion(indice + 1, &mut items, &mut todo)
+                }
+                Some(Token::Origin) => match self.leer_origen(indice) {
+                    Some((texto, siguiente)) => {
+                        ruta = Some(texto);
+                        indice = siguiente;
+                    }
+                    None => indice += 1,
+                },
+                _ => break,
+            }
+        }
+
+        let Some(ruta) = ruta else {
+            return indice;
+        };
+        if todo {
+            self.analisis.importaciones_totales.push(ruta.clone());
+        }
+        for (posicion, original) in items {
+            let nombre = self.identificador(posicion).unwrap_or_default().to_string();
+            let simbolo = self.declarar(posicion, TipoSimbolo::Importacion, None);
+            self.analisis.simbolos[simbolo].origen = Some(Origen {
+                ruta: ruta.clone(),
+                nombre: original,
+            });
+            self.referenciar(posicion, &nombre);
+        }
+        indice
+    }
+
+    // Devuelve el índice tras los items. Cada item guarda la posición del
+    // nombre local y el nombre exportado (`None` para el módulo completo).
+    fn leer_items_importacion(
+        &self,
+        mut indice: usize,
+        items: &mut Vec<(usize, Option<String>)>,
+        todo: &mut bool,
+    ) -> usize {
+        let en_lista = matches!(self.token(indice), Some(Token::LlaveIzq));
+        if en_lista {
+            indice += 1;
+        }
+
+        loop {
+            match self.token(indice) {
+                Some(Token::Multiplicacion | Token::Asterisco) => {
+                    if matches!(self.token(indice + 1), Some(Token::As))
+                        && self.identificador(indice + 2).is_some()
+                    {
+                        items.push((indice + 2, None));
+                        indice += 3;
+                    } else {
+                        *todo = true;
+                        indice += 1;
+                    }
+                }
+                Some(Token::Identificador(nombre)) => {
+                    let original = en_lista.then(|| nombre.clone());
+                    if matches!(self.token(indice + 1), Some(Token::As))
+                        && self.identificador(indice + 2).is_some()
+                    {
+                        items.push((indice + 2, original));
+                        indice += 3;
+                    } else {
+                        items.push((indice, original));
+                        indice += 1;
+                    }
+                }
+                _ => break,
+            }
+
+            if !en_lista || !matches!(self.token(indice), Some(Token::Coma)) {
+                break;
+            }
+            indice += 1;
+        }
+
+        if en_lista && matches!(self.token(indice), Some(Token::LlaveDer)) {
+            indice += 1;
+        }
+        indice
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn analizar_codigo(codigo: &str) -> Analisis {
+        let tokens = umbral_lexer::analizar_con_posiciones(codigo);
+        analizar(&tokens, codigo.chars().count())
+    }
+
+    #[test]
+    fn test_declaraciones_y_ambitos() {
+        let codigo = "
+            cs: Persona {
+                pr: nombre->Str;
+                pu f: saludar(otro->Str)->Str { r: (otro); }
+            }
+            f: sumar(a->Int, b) { v: total = a + b; r: (total); }
+            v: p = n: Persona('Ana');
+        ";
+        let analisis = analizar_codigo(codigo);
+        let nombres: Vec<(&str, TipoSimbolo)> = analisis
+            .simbolos
+            .iter()
+            .map(|simbolo| (simbolo.nombre.as_str(), simbolo.tipo))
+            .collect();
+        assert!(nombres.contains(&("Persona", TipoSimbolo::Clase)));
+        assert!(nombres.contains(&("nombre", TipoSimbolo::Propiedad)));
+        assert!(nombres.contains(&("saludar", TipoSimbolo::Metodo)));
+        assert!(nombres.contains(&("b", TipoSimbolo::Parametro)));
+
+        let sumar = analisis.nivel_superior("sumar").unwrap();
+        assert_eq!(analisis.simbolos[sumar].parametros.len(), 2);
+        let total = analisis
+            .simbolos
+            .iter()
+            .find(|s| s.nombre == "total")
+            .unwrap();
+        assert!(total.ambito.end < codigo.len());
+        let p = analisis.nivel_superior("p").unwrap();
+        assert_eq!(analisis.simbolos[p].instancia.as_deref(), Some("Persona"));
+    }
+
+    #[test]
+    fn test_importaciones_y_exportaciones() {
+        let analisis = analizar_codigo(
+            "equip { sumar as suma, PI } origin './base.um';
+             equip * as util origin './util.um';
+             out { suma as total };",
+        );
+        let suma = analisis.nivel_superior("suma").unwrap();
+        let origen = analisis.simbolos[suma].origen.as_ref().unwrap();
+        assert_eq!(origen.nombre.as_deref(), Some("sumar"));
+        assert_eq!(origen.ruta, "./base.um");
+
+        let util = analisis.nivel_superior("util").unwrap();
+        assert!(analisis.simbolos[util]
+            .origen
+            .as_ref()
+            .unwrap()
+            .nombre
+            .is_none());
+        assert_eq!(analisis.exportaciones[0].publico, "total");
+    }
+}
@@ -0,0 +1,292 @@
+use crate::analisis::{self, Simbolo, TipoSimbolo};
+use crate::descripcion::{self, miembros_std};
+use crate::espacio::{Documento, Espacio, Ubicacion};
+use lsp_types::{CompletionItem, CompletionItemKind};
+use std::collections::HashSet;
+use std::rc::Rc;
+use umbral_lexer::es_caracter_identificador;
+use umbral_runtime::runtime::stdlib;
+
+const PALABRAS_CLAVE: [(&str, &str); 33] = [
+    ("v:", "Declara una variable"),
+    ("c:", "Declara una constante"),
+    ("f:", "Declara una función"),
+    ("cs:", "Declara una clase"),
+    ("in:", "Declara una interfaz"),
+    ("em:", "Declara un enum"),
+    ("n:", "Crea una instancia"),
+    ("pr:", "Miembro privado"),
+    ("pu", "Miembro público"),
+    ("ext:", "Herencia de clase"),
+    ("imp:", "Implementa interfaces"),
+    ("i:", "Condicional if"),
+    ("ie:", "Condicional else if"),
+    ("e:", "Condicional else"),
+    ("sw:", "Switch"),
+    ("ca:", "Caso de un switch"),
+    ("def:", "Caso por defecto"),
+    ("fo:", "Bucle for"),
+    ("fe:", "Bucle foreach"),
+    ("wh:", "Bucle while"),
+    ("dw:", "Bucle do-while"),
+    ("r:", "Retorna un valor"),
+    ("tr:", "Bloque try"),
+    ("ct:", "Bloque catch"),
+    ("fy:", "Bloque finally"),
+    ("tw:", "Lanza un error"),
+    ("out", "Exporta una declaración"),
+    ("equip", "Importa desde un módulo"),
+    ("origin", "Módulo de una importación"),
+    ("asy", "Función asíncrona"),
+    ("awa", "Espera una promesa"),
+    ("th", "Instancia actual"),
+    ("tprint", "Imprime un valor"),
+];
+
+pub fn completar(
+    espacio: &Espacio,
+    documento: &Rc<Documento>,
+    indice: usize,
+) -> Vec<CompletionItem> {
+    let camino = camino_antes_de(documento.lineas.caracteres(), indice);
+    let camino: Vec<&str> = camino.iter().map(String::as_str).collect();
+
+    match camino.as_slice() {
+        [] => completar_nombres(espacio, documento, indice),
+        ["Std"] => completar_std(),
+        ["Std", modulo] => miembros_std(modulo)
+            .into_iter()
+            .map(|(nombre, es_funcion)| {
+                let tipo = if es_funcion {
+                    CompletionItemKind::FUNCTION
+                } else {
+                    CompletionItemKind::CONSTANT
+                };
+                elemento(nombre, tipo, Some(format!("Std.{}", modulo)))
+            })
+            .collect(),
+        ["th"] => match documento.analisis.clase_en(indice) {
+            Some(clase) => completar_miembros(
+                espacio,
+                Ubicacion {
+                    documento: documento.clone(),
+                    simbolo: clase,
+                },
+            ),
+            None => Vec::new(),
+        },
+        [objeto] => match espacio.resolver_nombre(documento, objeto, indice) {
+            Some(contenedor) => completar_miembros(espacio, contenedor),
+            None => todos_los_miembros(documento),
+        },
+        _ => todos_los_miembros(documento),
+    }
+}
+
+// Identificadores encadenados con `.` justo antes del nombre que se está
+// escribiendo: en `Std.str.up|` devuelve `["Std", "str"]`.
+fn camino_antes_de(caracteres: &[char], indice: usize) -> Vec<String> {
+    let mut posicion = indice.min(caracteres.len());
+    while posicion > 0 && es_caracter_identificador(caracteres[posicion - 1]) {
+        posicion -= 1;
+    }
+
+    let mut camino = Vec::new();
+    while posicion > 0 && caracteres[posicion - 1] == '.' {
+        let fin = posicion - 1;
+        let mut inicio = fin;
+        while inicio > 0 && es_caracter_identificador(caracteres[inicio - 1]) {
+            inicio -= 1;
+        }
+        if inicio == fin {
+            return vec![String::new()];
+        }
+        camino.insert(0, caracteres[inicio..fin].iter().collect());
+        posicion = inicio;
+    }
+    camino
+}
+
+fn completar_nombres(
+    espacio: &Espacio,
+    documento: &Rc<Documento>,
+    indice: usize,
+) -> Vec<CompletionItem> {
+    let mut vistos = HashSet::new();
+    let mut elementos = Vec::new();
+    let analisis = &documento.analisis;
+
+    for (posicion, simbolo) in analisis.simbolos.iter().enumerate() {
+        if analisis::es_miembro(simbolo.tipo) || vistos.contains(&simbolo.nombre) {
+            continue;
+        }
+        if analisis.visible(&simbolo.nombre, indice) == Some(posicion) {
+            vistos.insert(simbolo.nombre.clone());
+            elementos.push(elemento_de_simbolo(simbolo, &documento.analisis));
+        }
+    }
+
+    for ruta in &analisis.importaciones_totales {
+        if let Some(modulo) = espacio.resolver_origen(documento, ruta) {
+            for simbolo in exportados(espacio, &modulo) {
+                if vistos.insert(simbolo.simbolo().nombre.clone()) {
+                    elementos.push(elemento_de_simbolo(
+                        simbolo.simbolo(),
+                        &simbolo.documento.analisis,
+                    ));
+                }
+            }
+        }
+    }
+
+    elementos.push(elemento(
+        "Std".to_string(),
+        CompletionItemKind::MODULE,
+        Some("Biblioteca estándar".to_string()),
+    ));
+    for (palabra, detalle) in PALABRAS_CLAVE {
+        elementos.push(elemento(
+            palabra.to_string(),
+            CompletionItemKind::KEYWORD,
+            Some(detalle.to_string()),
+        ));
+    }
+    elementos
+}
+
+fn completar_std() -> Vec<CompletionItem> {
+    let mut vistos = HashSet::new();
+    let mut elementos = Vec::new();
+
+    for modulo in stdlib::MODULOS {
+        vistos.insert(modulo.to_string());
+        elementos.push(elemento(
+            modulo.to_string(),
+            CompletionItemKind::MODULE,
+            Some(format!("Std.{}", modulo)),
+        ));
+    }
+
+    // Capa plana: `Std.trim` equivale a `Std.str.trim`.
+    for (modulo, nombres) in stdlib::CAPA_PLANA {
+        for nombre in nombres {
+            if vistos.insert(nombre.to_string()) {
+                elementos.push(elemento(
+                    nombre.to_string(),
+                    CompletionItemKind::FUNCTION,
+                    Some(format!("Std.{}", modulo)),
+                ));
+            }
+        }
+    }
+
+    elementos.push(elemento(
+        stdlib::FUNCION_EXTENDER.to_string(),
+        CompletionItemKind::FUNCTION,
+        Some("Std".to_string()),
+    ));
+    elementos
+}
+
+fn completar_miembros(espacio: &Espacio, contenedor: Ubicacion) -> Vec<CompletionItem> {
+    let simbolo = contenedor.simbolo().clone();
+    match simbolo.tipo {
+        TipoSimbolo::Clase | TipoSimbolo::Interfaz | TipoSimbolo::Enum => contenedor
+            .documento
+            .analisis
+            .miembros_de(contenedor.simbolo)
+            .map(|(_, miembro)| elemento_de_simbolo(miembro, &contenedor.documento.analisis))
+            .collect(),
+        TipoSimbolo::Importacion => {
+            let modulo = simbolo
+                .origen
+                .as_ref()
+                .and_then(|origen| espacio.resolver_origen(&contenedor.documento, &origen.ruta));
+            match modulo {
+                Some(modulo) => exportados(espacio, &modulo)
+                    .iter()
+                    .map(|ubicacion| {
+                        elemento_de_simbolo(ubicacion.simbolo(), &ubicacion.documento.analisis)
+                    })
+                    .collect(),
+                None => Vec::new(),
+            }
+        }
+        _ => {
+            let clase = simbolo.instancia.or(simbolo.anotacion).and_then(|clase| {
+                espacio.resolver_nombre(&contenedor.documento, &clase, simbolo.rango.start)
+            });
+            match clase {
+                Some(clase) if clase.simbolo().tipo == TipoSimbolo::Clase => {
+                    completar_miembros(espacio, clase)
+                }
+                _ => todos_los_miembros(&contenedor.documento),
+            }
+        }
+    }
+}
+
+fn todos_los_miembros(documento: &Documento) -> Vec<CompletionItem> {
+    let mut vistos = HashSet::new();
+    documento
+        .analisis
+        .simbolos
+        .iter()
+        .filter(|simbolo| {
+            analisis::es_miembro(simbolo.tipo) && vistos.insert(simbolo.nombre.clone())
+        })
+        .map(|simbolo| elemento_de_simbolo(simbolo, &documento.analisis))
+        .collect()
+}
+
+fn exportados(espacio: &Espacio, modulo: &Rc<Documento>) -> Vec<Ubicacion> {
+    let mut nombres: Vec<String> = modulo
+        .analisis
+        .simbolos
+        .iter()
+        .filter(|simbolo| simbolo.exportado)
+        .map(|simbolo| simbolo.nombre.clone())
+        .collect();
+    nombres.extend(
+        modulo
+            .analisis
+            .exportaciones
+            .iter()
+            .filter(|exportacion| exportacion.publico != "*")
+            .map(|exportacion| exportacion.publico.clone()),
+    );
+
+    nombres
+        .iter()
+        .filter_map(|nombre| espacio.buscar_exportado(modulo, nombre, 0))
+        .collect()
+}
+
+fn elemento_de_simbolo(simbolo: &Simbolo, analisis: &analisis::Analisis) -> CompletionItem {
+    let tipo = match simbolo.tipo {
+        TipoSimbolo::Variable | TipoSimbolo::Parametro => CompletionItemKind::VARIABLE,
+        TipoSimbolo::Constante => CompletionItemKind::CONSTANT,
+        TipoSimbolo::Funcion => CompletionItemKind::FUNCTION,
+        TipoSimbolo::Metodo => CompletionItemKind::METHOD,
+        TipoSimbolo::Clase => CompletionItemKind::CLASS,
+        TipoSimbolo::Interfaz => CompletionItemKind::INTERFACE,
+        TipoSimbolo::Enum => CompletionItemKind::ENUM,
+        TipoSimbolo::Variante => CompletionItemKind::ENUM_MEMBER,
+        TipoSimbolo::Propiedad => CompletionItemKind::FIELD,
+        TipoSimbolo::Importacion => CompletionItemKind::MODULE,
+    };
+    elemento(
+        simbolo.nombre.clone(),
+        tipo,
+        Some(descripcion::firma(simbolo, analisis)),
+    )
+}
+
+fn elemento(nombre: String, tipo: CompletionItemKind, detalle: Option<String>) -> CompletionItem {
+    CompletionItem {
+        label: nombre,
+        kind: Some(tipo),
+        detail: detalle,
+        ..Default::default()
+    }
+}
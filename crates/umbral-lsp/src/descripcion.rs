@@ -0,0 +1,167 @@
+use crate::analisis::{Analisis, Referencia, Simbolo, TipoSimbolo};
+use crate::espacio::{Documento, Espacio};
+use lsp_types::{Hover, HoverContents, MarkupContent, MarkupKind};
+use std::rc::Rc;
+use umbral_runtime::runtime::stdlib;
+use umbral_runtime::runtime::valores::Valor;
+
+/// Declaración del símbolo tal como se escribiría en Umbral, con los tipos
+/// anotados con `->`.
+pub fn firma(simbolo: &Simbolo, analisis: &Analisis) -> String {
+    let con_tipo = |nombre: &str, tipo: &Option<String>| match tipo {
+        Some(tipo) => format!("{} -> {}", nombre, tipo),
+        None => nombre.to_string(),
+    };
+    let contenedor = simbolo
+        .contenedor
+        .map(|indice| format!("{}.", analisis.simbolos[indice].nombre))
+        .unwrap_or_default();
+
+    match simbolo.tipo {
+        TipoSimbolo::Variable => format!("v: {}", con_tipo(&simbolo.nombre, &simbolo.anotacion)),
+        TipoSimbolo::Constante => format!("c: {}", con_tipo(&simbolo.nombre, &simbolo.anotacion)),
+        TipoSimbolo::Parametro => con_tipo(&simbolo.nombre, &simbolo.anotacion),
+        TipoSimbolo::Funcion | TipoSimbolo::Metodo => {
+            let parametros: Vec<String> = simbolo
+                .parametros
+                .iter()
+                .map(|(nombre, tipo)| con_tipo(nombre, tipo))
+                .collect();
+            let llamada = format!(
+                "{}{}({})",
+                contenedor,
+                simbolo.nombre,
+                parametros.join(", ")
+            );
+            format!("f: {}", con_tipo(&llamada, &simbolo.anotacion))
+        }
+        TipoSimbolo::Clase => format!("cs: {}", simbolo.nombre),
+        TipoSimbolo::Interfaz => format!("in: {}", simbolo.nombre),
+        TipoSimbolo::Enum => format!("em: {}", simbolo.nombre),
+        TipoSimbolo::Variante => format!("{}{}", contenedor, simbolo.nombre),
+        TipoSimbolo::Propiedad => con_tipo(
+            &format!("{}{}", contenedor, simbolo.nombre),
+            &simbolo.anotacion,
+        ),
+        TipoSimbolo::Importacion => {
+            let origen = simbolo.origen.as_ref();
+            let ruta = origen
+                .map(|origen| origen.ruta.as_str())
+                .unwrap_or_default();
+            match origen.and_then(|origen| origen.nombre.as_deref()) {
+                Some(nombre) if nombre != simbolo.nombre => {
+                    format!(
+                        "equip {{ {} as {} }} origin '{}'",
+                        nombre, simbolo.nombre, ruta
+                    )
+                }
+                Some(_) => format!("equip {{ {} }} origin '{}'", simbolo.nombre, ruta),
+                None => format!("equip {} origin '{}'", simbolo.nombre, ruta),
+            }
+        }
+    }
+}
+
+pub fn hover(espacio: &Espacio, documento: &Rc<Documento>, indice: usize) -> Option<Hover> {
+    let referencia = documento.analisis.referencia_en(indice)?;
+
+    let texto = match descripcion_std(referencia) {
+        Some(texto) => texto,
+        None => {
+            let definicion = espacio
+                .definiciones(documento, referencia)
+                .into_iter()
+                .next()?;
+            let simbolo = definicion.simbolo();
+            let mut texto = bloque(&firma(simbolo, &definicion.documento.analisis));
+            if definicion.documento.url != documento.url {
+                let archivo = definicion
+                    .documento
+                    .url
+                    .path_segments()
+                    .and_then(|mut segmentos| segmentos.next_back().map(str::to_string))
+                    .unwrap_or_default();
+                texto.push_str(&format!("\n\nDefinido en `{}`", archivo));
+            }
+            texto
+        }
+    };
+
+    Some(Hover {
+        contents: HoverContents::Markup(MarkupContent {
+            kind: MarkupKind::Markdown,
+            value: texto,
+        }),
+        range: Some(documento.lineas.rango(&referencia.rango)),
+    })
+}
+
+fn bloque(codigo: &str) -> String {
+    format!("```umbral\n{}\n```", codigo)
+}
+
+// `Std`, `Std.modulo`, `Std.funcion` y `Std.modulo.funcion`.
+fn descripcion_std(referencia: &Referencia) -> Option<String> {
+    let camino: Vec<&str> = referencia.camino.iter().map(String::as_str).collect();
+    let nombre = referencia.nombre.as_str();
+
+    match camino.as_slice() {
+        [] if nombre == "Std" && !referencia.miembro => Some(format!(
+            "{}\n\nBiblioteca estándar. Módulos: {}.",
+            bloque("Std"),
+            stdlib::MODULOS.join(", ")
+        )),
+        ["Std"] if stdlib::MODULOS.contains(&nombre) => {
+            let funciones = miembros_std(nombre);
+            Some(format!(
+                "{}\n\nMódulo de la biblioteca estándar con {} elementos.",
+                bloque(&format!("Std.{}", nombre)),
+                funciones.len()
+            ))
+        }
+        ["Std"] => {
+            let (modulo, _) = stdlib::CAPA_PLANA
+                .iter()
+                .find(|(_, nombres)| nombres.contains(&nombre))?;
+            Some(describir_miembro_std(modulo, nombre))
+        }
+        ["Std", modulo] => {
+            miembros_std(modulo)
+                .iter()
+                .find(|(clave, _)| clave == nombre)?;
+            Some(describir_miembro_std(modulo, nombre))
+        }
+        _ => None,
+    }
+}
+
+fn describir_miembro_std(modulo: &str, nombre: &str) -> String {
+    let es_funcion = miembros_std(modulo)
+        .iter()
+        .any(|(clave, funcion)| clave == nombre && *funcion);
+    let tipo = if es_funcion {
+        "Función nativa"
+    } else {
+        "Constante"
+    };
+    format!(
+        "{}\n\n{} del módulo `Std.{}`.",
+        bloque(&format!("Std.{}.{}", modulo, nombre)),
+        tipo,
+        modulo
+    )
+}
+
+/// Nombres que exporta un módulo de `Std` y si cada uno es una función.
+pub fn miembros_std(modulo: &str) -> Vec<(String, bool)> {
+    match stdlib::crear_modulo(modulo) {
+        Some(Valor::Diccionario(miembros)) => miembros
+            .into_iter()
+            .map(|(nombre, valor)| {
+                let es_funcion = matches!(valor, Valor::FuncionNativa(..) | Valor::Funcion(_));
+                (nombre, es_funcion)
+            })
+            .collect(),
+        _ => Vec::new(),
+    }
+}
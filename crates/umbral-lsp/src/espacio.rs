@@ -0,0 +1,429 @@
+use crate::analisis::{self, Analisis, Referencia, TipoSimbolo};
+use crate::posiciones::{self, Lineas};
+use lsp_types::{Diagnostic, DiagnosticSeverity, Url};
+use std::cell::RefCell;
+use std::collections::HashMap;
+use std::ops::Range;
+use std::path::{Path, PathBuf};
+use std::rc::Rc;
+use umbral_parser::Parser;
+use umbral_runtime::runtime::modulos;
+
+// Límite de saltos al seguir reexportaciones, por si dos módulos se
+// reexportan mutuamente.
+const PROFUNDIDAD_MAXIMA: usize = 8;
+
+pub struct Documento {
+    pub url: Url,
+    pub lineas: Lineas,
+    pub analisis: Analisis,
+    pub diagnosticos: Vec<Diagnostic>,
+}
+
+impl Documento {
+    pub fn nuevo(url: Url, texto: String) -> Self {
+        let lineas = Lineas::nuevo(&texto);
+        let mut diagnosticos = Vec::new();
+
+        for error in umbral_lexer::tokenizar(&texto).err().unwrap_or_default() {
+            let inicio = posiciones::indice_de_byte(&texto, error.rango.start);
+            let fin = posiciones::indice_de_byte(&texto, error.rango.end).max(inicio + 1);
+            diagnosticos.push(diagnostico(lineas.rango(&(inicio..fin)), error.mensaje()));
+        }
+
+        let tokens = umbral_lexer::analizar_con_posiciones(&texto);
+        let analisis = analisis::analizar(&tokens, lineas.caracteres().len());
+
+        let mut parser = Parser::nuevo_con_posiciones(tokens.clone(), texto.clone());
+        let (_, errores) = parser.parsear_programa_parcial();
+        for error in errores {
+            let fin = tokens
+                .iter()
+                .find(|token| token.posicion > error.posicion)
+                .map_or(error.posicion + 1, |token| token.posicion);
+            let fin = longitud_hasta_espacio(lineas.caracteres(), error.posicion, fin);
+            diagnosticos.push(diagnostico(
+                lineas.rango(&(error.posicion..fin)),
+                error.mensaje,
+            ));
+        }
+
+        Self {
+            url,
+            lineas,
+            analisis,
+            diagnosticos,
+        }
+    }
+
+    pub fn ruta(&self) -> Option<PathBuf> {
+        self.url.to_file_path().ok()
+    }
+}
+
+fn diagnostico(rango: lsp_types::Range, mensaje: String) -> Diagnostic {
+    Diagnostic {
+        range: rango,
+        severity: Some(DiagnosticSeverity::ERROR),
+        source: Some("umbral".to_string()),
+        message: mensaje,
+        ..Default::default()
+    }
+}
+
+// Subraya el token del error sin arrastrar los espacios que lo siguen.
+fn longitud_hasta_espacio(caracteres: &[char], inicio: usize, fin: usize) -> usize {
+    let fin = fin.min(caracteres.len());
+    let recortado = caracteres[inicio.min(fin)..fin]
+        .iter()
+        .take_while(|c| !c.is_whitespace())
+        .count();
+    inicio + recortado.max(1)
+}
+
+/// Declaración concreta: un símbolo de un documento.
+#[derive(Clone)]
+pub struct Ubicacion {
+    pub documento: Rc<Documento>,
+    pub simbolo: usize,
+}
+
+impl Ubicacion {
+    pub fn simbolo(&self) -> &analisis::Simbolo {
+        &self.documento.analisis.simbolos[self.simbolo]
+    }
+
+    fn es(&self, otra: &Ubicacion) -> bool {
+        self.simbolo == otra.simbolo && self.documento.url == otra.documento.url
+    }
+}
+
+/// Documentos abiertos en el editor y acceso al resto de archivos del
+/// proyecto para seguir importaciones.
+pub struct Espacio {
+    abiertos: HashMap<Url, Rc<Documento>>,
+    raiz: Option<PathBuf>,
+    cargados: RefCell<HashMap<Url, Option<Rc<Documento>>>>,
+}
+
+impl Espacio {
+    pub fn nuevo(raiz: Option<PathBuf>) -> Self {
+        Self {
+            abiertos: HashMap::new(),
+            raiz,
+            cargados: RefCell::new(HashMap::new()),
+        }
+    }
+
+    pub fn abrir(&mut self, url: Url, texto: String) -> Rc<Documento> {
+        let url = normalizar_url(url);
+        let documento = Rc::new(Documento::nuevo(url.clone(), texto));
+        self.abiertos.insert(url, documento.clone());
+        self.cargados.borrow_mut().clear();
+        documento
+    }
+
+    pub fn cerrar(&mut self, url: &Url) {
+        self.abiertos.remove(&normalizar_url(url.clone()));
+        self.cargados.borrow_mut().clear();
+    }
+
+    pub fn documento(&self, url: &Url) -> Option<Rc<Documento>> {
+        let url = normalizar_url(url.clone());
+        if let Some(documento) = self.abiertos.get(&url) {
+            return Some(documento.clone());
+        }
+
+        self.cargados
+            .borrow_mut()
+            .entry(url.clone())
+            .or_insert_with(|| {
+                let texto = std::fs::read_to_string(url.to_file_path().ok()?).ok()?;
+                Some(Rc::new(Documento::nuevo(url, texto)))
+            })
+            .clone()
+    }
+
+    /// Documento al que apunta `origin 'ruta'` desde `desde`, con las mismas
+    /// reglas de búsqueda que el intérprete.
+    pub fn resolver_origen(&self, desde: &Documento, ruta: &str) -> Option<Rc<Documento>> {
+        let archivo = desde.ruta()?;
+        let directorio = archivo.parent()?.to_path_buf();
+        let base = self.raiz.clone().unwrap_or_else(|| directorio.clone());
+        let destino = modulos::resolver(ruta, &directorio, &base).ok()?;
+        self.documento(&Url::from_file_path(destino).ok()?)
+    }
+
+    /// Declaraciones a las que puede referirse `referencia` dentro de
+    /// `documento`. Vacío para nombres de `Std` o desconocidos.
+    pub fn definiciones(
+        &self,
+        documento: &Rc<Documento>,
+        referencia: &Referencia,
+    ) -> Vec<Ubicacion> {
+        if !referencia.miembro {
+            return self
+                .resolver_nombre(documento, &referencia.nombre, referencia.rango.start)
+                .into_iter()
+                .collect();
+        }
+
+        if let Some(definicion) = self.resolver_miembro(documento, referencia) {
+            return vec![definicion];
+        }
+
+        // Objeto de tipo desconocido: cualquier miembro con ese nombre.
+        documento
+            .analisis
+            .simbolos
+            .iter()
+            .enumerate()
+            .filter(|(_, simbolo)| {
+                analisis::es_miembro(simbolo.tipo) && simbolo.nombre == referencia.nombre
+            })
+            .map(|(simbolo, _)| Ubicacion {
+                documento: documento.clone(),
+                simbolo,
+            })
+            .collect()
+    }
+
+    /// Declaración de un nombre visible en `indice`, siguiendo importaciones
+    /// hasta el módulo que lo define.
+    pub fn resolver_nombre(
+        &self,
+        documento: &Rc<Documento>,
+        nombre: &str,
+        indice: usize,
+    ) -> Option<Ubicacion> {
+        if let Some(simbolo) = documento.analisis.visible(nombre, indice) {
+            return Some(self.seguir_importacion(
+                Ubicacion {
+                    documento: documento.clone(),
+                    simbolo,
+                },
+                0,
+            ));
+        }
+
+        documento
+            .analisis
+            .importaciones_totales
+            .iter()
+            .filter_map(|ruta| self.resolver_origen(documento, ruta))
+            .find_map(|modulo| self.buscar_exportado(&modulo, nombre, 0))
+    }
+
+    fn resolver_miembro(
+        &self,
+        documento: &Rc<Documento>,
+        referencia: &Referencia,
+    ) -> Option<Ubicacion> {
+        let objeto = referencia.camino.last()?;
+        let inicio = referencia.rango.start;
+
+        if objeto == "th" && referencia.camino.len() == 1 {
+            let clase = documento.analisis.clase_en(inicio)?;
+            return self.miembro(
+                Ubicacion {
+                    documento: documento.clone(),
+                    simbolo: clase,
+                },
+                &referencia.nombre,
+            );
+        }
+
+        if referencia.camino.len() != 1 {
+            return None;
+        }
+        let contenedor = self.resolver_nombre(documento, objeto, inicio)?;
+        self.miembro(contenedor, &referencia.nombre)
+    }
+
+    /// Miembro `nombre` de una clase, interfaz, enum o módulo importado. Para
+    /// variables usa la clase anotada o instanciada.
+    pub fn miembro(&self, contenedor: Ubicacion, nombre: &str) -> Option<Ubicacion> {
+        let simbolo = contenedor.simbolo().clone();
+        match simbolo.tipo {
+            TipoSimbolo::Clase | TipoSimbolo::Interfaz | TipoSimbolo::Enum => contenedor
+                .documento
+                .analisis
+                .miembros_de(contenedor.simbolo)
+                .find(|(_, miembro)| miembro.nombre == nombre)
+                .map(|(indice, _)| Ubicacion {
+                    documento: contenedor.documento.clone(),
+                    simbolo: indice,
+                }),
+            TipoSimbolo::Importacion => {
+                let origen = simbolo.origen.as_ref()?;
+                let modulo = self.resolver_origen(&contenedor.documento, &origen.ruta)?;
+                self.buscar_exportado(&modulo, nombre, 0)
+            }
+            _ => {
+                let clase = simbolo.instancia.or(simbolo.anotacion)?;
+                let clase =
+                    self.resolver_nombre(&contenedor.documento, &clase, simbolo.rango.start)?;
+                if clase.simbolo().tipo == TipoSimbolo::Clase {
+                    self.miembro(clase, nombre)
+                } else {
+                    None
+                }
+            }
+        }
+    }
+
+    fn seguir_importacion(&self, ubicacion: Ubicacion, profundidad: usize) -> Ubicacion {
+        let Some(origen) = ubicacion.simbolo().origen.clone() else {
+            return ubicacion;
+        };
+        let Some(nombre) = origen.nombre else {
+            return ubicacion;
+        };
+
+        self.resolver_origen(&ubicacion.documento, &origen.ruta)
+            .and_then(|modulo| self.buscar_exportado(&modulo, &nombre, profundidad + 1))
+            .unwrap_or(ubicacion)
+    }
+
+    /// Declaración que `documento` exporta con el nombre `nombre`.
+    pub fn buscar_exportado(
+        &self,
+        documento: &Rc<Documento>,
+        nombre: &str,
+        profundidad: usize,
+    ) -> Option<Ubicacion> {
+        if profundidad > PROFUNDIDAD_MAXIMA {
+            return None;
+        }
+        let analisis = &documento.analisis;
+        let local = |nombre: &str| {
+            analisis.nivel_superior(nombre).map(|simbolo| {
+                self.seguir_importacion(
+                    Ubicacion {
+                        documento: documento.clone(),
+                        simbolo,
+                    },
+                    profundidad,
+                )
+            })
+        };
+
+        let declarado = analisis.simbolos.iter().position(|simbolo| {
+            simbolo.exportado && simbolo.nivel == 0 && simbolo.nombre == nombre
+        });
+        if let Some(simbolo) = declarado {
+            return Some(Ubicacion {
+                documento: documento.clone(),
+                simbolo,
+            });
+        }
+
+        for exportacion in &analisis.exportaciones {
+            let coincide = exportacion.publico == nombre || exportacion.publico == "*";
+            if !coincide {
+                continue;
+            }
+            let local_exportado = if exportacion.publico == "*" {
+                nombre
+            } else {
+                exportacion.local.as_str()
+            };
+
+            let encontrado = match &exportacion.origen {
+                None => local(local_exportado),
+                Some(ruta) => self.resolver_origen(documento, ruta).and_then(|modulo| {
+                    self.buscar_exportado(&modulo, local_exportado, profundidad + 1)
+                }),
+            };
+            if encontrado.is_some() {
+                return encontrado;
+            }
+        }
+        None
+    }
+
+    /// Todas las apariciones de la declaración `objetivo` en los documentos
+    /// abiertos y en los archivos `.um` del proyecto.
+    pub fn referencias(&self, objetivo: &Ubicacion) -> Vec<(Rc<Documento>, Range<usize>)> {
+        let nombre = objetivo.simbolo().nombre.clone();
+        let mut resultado = Vec::new();
+
+        for url in self.urls_del_proyecto() {
+            let Some(documento) = self.documento(&url) else {
+                continue;
+            };
+
+            // Un alias de importación (`equip { sumar as suma }`) también
+            // cuenta como nombre del símbolo en ese documento.
+            let mut nombres = vec![nombre.clone()];
+            for simbolo in &documento.analisis.simbolos {
+                let origen = simbolo
+                    .origen
+                    .as_ref()
+                    .and_then(|origen| origen.nombre.as_deref());
+                if origen == Some(nombre.as_str()) {
+                    nombres.push(simbolo.nombre.clone());
+                }
+            }
+
+            for referencia in &documento.analisis.referencias {
+                if !nombres.contains(&referencia.nombre) {
+                    continue;
+                }
+                let coincide = self
+                    .definiciones(&documento, referencia)
+                    .iter()
+                    .any(|definicion| definicion.es(objetivo));
+                if coincide {
+                    resultado.push((documento.clone(), referencia.rango.clone()));
+                }
+            }
+        }
+
+        resultado
+    }
+
+    fn urls_del_proyecto(&self) -> Vec<Url> {
+        let mut urls: Vec<Url> = self.abiertos.keys().cloned().collect();
+        if let Some(raiz) = &self.raiz {
+            let mut archivos = Vec::new();
+            buscar_archivos_um(raiz, &mut archivos);
+            for archivo in archivos {
+                if let Ok(url) = Url::from_file_path(&archivo) {
+                    let url = normalizar_url(url);
+                    if !urls.contains(&url) {
+                        urls.push(url);
+                    }
+                }
+            }
+        }
+        urls
+    }
+}
+
+fn buscar_archivos_um(directorio: &Path, archivos: &mut Vec<PathBuf>) {
+    let Ok(entradas) = std::fs::read_dir(directorio) else {
+        return;
+    };
+    for entrada in entradas.flatten() {
+        let ruta = entrada.path();
+        let nombre = entrada.file_name().to_string_lossy().to_string();
+        if ruta.is_dir() {
+            if !nombre.starts_with('.') && nombre != "target" {
+                buscar_archivos_um(&ruta, archivos);
+            }
+        } else if ruta.extension().is_some_and(|extension| extension == "um") {
+            archivos.push(ruta);
+        }
+    }
+}
+
+// El mismo archivo puede llegar como `./a/../b.um` o con enlaces simbólicos;
+// las URLs de archivos existentes se canonicalizan para compararlas.
+fn normalizar_url(url: Url) -> Url {
+    url.to_file_path()
+        .ok()
+        .and_then(|ruta| ruta.canonicalize().ok())
+        .and_then(|ruta| Url::from_file_path(ruta).ok())
+        .unwrap_or(url)
+}
@@ -0,0 +1,412 @@
+//! Servidor de lenguaje (LSP) para Umbral.
+//!
+//! Se comunica por stdio y ofrece diagnósticos del lexer y del parser,
+//! símbolos del documento, ir a la definición, referencias, hover,
+//! completado y formateo.
+
+mod analisis;
+mod completado;
+mod descripcion;
+mod espacio;
+mod posiciones;
+
+use analisis::TipoSimbolo;
+use espacio::{Documento, Espacio};
+use lsp_server::{Connection, ErrorCode, Message, Notification, Request, RequestId, Response};
+use lsp_types::notification::{
+    DidChangeTextDocument, DidCloseTextDocument, DidOpenTextDocument, Notification as _,
+    PublishDiagnostics,
+};
+use lsp_types::request::{
+    Completion, DocumentSymbolRequest, Formatting, GotoDefinition, HoverRequest, References,
+};
+use lsp_types::{
+    CompletionOptions, DocumentSymbol, DocumentSymbolResponse, GotoDefinitionResponse,
+    HoverProviderCapability, InitializeParams, Location, OneOf, PublishDiagnosticsParams,
+    ServerCapabilities, SymbolKind, TextDocumentPositionParams, TextDocumentSyncCapability,
+    TextDocumentSyncKind, TextEdit, Url,
+};
+use std::error::Error;
+use std::rc::Rc;
+
+type Resultado<T> = Result<T, Box<dyn Error + Sync + Send>>;
+
+fn main() -> Resultado<()> {
+    let (conexion, hilos) = Connection::stdio();
+    servir(&conexion)?;
+    drop(conexion);
+    hilos.join()?;
+    Ok(())
+}
+
+// Completa la inicialización y atiende mensajes hasta el `shutdown`.
+fn servir(conexion: &Connection) -> Resultado<()> {
+    let capacidades = ServerCapabilities {
+        text_document_sync: Some(TextDocumentSyncCapability::Kind(TextDocumentSyncKind::FULL)),
+        document_symbol_provider: Some(OneOf::Left(true)),
+        definition_provider: Some(OneOf::Left(true)),
+        references_provider: Some(OneOf::Left(true)),
+        hover_provider: Some(HoverProviderCapability::Simple(true)),
+        document_formatting_provider: Some(OneOf::Left(true)),
+        completion_provider: Some(CompletionOptions {
+            trigger_characters: Some(vec![".".to_string()]),
+            ..Default::default()
+        }),
+        ..Default::default()
+    };
+
+    let (id, parametros) = conexion.initialize_start()?;
+    let parametros: InitializeParams = serde_json::from_value(parametros)?;
+    conexion.initialize_finish(
+        id,
+        serde_json::json!({
+            "capabilities": capacidades,
+            "serverInfo": { "name": "umbral-lsp", "version": env!("CARGO_PKG_VERSION") },
+        }),
+    )?;
+
+    #[allow(deprecated)]
+    let raiz = parametros
+        .workspace_folders
+        .as_ref()
+        .and_then(|carpetas| carpetas.first().map(|carpeta| carpeta.uri.clone()))
+        .or(parametros.root_uri)
+        .and_then(|url| url.to_file_path().ok());
+
+    let mut espacio = Espacio::nuevo(raiz);
+    bucle(conexion, &mut espacio)
+}
+
+fn bucle(conexion: &Connection, espacio: &mut Espacio) -> Resultado<()> {
+    for mensaje in &conexion.receiver {
+        match mensaje {
+            Message::Request(peticion) => {
+                if conexion.handle_shutdown(&peticion)? {
+                    return Ok(());
+                }
+                let respuesta = atender(espacio, peticion);
+                conexion.sender.send(Message::Response(respuesta))?;
+            }
+            Message::Notification(notificacion) => notificar(conexion, espacio, notificacion)?,
+            Message::Response(_) => {}
+        }
+    }
+    Ok(())
+}
+
+fn notificar(
+    conexion: &Connection,
+    espacio: &mut Espacio,
+    notificacion: Notification,
+) -> Resultado<()> {
+    match notificacion.method.as_str() {
+        DidOpenTextDocument::METHOD => {
+            let parametros: lsp_types::DidOpenTextDocumentParams =
+                serde_json::from_value(notificacion.params)?;
+            let documento =
+                espacio.abrir(parametros.text_document.uri, parametros.text_document.text);
+            publicar(
+                conexion,
+                documento.url.clone(),
+                documento.diagnosticos.clone(),
+            )?;
+        }
+        DidChangeTextDocument::METHOD => {
+            let parametros: lsp_types::DidChangeTextDocumentParams =
+                serde_json::from_value(notificacion.params)?;
+            // Con sincronización completa el último cambio trae el texto entero.
+            if let Some(cambio) = parametros.content_changes.into_iter().last() {
+                let documento = espacio.abrir(parametros.text_document.uri, cambio.text);
+                publicar(
+                    conexion,
+                    documento.url.clone(),
+                    documento.diagnosticos.clone(),
+                )?;
+            }
+        }
+        DidCloseTextDocument::METHOD => {
+            let parametros: lsp_types::DidCloseTextDocumentParams =
+                serde_json::from_value(notificacion.params)?;
+            espacio.cerrar(&parametros.text_document.uri);
+            publicar(conexion, parametros.text_document.uri, Vec::new())?;
+        }
+        _ => {}
+    }
+    Ok(())
+}
+
+fn publicar(
+    conexion: &Connection,
+    url: Url,
+    diagnosticos: Vec<lsp_types::Diagnostic>,
+) -> Resultado<()> {
+    let parametros = PublishDiagnosticsParams::new(url, diagnosticos, None);
+    conexion
+        .sender
+        .send(Message::Notification(Notification::new(
+            PublishDiagnostics::METHOD.to_string(),
+            parametros,
+        )))?;
+    Ok(())
+}
+
+fn atender(espacio: &Espacio, peticion: Request) -> Response {
+    match peticion.method.as_str() {
+        "textDocument/documentSymbol" => {
+            ejecutar::<DocumentSymbolRequest>(peticion, |parametros| {
+                let documento = espacio.documento(&parametros.text_document.uri)?;
+                Some(DocumentSymbolResponse::Nested(simbolos_del_documento(
+                    &documento,
+                )))
+            })
+        }
+        "textDocument/definition" => ejecutar::<GotoDefinition>(peticion, |parametros| {
+            let (documento, indice) = ubicar(espacio, &parametros.text_document_position_params)?;
+            let referencia = documento.analisis.referencia_en(indice)?;
+            let ubicaciones: Vec<Location> = espacio
+                .definiciones(&documento, referencia)
+                .into_iter()
+                .map(|ubicacion| {
+                    let rango = ubicacion.documento.lineas.rango(&ubicacion.simbolo().rango);
+                    Location::new(ubicacion.documento.url.clone(), rango)
+                })
+                .collect();
+            (!ubicaciones.is_empty()).then_some(GotoDefinitionResponse::Array(ubicaciones))
+        }),
+        "textDocument/references" => ejecutar::<References>(peticion, |parametros| {
+            let (documento, indice) = ubicar(espacio, &parametros.text_document_position)?;
+            let referencia = documento.analisis.referencia_en(indice)?;
+            let objetivo = espacio
+                .definiciones(&documento, referencia)
+                .into_iter()
+                .next()?;
+            let declaracion = (
+                objetivo.documento.url.clone(),
+                objetivo.simbolo().rango.clone(),
+            );
+            let incluir = parametros.context.include_declaration;
+            let ubicaciones = espacio
+                .referencias(&objetivo)
+                .into_iter()
+                .filter(|(documento, rango)| {
+                    incluir || (documento.url.clone(), rango.clone()) != declaracion
+                })
+                .map(|(documento, rango)| {
+                    Location::new(documento.url.clone(), documento.lineas.rango(&rango))
+                })
+                .collect();
+            Some(ubicaciones)
+        }),
+        "textDocument/hover" => ejecutar::<HoverRequest>(peticion, |parametros| {
+            let (documento, indice) = ubicar(espacio, &parametros.text_document_position_params)?;
+            descripcion::hover(espacio, &documento, indice)
+        }),
+        "textDocument/completion" => ejecutar::<Completion>(peticion, |parametros| {
+            let (documento, indice) = ubicar(espacio, &parametros.text_document_position)?;
+            Some(completado::completar(espacio, &documento, indice).into())
+        }),
+        "textDocument/formatting" => ejecutar::<Formatting>(peticion, |parametros| {
+            let documento = espacio.documento(&parametros.text_document.uri)?;
+            formatear(&documento)
+        }),
+        _ => Response::new_err(
+            peticion.id,
+            ErrorCode::MethodNotFound as i32,
+            format!("Método no soportado: {}", peticion.method),
+        ),
+    }
+}
+
+// Deserializa los parámetros de la petición `R`, ejecuta el manejador y
+// serializa su resultado; `None` se responde como `null`.
+fn ejecutar<R>(peticion: Request, manejador: impl FnOnce(R::Params) -> R::Result) -> Response
+where
+    R: lsp_types::request::Request,
+{
+    let id: RequestId = peticion.id;
+    match serde_json::from_value::<R::Params>(peticion.params) {
+        Ok(parametros) => Response::new_ok(id, manejador(parametros)),
+        Err(error) => Response::new_err(id, ErrorCode::InvalidParams as i32, error.to_string()),
+    }
+}
+
+// Un archivo con errores de sintaxis no se formatea: se responde `null` y
+// los diagnósticos ya señalan el problema.
+fn formatear(documento: &Documento) -> Option<Vec<TextEdit>> {
+    let caracteres = documento.lineas.caracteres();
+    let texto: String = caracteres.iter().collect();
+    let formateado = umbral_fmt::formatear(&texto).ok()?;
+    if formateado == texto {
+        return Some(Vec::new());
+    }
+    let rango = documento.lineas.rango(&(0..caracteres.len()));
+    Some(vec![TextEdit::new(rango, formateado)])
+}
+
+fn ubicar(
+    espacio: &Espacio,
+    posicion: &TextDocumentPositionParams,
+) -> Option<(Rc<Documento>, usize)> {
+    let documento = espacio.documento(&posicion.text_document.uri)?;
+    let indice = documento.lineas.indice(posicion.position);
+    Some((documento, indice))
+}
+
+fn simbolos_del_documento(documento: &Documento) -> Vec<DocumentSymbol> {
+    let analisis = &documento.analisis;
+    analisis
+        .simbolos
+        .iter()
+        .enumerate()
+        .filter(|(_, simbolo)| simbolo.contenedor.is_none() && simbolo.nivel == 0)
+        .filter_map(|(indice, simbolo)| {
+            let tipo = match simbolo.tipo {
+                TipoSimbolo::Funcion => SymbolKind::FUNCTION,
+                TipoSimbolo::Clase => SymbolKind::CLASS,
+                TipoSimbolo::Interfaz => SymbolKind::INTERFACE,
+                TipoSimbolo::Enum => SymbolKind::ENUM,
+                TipoSimbolo::Variable => SymbolKind::VARIABLE,
+                TipoSimbolo::Constante => SymbolKind::CONSTANT,
+                _ => return None,
+            };
+            let hijos: Vec<DocumentSymbol> = analisis
+                .miembros_de(indice)
+                .map(|(_, miembro)| {
+                    let tipo = match miembro.tipo {
+                        TipoSimbolo::Metodo => SymbolKind::METHOD,
+                        TipoSimbolo::Variante => SymbolKind::ENUM_MEMBER,
+                        _ => SymbolKind::PROPERTY,
+                    };
+                    simbolo_lsp(documento, miembro, tipo, None)
+                })
+                .collect();
+            let hijos = (!hijos.is_empty()).then_some(hijos);
+            Some(simbolo_lsp(documento, simbolo, tipo, hijos))
+        })
+        .collect()
+}
+
+fn simbolo_lsp(
+    documento: &Documento,
+    simbolo: &analisis::Simbolo,
+    tipo: SymbolKind,
+    hijos: Option<Vec<DocumentSymbol>>,
+) -> DocumentSymbol {
+    let seleccion = documento.lineas.rango(&simbolo.rango);
+    let rango = match &simbolo.cuerpo {
+        Some(cuerpo) => documento
+            .lineas
+            .rango(&(simbolo.rango.start..cuerpo.end.max(simbolo.rango.end))),
+        None => seleccion,
+    };
+    #[allow(deprecated)]
+    DocumentSymbol {
+        name: simbolo.nombre.clone(),
+        detail: Some(descripcion::firma(simbolo, &documento.analisis)),
+        kind: tipo,
+        tags: None,
+        deprecated: None,
+        range: rango,
+        selection_range: seleccion,
+        children: hijos,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use lsp_server::Message;
+    use serde_json::{json, Value};
+
+    struct Cliente {
+        conexion: Connection,
+        siguiente_id: i32,
+    }
+
+    impl Cliente {
+        fn pedir(&mut self, metodo: &str, parametros: Value) -> Value {
+            self.siguiente_id += 1;
+            let peticion = Request::new(self.siguiente_id.into(), metodo.to_string(), parametros);
+            self.conexion.sender.send(peticion.into()).unwrap();
+            loop {
+                match self.conexion.receiver.recv().unwrap() {
+                    Message::Response(respuesta) => {
+                        assert!(respuesta.error.is_none(), "{:?}", respuesta.error);
+                        return respuesta.result.unwrap_or(Value::Null);
+                    }
+                    _ => continue,
+                }
+            }
+        }
+
+        fn notificar(&self, metodo: &str, parametros: Value) {
+            let notificacion = Notification::new(metodo.to_string(), parametros);
+            self.conexion.sender.send(notificacion.into()).unwrap();
+        }
+
+        fn esperar_notificacion(&self, metodo: &str) -> Value {
+            loop {
+                if let Message::Notification(notificacion) = self.conexion.receiver.recv().unwrap()
+                {
+                    if notificacion.method == metodo {
+                        return notificacion.params;
+                    }
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_sesion_lsp_completa() {
+        let (servidor, cliente) = Connection::memory();
+        let hilo = std::thread::spawn(move || servir(&servidor).unwrap());
+        let mut cliente = Cliente {
+            conexion: cliente,
+            siguiente_id: 0,
+        };
+
+        let respuesta = cliente.pedir("initialize", json!({ "capabilities": {} }));
+        assert_eq!(respuesta["serverInfo"]["name"], "umbral-lsp");
+        assert_eq!(respuesta["capabilities"]["definitionProvider"], true);
+        cliente.notificar("initialized", json!({}));
+
+        let url = "file:///tmp/ejemplo.um";
+        let texto = "f: sumar(a, b) { r: (a + b); }\nv: total = sumar(1, 2);\nv: mal = (1 + ;\n";
+        cliente.notificar(
+            DidOpenTextDocument::METHOD,
+            json!({ "textDocument": { "uri": url, "languageId": "umbral", "version": 1, "text": texto } }),
+        );
+        let diagnosticos = cliente.esperar_notificacion(PublishDiagnostics::METHOD);
+        assert_eq!(diagnosticos["uri"], url);
+        assert_eq!(diagnosticos["diagnostics"].as_array().unwrap().len(), 1);
+        assert_eq!(diagnosticos["diagnostics"][0]["range"]["start"]["line"], 2);
+
+        // `sumar` en la segunda línea lleva a su declaración en la primera.
+        let posicion =
+            json!({ "textDocument": { "uri": url }, "position": { "line": 1, "character": 12 } });
+        let definicion = cliente.pedir("textDocument/definition", posicion.clone());
+        assert_eq!(definicion[0]["uri"], url);
+        assert_eq!(
+            definicion[0]["range"],
+            json!({ "start": { "line": 0, "character": 3 }, "end": { "line": 0, "character": 8 } })
+        );
+
+        let hover = cliente.pedir("textDocument/hover", posicion);
+        let contenido = hover["contents"].to_string();
+        assert!(contenido.contains("sumar(a, b)"), "{}", contenido);
+
+        let desconocido = Request::new(99.into(), "umbral/desconocido".to_string(), json!({}));
+        cliente.conexion.sender.send(desconocido.into()).unwrap();
+        let Message::Response(respuesta) = cliente.conexion.receiver.recv().unwrap() else {
+            panic!("se esperaba una respuesta");
+        };
+        assert_eq!(
+            respuesta.error.unwrap().code,
+            ErrorCode::MethodNotFound as i32
+        );
+
+        cliente.pedir("shutdown", Value::Null);
+        cliente.notificar("exit", Value::Null);
+        hilo.join().unwrap();
+    }
+}
@@ -0,0 +1,65 @@
+use lsp_types::{Position, Range as RangoLsp};
+use std::ops::Range;
+
+/// Convierte entre índices de carácter (los que usan el lexer y el parser) y
+/// posiciones LSP, cuyas columnas se cuentan en unidades UTF-16.
+pub struct Lineas {
+    caracteres: Vec<char>,
+    inicios: Vec<usize>,
+}
+
+impl Lineas {
+    pub fn nuevo(texto: &str) -> Self {
+        let caracteres: Vec<char> = texto.chars().collect();
+        let mut inicios = vec![0];
+        for (indice, caracter) in caracteres.iter().enumerate() {
+            if *caracter == '\n' {
+                inicios.push(indice + 1);
+            }
+        }
+        Self {
+            caracteres,
+            inicios,
+        }
+    }
+
+    pub fn posicion(&self, indice: usize) -> Position {
+        let indice = indice.min(self.caracteres.len());
+        let linea = self.inicios.partition_point(|inicio| *inicio <= indice) - 1;
+        let columna: usize = self.caracteres[self.inicios[linea]..indice]
+            .iter()
+            .map(|c| c.len_utf16())
+            .sum();
+        Position::new(linea as u32, columna as u32)
+    }
+
+    pub fn indice(&self, posicion: Position) -> usize {
+        let Some(&inicio) = self.inicios.get(posicion.line as usize) else {
+            return self.caracteres.len();
+        };
+
+        let mut columna = 0;
+        let mut indice = inicio;
+        while indice < self.caracteres.len() && self.caracteres[indice] != '\n' {
+            if columna >= posicion.character as usize {
+                break;
+            }
+            columna += self.caracteres[indice].len_utf16();
+            indice += 1;
+        }
+        indice
+    }
+
+    pub fn rango(&self, rango: &Range<usize>) -> RangoLsp {
+        RangoLsp::new(self.posicion(rango.start), self.posicion(rango.end))
+    }
+
+    pub fn caracteres(&self) -> &[char] {
+        &self.caracteres
+    }
+}
+
+/// Índice de carácter que corresponde a un desplazamiento en bytes.
+pub fn indice_de_byte(texto: &str, byte: usize) -> usize {
+    texto[..byte.min(texto.len())].chars().count()
+}
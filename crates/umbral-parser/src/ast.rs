@@ -26,6 +26,19 @@ pub enum Sentencia {
     TryCatch(TryCatch),
     Throw(Throw),
     Exportacion(String),
+    ListaExportacion(ListaExportacion),
+    ExportacionPorDefecto(Box<Sentencia>),
+    /// Sentencia de un bloque junto al lugar donde empieza. Solo aparece si
+    /// el parser se configuró con `anotar_lineas`.
+    Ubicada(Ubicacion, Box<Sentencia>),
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Ubicacion {
+    /// Identificador del archivo que se pasó a `anotar_lineas`.
+    pub archivo: usize,
+    /// Línea, empezando por 1.
+    pub linea: usize,
 }
 
 #[derive(Debug, Clone)]
@@ -40,6 +53,8 @@ pub struct DeclaracionVariable {
 pub struct DeclaracionConstante {
     pub nombre: String,
     pub tipo: Option<Tipo>,
+    /// El tipo no se escribió con `->`: se dedujo del valor.
+    pub tipo_inferido: bool,
     pub valor: Expresion,
     pub exportado: bool,
 }
@@ -149,8 +164,10 @@ pub struct DeclaracionEnum {
 pub enum Expresion {
     LiteralEntero(i64),
     LiteralFloat(f64),
+    LiteralDecimal(String),
     LiteralCadena(String),
     LiteralCadenaLiteral(String),
+    Plantilla(Vec<ParteCadena>),
     LiteralBool(bool),
     LiteralNulo,
     Identificador(String),
@@ -181,6 +198,11 @@ pub enum Expresion {
         objeto: Box<Expresion>,
         indice: Box<Expresion>,
     },
+    Rebanada {
+        objeto: Box<Expresion>,
+        inicio: Option<Box<Expresion>>,
+        fin: Option<Box<Expresion>>,
+    },
     LlamadoMetodo {
         objeto: Box<Expresion>,
         metodo: String,
@@ -192,6 +214,14 @@ pub enum Expresion {
     },
 }
 
+/// Fragmento de una cadena con interpolaciones: texto literal o una
+/// expresión (`&nombre`, `&obj.metodo(x)`, `&{ a + b }`).
+#[derive(Debug, Clone)]
+pub enum ParteCadena {
+    Texto(String),
+    Expresion(Expresion),
+}
+
 #[derive(Debug, Clone)]
 pub struct If {
     pub condicion: Expresion,
@@ -251,6 +281,8 @@ pub struct DoWhile {
 pub struct Importacion {
     pub items: Vec<ItemImportacion>,
     pub ruta: String,
+    /// Se escribió con la sintaxis inversa: `origin "..." equip ...`.
+    pub origen_primero: bool,
 }
 
 #[derive(Debug, Clone)]
@@ -258,7 +290,19 @@ pub enum ItemImportacion {
     Todo(Option<String>),
     Nombre(String, Option<String>),
     ListaNombres(Vec<ItemImportacion>),
-    Modulo(String),
+    Modulo(String, Option<String>),
+}
+
+#[derive(Debug, Clone)]
+pub struct ListaExportacion {
+    pub items: Vec<ItemExportacion>,
+    pub origen: Option<String>,
+}
+
+#[derive(Debug, Clone)]
+pub enum ItemExportacion {
+    Todo,
+    Nombre(String, Option<String>),
 }
 
 #[derive(Debug, Clone)]
@@ -76,7 +76,7 @@ fn validar_linea(linea: usize, total_lineas: usize) -> bool {
 fn agregar_encabezado_error(resultado: &mut String, mensaje: &str, linea: usize, columna: usize) {
     resultado.push_str(&format!("Error: {}\n", mensaje));
     resultado.push_str(&format!("  --> línea {}, columna {}\n", linea, columna));
-    resultado.push_str(&format!("   |\n"));
+    resultado.push_str("   |\n");
 }
 
 fn agregar_linea_contexto(resultado: &mut String, numero: usize, contenido: &str) {
@@ -116,6 +116,6 @@ fn formatear_error_con_indicador(
         agregar_linea_contexto(&mut resultado, linea + 1, lineas[indice_linea + 1]);
     }
     
-    resultado.push_str(&format!("   |\n"));
+    resultado.push_str("   |\n");
     resultado
 }
@@ -8,7 +8,7 @@ pub use parser::Parser;
 
 use umbral_lexer::Token;
 
-pub fn parsear_programa(tokens: Vec<Token>) -> Result<Programa, ParseError> {
+pub fn parsear_programa(tokens: Vec<Token>) -> Result<Programa, Vec<ParseError>> {
     let mut p = Parser::nuevo(tokens);
     p.parsear_programa()
 }
@@ -16,11 +16,13 @@ pub fn parsear_declaracion_constante(parseador: &mut Parser, exportado: bool) ->
     let valor = expresiones::parsear_expresion_principal(parseador)?;
     parseador.coincidir(|t| matches!(t, LexToken::PuntoYComa));
 
+    let tipo_inferido = tipo.is_none();
     let tipo = tipo.or_else(|| Some(inferir_tipo(&valor)));
 
     Ok(Sentencia::DeclaracionConstante(DeclaracionConstante {
         nombre,
         tipo,
+        tipo_inferido,
         valor,
         exportado,
     }))
@@ -48,7 +50,10 @@ fn inferir_tipo(expresion: &Expresion) -> Tipo {
     match expresion {
         Expresion::LiteralEntero(_) => crear_tipo("Int"),
         Expresion::LiteralFloat(_) => crear_tipo("Flo"),
-        Expresion::LiteralCadena(_) | Expresion::LiteralCadenaLiteral(_) => crear_tipo("Str"),
+        Expresion::LiteralDecimal(_) => crear_tipo("Dec"),
+        Expresion::LiteralCadena(_)
+        | Expresion::LiteralCadenaLiteral(_)
+        | Expresion::Plantilla(_) => crear_tipo("Str"),
         Expresion::LiteralBool(_) => crear_tipo("Bool"),
         Expresion::Objeto(_) => crear_tipo("Obj"),
         Expresion::Array(_) => crear_tipo("Array"),
@@ -80,6 +85,10 @@ fn inferir_tipo_aritmetico(izquierda: &Expresion, derecha: &Expresion) -> Tipo {
     let tipo_izq = inferir_tipo(izquierda);
     let tipo_der = inferir_tipo(derecha);
     
+    if tipo_izq.nombre == "Dec" || tipo_der.nombre == "Dec" {
+        return crear_tipo("Dec");
+    }
+
     let es_flotante = tipo_izq.nombre == "Flo" || tipo_der.nombre == "Flo";
     
     if es_flotante {
@@ -22,7 +22,7 @@ fn parsear_condicion_entre_parentesis(
     contexto: &str,
 ) -> Result<Expresion, ParseError> {
     if !parseador.coincidir(|t| matches!(t, LexToken::ParentesisIzq)) {
-        return Err(parseador.crear_error(&format!("Se esperaba '(' después de {}", contexto)));
+        return Err(parseador.crear_error(format!("Se esperaba '(' después de {}", contexto)));
     }
 
     let condicion = crate::parser::expresiones::parsear_expresion_principal(parseador)?;
@@ -112,8 +112,7 @@ fn parsear_caso_individual(parseador: &mut Parser) -> Result<Case, ParseError> {
         return Err(parseador.crear_error("Se esperaba '=>'"));
     }
 
-    let mut bloque = Vec::new();
-    bloque.push(parseador.parsear_sentencia()?);
+    let bloque = vec![parseador.parsear_sentencia()?];
 
     Ok(Case { valor, bloque })
 }
@@ -123,8 +122,7 @@ fn parsear_bloque_default(parseador: &mut Parser) -> Result<Vec<Sentencia>, Pars
         return Err(parseador.crear_error("Se esperaba '=>'"));
     }
 
-    let mut bloque = Vec::new();
-    bloque.push(parseador.parsear_sentencia()?);
+    let bloque = vec![parseador.parsear_sentencia()?];
 
     Ok(bloque)
 }
@@ -233,7 +231,10 @@ pub fn parsear_dowhile(p: &mut Parser) -> Result<Sentencia, ParseError> {
 fn parsear_bloque(p: &mut Parser) -> Result<Vec<Sentencia>, ParseError> {
     let mut sentencias = Vec::new();
     while !p.coincidir(|t| matches!(t, LexToken::LlaveDer)) {
-        sentencias.push(p.parsear_sentencia()?);
+        if p.esta_fin() {
+            return Err(p.crear_error("Bloque sin cerrar, se esperaba '}'"));
+        }
+        sentencias.extend(p.parsear_sentencia_recuperando());
     }
     Ok(sentencias)
 }
@@ -28,10 +28,10 @@ pub fn parsear_declaracion_enum(p: &mut Parser, exportado: bool) -> Result<Sente
                     valor,
                 });
                 
-                if !p.coincidir(|t| matches!(t, LexToken::Coma)) {
-                    if !matches!(p.peekear(), Some(LexToken::LlaveDer)) {
-                        return Err(p.crear_error("Se esperaba ',' o '}' en enum"));
-                    }
+                if !p.coincidir(|t| matches!(t, LexToken::Coma))
+                    && !matches!(p.peekear(), Some(LexToken::LlaveDer))
+                {
+                    return Err(p.crear_error("Se esperaba ',' o '}' en enum"));
                 }
             }
             Some(LexToken::LlaveDer) => {
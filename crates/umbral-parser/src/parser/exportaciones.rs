@@ -0,0 +1,108 @@
+use crate::ast::*;
+use crate::error::ParseError;
+use crate::parser::Parser;
+use umbral_lexer::Token as LexToken;
+
+pub fn intentar_parsear_exportacion(p: &mut Parser) -> Option<Result<Sentencia, ParseError>> {
+    match p.peekear() {
+        Some(LexToken::LlaveIzq) => {
+            p.avanzar();
+            Some(parsear_lista_exportacion(p))
+        }
+        Some(LexToken::Multiplicacion) => {
+            p.avanzar();
+            Some(parsear_reexportacion_total(p))
+        }
+        Some(LexToken::Default) => {
+            p.avanzar();
+            Some(parsear_exportacion_por_defecto(p))
+        }
+        _ => None,
+    }
+}
+
+fn parsear_lista_exportacion(p: &mut Parser) -> Result<Sentencia, ParseError> {
+    let mut items = Vec::new();
+
+    while !p.coincidir(|t| matches!(t, LexToken::LlaveDer)) {
+        let nombre = parsear_identificador(p)?;
+        let alias = parsear_alias_opcional(p)?;
+        items.push(ItemExportacion::Nombre(nombre, alias));
+
+        if p.coincidir(|t| matches!(t, LexToken::Coma)) {
+            continue;
+        }
+
+        if !matches!(p.peekear(), Some(LexToken::LlaveDer)) {
+            return Err(p.crear_error("Se esperaba ',' o '}' en la lista de exportación"));
+        }
+    }
+
+    let origen = parsear_origen_opcional(p)?;
+    p.coincidir(|t| matches!(t, LexToken::PuntoYComa));
+
+    Ok(Sentencia::ListaExportacion(ListaExportacion {
+        items,
+        origen,
+    }))
+}
+
+fn parsear_reexportacion_total(p: &mut Parser) -> Result<Sentencia, ParseError> {
+    let Some(origen) = parsear_origen_opcional(p)? else {
+        return Err(p.crear_error("Se esperaba 'origin' después de 'out: *'"));
+    };
+
+    p.coincidir(|t| matches!(t, LexToken::PuntoYComa));
+
+    Ok(Sentencia::ListaExportacion(ListaExportacion {
+        items: vec![ItemExportacion::Todo],
+        origen: Some(origen),
+    }))
+}
+
+fn parsear_exportacion_por_defecto(p: &mut Parser) -> Result<Sentencia, ParseError> {
+    if let Some(declaracion) = p.intentar_parsear_declaraciones(false) {
+        return Ok(Sentencia::ExportacionPorDefecto(Box::new(declaracion?)));
+    }
+
+    let expresion = crate::parser::expresiones::parsear_expresion_principal(p)?;
+    p.coincidir(|t| matches!(t, LexToken::PuntoYComa));
+
+    Ok(Sentencia::ExportacionPorDefecto(Box::new(
+        Sentencia::Expresion(expresion),
+    )))
+}
+
+fn parsear_origen_opcional(p: &mut Parser) -> Result<Option<String>, ParseError> {
+    if !p.coincidir(|t| matches!(t, LexToken::Origin)) {
+        return Ok(None);
+    }
+
+    let Some(LexToken::Cadena(ruta) | LexToken::CadenaLiteral(ruta)) = p.peekear() else {
+        return Err(
+            p.crear_error("Se esperaba una ruta de archivo como cadena después de 'origin'")
+        );
+    };
+
+    let ruta = ruta.clone();
+    p.avanzar();
+    Ok(Some(ruta))
+}
+
+fn parsear_alias_opcional(p: &mut Parser) -> Result<Option<String>, ParseError> {
+    if !p.coincidir(|t| matches!(t, LexToken::As)) {
+        return Ok(None);
+    }
+
+    Ok(Some(parsear_identificador(p)?))
+}
+
+fn parsear_identificador(p: &mut Parser) -> Result<String, ParseError> {
+    let Some(LexToken::Identificador(nombre)) = p.peekear() else {
+        return Err(p.crear_error("Se esperaba un identificador"));
+    };
+
+    let nombre = nombre.clone();
+    p.avanzar();
+    Ok(nombre)
+}
@@ -1,6 +1,6 @@
 use crate::ast::*;
 use crate::error::ParseError;
-use crate::parser::{objetos, Parser};
+use crate::parser::{objetos, plantillas, Parser};
 use umbral_lexer::Token as LexToken;
 
 pub fn parsear_expresion_principal(parseador: &mut Parser) -> Result<Expresion, ParseError> {
@@ -224,7 +224,15 @@ fn procesar_acceso_indice(
         return Ok(false);
     }
 
-    let indice = parsear_expresion_principal(parseador)?;
+    let inicio = if matches!(parseador.peekear(), Some(LexToken::DosPuntos)) {
+        None
+    } else {
+        Some(parsear_expresion_principal(parseador)?)
+    };
+
+    if parseador.coincidir(|t| matches!(t, LexToken::DosPuntos)) {
+        return procesar_rebanada(parseador, expresion, inicio);
+    }
 
     if !parseador.coincidir(|t| matches!(t, LexToken::CorcheteDer)) {
         return Err(parseador.crear_error("Se esperaba ']'"));
@@ -232,7 +240,32 @@ fn procesar_acceso_indice(
 
     *expresion = Expresion::AccesoIndice {
         objeto: Box::new(expresion.clone()),
-        indice: Box::new(indice),
+        indice: Box::new(inicio.expect("el índice se parsea cuando no hay ':'")),
+    };
+
+    Ok(true)
+}
+
+// objeto[inicio:fin], con ambos extremos opcionales.
+fn procesar_rebanada(
+    parseador: &mut Parser,
+    expresion: &mut Expresion,
+    inicio: Option<Expresion>,
+) -> Result<bool, ParseError> {
+    let fin = if matches!(parseador.peekear(), Some(LexToken::CorcheteDer)) {
+        None
+    } else {
+        Some(parsear_expresion_principal(parseador)?)
+    };
+
+    if !parseador.coincidir(|t| matches!(t, LexToken::CorcheteDer)) {
+        return Err(parseador.crear_error("Se esperaba ']'"));
+    }
+
+    *expresion = Expresion::Rebanada {
+        objeto: Box::new(expresion.clone()),
+        inicio: inicio.map(Box::new),
+        fin: fin.map(Box::new),
     };
 
     Ok(true)
@@ -293,19 +326,54 @@ fn parsear_primaria(parseador: &mut Parser) -> Result<Expresion, ParseError> {
 }
 
 fn parsear_numero(parseador: &mut Parser, numero: &str) -> Result<Expresion, ParseError> {
-    parseador.avanzar();
-    let valor = numero.parse::<f64>().unwrap_or(0.0);
+    let limpio = numero.replace('_', "");
 
-    if numero.contains('.') {
-        return Ok(Expresion::LiteralFloat(valor));
+    if let Some(decimal) = limpio.strip_suffix('d') {
+        parseador.avanzar();
+        return Ok(Expresion::LiteralDecimal(decimal.to_string()));
     }
 
-    Ok(Expresion::LiteralEntero(valor as i64))
+    let base = match limpio.get(..2) {
+        Some("0x" | "0X") => 16,
+        Some("0b" | "0B") => 2,
+        Some("0o" | "0O") => 8,
+        _ => 10,
+    };
+
+    if base == 10 && limpio.contains('.') {
+        parseador.avanzar();
+        return Ok(Expresion::LiteralFloat(
+            limpio.parse::<f64>().unwrap_or(0.0),
+        ));
+    }
+
+    let digitos = if base == 10 {
+        &limpio[..]
+    } else {
+        &limpio[2..]
+    };
+    match i64::from_str_radix(digitos, base) {
+        Ok(valor) => {
+            parseador.avanzar();
+            Ok(Expresion::LiteralEntero(valor))
+        }
+        Err(error) => {
+            use std::num::IntErrorKind;
+            let mensaje = match error.kind() {
+                IntErrorKind::PosOverflow | IntErrorKind::NegOverflow => {
+                    format!("Literal entero fuera de rango: {}", numero)
+                }
+                _ => format!("Literal numérico inválido: {}", numero),
+            };
+            Err(parseador.crear_error(&mensaje))
+        }
+    }
 }
 
 fn parsear_cadena(parseador: &mut Parser, texto: &str) -> Result<Expresion, ParseError> {
+    let expresion = plantillas::parsear_plantilla(parseador, texto.trim_matches('"'))?;
     parseador.avanzar();
-    Ok(Expresion::LiteralCadena(texto.to_string()))
+    Ok(expresion)
 }
 
 fn parsear_cadena_literal(parseador: &mut Parser, texto: &str) -> Result<Expresion, ParseError> {
@@ -112,7 +112,7 @@ impl Parser {
             if self.esta_fin() {
                 return Err(self.crear_error("Bloque sin cerrar, se esperaba '}'"));
             }
-            sentencias.push(self.parsear_sentencia()?);
+            sentencias.extend(self.parsear_sentencia_recuperando());
         }
         Ok(sentencias)
     }
@@ -8,6 +8,7 @@ pub fn parsear_importacion(p: &mut Parser) -> Result<Sentencia, ParseError> {
     let mut tiene_origin = false;
     let mut items: Vec<ItemImportacion> = Vec::new();
     let mut ruta = String::new();
+    let origen_primero = matches!(p.peekear(), Some(LexToken::Origin));
 
     loop {
         if p.coincidir(|t| matches!(t, LexToken::Equip)) {
@@ -39,7 +40,11 @@ pub fn parsear_importacion(p: &mut Parser) -> Result<Sentencia, ParseError> {
 
     p.coincidir(|t| matches!(t, LexToken::PuntoYComa));
 
-    Ok(Sentencia::Importacion(Importacion { items, ruta }))
+    Ok(Sentencia::Importacion(Importacion {
+        items,
+        ruta,
+        origen_primero,
+    }))
 }
 
 fn parsear_items_importacion(p: &mut Parser) -> Result<Vec<ItemImportacion>, ParseError> {
@@ -55,7 +60,7 @@ fn parsear_items_importacion(p: &mut Parser) -> Result<Vec<ItemImportacion>, Par
     let nombre = parsear_identificador(p)?;
     let alias = parsear_alias_opcional(p)?;
 
-    Ok(vec![ItemImportacion::Modulo(alias.unwrap_or(nombre))])
+    Ok(vec![ItemImportacion::Modulo(nombre, alias)])
 }
 
 fn parsear_alias_opcional(p: &mut Parser) -> Result<Option<String>, ParseError> {
@@ -4,15 +4,13 @@ use crate::parser::Parser;
 use umbral_lexer::Token as LexToken;
 
 fn es_inicio_instancia(parseador: &Parser, nombre: &str) -> bool {
-    let primera_letra_mayuscula = nombre.chars().next().map_or(false, |c| c.is_uppercase());
+    let primera_letra_mayuscula = nombre.chars().next().is_some_and(|c| c.is_uppercase());
     if !primera_letra_mayuscula {
         return false;
     }
 
-    let hay_parentesis = parseador.posicion + 1 < parseador.tokens.len()
-        && matches!(parseador.tokens[parseador.posicion + 1], LexToken::ParentesisIzq);
-    
-    hay_parentesis
+    parseador.posicion + 1 < parseador.tokens.len()
+        && matches!(parseador.tokens[parseador.posicion + 1], LexToken::ParentesisIzq)
 }
 
 fn obtener_nombre_clase(parseador: &mut Parser) -> Option<String> {
@@ -2,12 +2,14 @@ pub mod clases;
 pub mod constantes;
 pub mod controles;
 pub mod enums;
+pub mod exportaciones;
 pub mod expresiones;
 pub mod funciones;
 pub mod importaciones;
 pub mod instancias;
 pub mod interfaces;
 pub mod objetos;
+pub mod plantillas;
 pub mod sentencias;
 pub mod tokens;
 pub mod utilidades;
@@ -23,6 +25,44 @@ pub struct Parser {
     pub posiciones: Vec<usize>,
     pub posicion: usize,
     pub codigo_fuente: String,
+    /// Errores de las sentencias descartadas durante la recuperación.
+    pub errores: Vec<ParseError>,
+    /// Índice del primer token de cada sentencia parseada, en preorden: una
+    /// sentencia aparece antes que las de sus bloques. Las herramientas que
+    /// recorren el AST en ese mismo orden lo usan para ubicar cada sentencia.
+    pub inicios_sentencias: Vec<usize>,
+    /// Líneas de las sentencias anotadas con `anotar_lineas`, en el orden
+    /// en que se parsearon.
+    pub lineas_sentencias: Vec<usize>,
+    // Archivo y carácter donde empieza cada línea; solo existen si se
+    // anotan las líneas.
+    inicios_lineas: Option<(usize, Vec<usize>)>,
+}
+
+fn es_inicio_sentencia(token: &LexToken) -> bool {
+    use LexToken::*;
+    matches!(
+        token,
+        DeclararVariable
+            | DeclararConstante
+            | DeclararFuncion
+            | Asy
+            | DeclararClase
+            | DeclararInterfaz
+            | DeclararEnum
+            | Equip
+            | Out
+            | If
+            | Switch
+            | For
+            | ForEach
+            | While
+            | DoWhile
+            | Try
+            | TPrint
+            | Return
+            | Throw
+    )
 }
 
 impl Parser {
@@ -32,6 +72,10 @@ impl Parser {
             posiciones: Vec::new(),
             posicion: 0,
             codigo_fuente: String::new(),
+            errores: Vec::new(),
+            inicios_sentencias: Vec::new(),
+            lineas_sentencias: Vec::new(),
+            inicios_lineas: None,
         }
     }
 
@@ -41,6 +85,10 @@ impl Parser {
             posiciones: Vec::new(),
             posicion: 0,
             codigo_fuente,
+            errores: Vec::new(),
+            inicios_sentencias: Vec::new(),
+            lineas_sentencias: Vec::new(),
+            inicios_lineas: None,
         }
     }
 
@@ -55,6 +103,10 @@ impl Parser {
             posiciones,
             posicion: 0,
             codigo_fuente,
+            errores: Vec::new(),
+            inicios_sentencias: Vec::new(),
+            lineas_sentencias: Vec::new(),
+            inicios_lineas: None,
         }
     }
 
@@ -100,12 +152,98 @@ impl Parser {
         }
     }
 
-    pub fn parsear_programa(&mut self) -> Result<Programa, ParseError> {
+    /// Parsea el programa completo y devuelve todos los errores de sintaxis,
+    /// no solo el primero.
+    pub fn parsear_programa(&mut self) -> Result<Programa, Vec<ParseError>> {
+        let (programa, errores) = self.parsear_programa_parcial();
+        match errores.is_empty() {
+            true => Ok(programa),
+            false => Err(errores),
+        }
+    }
+
+    /// Envuelve cada sentencia de los bloques en `Sentencia::Ubicada` con su
+    /// línea y el identificador `archivo`, para las herramientas que siguen
+    /// la ejecución línea a línea. Requiere un parser creado con
+    /// `nuevo_con_posiciones`.
+    pub fn anotar_lineas(&mut self, archivo: usize) {
+        let mut inicios = vec![0];
+        for (indice, caracter) in self.codigo_fuente.chars().enumerate() {
+            if caracter == '\n' {
+                inicios.push(indice + 1);
+            }
+        }
+        self.inicios_lineas = Some((archivo, inicios));
+    }
+
+    /// Parsea el programa aunque tenga errores: las sentencias inválidas se
+    /// descartan y el resto del árbol se conserva junto con los errores.
+    pub fn parsear_programa_parcial(&mut self) -> (Programa, Vec<ParseError>) {
         let mut sentencias = Vec::new();
         while !self.esta_fin() {
-            sentencias.push(self.parsear_sentencia()?);
+            sentencias.extend(self.parsear_sentencia_recuperando());
+        }
+        let errores = std::mem::take(&mut self.errores);
+        (Programa { sentencias }, errores)
+    }
+
+    /// Parsea una sentencia; si falla, guarda el error y salta hasta el
+    /// inicio de la siguiente para seguir analizando.
+    fn parsear_sentencia_recuperando(&mut self) -> Option<Sentencia> {
+        let inicio = self.posicion;
+        match self.parsear_sentencia() {
+            Ok(sentencia) => Some(self.ubicar(sentencia, inicio)),
+            Err(error) => {
+                // Un bloque sin cerrar se informa una sola vez aunque afecte
+                // a todos los bloques que lo contienen.
+                let repetido = self.errores.last().is_some_and(|ultimo| {
+                    ultimo.posicion == error.posicion && ultimo.mensaje == error.mensaje
+                });
+                if !repetido {
+                    self.errores.push(error);
+                }
+                self.sincronizar(inicio);
+                None
+            }
+        }
+    }
+
+    fn ubicar(&mut self, sentencia: Sentencia, token: usize) -> Sentencia {
+        let (Some((archivo, inicios)), Some(&caracter)) =
+            (&self.inicios_lineas, self.posiciones.get(token))
+        else {
+            return sentencia;
+        };
+        let ubicacion = Ubicacion {
+            archivo: *archivo,
+            linea: inicios.partition_point(|&inicio| inicio <= caracter),
+        };
+        self.lineas_sentencias.push(ubicacion.linea);
+        Sentencia::Ubicada(ubicacion, Box::new(sentencia))
+    }
+
+    // Modo pánico: descarta tokens hasta pasar un `;`, o hasta antes de una
+    // palabra clave de sentencia o de la `}` que cierra el bloque actual.
+    fn sincronizar(&mut self, inicio: usize) {
+        if self.posicion == inicio {
+            self.avanzar();
+        }
+
+        let mut nivel = 0usize;
+        while let Some(token) = self.peekear() {
+            match token {
+                LexToken::PuntoYComa if nivel == 0 => {
+                    self.avanzar();
+                    return;
+                }
+                LexToken::LlaveDer if nivel == 0 => return,
+                LexToken::LlaveIzq | LexToken::ParentesisIzq => nivel += 1,
+                LexToken::LlaveDer | LexToken::ParentesisDer => nivel = nivel.saturating_sub(1),
+                token if nivel == 0 && es_inicio_sentencia(token) => return,
+                _ => {}
+            }
+            self.avanzar();
         }
-        Ok(Programa { sentencias })
     }
 
     fn esta_fin(&self) -> bool {
@@ -140,8 +278,24 @@ impl Parser {
     }
 
     fn parsear_sentencia(&mut self) -> Result<Sentencia, ParseError> {
+        let registradas = self.inicios_sentencias.len();
+        self.inicios_sentencias.push(self.posicion);
+        let resultado = self.parsear_sentencia_sin_registrar();
+        if resultado.is_err() {
+            self.inicios_sentencias.truncate(registradas);
+        }
+        resultado
+    }
+
+    fn parsear_sentencia_sin_registrar(&mut self) -> Result<Sentencia, ParseError> {
         let exportado = self.coincidir(|t| matches!(t, LexToken::Out));
 
+        if exportado {
+            if let Some(res) = exportaciones::intentar_parsear_exportacion(self) {
+                return res;
+            }
+        }
+
         if let Some(res) = self.intentar_parsear_declaraciones(exportado) {
             return res;
         }
@@ -309,7 +463,7 @@ impl Parser {
                 Ok(Some(Tipo { nombre }))
             }
             Some(LexToken::Identificador(n))
-                if n.chars().next().unwrap_or('a').is_ascii_uppercase() =>
+                if n.chars().next().unwrap_or('a').is_uppercase() =>
             {
                 let nombre = format!("{}{}", prefijo, n);
                 self.avanzar();
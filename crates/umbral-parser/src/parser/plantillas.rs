@@ -0,0 +1,160 @@
+use crate::ast::*;
+use crate::error::ParseError;
+use crate::parser::expresiones::parsear_expresion_principal;
+use crate::parser::Parser;
+use std::iter::Peekable;
+use std::str::Chars;
+use umbral_lexer::{es_caracter_identificador, es_inicio_identificador};
+
+/// Separa una cadena con comillas dobles en texto literal y expresiones.
+/// `&nombre`, `&obj.prop`, `&lista[0]` y `&obj.metodo(args)` interpolan una
+/// cadena de accesos; `&{ expr }` admite cualquier expresión y `\&` escribe
+/// un `&` literal. Sin interpolaciones devuelve un `LiteralCadena`.
+pub fn parsear_plantilla(parseador: &Parser, texto: &str) -> Result<Expresion, ParseError> {
+    let mut partes = Vec::new();
+    let mut literal = String::new();
+    let mut caracteres = texto.chars().peekable();
+
+    while let Some(caracter) = caracteres.next() {
+        if caracter == '\\' && caracteres.peek() == Some(&'&') {
+            caracteres.next();
+            literal.push('&');
+            continue;
+        }
+
+        if caracter != '&' {
+            literal.push(caracter);
+            continue;
+        }
+
+        let fuente = match caracteres.peek() {
+            Some('{') => {
+                caracteres.next();
+                leer_bloque(&mut caracteres).ok_or_else(|| {
+                    parseador.crear_error("Interpolación '&{' sin cerrar en la cadena")
+                })?
+            }
+            Some(&c) if es_inicio_identificador(c) => leer_encadenamiento(&mut caracteres),
+            _ => {
+                literal.push('&');
+                continue;
+            }
+        };
+
+        if !literal.is_empty() {
+            partes.push(ParteCadena::Texto(std::mem::take(&mut literal)));
+        }
+        partes.push(ParteCadena::Expresion(parsear_interpolacion(
+            parseador, &fuente,
+        )?));
+    }
+
+    if partes.is_empty() {
+        return Ok(Expresion::LiteralCadena(literal));
+    }
+
+    if !literal.is_empty() {
+        partes.push(ParteCadena::Texto(literal));
+    }
+    Ok(Expresion::Plantilla(partes))
+}
+
+fn parsear_interpolacion(parseador: &Parser, fuente: &str) -> Result<Expresion, ParseError> {
+    let error = |detalle: &str| {
+        parseador.crear_error(format!(
+            "Interpolación inválida '{}': {}",
+            fuente.trim(),
+            detalle
+        ))
+    };
+
+    if fuente.trim().is_empty() {
+        return Err(error("la expresión está vacía"));
+    }
+
+    let tokens = umbral_lexer::tokenizar(fuente).map_err(|errores| error(&errores[0].mensaje()))?;
+    let mut interno = Parser::nuevo(tokens.into_iter().map(|t| t.token).collect());
+    let expresion = parsear_expresion_principal(&mut interno).map_err(|e| error(&e.mensaje))?;
+
+    if !interno.esta_fin() {
+        return Err(error("sobran símbolos después de la expresión"));
+    }
+    Ok(expresion)
+}
+
+// `&nombre` seguido de `.prop`, `[indice]` o `(args)`. Un punto solo continúa
+// la cadena si le sigue un identificador: "Hola &nombre." termina en `nombre`.
+fn leer_encadenamiento(caracteres: &mut Peekable<Chars>) -> String {
+    let mut fuente = String::new();
+
+    loop {
+        while let Some(&c) = caracteres.peek() {
+            if !es_caracter_identificador(c) {
+                break;
+            }
+            fuente.push(c);
+            caracteres.next();
+        }
+
+        while let Some(&abre @ ('(' | '[')) = caracteres.peek() {
+            let cierra = if abre == '(' { ')' } else { ']' };
+            leer_agrupado(caracteres, &mut fuente, abre, cierra);
+        }
+
+        if !sigue_acceso_propiedad(caracteres) {
+            return fuente;
+        }
+        fuente.push('.');
+        caracteres.next();
+    }
+}
+
+fn sigue_acceso_propiedad(caracteres: &Peekable<Chars>) -> bool {
+    let mut adelante = caracteres.clone();
+    adelante.next() == Some('.') && adelante.peek().is_some_and(|c| es_inicio_identificador(*c))
+}
+
+// Copia un grupo equilibrado `(...)` o `[...]`, ignorando los delimitadores
+// dentro de cadenas.
+fn leer_agrupado(caracteres: &mut Peekable<Chars>, fuente: &mut String, abre: char, cierra: char) {
+    let mut nivel = 0;
+    let mut comilla: Option<char> = None;
+
+    for c in caracteres.by_ref() {
+        fuente.push(c);
+        match comilla {
+            Some(q) if c == q => comilla = None,
+            Some(_) => {}
+            None if c == '"' || c == '\'' => comilla = Some(c),
+            None if c == abre => nivel += 1,
+            None if c == cierra => {
+                nivel -= 1;
+                if nivel == 0 {
+                    return;
+                }
+            }
+            None => {}
+        }
+    }
+}
+
+// Contenido de `&{ ... }` sin las llaves exteriores; `None` si no se cierra.
+fn leer_bloque(caracteres: &mut Peekable<Chars>) -> Option<String> {
+    let mut fuente = String::new();
+    let mut nivel = 0;
+    let mut comilla: Option<char> = None;
+
+    for c in caracteres.by_ref() {
+        match comilla {
+            Some(q) if c == q => comilla = None,
+            Some(_) => {}
+            None if c == '"' || c == '\'' => comilla = Some(c),
+            None if c == '{' => nivel += 1,
+            None if c == '}' && nivel == 0 => return Some(fuente),
+            None if c == '}' => nivel -= 1,
+            None => {}
+        }
+        fuente.push(c);
+    }
+    None
+}
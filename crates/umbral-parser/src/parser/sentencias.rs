@@ -5,7 +5,7 @@ use umbral_lexer::Token as LexToken;
 
 fn validar_parentesis_apertura(parseador: &mut Parser, contexto: &str) -> Result<(), ParseError> {
     if !parseador.coincidir(|t| matches!(t, LexToken::ParentesisIzq)) {
-        return Err(parseador.crear_error(&format!("Se esperaba '(' despues de {}", contexto)));
+        return Err(parseador.crear_error(format!("Se esperaba '(' despues de {}", contexto)));
     }
     Ok(())
 }
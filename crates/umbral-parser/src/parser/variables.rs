@@ -5,7 +5,7 @@ use umbral_lexer::Token as LexToken;
 
 fn parsear_tipo_opcional(parseador: &mut Parser) -> Result<Option<Tipo>, ParseError> {
     if parseador.coincidir(|t| matches!(t, LexToken::OperadorTipo)) {
-        return Ok(parseador.parsear_tipo()?);
+        return parseador.parsear_tipo();
     }
     Ok(None)
 }
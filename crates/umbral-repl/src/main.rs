@@ -1,6 +1,6 @@
 use rustyline::error::ReadlineError;
 use rustyline::DefaultEditor;
-use umbral_interpreter::Interpreter;
+use umbral_interpreter::{Interpreter, InterpreterError};
 
 const VERSION: &str = env!("CARGO_PKG_VERSION");
 const PROMPT: &str = "umbral> ";
@@ -240,6 +240,7 @@ fn cuenta_balanceada(codigo: &str) -> bool {
 async fn ejecutar_codigo(interprete: &mut Interpreter, codigo: &str) {
     match interprete.ejecutar(codigo).await {
         Ok(()) => {}
+        Err(InterpreterError::Salida(codigo)) => std::process::exit(codigo),
         Err(e) => eprintln!("✗ {}", e),
     }
 }
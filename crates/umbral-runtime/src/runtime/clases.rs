@@ -1,4 +1,5 @@
 use crate::runtime::valores::{Instancia, Valor};
+use indexmap::IndexMap;
 use std::collections::HashMap;
 use std::sync::{Arc, Mutex};
 use umbral_parser::ast::{DeclaracionClase, Metodo};
@@ -6,7 +7,7 @@ use umbral_parser::ast::{DeclaracionClase, Metodo};
 #[derive(Debug, Clone)]
 pub struct Clase {
     pub nombre: String,
-    pub propiedades: HashMap<String, Valor>,
+    pub propiedades: IndexMap<String, Valor>,
     pub metodos: HashMap<String, Metodo>,
     pub constructor: Option<Metodo>,
 }
@@ -15,7 +16,7 @@ impl Clase {
     pub fn nueva(nombre: &str) -> Self {
         Self {
             nombre: nombre.to_string(),
-            propiedades: HashMap::new(),
+            propiedades: IndexMap::new(),
             metodos: HashMap::new(),
             constructor: None,
         }
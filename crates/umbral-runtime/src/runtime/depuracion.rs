@@ -0,0 +1,427 @@
+//! Depurador paso a paso.
+//!
+//! El parser anota las sentencias con su línea (`Sentencia::Ubicada`) y el
+//! intérprete consulta a `Depuracion` antes de ejecutar cada una. Cuando
+//! debe detenerse avisa al `Cliente` y atiende sus órdenes (pila, ámbitos,
+//! variables, evaluar) hasta que le pide reanudar.
+//!
+//! Solo se depura el archivo principal: los módulos importados no se
+//! anotan y sus funciones se ejecutan sin detenerse.
+
+use crate::runtime::describir_excepcion;
+use crate::runtime::interpretador::Interpretador;
+use crate::runtime::stdlib;
+use crate::runtime::valores::Valor;
+use std::collections::HashSet;
+use std::sync::{Arc, Mutex};
+use tokio::sync::mpsc::{self, UnboundedReceiver, UnboundedSender};
+use tokio::sync::oneshot;
+use umbral_parser::ast::Sentencia;
+use umbral_parser::Parser;
+
+pub const MARCO_PRINCIPAL: &str = "<principal>";
+
+/// Cómo seguir después de una detención.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Paso {
+    /// Hasta el siguiente punto de ruptura.
+    Continuar,
+    /// Hasta la siguiente sentencia, aunque esté dentro de una llamada.
+    Entrar,
+    /// Hasta la siguiente sentencia de esta función o de quien la llamó.
+    Saltar,
+    /// Hasta volver a quien llamó a esta función.
+    Salir,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Motivo {
+    Entrada,
+    PuntoDeRuptura,
+    Paso,
+    Pausa,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Detencion {
+    pub motivo: Motivo,
+    pub linea: usize,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Marco {
+    pub nombre: String,
+    /// Línea de la última sentencia que empezó en este marco.
+    pub linea: usize,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Ambito {
+    pub nombre: String,
+    pub referencia: usize,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct VariableDepuracion {
+    pub nombre: String,
+    pub valor: String,
+    pub tipo: String,
+    /// Mayor que cero si el valor tiene elementos que se pueden pedir con
+    /// `Cliente::variables`.
+    pub referencia: usize,
+}
+
+enum Orden {
+    Pila(oneshot::Sender<Vec<Marco>>),
+    Ambitos(oneshot::Sender<Vec<Ambito>>),
+    Variables(usize, oneshot::Sender<Vec<VariableDepuracion>>),
+    Evaluar(String, oneshot::Sender<Result<VariableDepuracion, String>>),
+    Reanudar(Paso),
+}
+
+struct Estado {
+    puntos: HashSet<usize>,
+    paso: Paso,
+    // Profundidad de la pila al reanudar con `paso`.
+    origen: usize,
+    pausa: bool,
+    al_entrar: bool,
+    // Mientras se evalúa una expresión no hay detenciones.
+    evaluando: bool,
+}
+
+impl Estado {
+    fn motivo(&mut self, linea: usize, profundidad: usize) -> Option<Motivo> {
+        if self.evaluando {
+            return None;
+        }
+        if std::mem::take(&mut self.pausa) {
+            return Some(Motivo::Pausa);
+        }
+
+        let por_paso = match self.paso {
+            Paso::Continuar => false,
+            Paso::Entrar => true,
+            Paso::Saltar => profundidad <= self.origen,
+            Paso::Salir => profundidad < self.origen,
+        };
+        if por_paso {
+            return match std::mem::take(&mut self.al_entrar) {
+                true => Some(Motivo::Entrada),
+                false => Some(Motivo::Paso),
+            };
+        }
+        self.puntos
+            .contains(&linea)
+            .then_some(Motivo::PuntoDeRuptura)
+    }
+}
+
+/// Lado del intérprete. Se clona junto a él en las tareas asíncronas.
+#[derive(Clone)]
+pub struct Depuracion {
+    estado: Arc<Mutex<Estado>>,
+    detenciones: UnboundedSender<Detencion>,
+    ordenes: Arc<tokio::sync::Mutex<UnboundedReceiver<Orden>>>,
+}
+
+/// Lado de quien controla la ejecución. Las consultas solo se responden
+/// mientras el programa está detenido.
+#[derive(Clone)]
+pub struct Cliente {
+    estado: Arc<Mutex<Estado>>,
+    ordenes: UnboundedSender<Orden>,
+}
+
+/// Crea un depurador. Con `detener_al_entrar` el programa se detiene antes
+/// de su primera sentencia. Las detenciones llegan por el receptor devuelto.
+pub fn crear(detener_al_entrar: bool) -> (Depuracion, Cliente, UnboundedReceiver<Detencion>) {
+    let estado = Arc::new(Mutex::new(Estado {
+        puntos: HashSet::new(),
+        paso: match detener_al_entrar {
+            true => Paso::Entrar,
+            false => Paso::Continuar,
+        },
+        origen: 0,
+        pausa: false,
+        al_entrar: detener_al_entrar,
+        evaluando: false,
+    }));
+    let (enviar_detencion, detenciones) = mpsc::unbounded_channel();
+    let (enviar_orden, ordenes) = mpsc::unbounded_channel();
+
+    let depuracion = Depuracion {
+        estado: estado.clone(),
+        detenciones: enviar_detencion,
+        ordenes: Arc::new(tokio::sync::Mutex::new(ordenes)),
+    };
+    let cliente = Cliente {
+        estado,
+        ordenes: enviar_orden,
+    };
+    (depuracion, cliente, detenciones)
+}
+
+impl Cliente {
+    /// Reemplaza los puntos de ruptura por los de `lineas`.
+    pub fn establecer_puntos(&self, lineas: impl IntoIterator<Item = usize>) {
+        self.estado.lock().unwrap().puntos = lineas.into_iter().collect();
+    }
+
+    /// Detiene el programa antes de su siguiente sentencia.
+    pub fn pausar(&self) {
+        self.estado.lock().unwrap().pausa = true;
+    }
+
+    pub fn reanudar(&self, paso: Paso) {
+        let _ = self.ordenes.send(Orden::Reanudar(paso));
+    }
+
+    /// Marcos de la pila, empezando por el más interno.
+    pub async fn pila(&self) -> Vec<Marco> {
+        self.consultar(Orden::Pila).await.unwrap_or_default()
+    }
+
+    /// Ámbitos del marco actual, del más interno a los globales.
+    pub async fn ambitos(&self) -> Vec<Ambito> {
+        self.consultar(Orden::Ambitos).await.unwrap_or_default()
+    }
+
+    pub async fn variables(&self, referencia: usize) -> Vec<VariableDepuracion> {
+        self.consultar(|respuesta| Orden::Variables(referencia, respuesta))
+            .await
+            .unwrap_or_default()
+    }
+
+    /// Evalúa código en el marco actual; puede modificar variables.
+    pub async fn evaluar(&self, codigo: &str) -> Result<VariableDepuracion, String> {
+        self.consultar(|respuesta| Orden::Evaluar(codigo.to_string(), respuesta))
+            .await
+            .unwrap_or_else(|| Err("el programa no está detenido".to_string()))
+    }
+
+    async fn consultar<T>(&self, orden: impl FnOnce(oneshot::Sender<T>) -> Orden) -> Option<T> {
+        let (respuesta, recibir) = oneshot::channel();
+        self.ordenes.send(orden(respuesta)).ok()?;
+        recibir.await.ok()
+    }
+}
+
+enum Referencia {
+    // Nivel en la cadena de entornos, empezando por el actual.
+    Entorno(usize),
+    Valor(Valor),
+}
+
+impl Interpretador {
+    /// Activa la depuración. El programa debe parsearse con las líneas
+    /// anotadas para que haya dónde detenerse.
+    pub fn activar_depuracion(&mut self, depuracion: Depuracion) {
+        self.depuracion = Some(depuracion);
+        self.pila_llamadas = vec![Marco {
+            nombre: MARCO_PRINCIPAL.to_string(),
+            linea: 0,
+        }];
+    }
+
+    pub(crate) async fn antes_de_sentencia(&mut self, linea: usize) {
+        let Some(depuracion) = self.depuracion.clone() else {
+            return;
+        };
+        if let Some(marco) = self.pila_llamadas.last_mut() {
+            marco.linea = linea;
+        }
+
+        let profundidad = self.pila_llamadas.len();
+        let Some(motivo) = depuracion.estado.lock().unwrap().motivo(linea, profundidad) else {
+            return;
+        };
+        if depuracion
+            .detenciones
+            .send(Detencion { motivo, linea })
+            .is_err()
+        {
+            self.depuracion = None;
+            return;
+        }
+
+        let mut ordenes = depuracion.ordenes.lock().await;
+        let mut referencias = Vec::new();
+        while let Some(orden) = ordenes.recv().await {
+            match orden {
+                Orden::Reanudar(paso) => {
+                    let mut estado = depuracion.estado.lock().unwrap();
+                    estado.paso = paso;
+                    estado.origen = profundidad;
+                    return;
+                }
+                Orden::Pila(respuesta) => {
+                    let _ = respuesta.send(self.pila_llamadas.iter().rev().cloned().collect());
+                }
+                Orden::Ambitos(respuesta) => {
+                    let _ = respuesta.send(self.ambitos(&mut referencias));
+                }
+                Orden::Variables(referencia, respuesta) => {
+                    let _ = respuesta.send(self.variables(referencia, &mut referencias));
+                }
+                Orden::Evaluar(codigo, respuesta) => {
+                    depuracion.estado.lock().unwrap().evaluando = true;
+                    let resultado = self.evaluar_en_pausa(&codigo).await;
+                    depuracion.estado.lock().unwrap().evaluando = false;
+                    let _ = respuesta.send(
+                        resultado.map(|valor| variable(codigo.trim(), valor, &mut referencias)),
+                    );
+                }
+            }
+        }
+
+        // El cliente se fue: el programa sigue sin volver a detenerse.
+        self.depuracion = None;
+    }
+
+    fn ambitos(&self, referencias: &mut Vec<Referencia>) -> Vec<Ambito> {
+        let mut niveles = 1;
+        let mut entorno = &self.entorno_actual;
+        while let Some(padre) = &entorno.parent {
+            niveles += 1;
+            entorno = padre;
+        }
+
+        (0..niveles)
+            .map(|nivel| {
+                let nombre = match nivel {
+                    _ if nivel + 1 == niveles => "Globales".to_string(),
+                    0 => "Locales".to_string(),
+                    _ => format!("Ámbito {}", nivel),
+                };
+                referencias.push(Referencia::Entorno(nivel));
+                Ambito {
+                    nombre,
+                    referencia: referencias.len(),
+                }
+            })
+            .collect()
+    }
+
+    fn variables(
+        &self,
+        referencia: usize,
+        referencias: &mut Vec<Referencia>,
+    ) -> Vec<VariableDepuracion> {
+        let hijos: Vec<(String, Valor)> =
+            match referencia.checked_sub(1).and_then(|i| referencias.get(i)) {
+                Some(Referencia::Entorno(nivel)) => {
+                    let mut entorno = Some(&self.entorno_actual);
+                    for _ in 0..*nivel {
+                        entorno = entorno.and_then(|entorno| entorno.parent.as_deref());
+                    }
+                    let Some(entorno) = entorno else {
+                        return Vec::new();
+                    };
+                    let mut hijos: Vec<(String, Valor)> = entorno
+                        .variables
+                        .iter()
+                        .chain(&entorno.constantes)
+                        .filter(|(_, valor)| !matches!(valor, Valor::FuncionNativa(..)))
+                        .map(|(nombre, valor)| {
+                            // La instancia de un método se escribe `th`.
+                            let nombre = match nombre.as_str() {
+                                "__this__" => "th",
+                                otro => otro,
+                            };
+                            (nombre.to_string(), valor.clone())
+                        })
+                        .collect();
+                    hijos.sort_by(|a, b| a.0.cmp(&b.0));
+                    hijos
+                }
+                Some(Referencia::Valor(Valor::Lista(elementos))) => elementos
+                    .iter()
+                    .enumerate()
+                    .map(|(indice, valor)| (format!("[{}]", indice), valor.clone()))
+                    .collect(),
+                Some(Referencia::Valor(Valor::Diccionario(mapa))) => mapa
+                    .iter()
+                    .map(|(clave, valor)| (clave.clone(), valor.clone()))
+                    .collect(),
+                Some(Referencia::Valor(Valor::Objeto(instancia))) => instancia
+                    .propiedades
+                    .lock()
+                    .unwrap()
+                    .iter()
+                    .map(|(nombre, valor)| (nombre.clone(), valor.clone()))
+                    .collect(),
+                _ => Vec::new(),
+            };
+
+        hijos
+            .into_iter()
+            .map(|(nombre, valor)| variable(&nombre, valor, referencias))
+            .collect()
+    }
+
+    async fn evaluar_en_pausa(&mut self, codigo: &str) -> Result<Valor, String> {
+        let codigo = format!("{};", codigo.trim().trim_end_matches(';'));
+        let tokens = umbral_lexer::tokenizar(&codigo).map_err(|errores| {
+            let mensajes: Vec<String> = errores.iter().map(|e| e.mensaje()).collect();
+            mensajes.join("\n")
+        })?;
+        let mut parser = Parser::nuevo_con_posiciones(tokens, codigo.clone());
+        let programa = parser.parsear_programa().map_err(|errores| {
+            let mensajes: Vec<String> = errores.iter().map(|e| e.formatear_error()).collect();
+            mensajes.join("\n")
+        })?;
+
+        let retorno = self.valor_retorno.take();
+        let mut resultado = Valor::Nulo;
+        for sentencia in programa.sentencias {
+            resultado = match sentencia {
+                Sentencia::Expresion(expresion) => self.evaluar_expresion(expresion).await,
+                otra => {
+                    self.ejecutar_sentencia(otra).await;
+                    Valor::Nulo
+                }
+            };
+            if self.estado_excepcion.is_some() {
+                break;
+            }
+        }
+        self.valor_retorno = retorno;
+
+        match self.estado_excepcion.take() {
+            Some(excepcion) => Err(describir_excepcion(&excepcion)),
+            None => Ok(resultado),
+        }
+    }
+}
+
+fn variable(nombre: &str, valor: Valor, referencias: &mut Vec<Referencia>) -> VariableDepuracion {
+    let tipo = match &valor {
+        Valor::Objeto(instancia) => instancia.clase.clone(),
+        Valor::Funcion(_) | Valor::FuncionNativa(..) => "Function".to_string(),
+        Valor::Nulo => "Null".to_string(),
+        otro => stdlib::nombre_tipo(otro).unwrap_or("Object").to_string(),
+    };
+    let (texto, tiene_hijos) = match &valor {
+        Valor::Texto(texto) => (format!("{:?}", texto), false),
+        Valor::Lista(elementos) => (format!("List({})", elementos.len()), !elementos.is_empty()),
+        Valor::Diccionario(mapa) => (format!("Dict({})", mapa.len()), !mapa.is_empty()),
+        Valor::Objeto(instancia) => (instancia.to_string(), true),
+        Valor::Funcion(funcion) => (format!("f: {}", funcion.nombre), false),
+        otro => (otro.to_string(), false),
+    };
+
+    let referencia = match tiene_hijos {
+        true => {
+            referencias.push(Referencia::Valor(valor));
+            referencias.len()
+        }
+        false => 0,
+    };
+    VariableDepuracion {
+        nombre: nombre.to_string(),
+        valor: texto,
+        tipo,
+        referencia,
+    }
+}
@@ -1,11 +1,19 @@
 use crate::runtime::valores::Valor;
 use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+
+/// Entorno global de un módulo, compartido por todas las llamadas a sus
+/// funciones para que conserven el estado del módulo entre llamadas.
+pub type EntornoCompartido = Arc<Mutex<Entorno>>;
 
 #[derive(Debug, Clone)]
 pub struct Entorno {
     pub variables: HashMap<String, Valor>,
     pub constantes: HashMap<String, Valor>,
     pub parent: Option<Box<Entorno>>,
+    /// Entorno del módulo donde se declaró la función en ejecución; se
+    /// consulta después de `parent`.
+    pub modulo: Option<EntornoCompartido>,
 }
 
 impl Entorno {
@@ -14,6 +22,16 @@ impl Entorno {
             variables: HashMap::new(),
             constantes: HashMap::new(),
             parent: parent.map(Box::new),
+            modulo: None,
+        }
+    }
+
+    /// Entorno de una llamada a una función de otro módulo: lo que no sea
+    /// local se busca en el entorno de ese módulo.
+    pub fn de_modulo(modulo: EntornoCompartido) -> Self {
+        Self {
+            modulo: Some(modulo),
+            ..Self::nuevo(None)
         }
     }
 
@@ -31,9 +49,13 @@ impl Entorno {
             return true;
         }
 
-        self.parent
-            .as_mut()
-            .map_or(false, |parent| parent.asignar(nombre, valor))
+        match &mut self.parent {
+            Some(parent) => parent.asignar(nombre, valor),
+            None => self
+                .modulo
+                .as_ref()
+                .is_some_and(|modulo| modulo.lock().unwrap().asignar(nombre, valor)),
+        }
     }
 
     pub fn obtener(&self, nombre: &str) -> Option<Valor> {
@@ -42,11 +64,20 @@ impl Entorno {
             .or_else(|| self.constantes.get(nombre))
             .cloned()
             .or_else(|| self.parent.as_ref().and_then(|p| p.obtener(nombre)))
+            .or_else(|| {
+                self.modulo
+                    .as_ref()
+                    .and_then(|modulo| modulo.lock().unwrap().obtener(nombre))
+            })
     }
     
     pub fn existe(&self, nombre: &str) -> bool {
         self.variables.contains_key(nombre) 
             || self.constantes.contains_key(nombre)
-            || self.parent.as_ref().map_or(false, |p| p.existe(nombre))
+            || self.parent.as_ref().is_some_and(|p| p.existe(nombre))
+            || self
+                .modulo
+                .as_ref()
+                .is_some_and(|modulo| modulo.lock().unwrap().existe(nombre))
     }
 }
@@ -0,0 +1,52 @@
+use crate::runtime::clases::Clase;
+use crate::runtime::enums::Enum;
+use crate::runtime::interfaces::Interfaz;
+use crate::runtime::interpretador::Interpretador;
+use crate::runtime::valores::Valor;
+use indexmap::IndexMap;
+
+pub const NOMBRE_POR_DEFECTO: &str = "def";
+
+#[derive(Debug, Clone, Default)]
+pub struct ElementoExportado {
+    pub valor: Option<Valor>,
+    pub clase: Option<Clase>,
+    pub interfaz: Option<Interfaz>,
+    pub enumeracion: Option<Enum>,
+}
+
+impl ElementoExportado {
+    pub fn desde_modulo(modulo: &Interpretador, nombre_local: &str) -> Option<Self> {
+        let elemento = Self {
+            valor: modulo.entorno_actual.obtener(nombre_local),
+            clase: modulo.gestor_clases.obtener_clase(nombre_local).cloned(),
+            interfaz: modulo.gestor_interfaces.obtener(nombre_local).cloned(),
+            enumeracion: modulo.gestor_enums.obtener(nombre_local).cloned(),
+        };
+
+        let vacio = elemento.valor.is_none()
+            && elemento.clase.is_none()
+            && elemento.interfaz.is_none()
+            && elemento.enumeracion.is_none();
+
+        (!vacio).then_some(elemento)
+    }
+}
+
+pub fn recolectar(modulo: &Interpretador) -> IndexMap<String, ElementoExportado> {
+    let mut exportados = modulo.reexportaciones.clone();
+
+    for (nombre_publico, nombre_local) in &modulo.exportaciones {
+        match ElementoExportado::desde_modulo(modulo, nombre_local) {
+            Some(elemento) => {
+                exportados.insert(nombre_publico.clone(), elemento);
+            }
+            None => eprintln!(
+                "Advertencia: '{}' se exporta pero no está definido en el módulo",
+                nombre_local
+            ),
+        }
+    }
+
+    exportados
+}
@@ -14,8 +14,13 @@ impl GestorFunciones {
         argumentos: Vec<Valor>,
         interprete: &mut crate::runtime::interpretador::Interpretador,
     ) -> Valor {
+        interprete.entrar_marco(|| funcion.nombre.clone());
         let anterior = std::mem::replace(&mut interprete.entorno_actual, Entorno::nuevo(None));
-        interprete.entorno_actual = Entorno::nuevo(Some(anterior));
+        let (entorno, entorno_llamador) = match &funcion.contexto {
+            Some(contexto) => (Entorno::de_modulo(contexto.clone()), Some(anterior)),
+            None => (Entorno::nuevo(Some(anterior)), None),
+        };
+        interprete.entorno_actual = entorno;
 
         for (i, param) in funcion.parametros.iter().enumerate() {
             let valor = argumentos.get(i).cloned().unwrap_or(Valor::Nulo);
@@ -30,11 +35,20 @@ impl GestorFunciones {
                 resultado = valor;
                 break;
             }
+            if interprete.estado_excepcion.is_some() {
+                break;
+            }
         }
 
-        if let Some(parent) = interprete.entorno_actual.parent.take() {
-            interprete.entorno_actual = *parent;
+        match entorno_llamador {
+            Some(entorno) => interprete.entorno_actual = entorno,
+            None => {
+                if let Some(parent) = interprete.entorno_actual.parent.take() {
+                    interprete.entorno_actual = *parent;
+                }
+            }
         }
+        interprete.salir_marco();
 
         resultado
     }
@@ -1,15 +1,36 @@
 use crate::runtime::clases::{Clase, GestorClases};
+use crate::runtime::depuracion::{Depuracion, Marco};
 use crate::runtime::entorno::Entorno;
 use crate::runtime::enums::GestorEnums;
+use crate::runtime::exportaciones::{self, ElementoExportado};
 use crate::runtime::funciones::GestorFunciones;
 use crate::runtime::interfaces::{GestorInterfaces, Interfaz};
-use crate::runtime::valores::{Funcion, SharedPromesa, Valor};
+use crate::runtime::modulos::{self, ErrorResolucion};
+use crate::runtime::paquetes;
+use crate::runtime::perfil::Perfil;
+use crate::runtime::stdlib::{self, NativaDelInterprete};
+use crate::runtime::stdlib::sesion::{self, Sesion};
+use crate::runtime::stdlib::time::{self, FechaHora};
+use crate::runtime::valores::{Funcion, NativeFn, SharedPromesa, Valor};
 use async_recursion::async_recursion;
+use chrono::TimeDelta;
+use indexmap::IndexMap;
+use rust_decimal::Decimal;
 use std::collections::HashMap;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 use std::sync::{Arc, Mutex};
+use std::time::Instant;
+use tokio::sync::mpsc::UnboundedSender;
 use umbral_parser::ast::*;
 
+/// Destino de `tprint` cuando no es la salida estándar.
+#[derive(Clone)]
+pub enum Salida {
+    Bufer(Arc<Mutex<String>>),
+    /// Cada línea se envía por el canal, sin el salto final.
+    Canal(UnboundedSender<String>),
+}
+
 #[derive(Clone)]
 pub struct Interpretador {
     pub entorno_actual: Entorno,
@@ -19,8 +40,22 @@ pub struct Interpretador {
     pub gestor_enums: GestorEnums,
     pub valor_retorno: Option<Valor>,
     pub estado_excepcion: Option<Valor>,
-    pub exportaciones: HashMap<String, bool>,
+    pub exportaciones: IndexMap<String, String>,
+    pub reexportaciones: IndexMap<String, ElementoExportado>,
     pub directorio_base: PathBuf,
+    pub archivo_actual: Option<PathBuf>,
+    // Métodos añadidos desde Umbral con `Std.extend`, por tipo.
+    pub metodos_tipo: HashMap<String, HashMap<String, Valor>>,
+    // Si está presente, `tprint` escribe aquí en lugar de en la salida
+    // estándar. Lo comparten las tareas asíncronas que se lanzan.
+    pub salida: Option<Salida>,
+    pub depuracion: Option<Depuracion>,
+    // Marcos de las llamadas en curso; solo se mantienen al depurar.
+    pub pila_llamadas: Vec<Marco>,
+    pub perfil: Option<Perfil>,
+    // Estado de la biblioteca estándar de este intérprete (archivos
+    // abiertos...). Los módulos que importa usan la misma sesión.
+    pub sesion: Arc<Sesion>,
 }
 
 impl Interpretador {
@@ -33,11 +68,20 @@ impl Interpretador {
             gestor_enums: GestorEnums::nuevo(),
             valor_retorno: None,
             estado_excepcion: None,
-            exportaciones: HashMap::new(),
+            exportaciones: IndexMap::new(),
+            reexportaciones: IndexMap::new(),
             directorio_base: std::env::current_dir().unwrap_or_else(|_| PathBuf::from(".")),
+            archivo_actual: None,
+            metodos_tipo: HashMap::new(),
+            salida: None,
+            depuracion: None,
+            pila_llamadas: Vec::new(),
+            perfil: None,
+            sesion: Sesion::nueva(),
         };
 
-        crate::runtime::stdlib::registrar_stdlib(&mut inter);
+        stdlib::registrar_stdlib(&mut inter);
+        inter.definir_argumentos();
         inter
     }
 
@@ -45,6 +89,78 @@ impl Interpretador {
         self.directorio_base = ruta;
     }
 
+    pub fn establecer_argumentos(&mut self, argumentos: Vec<String>) {
+        *self.sesion.argumentos.lock().unwrap() = argumentos;
+        self.definir_argumentos();
+    }
+
+    // `ARGS` existe en el programa y en cada módulo que importa.
+    fn definir_argumentos(&mut self) {
+        let argumentos = self.sesion.argumentos.lock().unwrap().clone();
+        let lista = argumentos.into_iter().map(Valor::Texto).collect();
+        self.entorno_actual
+            .definir_constante("ARGS".to_string(), Valor::Lista(lista));
+    }
+
+    pub fn establecer_archivo_actual(&mut self, ruta: PathBuf) {
+        let ruta = ruta.canonicalize().unwrap_or(ruta);
+        let directorio = ruta
+            .parent()
+            .map(Path::to_path_buf)
+            .unwrap_or_else(|| self.directorio_base.clone());
+
+        self.entorno_actual.definir_constante(
+            "__file__".to_string(),
+            Valor::Texto(ruta.to_string_lossy().to_string()),
+        );
+        self.entorno_actual.definir_constante(
+            "__dir__".to_string(),
+            Valor::Texto(directorio.to_string_lossy().to_string()),
+        );
+
+        self.directorio_base = directorio;
+        self.archivo_actual = Some(ruta);
+    }
+
+    /// Mide la ejecución a partir de aquí. Las sentencias solo se miden si
+    /// el programa se parseó con las líneas anotadas.
+    pub fn activar_perfil(&mut self, mut perfil: Perfil) {
+        perfil.entrar(crate::runtime::depuracion::MARCO_PRINCIPAL.to_string());
+        self.perfil = Some(perfil);
+    }
+
+    // Marcos de llamada para el depurador y el perfilador; sin ninguno de
+    // los dos activo no se mantienen.
+    pub(crate) fn entrar_marco(&mut self, nombre: impl FnOnce() -> String) {
+        if self.depuracion.is_none() && self.perfil.is_none() {
+            return;
+        }
+        let nombre = nombre();
+        if let Some(perfil) = &mut self.perfil {
+            perfil.entrar(nombre.clone());
+        }
+        if self.depuracion.is_some() {
+            self.pila_llamadas.push(Marco { nombre, linea: 0 });
+        }
+    }
+
+    pub(crate) fn salir_marco(&mut self) {
+        if let Some(perfil) = &mut self.perfil {
+            perfil.salir();
+        }
+        if self.depuracion.is_some() {
+            self.pila_llamadas.pop();
+        }
+    }
+
+    fn directorio_modulo(&self) -> PathBuf {
+        self.archivo_actual
+            .as_ref()
+            .and_then(|archivo| archivo.parent())
+            .map(Path::to_path_buf)
+            .unwrap_or_else(|| self.directorio_base.clone())
+    }
+
     #[async_recursion]
     pub async fn ejecutar_sentencia(&mut self, sentencia: Sentencia) -> Option<Valor> {
         if self.tiene_valor_retorno() {
@@ -57,6 +173,21 @@ impl Interpretador {
                 self.ejecutar_declaracion_constante(decl).await
             }
             Sentencia::Asignacion(asig) => self.ejecutar_asignacion(asig).await,
+            Sentencia::Ubicada(ubicacion, sentencia) => {
+                if self.depuracion.is_some() {
+                    self.antes_de_sentencia(ubicacion.linea).await;
+                }
+                if let Some(perfil) = &mut self.perfil {
+                    perfil.entrar_linea(ubicacion);
+                }
+                // Sin perfilador no se consulta el reloj.
+                let inicio = self.perfil.is_some().then(Instant::now);
+                let resultado = self.ejecutar_sentencia(*sentencia).await;
+                if let (Some(perfil), Some(inicio)) = (&mut self.perfil, inicio) {
+                    perfil.salir_linea(inicio.elapsed());
+                }
+                resultado
+            }
             Sentencia::LlamadoTPrint(lt) => self.ejecutar_tprint(lt).await,
             Sentencia::Return(expr) => self.ejecutar_return(expr).await,
             Sentencia::If(if_stmt) => self.ejecutar_if(if_stmt).await,
@@ -88,9 +219,13 @@ impl Interpretador {
             Sentencia::TryCatch(stmt) => self.ejecutar_try_catch(stmt).await,
             Sentencia::Throw(stmt) => self.ejecutar_throw(stmt).await,
             Sentencia::Exportacion(nombre) => {
-                self.exportaciones.insert(nombre, true);
+                self.exportaciones.insert(nombre.clone(), nombre);
                 None
             }
+            Sentencia::ListaExportacion(lista) => self.ejecutar_lista_exportacion(lista).await,
+            Sentencia::ExportacionPorDefecto(sentencia) => {
+                self.ejecutar_exportacion_por_defecto(*sentencia).await
+            }
             Sentencia::Expresion(expr) => {
                 self.evaluar_expresion(expr).await;
                 None
@@ -115,7 +250,7 @@ impl Interpretador {
         self.entorno_actual
             .definir_variable(decl.nombre.clone(), valor);
         if decl.exportado {
-            self.exportaciones.insert(decl.nombre, true);
+            self.exportaciones.insert(decl.nombre.clone(), decl.nombre);
         }
         None
     }
@@ -128,7 +263,7 @@ impl Interpretador {
         self.entorno_actual
             .definir_constante(decl.nombre.clone(), valor);
         if decl.exportado {
-            self.exportaciones.insert(decl.nombre, true);
+            self.exportaciones.insert(decl.nombre.clone(), decl.nombre);
         }
         None
     }
@@ -202,7 +337,7 @@ impl Interpretador {
         self.entorno_actual
             .definir_variable(func.nombre.clone(), Valor::Funcion(funcion));
         if func.exportado {
-            self.exportaciones.insert(func.nombre, true);
+            self.exportaciones.insert(func.nombre.clone(), func.nombre);
         }
         None
     }
@@ -212,7 +347,7 @@ impl Interpretador {
         let nombre = interfaz_obj.nombre.clone();
         self.gestor_interfaces.registrar(interfaz_obj);
         if interfaz.exportado {
-            self.exportaciones.insert(nombre, true);
+            self.exportaciones.insert(nombre.clone(), nombre);
         }
         None
     }
@@ -223,7 +358,7 @@ impl Interpretador {
 
         self.gestor_enums.registrar(enum_obj);
 
-        let mut dict_variantes = HashMap::new();
+        let mut dict_variantes = IndexMap::new();
 
         for (indice, variante_enum) in decl_enum.variantes.iter().enumerate() {
             let nombre_variante = variante_enum.nombre.clone();
@@ -241,7 +376,7 @@ impl Interpretador {
             .definir_variable(nombre_enum.clone(), Valor::Diccionario(dict_variantes));
 
         if decl_enum.exportado {
-            self.exportaciones.insert(nombre_enum, true);
+            self.exportaciones.insert(nombre_enum.clone(), nombre_enum);
         }
         None
     }
@@ -253,7 +388,8 @@ impl Interpretador {
         let nombre_clase = clase_obj.nombre.clone();
         self.gestor_clases.registrar_clase(clase_obj);
         if clase.exportado {
-            self.exportaciones.insert(nombre_clase, true);
+            self.exportaciones
+                .insert(nombre_clase.clone(), nombre_clase);
         }
         None
     }
@@ -308,112 +444,162 @@ impl Interpretador {
         &mut self,
         imp: umbral_parser::ast::Importacion,
     ) -> Option<Valor> {
-        let (contenido, ruta_encontrada) = self.buscar_modulo(&imp.ruta)?;
-        let programa = self.parsear_modulo(&contenido, &ruta_encontrada)?;
-        let interprete_modulo = self.ejecutar_modulo(programa, &ruta_encontrada).await;
-        self.importar_items(imp.items, &interprete_modulo);
+        let modulo = self.cargar_modulo(&imp.ruta).await?;
+        self.importar_items(imp.items, &modulo);
+        self.incorporar_metodos_tipo(&modulo);
         None
     }
 
-    fn buscar_modulo(&self, ruta: &str) -> Option<(String, PathBuf)> {
-        let es_ruta_relativa = self.es_ruta_relativa(ruta);
+    // Las extensiones de tipos que hace un módulo también valen para quien
+    // lo importa.
+    fn incorporar_metodos_tipo(&mut self, modulo: &Interpretador) {
+        for (tipo, metodos) in &modulo.metodos_tipo {
+            self.metodos_tipo
+                .entry(tipo.clone())
+                .or_default()
+                .extend(metodos.clone());
+        }
+    }
 
-        if es_ruta_relativa {
-            return self.buscar_ruta_relativa(ruta);
+    async fn cargar_modulo(&self, ruta: &str) -> Option<Interpretador> {
+        if let Some(nombre_std) = ruta.strip_prefix(stdlib::PREFIJO_IMPORTACION) {
+            return self.crear_modulo_std(nombre_std);
         }
 
-        self.buscar_modulo_ump(ruta)
+        let (contenido, ruta_encontrada) = self.buscar_modulo(ruta)?;
+        let programa = self.parsear_modulo(&contenido, &ruta_encontrada)?;
+        let mut interprete_modulo = self.ejecutar_modulo(programa, &ruta_encontrada).await;
+        Self::vincular_funciones_modulo(&mut interprete_modulo);
+        Some(interprete_modulo)
     }
 
-    fn es_ruta_relativa(&self, ruta: &str) -> bool {
-        ruta.contains('/')
-            || ruta.starts_with("./")
-            || ruta.starts_with("../")
-            || ruta.ends_with(".um")
-    }
+    async fn ejecutar_lista_exportacion(&mut self, lista: ListaExportacion) -> Option<Valor> {
+        let Some(ruta) = lista.origen else {
+            for item in lista.items {
+                if let ItemExportacion::Nombre(nombre, alias) = item {
+                    self.exportaciones
+                        .insert(alias.unwrap_or_else(|| nombre.clone()), nombre);
+                }
+            }
+            return None;
+        };
 
-    fn buscar_ruta_relativa(&self, ruta: &str) -> Option<(String, PathBuf)> {
-        let ruta_original = PathBuf::from(ruta);
-        let rutas_posibles = self.construir_rutas_relativas(&ruta_original);
+        let modulo = self.cargar_modulo(&ruta).await?;
+        let exportados = exportaciones::recolectar(&modulo);
 
-        self.intentar_leer_archivos(rutas_posibles, ruta)
-    }
+        for item in lista.items {
+            match item {
+                ItemExportacion::Todo => {
+                    for (nombre, elemento) in &exportados {
+                        if nombre != exportaciones::NOMBRE_POR_DEFECTO {
+                            self.reexportaciones
+                                .insert(nombre.clone(), elemento.clone());
+                        }
+                    }
+                }
+                ItemExportacion::Nombre(nombre, alias) => match exportados.get(&nombre) {
+                    Some(elemento) => {
+                        self.reexportaciones
+                            .insert(alias.unwrap_or(nombre), elemento.clone());
+                    }
+                    None => eprintln!(
+                        "Advertencia: '{}' no está exportado en el módulo '{}'",
+                        nombre, ruta
+                    ),
+                },
+            }
+        }
 
-    fn construir_rutas_relativas(&self, ruta: &PathBuf) -> Vec<PathBuf> {
-        vec![
-            self.directorio_base.join(ruta),
-            self.directorio_base.join("modules_ump").join(ruta),
-            self.directorio_base
-                .join("modules_ump")
-                .join(ruta)
-                .join("main.um"),
-            self.directorio_base
-                .join("modules_ump")
-                .join(ruta)
-                .join("index.um"),
-        ]
+        None
     }
 
-    fn buscar_modulo_ump(&self, nombre_modulo: &str) -> Option<(String, PathBuf)> {
-        let mut dir_actual = self.directorio_base.clone();
-
-        loop {
-            let resultado = self.buscar_en_directorio(&dir_actual, nombre_modulo);
-            if resultado.is_some() {
-                return resultado;
+    async fn ejecutar_exportacion_por_defecto(&mut self, sentencia: Sentencia) -> Option<Valor> {
+        let nombre_local = match sentencia {
+            Sentencia::Expresion(Expresion::Identificador(nombre)) => nombre,
+            Sentencia::Expresion(expresion) => {
+                let valor = self.evaluar_expresion(expresion).await;
+                let nombre = "__def__".to_string();
+                self.entorno_actual.definir_constante(nombre.clone(), valor);
+                nombre
             }
-
-            if !dir_actual.pop() {
-                break;
+            declaracion => {
+                let Some(nombre) = Self::nombre_declaracion(&declaracion) else {
+                    eprintln!(
+                        "Error: Solo se pueden exportar por defecto declaraciones con nombre"
+                    );
+                    return None;
+                };
+                self.ejecutar_sentencia(declaracion).await;
+                nombre
             }
-        }
+        };
 
-        self.reportar_modulo_no_encontrado(nombre_modulo);
+        self.exportaciones
+            .insert(exportaciones::NOMBRE_POR_DEFECTO.to_string(), nombre_local);
         None
     }
 
-    fn buscar_en_directorio(
-        &self,
-        dir: &PathBuf,
-        nombre_modulo: &str,
-    ) -> Option<(String, PathBuf)> {
-        use std::fs;
-        let modules_ump = dir.join("modules_ump");
-
-        if !modules_ump.exists() || !modules_ump.is_dir() {
-            return None;
+    fn nombre_declaracion(sentencia: &Sentencia) -> Option<String> {
+        match sentencia {
+            Sentencia::DeclaracionVariable(decl) => Some(decl.nombre.clone()),
+            Sentencia::DeclaracionConstante(decl) => Some(decl.nombre.clone()),
+            Sentencia::Funcion(decl) => Some(decl.nombre.clone()),
+            Sentencia::Clase(decl) => Some(decl.nombre.clone()),
+            Sentencia::Interfaz(decl) => Some(decl.nombre.clone()),
+            Sentencia::Enum(decl) => Some(decl.nombre.clone()),
+            _ => None,
         }
+    }
 
-        let rutas = vec![
-            modules_ump.join(nombre_modulo).join("src").join("main.um"),
-            modules_ump.join(nombre_modulo).join("main.um"),
-            modules_ump.join(nombre_modulo).join("index.um"),
-        ];
+    fn crear_modulo_std(&self, nombre: &str) -> Option<Interpretador> {
+        let Some(Valor::Diccionario(funciones)) = stdlib::crear_modulo(nombre) else {
+            eprintln!(
+                "Error: El módulo estándar '{}' no existe. Disponibles: {}.",
+                nombre,
+                stdlib::MODULOS.join(", ")
+            );
+            return None;
+        };
 
-        for ruta in rutas {
-            if let Ok(contenido) = fs::read_to_string(&ruta) {
-                return Some((contenido, ruta));
-            }
+        let mut modulo = Interpretador::nuevo();
+        for (nombre_funcion, funcion) in funciones {
+            modulo
+                .exportaciones
+                .insert(nombre_funcion.clone(), nombre_funcion.clone());
+            modulo
+                .entorno_actual
+                .definir_variable(nombre_funcion, funcion);
         }
 
-        None
+        Some(modulo)
     }
 
-    fn intentar_leer_archivos(
-        &self,
-        rutas: Vec<PathBuf>,
-        ruta_original: &str,
-    ) -> Option<(String, PathBuf)> {
-        use std::fs;
+    fn buscar_modulo(&self, ruta: &str) -> Option<(String, PathBuf)> {
+        let resolucion = modulos::resolver(ruta, &self.directorio_modulo(), &self.directorio_base);
 
-        for ruta in &rutas {
-            if let Ok(contenido) = fs::read_to_string(ruta) {
-                return Some((contenido, ruta.clone()));
+        let ruta_encontrada = match resolucion {
+            Ok(ruta_encontrada) => ruta_encontrada,
+            Err(ErrorResolucion::RutaNoEncontrada(rutas)) => {
+                self.reportar_ruta_no_encontrada(ruta, &rutas);
+                return None;
             }
-        }
+            Err(ErrorResolucion::PaqueteNoEncontrado) => {
+                self.reportar_modulo_no_encontrado(ruta);
+                return None;
+            }
+            Err(ErrorResolucion::Manifiesto(mensaje)) => {
+                eprintln!("Error: {}", mensaje);
+                return None;
+            }
+        };
 
-        self.reportar_ruta_no_encontrada(ruta_original, &rutas);
-        None
+        match std::fs::read_to_string(&ruta_encontrada) {
+            Ok(contenido) => Some((contenido, ruta_encontrada)),
+            Err(_) => {
+                self.reportar_ruta_no_encontrada(ruta, &[ruta_encontrada]);
+                None
+            }
+        }
     }
 
     fn reportar_ruta_no_encontrada(&self, ruta: &str, rutas_intentadas: &[PathBuf]) {
@@ -432,21 +618,40 @@ impl Interpretador {
             nombre
         );
         eprintln!(
-            "Asegúrate de que el módulo esté instalado con 'ump add {}'.",
+            "Declara la dependencia en {} e instálala con 'ump add {}'.",
+            paquetes::ARCHIVO_MANIFIESTO,
             nombre
         );
     }
 
-    fn parsear_modulo(
-        &self,
-        contenido: &str,
-        ruta: &PathBuf,
-    ) -> Option<umbral_parser::ast::Programa> {
-        let tokens = umbral_lexer::analizar(contenido);
-        match umbral_parser::parsear_programa(tokens) {
-            Ok(programa) => Some(programa),
-            Err(e) => {
-                eprintln!("Error al parsear archivo '{}': {:?}", ruta.display(), e);
+    fn parsear_modulo(&self, contenido: &str, ruta: &Path) -> Option<umbral_parser::ast::Programa> {
+        let tokens = match umbral_lexer::tokenizar(contenido) {
+            Ok(tokens) => tokens,
+            Err(errores) => {
+                eprintln!("Error al analizar archivo '{}':", ruta.display());
+                for error in errores {
+                    eprintln!("{}", error);
+                }
+                return None;
+            }
+        };
+        let mut parser = umbral_parser::Parser::nuevo_con_posiciones(tokens, contenido.to_string());
+        let archivo = self.perfil.as_ref().map(|perfil| perfil.registrar_archivo(ruta));
+        if let Some(archivo) = archivo {
+            parser.anotar_lineas(archivo);
+        }
+        match parser.parsear_programa() {
+            Ok(programa) => {
+                if let (Some(perfil), Some(archivo)) = (&self.perfil, archivo) {
+                    perfil.registrar_lineas(archivo, &parser.lineas_sentencias);
+                }
+                Some(programa)
+            }
+            Err(errores) => {
+                eprintln!("Error al parsear archivo '{}':", ruta.display());
+                for error in errores {
+                    eprintln!("{}", error.formatear_error());
+                }
                 None
             }
         }
@@ -455,13 +660,14 @@ impl Interpretador {
     async fn ejecutar_modulo(
         &self,
         programa: umbral_parser::ast::Programa,
-        ruta: &PathBuf,
+        ruta: &Path,
     ) -> Interpretador {
         let mut interprete = Interpretador::nuevo();
-
-        if let Some(parent) = ruta.parent() {
-            interprete.establecer_directorio_base(parent.to_path_buf());
-        }
+        interprete.establecer_archivo_actual(ruta.to_path_buf());
+        interprete.salida = self.salida.clone();
+        interprete.perfil = self.perfil.clone();
+        interprete.sesion = self.sesion.clone();
+        interprete.definir_argumentos();
 
         for sentencia in programa.sentencias {
             interprete.ejecutar_sentencia(sentencia).await;
@@ -470,61 +676,81 @@ impl Interpretador {
         interprete
     }
 
+    fn vincular_funciones_modulo(modulo: &mut Interpretador) {
+        // El entorno compartido guarda las funciones sin contexto: dentro
+        // del módulo se llaman encadenadas al entorno de quien llama, que ya
+        // llega al del módulo.
+        let contexto = Arc::new(Mutex::new(modulo.entorno_actual.clone()));
+
+        for valor in modulo.entorno_actual.variables.values_mut() {
+            if let Valor::Funcion(funcion) = valor {
+                funcion.contexto = Some(contexto.clone());
+            }
+        }
+    }
+
     fn importar_items(
         &mut self,
         items: Vec<umbral_parser::ast::ItemImportacion>,
         modulo: &Interpretador,
     ) {
+        let exportados = exportaciones::recolectar(modulo);
+
         for item in items {
-            self.procesar_item_importacion(item, modulo);
+            self.procesar_item_importacion(item, &exportados);
         }
     }
 
     fn procesar_item_importacion(
         &mut self,
         item: umbral_parser::ast::ItemImportacion,
-        modulo: &Interpretador,
+        exportados: &IndexMap<String, ElementoExportado>,
     ) {
         use umbral_parser::ast::ItemImportacion;
 
         match item {
-            ItemImportacion::Todo(alias) => self.importar_todo(alias, modulo),
-            ItemImportacion::Nombre(nombre, alias) => self.importar_nombre(nombre, alias, modulo),
-            ItemImportacion::Modulo(nombre_var) => {
-                self.importar_modulo_como_objeto(nombre_var, modulo)
+            ItemImportacion::Todo(alias) => self.importar_todo(alias, exportados),
+            ItemImportacion::Nombre(nombre, alias) => {
+                self.importar_nombre(nombre, alias, exportados)
+            }
+            ItemImportacion::Modulo(nombre, alias) => {
+                self.importar_modulo_como_objeto(alias.unwrap_or(nombre), exportados)
             }
             ItemImportacion::ListaNombres(items) => {
                 for sub_item in items {
-                    self.procesar_item_importacion(sub_item, modulo);
+                    self.procesar_item_importacion(sub_item, exportados);
                 }
             }
         }
     }
 
-    fn importar_modulo_como_objeto(&mut self, nombre_var: String, modulo: &Interpretador) {
-        let mut mapa_exportaciones = HashMap::new();
+    fn importar_modulo_como_objeto(
+        &mut self,
+        nombre_var: String,
+        exportados: &IndexMap<String, ElementoExportado>,
+    ) {
+        let mut mapa_exportaciones = IndexMap::new();
 
-        for (nombre, valor) in &modulo.entorno_actual.variables {
-            if modulo.exportaciones.get(nombre).copied().unwrap_or(false) {
-                mapa_exportaciones.insert(nombre.clone(), valor.clone());
+        for (nombre, elemento) in exportados {
+            if let Some(interfaz) = &elemento.interfaz {
+                self.gestor_interfaces.registrar(interfaz.clone());
             }
-        }
 
-        for (nombre, valor) in &modulo.entorno_actual.constantes {
-            if modulo.exportaciones.get(nombre).copied().unwrap_or(false) {
+            if let Some(enumeracion) = &elemento.enumeracion {
+                self.gestor_enums.registrar(enumeracion.clone());
+            }
+
+            if let Some(valor) = &elemento.valor {
                 mapa_exportaciones.insert(nombre.clone(), valor.clone());
+                continue;
             }
-        }
 
-        for (nombre, clase) in &modulo.gestor_clases.clases {
-            if modulo.exportaciones.get(nombre).copied().unwrap_or(false) {
+            if let Some(clase) = &elemento.clase {
                 let nombre_unico = format!("__modulo_{}_{}", nombre_var, nombre);
 
                 let mut clase_copia = clase.clone();
                 clase_copia.nombre = nombre_unico.clone();
-                self.gestor_clases
-                    .clases
-                    .insert(nombre_unico.clone(), clase_copia);
+                self.gestor_clases.registrar_clase(clase_copia);
 
                 mapa_exportaciones.insert(nombre.clone(), Valor::Clase(nombre_unico));
             }
@@ -534,84 +760,57 @@ impl Interpretador {
             .definir_variable(nombre_var, Valor::Diccionario(mapa_exportaciones));
     }
 
-    fn importar_todo(&mut self, alias: Option<String>, modulo: &Interpretador) {
+    fn importar_todo(
+        &mut self,
+        alias: Option<String>,
+        exportados: &IndexMap<String, ElementoExportado>,
+    ) {
         let alias_nombre = alias.unwrap_or_else(|| "mod".to_string());
 
-        self.importar_variables_exportadas(&alias_nombre, modulo);
-        self.importar_clases_exportadas(&alias_nombre, modulo);
-    }
-
-    fn importar_variables_exportadas(&mut self, alias: &str, modulo: &Interpretador) {
-        for (nombre, valor) in &modulo.entorno_actual.variables {
-            if !modulo.exportaciones.get(nombre).copied().unwrap_or(false) {
-                continue;
-            }
-
-            let nombre_final = format!("{}_{}", alias, nombre);
-            self.entorno_actual
-                .definir_variable(nombre_final, valor.clone());
-        }
-    }
-
-    fn importar_clases_exportadas(&mut self, alias: &str, modulo: &Interpretador) {
-        for (nombre, clase) in &modulo.gestor_clases.clases {
-            if !modulo.exportaciones.get(nombre).copied().unwrap_or(false) {
-                continue;
-            }
-
-            let nombre_final = format!("{}_{}", alias, nombre);
-            self.gestor_clases
-                .clases
-                .insert(nombre_final, clase.clone());
+        for (nombre, elemento) in exportados {
+            let nombre_final = format!("{}_{}", alias_nombre, nombre);
+            self.definir_elemento_importado(&nombre_final, elemento);
         }
     }
 
-    fn importar_nombre(&mut self, nombre: String, alias: Option<String>, modulo: &Interpretador) {
-        if !modulo.exportaciones.get(&nombre).copied().unwrap_or(false) {
+    fn importar_nombre(
+        &mut self,
+        nombre: String,
+        alias: Option<String>,
+        exportados: &IndexMap<String, ElementoExportado>,
+    ) {
+        let Some(elemento) = exportados.get(&nombre) else {
             eprintln!("Advertencia: '{}' no está exportado en el módulo", nombre);
             return;
-        }
+        };
 
-        let nombre_final = alias.unwrap_or_else(|| nombre.clone());
+        let nombre_final = alias.unwrap_or(nombre);
+        self.definir_elemento_importado(&nombre_final, elemento);
+    }
 
-        if self.intentar_importar_variable(&nombre, &nombre_final, modulo) {
-            return;
+    fn definir_elemento_importado(&mut self, nombre: &str, elemento: &ElementoExportado) {
+        if let Some(valor) = &elemento.valor {
+            self.entorno_actual
+                .definir_variable(nombre.to_string(), valor.clone());
         }
 
-        if self.intentar_importar_clase(&nombre, &nombre_final, modulo) {
-            return;
+        if let Some(clase) = &elemento.clase {
+            let mut clase_copia = clase.clone();
+            clase_copia.nombre = nombre.to_string();
+            self.gestor_clases.registrar_clase(clase_copia);
         }
 
-        eprintln!("Advertencia: '{}' no encontrado en el módulo", nombre);
-    }
-
-    fn intentar_importar_variable(
-        &mut self,
-        nombre: &str,
-        nombre_final: &str,
-        modulo: &Interpretador,
-    ) -> bool {
-        if let Some(valor) = modulo.entorno_actual.obtener(nombre) {
-            self.entorno_actual
-                .definir_variable(nombre_final.to_string(), valor);
-            return true;
+        if let Some(interfaz) = &elemento.interfaz {
+            let mut interfaz_copia = interfaz.clone();
+            interfaz_copia.nombre = nombre.to_string();
+            self.gestor_interfaces.registrar(interfaz_copia);
         }
-        false
-    }
 
-    fn intentar_importar_clase(
-        &mut self,
-        nombre: &str,
-        nombre_final: &str,
-        modulo: &Interpretador,
-    ) -> bool {
-        if let Some(clase) = modulo.gestor_clases.clases.get(nombre) {
-            self.gestor_clases
-                .clases
-                .insert(nombre_final.to_string(), clase.clone());
-            return true;
+        if let Some(enumeracion) = &elemento.enumeracion {
+            let mut enum_copia = enumeracion.clone();
+            enum_copia.nombre = nombre.to_string();
+            self.gestor_enums.registrar(enum_copia);
         }
-        false
     }
 
     #[async_recursion]
@@ -619,8 +818,16 @@ impl Interpretador {
         match expr {
             Expresion::LiteralEntero(i) => Valor::Entero(i),
             Expresion::LiteralFloat(f) => Valor::Flotante(f),
+            Expresion::LiteralDecimal(texto) => match stdlib::decimal::desde_literal(&texto) {
+                Some(decimal) => Valor::Decimal(decimal),
+                None => self.lanzar_error_aritmetico(
+                    &format!("Literal decimal fuera de rango: {}d", texto),
+                    "Overflow",
+                ),
+            },
             Expresion::LiteralBool(b) => Valor::Booleano(b),
-            Expresion::LiteralCadena(s) => self.evaluar_literal_cadena(s).await,
+            Expresion::LiteralCadena(s) => Valor::Texto(s),
+            Expresion::Plantilla(partes) => self.evaluar_plantilla(partes).await,
             Expresion::LiteralCadenaLiteral(s) => Valor::Texto(s),
             Expresion::LiteralNulo => Valor::Nulo,
             Expresion::Identificador(nombre) => self.evaluar_identificador(&nombre),
@@ -647,6 +854,11 @@ impl Interpretador {
             Expresion::AccesoIndice { objeto, indice } => {
                 self.evaluar_acceso_indice(*objeto, *indice).await
             }
+            Expresion::Rebanada {
+                objeto,
+                inicio,
+                fin,
+            } => self.evaluar_rebanada(*objeto, inicio, fin).await,
             Expresion::LlamadoMetodo {
                 objeto,
                 metodo,
@@ -664,10 +876,18 @@ impl Interpretador {
     }
 
     #[async_recursion]
-    async fn evaluar_literal_cadena(&mut self, s: String) -> Valor {
-        let contenido = s.trim_matches('"').to_string();
-        let interpolado = self.procesar_interpolaciones(contenido).await;
-        Valor::Texto(interpolado)
+    async fn evaluar_plantilla(&mut self, partes: Vec<ParteCadena>) -> Valor {
+        let mut texto = String::new();
+        for parte in partes {
+            match parte {
+                ParteCadena::Texto(literal) => texto.push_str(&literal),
+                ParteCadena::Expresion(expresion) => {
+                    let valor = self.evaluar_expresion(expresion).await;
+                    texto.push_str(&self.convertir_a_texto(valor).await);
+                }
+            }
+        }
+        Valor::Texto(texto)
     }
 
     #[async_recursion]
@@ -705,8 +925,14 @@ impl Interpretador {
             return Valor::Nulo;
         };
 
+        let id = handle.id();
         match handle.await {
-            Ok(v) => v,
+            Ok(v) => {
+                if let Some(error) = stdlib::errores::tomar_error_de_tarea(id) {
+                    self.estado_excepcion = Some(error);
+                }
+                v
+            }
             Err(e) => {
                 eprintln!("Error en tarea asincrona: {:?}", e);
                 Valor::Nulo
@@ -715,10 +941,16 @@ impl Interpretador {
     }
 
     fn evaluar_identificador(&self, nombre: &str) -> Valor {
-        self.entorno_actual.obtener(nombre).unwrap_or_else(|| {
-            eprintln!("Variable '{}' no encontrada", nombre);
-            Valor::Nulo
-        })
+        if let Some(valor) = self.entorno_actual.obtener(nombre) {
+            return valor;
+        }
+
+        if self.gestor_clases.obtener_clase(nombre).is_some() {
+            return Valor::Clase(nombre.to_string());
+        }
+
+        eprintln!("Variable '{}' no encontrada", nombre);
+        Valor::Nulo
     }
 
     fn evaluar_this(&self) -> Valor {
@@ -754,7 +986,7 @@ impl Interpretador {
 
     #[async_recursion]
     async fn evaluar_objeto(&mut self, pares: Vec<(String, Expresion)>) -> Valor {
-        let mut mapa = HashMap::new();
+        let mut mapa = IndexMap::new();
         for (clave, valor_expr) in pares {
             let valor = self.evaluar_expresion(valor_expr).await;
             mapa.insert(clave, valor);
@@ -818,8 +1050,12 @@ impl Interpretador {
     async fn evaluar_negativo(&mut self, expr: Expresion) -> Valor {
         let valor = self.evaluar_expresion(expr).await;
         match valor {
-            Valor::Entero(i) => Valor::Entero(-i),
+            Valor::Entero(i) => match i.checked_neg() {
+                Some(negado) => Valor::Entero(negado),
+                None => self.lanzar_desbordamiento(&format!("-({})", i)),
+            },
             Valor::Flotante(f) => Valor::Flotante(-f),
+            Valor::Decimal(d) => Valor::Decimal(-d),
             _ => Valor::Nulo,
         }
     }
@@ -854,67 +1090,233 @@ impl Interpretador {
         nuevo_valor
     }
 
-    fn incrementar_valor(&self, valor: Valor) -> Valor {
+    fn incrementar_valor(&mut self, valor: Valor) -> Valor {
         match valor {
-            Valor::Entero(i) => Valor::Entero(i + 1),
+            Valor::Entero(i) => match i.checked_add(1) {
+                Some(nuevo) => Valor::Entero(nuevo),
+                None => self.lanzar_desbordamiento(&format!("{} + 1", i)),
+            },
             Valor::Flotante(f) => Valor::Flotante(f + 1.0),
+            Valor::Decimal(d) => Valor::Decimal(d + Decimal::ONE),
             _ => valor,
         }
     }
 
-    fn decrementar_valor(&self, valor: Valor) -> Valor {
+    fn decrementar_valor(&mut self, valor: Valor) -> Valor {
         match valor {
-            Valor::Entero(i) => Valor::Entero(i - 1),
+            Valor::Entero(i) => match i.checked_sub(1) {
+                Some(nuevo) => Valor::Entero(nuevo),
+                None => self.lanzar_desbordamiento(&format!("{} - 1", i)),
+            },
             Valor::Flotante(f) => Valor::Flotante(f - 1.0),
+            Valor::Decimal(d) => Valor::Decimal(d - Decimal::ONE),
             _ => valor,
         }
     }
 
-    fn sumar(&self, izq: Valor, der: Valor) -> Valor {
+    fn lanzar_error_aritmetico(&mut self, mensaje: &str, tipo: &str) -> Valor {
+        let mut data = IndexMap::new();
+        data.insert("kind".to_string(), Valor::Texto(tipo.to_string()));
+        self.estado_excepcion = Some(stdlib::errores::crear_error(
+            mensaje,
+            Valor::Diccionario(data),
+        ));
+        Valor::Nulo
+    }
+
+    fn lanzar_desbordamiento(&mut self, operacion: &str) -> Valor {
+        self.lanzar_error_aritmetico(
+            &format!("Desbordamiento de entero en {}", operacion),
+            "Overflow",
+        )
+    }
+
+    fn sumar(&mut self, izq: Valor, der: Valor) -> Valor {
         match (izq, der) {
             (Valor::Texto(a), Valor::Texto(b)) => Valor::Texto(format!("{}{}", a, b)),
             (Valor::Lista(mut a), Valor::Lista(b)) => {
                 a.extend(b);
                 Valor::Lista(a)
             }
-            (a, b) => self.operar_numeros(a, b, |x, y| x + y, |x, y| x + y),
+            (Valor::FechaHora(f), Valor::Duracion(d))
+            | (Valor::Duracion(d), Valor::FechaHora(f)) => self.desplazar_fecha(f, d),
+            (Valor::Duracion(a), Valor::Duracion(b)) => {
+                let suma = a.checked_add(&b).map(Valor::Duracion);
+                self.resultado_temporal(suma, &format!("{} + {}", a, b))
+            }
+            (a, b) => self.operar_numeros(
+                a,
+                b,
+                "+",
+                i64::checked_add,
+                |x, y| x + y,
+                Decimal::checked_add,
+            ),
+        }
+    }
+
+    fn restar(&mut self, izq: Valor, der: Valor) -> Valor {
+        match (izq, der) {
+            (Valor::FechaHora(f), Valor::Duracion(d)) => self.desplazar_fecha(f, -d),
+            (Valor::FechaHora(a), Valor::FechaHora(b)) => {
+                Valor::Duracion(a.instante.signed_duration_since(b.instante))
+            }
+            (Valor::Duracion(a), Valor::Duracion(b)) => {
+                let resta = a.checked_sub(&b).map(Valor::Duracion);
+                self.resultado_temporal(resta, &format!("{} - {}", a, b))
+            }
+            (a, b) => self.operar_numeros(
+                a,
+                b,
+                "-",
+                i64::checked_sub,
+                |x, y| x - y,
+                Decimal::checked_sub,
+            ),
         }
     }
 
-    fn restar(&self, izq: Valor, der: Valor) -> Valor {
-        self.operar_numeros(izq, der, |x, y| x - y, |x, y| x - y)
+    fn desplazar_fecha(&mut self, fecha: FechaHora, duracion: TimeDelta) -> Valor {
+        let desplazada = fecha.desplazar(duracion).map(Valor::FechaHora);
+        self.resultado_temporal(desplazada, &format!("{} + {}", fecha, duracion))
     }
 
-    fn multiplicar(&self, izq: Valor, der: Valor) -> Valor {
-        self.operar_numeros(izq, der, |x, y| x * y, |x, y| x * y)
+    fn escalar_duracion(&mut self, duracion: TimeDelta, factor: f64) -> Valor {
+        let escalada = time::duracion_desde_segundos(time::segundos_flotantes(&duracion) * factor)
+            .map(Valor::Duracion);
+        self.resultado_temporal(escalada, &format!("{} * {}", duracion, factor))
+    }
+
+    // Igual que con los enteros, salir del rango de fechas y duraciones
+    // lanza un error `Overflow` en lugar de devolver null.
+    fn resultado_temporal(&mut self, resultado: Option<Valor>, operacion: &str) -> Valor {
+        match resultado {
+            Some(valor) => valor,
+            None => self.lanzar_error_aritmetico(
+                &format!("Fecha o duración fuera de rango en {}", operacion),
+                "Overflow",
+            ),
+        }
+    }
+
+    fn multiplicar(&mut self, izq: Valor, der: Valor) -> Valor {
+        match (izq, der) {
+            (Valor::Duracion(d), n) | (n, Valor::Duracion(d)) if n.a_numero().is_some() => {
+                self.escalar_duracion(d, n.a_numero().unwrap_or(1.0))
+            }
+            (a, b) => self.operar_numeros(
+                a,
+                b,
+                "*",
+                i64::checked_mul,
+                |x, y| x * y,
+                Decimal::checked_mul,
+            ),
+        }
     }
 
-    fn dividir(&self, izq: Valor, der: Valor) -> Valor {
+    fn dividir(&mut self, izq: Valor, der: Valor) -> Valor {
         if self.es_division_por_cero(&der) {
             eprintln!("División por cero");
             return Valor::Nulo;
         }
-        self.operar_numeros(izq, der, |x, y| x / y, |x, y| x / y)
+        let sesion = self.sesion.clone();
+        match (izq, der) {
+            (Valor::Duracion(a), Valor::Duracion(b)) => {
+                Valor::Flotante(time::segundos_flotantes(&a) / time::segundos_flotantes(&b))
+            }
+            (Valor::Duracion(d), Valor::Entero(n)) => self.escalar_duracion(d, 1.0 / n as f64),
+            (Valor::Duracion(d), Valor::Flotante(n)) => self.escalar_duracion(d, 1.0 / n),
+            (a, b) => self.operar_numeros(
+                a,
+                b,
+                "/",
+                i64::checked_div,
+                |x, y| x / y,
+                |x, y| stdlib::decimal::dividir(&sesion, x, y),
+            ),
+        }
     }
 
-    fn modulo(&self, izq: Valor, der: Valor) -> Valor {
+    fn modulo(&mut self, izq: Valor, der: Valor) -> Valor {
         match (izq, der) {
-            (Valor::Entero(a), Valor::Entero(b)) if b != 0 => Valor::Entero(a % b),
+            (Valor::Entero(a), Valor::Entero(b)) if b != 0 => match a.checked_rem(b) {
+                Some(resto) => Valor::Entero(resto),
+                None => self.lanzar_desbordamiento(&format!("{} % {}", a, b)),
+            },
+            (a, b) if matches!(a, Valor::Decimal(_)) || matches!(b, Valor::Decimal(_)) => {
+                if self.es_division_por_cero(&b) {
+                    return self.lanzar_error_aritmetico(
+                        &format!("División por cero en {} % {}", a, b),
+                        "DivisionByZero",
+                    );
+                }
+                self.operar_numeros(
+                    a,
+                    b,
+                    "%",
+                    i64::checked_rem,
+                    |x, y| x % y,
+                    Decimal::checked_rem,
+                )
+            }
             _ => Valor::Nulo,
         }
     }
 
-    fn operar_numeros<F, G>(&self, izq: Valor, der: Valor, op_int: F, op_float: G) -> Valor
+    // Los enteros nunca se desbordan en silencio: la operación lanza un
+    // error `Overflow`. Los decimales se combinan con enteros de forma
+    // exacta, pero mezclarlos con flotantes perdería precisión.
+    fn operar_numeros<F, G, H>(
+        &mut self,
+        izq: Valor,
+        der: Valor,
+        op: &str,
+        op_int: F,
+        op_float: G,
+        op_decimal: H,
+    ) -> Valor
     where
-        F: Fn(i64, i64) -> i64,
+        F: Fn(i64, i64) -> Option<i64>,
         G: Fn(f64, f64) -> f64,
+        H: Fn(Decimal, Decimal) -> Option<Decimal>,
     {
-        match (izq, der) {
-            (Valor::Entero(a), Valor::Entero(b)) => Valor::Entero(op_int(a, b)),
-            (Valor::Flotante(a), Valor::Flotante(b)) => Valor::Flotante(op_float(a, b)),
-            (Valor::Entero(a), Valor::Flotante(b)) => Valor::Flotante(op_float(a as f64, b)),
-            (Valor::Flotante(a), Valor::Entero(b)) => Valor::Flotante(op_float(a, b as f64)),
-            _ => Valor::Nulo,
+        let (a, b) = match (izq, der) {
+            (Valor::Entero(a), Valor::Entero(b)) => {
+                return match op_int(a, b) {
+                    Some(resultado) => Valor::Entero(resultado),
+                    None => self.lanzar_desbordamiento(&format!("{} {} {}", a, op, b)),
+                };
+            }
+            (Valor::Flotante(a), Valor::Flotante(b)) => return Valor::Flotante(op_float(a, b)),
+            (Valor::Entero(a), Valor::Flotante(b)) => {
+                return Valor::Flotante(op_float(a as f64, b))
+            }
+            (Valor::Flotante(a), Valor::Entero(b)) => {
+                return Valor::Flotante(op_float(a, b as f64))
+            }
+            (Valor::Decimal(a), Valor::Decimal(b)) => (a, b),
+            (Valor::Decimal(a), Valor::Entero(b)) => (a, Decimal::from(b)),
+            (Valor::Entero(a), Valor::Decimal(b)) => (Decimal::from(a), b),
+            (Valor::Decimal(_), Valor::Flotante(_)) | (Valor::Flotante(_), Valor::Decimal(_)) => {
+                return self.lanzar_error_aritmetico(
+                    &format!(
+                        "No se puede operar ({}) un decimal con un flotante; \
+                         conviértelo con Std.decimal.new()",
+                        op
+                    ),
+                    "InvalidInput",
+                );
+            }
+            _ => return Valor::Nulo,
+        };
+
+        match op_decimal(a, b) {
+            Some(resultado) => Valor::Decimal(resultado),
+            None => self.lanzar_error_aritmetico(
+                &format!("Desbordamiento decimal en {} {} {}", a, op, b),
+                "Overflow",
+            ),
         }
     }
 
@@ -922,6 +1324,7 @@ impl Interpretador {
         match valor {
             Valor::Entero(0) => true,
             Valor::Flotante(f) if *f == 0.0 => true,
+            Valor::Decimal(d) if d.is_zero() => true,
             _ => false,
         }
     }
@@ -932,8 +1335,14 @@ impl Interpretador {
             (Valor::Flotante(x), Valor::Flotante(y)) => (x - y).abs() < f64::EPSILON,
             (Valor::Entero(x), Valor::Flotante(y)) => (*x as f64 - y).abs() < f64::EPSILON,
             (Valor::Flotante(x), Valor::Entero(y)) => (x - *y as f64).abs() < f64::EPSILON,
+            (Valor::Decimal(x), Valor::Decimal(y)) => x == y,
+            (Valor::Decimal(x), Valor::Entero(y)) | (Valor::Entero(y), Valor::Decimal(x)) => {
+                *x == Decimal::from(*y)
+            }
             (Valor::Booleano(x), Valor::Booleano(y)) => x == y,
             (Valor::Texto(x), Valor::Texto(y)) => x == y,
+            (Valor::FechaHora(x), Valor::FechaHora(y)) => x.instante == y.instante,
+            (Valor::Duracion(x), Valor::Duracion(y)) => x == y,
             (Valor::Nulo, Valor::Nulo) => true,
             _ => false,
         }
@@ -964,6 +1373,19 @@ impl Interpretador {
             (Valor::Flotante(a), Valor::Flotante(b)) => comparador(a, b),
             (Valor::Entero(a), Valor::Flotante(b)) => comparador(a as f64, b),
             (Valor::Flotante(a), Valor::Entero(b)) => comparador(a, b as f64),
+            (Valor::Decimal(a), Valor::Decimal(b)) => comparador(a.cmp(&b) as i8 as f64, 0.0),
+            (Valor::Decimal(a), Valor::Entero(b)) => {
+                comparador(a.cmp(&Decimal::from(b)) as i8 as f64, 0.0)
+            }
+            (Valor::Entero(a), Valor::Decimal(b)) => {
+                comparador(Decimal::from(a).cmp(&b) as i8 as f64, 0.0)
+            }
+            // Fechas y duraciones se comparan por orden: el signo del Ordering
+            // contra cero reproduce el operador pedido.
+            (Valor::FechaHora(a), Valor::FechaHora(b)) => {
+                comparador(a.instante.cmp(&b.instante) as i8 as f64, 0.0)
+            }
+            (Valor::Duracion(a), Valor::Duracion(b)) => comparador(a.cmp(&b) as i8 as f64, 0.0),
             _ => false,
         };
         Valor::Booleano(resultado)
@@ -1088,8 +1510,10 @@ impl Interpretador {
 
     #[async_recursion]
     async fn ejecutar_foreach(&mut self, foreach: ForEach) -> Option<Valor> {
-        let Valor::Lista(items) = self.evaluar_expresion(foreach.iterable).await else {
-            return None;
+        let items = match self.evaluar_expresion(foreach.iterable).await {
+            Valor::Lista(items) => items,
+            Valor::Diccionario(mapa) => mapa.into_keys().map(Valor::Texto).collect(),
+            _ => return None,
         };
 
         let anterior = std::mem::replace(&mut self.entorno_actual, Entorno::nuevo(None));
@@ -1210,7 +1634,10 @@ impl Interpretador {
                     resultado
                 }
             }
-            Valor::FuncionNativa(_, native_fn) => native_fn(argumentos),
+            Valor::FuncionNativa(nombre_nativa, native_fn) => {
+                self.llamar_funcion_nativa(&nombre_nativa, native_fn, argumentos)
+                    .await
+            }
             _ => {
                 eprintln!("'{}' no es una función", nombre);
                 Valor::Nulo
@@ -1218,48 +1645,271 @@ impl Interpretador {
         }
     }
 
-    async fn evaluar_instanciacion(&mut self, tipo: &str, argumentos: Vec<Expresion>) -> Valor {
-        let args = self.evaluar_argumentos(argumentos).await;
+    async fn llamar_funcion_nativa(
+        &mut self,
+        nombre: &str,
+        funcion: NativeFn,
+        argumentos: Vec<Valor>,
+    ) -> Valor {
+        // Las nativas solo cuentan como marco para el perfilador.
+        if let Some(perfil) = &mut self.perfil {
+            perfil.entrar(format!("Std.{}", nombre));
+        }
+        let resultado = self.ejecutar_funcion_nativa(funcion, argumentos).await;
+        if let Some(perfil) = &mut self.perfil {
+            perfil.salir();
+        }
+        resultado
+    }
 
-        if self.es_funcion_builtin_instanciacion(tipo) {
-            return self.ejecutar_builtin_instanciacion(tipo, args).await;
+    async fn ejecutar_funcion_nativa(
+        &mut self,
+        funcion: NativeFn,
+        mut argumentos: Vec<Valor>,
+    ) -> Valor {
+        let mut clase_destino = None;
+
+        match stdlib::nativa_del_interprete(funcion) {
+            Some(NativaDelInterprete::JsonStringify) if !argumentos.is_empty() => {
+                argumentos[0] = self.preparar_para_json(argumentos[0].clone()).await;
+            }
+            Some(NativaDelInterprete::JsonParse) => {
+                if let Some(Valor::Clase(clase)) = argumentos.get(1) {
+                    clase_destino = Some(clase.clone());
+                }
+            }
+            Some(NativaDelInterprete::Extender) => {
+                let resultado = sesion::con_sesion(&self.sesion, || funcion(argumentos.clone()));
+                if let Some(error) = stdlib::errores::tomar_error_pendiente() {
+                    self.estado_excepcion = Some(error);
+                    return resultado;
+                }
+                if let [Valor::Texto(tipo), Valor::Texto(nombre), metodo, ..] =
+                    argumentos.as_slice()
+                {
+                    self.metodos_tipo
+                        .entry(tipo.clone())
+                        .or_default()
+                        .insert(nombre.clone(), metodo.clone());
+                }
+                return resultado;
+            }
+            Some(NativaDelInterprete::RegexReplace)
+                if matches!(argumentos.get(2), Some(Valor::Funcion(_))) =>
+            {
+                return self.reemplazar_con_funcion(argumentos).await;
+            }
+            Some(NativaDelInterprete::AssertThrows)
+                if matches!(argumentos.first(), Some(Valor::Funcion(_))) =>
+            {
+                return self.afirmar_que_lanza(argumentos).await;
+            }
+            _ => {}
         }
 
-        if let Some(resultado) = self
-            .intentar_ejecutar_como_funcion(tipo, args.clone())
-            .await
-        {
+        let resultado = sesion::con_sesion(&self.sesion, || funcion(argumentos));
+        if let Some(error) = stdlib::errores::tomar_error_pendiente() {
+            self.estado_excepcion = Some(error);
             return resultado;
         }
 
-        self.crear_y_inicializar_instancia(tipo, args).await
+        match clase_destino {
+            Some(clase) => self.construir_desde_json(&clase, resultado).await,
+            None => resultado,
+        }
     }
 
-    async fn evaluar_argumentos(&mut self, argumentos: Vec<Expresion>) -> Vec<Valor> {
-        let mut valores = Vec::new();
-        for arg in argumentos {
-            valores.push(self.evaluar_expresion(arg).await);
+    // replace(regex, texto, funcion): el resultado de la función, convertido a
+    // texto, sustituye cada coincidencia.
+    async fn reemplazar_con_funcion(&mut self, argumentos: Vec<Valor>) -> Valor {
+        let coincidencias = stdlib::regex::coincidencias(&argumentos);
+        if let Some(error) = stdlib::errores::tomar_error_pendiente() {
+            self.estado_excepcion = Some(error);
+            return Valor::Nulo;
         }
-        valores
-    }
 
-    fn es_funcion_builtin_instanciacion(&self, tipo: &str) -> bool {
-        tipo == "tprint"
-    }
+        let (Some(coincidencias), Some(Valor::Texto(texto)), Some(Valor::Funcion(funcion))) =
+            (coincidencias, argumentos.get(1), argumentos.get(2))
+        else {
+            return Valor::Nulo;
+        };
 
-    async fn ejecutar_builtin_instanciacion(&mut self, tipo: &str, args: Vec<Valor>) -> Valor {
-        match tipo {
-            "tprint" => {
-                for arg in args {
-                    self.tprint(arg).await;
-                }
-                Valor::Nulo
+        let mut resultado = String::with_capacity(texto.len());
+        let mut ultimo = 0;
+        for (inicio, fin, datos) in coincidencias {
+            resultado.push_str(&texto[ultimo..inicio]);
+
+            self.valor_retorno = None;
+            let reemplazo = GestorFunciones::ejecutar_funcion(funcion, vec![datos], self).await;
+            self.valor_retorno = None;
+            if self.estado_excepcion.is_some() {
+                return Valor::Nulo;
             }
-            _ => Valor::Nulo,
+
+            resultado.push_str(&reemplazo.to_string());
+            ultimo = fin;
         }
+        resultado.push_str(&texto[ultimo..]);
+
+        Valor::Texto(resultado)
     }
 
-    async fn intentar_ejecutar_como_funcion(
+    // throws(funcion, mensaje?): la excepción que lance la función se captura
+    // y se devuelve; si no lanza ninguna, falla la aserción.
+    async fn afirmar_que_lanza(&mut self, argumentos: Vec<Valor>) -> Valor {
+        let Some(Valor::Funcion(funcion)) = argumentos.first() else {
+            return Valor::Nulo;
+        };
+
+        self.valor_retorno = None;
+        GestorFunciones::ejecutar_funcion(funcion, vec![], self).await;
+        self.valor_retorno = None;
+
+        if self
+            .estado_excepcion
+            .as_ref()
+            .is_some_and(|excepcion| stdlib::errores::codigo_salida(excepcion).is_some())
+        {
+            return Valor::Nulo;
+        }
+
+        let excepcion = self.estado_excepcion.take();
+        let resultado = stdlib::assert::comprobar_excepcion(excepcion, argumentos.get(1));
+        if let Some(error) = stdlib::errores::tomar_error_pendiente() {
+            self.estado_excepcion = Some(error);
+        }
+        resultado
+    }
+
+    // Convierte las instancias en datos serializables usando su método
+    // `to_json` cuando lo definen.
+    #[async_recursion]
+    async fn preparar_para_json(&mut self, valor: Valor) -> Valor {
+        match valor {
+            Valor::Objeto(instancia) => {
+                let metodo = self
+                    .gestor_clases
+                    .obtener_clase(&instancia.clase)
+                    .and_then(|clase| clase.obtener_metodo("to_json"))
+                    .cloned();
+
+                let datos = match metodo {
+                    Some(metodo) => self.ejecutar_metodo_clase(metodo, instancia, vec![]).await,
+                    None => Valor::Diccionario(instancia.propiedades.lock().unwrap().clone()),
+                };
+                self.preparar_para_json(datos).await
+            }
+            Valor::Lista(items) => {
+                let mut lista = Vec::with_capacity(items.len());
+                for item in items {
+                    lista.push(self.preparar_para_json(item).await);
+                }
+                Valor::Lista(lista)
+            }
+            Valor::Diccionario(mapa) => {
+                let mut resultado = IndexMap::with_capacity(mapa.len());
+                for (clave, item) in mapa {
+                    resultado.insert(clave, self.preparar_para_json(item).await);
+                }
+                Valor::Diccionario(resultado)
+            }
+            otro => otro,
+        }
+    }
+
+    // Una lista en la raíz produce una lista de instancias.
+    async fn construir_desde_json(&mut self, nombre_clase: &str, datos: Valor) -> Valor {
+        let Valor::Lista(items) = datos else {
+            return self.construir_instancia_json(nombre_clase, datos).await;
+        };
+
+        let mut lista = Vec::with_capacity(items.len());
+        for item in items {
+            lista.push(self.construir_instancia_json(nombre_clase, item).await);
+        }
+        Valor::Lista(lista)
+    }
+
+    // Si la clase define `from_json(datos)`, se invoca sobre una instancia
+    // nueva; si no, se copian las claves del objeto como propiedades.
+    async fn construir_instancia_json(&mut self, nombre_clase: &str, datos: Valor) -> Valor {
+        let Some(clase) = self.gestor_clases.obtener_clase(nombre_clase) else {
+            eprintln!("Clase '{}' no encontrada", nombre_clase);
+            return datos;
+        };
+
+        let instancia = clase.crear_instancia();
+
+        if let Some(metodo) = clase.obtener_metodo("from_json").cloned() {
+            let resultado = self
+                .ejecutar_metodo_clase(metodo, instancia.clone(), vec![datos])
+                .await;
+            return match resultado {
+                Valor::Objeto(_) => resultado,
+                _ => Valor::Objeto(instancia),
+            };
+        }
+
+        let Valor::Diccionario(mapa) = datos else {
+            let mensaje = format!(
+                "parse: no se puede construir '{}' a partir de {}",
+                nombre_clase, datos
+            );
+            let mut data = IndexMap::new();
+            data.insert("kind".to_string(), Valor::Texto("InvalidData".to_string()));
+            self.estado_excepcion = Some(stdlib::errores::crear_error(
+                &mensaje,
+                Valor::Diccionario(data),
+            ));
+            return Valor::Nulo;
+        };
+
+        instancia.propiedades.lock().unwrap().extend(mapa);
+        Valor::Objeto(instancia)
+    }
+
+    async fn evaluar_instanciacion(&mut self, tipo: &str, argumentos: Vec<Expresion>) -> Valor {
+        let args = self.evaluar_argumentos(argumentos).await;
+
+        if self.es_funcion_builtin_instanciacion(tipo) {
+            return self.ejecutar_builtin_instanciacion(tipo, args).await;
+        }
+
+        if let Some(resultado) = self
+            .intentar_ejecutar_como_funcion(tipo, args.clone())
+            .await
+        {
+            return resultado;
+        }
+
+        self.crear_y_inicializar_instancia(tipo, args).await
+    }
+
+    async fn evaluar_argumentos(&mut self, argumentos: Vec<Expresion>) -> Vec<Valor> {
+        let mut valores = Vec::new();
+        for arg in argumentos {
+            valores.push(self.evaluar_expresion(arg).await);
+        }
+        valores
+    }
+
+    fn es_funcion_builtin_instanciacion(&self, tipo: &str) -> bool {
+        tipo == "tprint"
+    }
+
+    async fn ejecutar_builtin_instanciacion(&mut self, tipo: &str, args: Vec<Valor>) -> Valor {
+        match tipo {
+            "tprint" => {
+                for arg in args {
+                    self.tprint(arg).await;
+                }
+                Valor::Nulo
+            }
+            _ => Valor::Nulo,
+        }
+    }
+
+    async fn intentar_ejecutar_como_funcion(
         &mut self,
         tipo: &str,
         args: Vec<Valor>,
@@ -1393,7 +2043,7 @@ impl Interpretador {
         Some(Valor::Funcion(funcion))
     }
 
-    fn acceder_clave_diccionario(&self, mapa: HashMap<String, Valor>, propiedad: &str) -> Valor {
+    fn acceder_clave_diccionario(&self, mapa: IndexMap<String, Valor>, propiedad: &str) -> Valor {
         mapa.get(propiedad).cloned().unwrap_or_else(|| {
             eprintln!("Clave '{}' no encontrada", propiedad);
             Valor::Nulo
@@ -1420,6 +2070,14 @@ impl Interpretador {
             Valor::Objeto(ref instancia) => self.acceder_propiedad_objeto(instancia, propiedad),
             Valor::Diccionario(mapa) => self.acceder_clave_diccionario(mapa, propiedad),
             Valor::Lista(ref items) if propiedad == "length" => Valor::Entero(items.len() as i64),
+            Valor::Texto(ref texto) if propiedad == "length" => {
+                Valor::Entero(texto.chars().count() as i64)
+            }
+            Valor::FechaHora(ref fecha) => fecha
+                .componente(propiedad)
+                .unwrap_or_else(|| self.error_acceso_propiedad_invalido(propiedad, &obj_valor)),
+            Valor::Duracion(ref duracion) => time::componente_duracion(duracion, propiedad)
+                .unwrap_or_else(|| self.error_acceso_propiedad_invalido(propiedad, &obj_valor)),
             _ => self.error_acceso_propiedad_invalido(propiedad, &obj_valor),
         }
     }
@@ -1430,20 +2088,61 @@ impl Interpretador {
 
         match (obj_valor, indice_valor) {
             (Valor::Lista(items), Valor::Entero(i)) => self.acceder_elemento_lista(items, i),
+            (Valor::Texto(texto), Valor::Entero(i)) => self.acceder_caracter_texto(&texto, i),
             _ => Valor::Nulo,
         }
     }
 
-    fn acceder_elemento_lista(&self, items: Vec<Valor>, indice: i64) -> Valor {
-        if !self.es_indice_valido(indice, items.len()) {
-            eprintln!("Índice fuera de rango: {}", indice);
-            return Valor::Nulo;
+    // Los textos se indexan por caracteres Unicode, no por bytes.
+    fn acceder_caracter_texto(&self, texto: &str, indice: i64) -> Valor {
+        match stdlib::str::caracter_de_texto(texto, indice) {
+            Some(caracter) => Valor::Texto(caracter.to_string()),
+            None => {
+                eprintln!("Índice fuera de rango: {}", indice);
+                Valor::Nulo
+            }
         }
+    }
 
-        items[indice as usize].clone()
+    async fn evaluar_rebanada(
+        &mut self,
+        objeto: Expresion,
+        inicio: Option<Box<Expresion>>,
+        fin: Option<Box<Expresion>>,
+    ) -> Valor {
+        let obj_valor = self.evaluar_expresion(objeto).await;
+        let inicio = self.evaluar_limite_rebanada(inicio).await;
+        let fin = self.evaluar_limite_rebanada(fin).await;
+
+        match obj_valor {
+            Valor::Texto(texto) => Valor::Texto(stdlib::str::rebanar_texto(&texto, inicio, fin)),
+            Valor::Lista(items) => {
+                let (inicio, fin) = stdlib::str::limites_rebanada(items.len(), inicio, fin);
+                Valor::Lista(items[inicio..fin].to_vec())
+            }
+            otro => {
+                eprintln!("No se puede rebanar {:?}", otro);
+                Valor::Nulo
+            }
+        }
     }
-    fn es_indice_valido(&self, indice: i64, longitud: usize) -> bool {
-        indice >= 0 && (indice as usize) < longitud
+
+    async fn evaluar_limite_rebanada(&mut self, limite: Option<Box<Expresion>>) -> Option<i64> {
+        let limite = limite?;
+        match self.evaluar_expresion(*limite).await {
+            Valor::Entero(n) => Some(n),
+            _ => None,
+        }
+    }
+
+    fn acceder_elemento_lista(&self, items: Vec<Valor>, indice: i64) -> Valor {
+        match stdlib::str::indice_absoluto(indice, items.len()) {
+            Some(posicion) => items[posicion].clone(),
+            None => {
+                eprintln!("Índice fuera de rango: {}", indice);
+                Valor::Nulo
+            }
+        }
     }
 
     #[async_recursion]
@@ -1455,35 +2154,19 @@ impl Interpretador {
     ) -> Valor {
         let obj_valor = self.evaluar_expresion(objeto).await;
 
-        if let Valor::Lista(ref items) = obj_valor {
-            match metodo {
-                "push" => {
-                    if argumentos.is_empty() {
-                        eprintln!("push() requiere al menos un argumento");
-                        return Valor::Nulo;
-                    }
-                    let mut nueva_lista = items.clone();
-                    for arg in argumentos {
-                        let valor = self.evaluar_expresion(arg).await;
-                        nueva_lista.push(valor);
-                    }
-                    return Valor::Lista(nueva_lista);
-                }
-                "pop" => {
-                    if !items.is_empty() {
-                        let mut nueva_lista = items.clone();
-                        nueva_lista.pop();
-                        return Valor::Lista(nueva_lista);
-                    }
-                    return Valor::Lista(vec![]);
-                }
-                "len" => {
-                    return Valor::Entero(items.len() as i64);
-                }
-                _ => {
-                    eprintln!("Método '{}' no existe para arreglos", metodo);
-                    return Valor::Nulo;
-                }
+        // En los diccionarios, una clave que guarda una función tiene prioridad
+        // sobre la tabla de métodos (así funcionan `Std.str.trim(...)` y los
+        // módulos importados).
+        let es_funcion_del_diccionario = matches!(
+            &obj_valor,
+            Valor::Diccionario(mapa) if mapa.contains_key(metodo)
+        );
+
+        if let Some(tipo) = stdlib::nombre_tipo(&obj_valor) {
+            if !es_funcion_del_diccionario {
+                return self
+                    .llamar_metodo_tipo(tipo, obj_valor, metodo, argumentos)
+                    .await;
             }
         }
 
@@ -1495,7 +2178,10 @@ impl Interpretador {
                 }
 
                 return match funcion_val {
-                    Valor::FuncionNativa(_, native_fn) => native_fn(args),
+                    Valor::FuncionNativa(nombre_nativa, native_fn) => {
+                        self.llamar_funcion_nativa(nombre_nativa, *native_fn, args)
+                            .await
+                    }
                     Valor::Clase(nombre_clase) => {
                         self.crear_y_inicializar_instancia(nombre_clase, args).await
                     }
@@ -1549,6 +2235,11 @@ impl Interpretador {
         let metodo_def = match clase.obtener_metodo(metodo) {
             Some(m) => m.clone(),
             None => {
+                if let Some(funcion) = stdlib::metodo_nativo(&instancia.clase, metodo) {
+                    return self
+                        .llamar_metodo_nativo(Valor::Objeto(instancia), funcion, argumentos)
+                        .await;
+                }
                 eprintln!(
                     "Método '{}' no encontrado en clase '{}'",
                     metodo, instancia.clase
@@ -1580,15 +2271,71 @@ impl Interpretador {
         }
     }
 
+    // Primero se buscan los métodos añadidos con `Std.extend` y después la
+    // tabla de la stdlib. En ambos casos el receptor es el primer argumento.
+    async fn llamar_metodo_tipo(
+        &mut self,
+        tipo: &str,
+        receptor: Valor,
+        metodo: &str,
+        argumentos: Vec<Expresion>,
+    ) -> Valor {
+        let extension = self
+            .metodos_tipo
+            .get(tipo)
+            .and_then(|metodos| metodos.get(metodo))
+            .cloned();
+
+        if let Some(Valor::Funcion(funcion)) = extension {
+            let mut args = vec![receptor];
+            args.extend(self.evaluar_argumentos(argumentos).await);
+
+            self.valor_retorno = None;
+            let resultado = GestorFunciones::ejecutar_funcion(&funcion, args, self).await;
+            self.valor_retorno = None;
+            return resultado;
+        }
+
+        match extension.or_else(|| stdlib::metodo_de_tipo(tipo, metodo)) {
+            Some(funcion) => {
+                self.llamar_metodo_nativo(receptor, funcion, argumentos)
+                    .await
+            }
+            None => {
+                eprintln!("Método '{}' no encontrado para el tipo {}", metodo, tipo);
+                Valor::Nulo
+            }
+        }
+    }
+
+    // Los métodos implementados en la stdlib reciben el receptor como primer
+    // argumento.
+    async fn llamar_metodo_nativo(
+        &mut self,
+        receptor: Valor,
+        funcion: Valor,
+        argumentos: Vec<Expresion>,
+    ) -> Valor {
+        let Valor::FuncionNativa(nombre, funcion) = funcion else {
+            return Valor::Nulo;
+        };
+
+        let mut args = vec![receptor];
+        args.extend(self.evaluar_argumentos(argumentos).await);
+        self.llamar_funcion_nativa(&nombre, funcion, args).await
+    }
+
     async fn ejecutar_metodo_clase(
         &mut self,
         metodo_def: umbral_parser::ast::Metodo,
         instancia: crate::runtime::valores::Instancia,
         args: Vec<Valor>,
     ) -> Valor {
+        self.entrar_marco(|| format!("{}.{}", instancia.clase, metodo_def.nombre));
         self.preparar_entorno_metodo(&instancia, &metodo_def.parametros, &args);
         let resultado = self.ejecutar_cuerpo_metodo(metodo_def.cuerpo).await;
         self.restaurar_entorno();
+        self.salir_marco();
         self.valor_retorno = None;
         resultado
     }
@@ -1621,25 +2368,41 @@ impl Interpretador {
             if let Some(valor) = self.ejecutar_sentencia(sentencia).await {
                 return valor;
             }
+            if self.estado_excepcion.is_some() {
+                break;
+            }
         }
         Valor::Nulo
     }
 
     async fn tprint(&mut self, valor: Valor) {
-        let salida = self.convertir_a_texto(valor).await;
-        println!("{}", salida);
+        let texto = self.convertir_a_texto(valor).await;
+        match &self.salida {
+            Some(Salida::Bufer(salida)) => {
+                let mut salida = salida.lock().unwrap();
+                salida.push_str(&texto);
+                salida.push('\n');
+            }
+            Some(Salida::Canal(canal)) => {
+                let _ = canal.send(texto);
+            }
+            None => println!("{}", texto),
+        }
     }
 
     #[async_recursion]
     async fn convertir_a_texto(&mut self, valor: Valor) -> String {
         match valor {
-            Valor::Texto(t) => self.procesar_texto(t).await,
+            Valor::Texto(t) => self.procesar_texto(t),
             Valor::Entero(e) => e.to_string(),
             Valor::Flotante(f) => f.to_string(),
+            Valor::Decimal(d) => d.to_string(),
             Valor::Booleano(b) => self.booleano_a_texto(b),
             Valor::Lista(l) => self.lista_a_texto(l).await,
             Valor::Diccionario(m) => self.diccionario_a_texto(m).await,
             Valor::Objeto(o) => o.to_string(),
+            Valor::FechaHora(f) => f.to_string(),
+            Valor::Duracion(d) => time::formatear_duracion(&d),
             Valor::Nulo => "null".to_string(),
             _ => "<valor no imprimible>".to_string(),
         }
@@ -1659,7 +2422,7 @@ impl Interpretador {
     }
 
     #[async_recursion]
-    async fn diccionario_a_texto(&mut self, mapa: HashMap<String, Valor>) -> String {
+    async fn diccionario_a_texto(&mut self, mapa: IndexMap<String, Valor>) -> String {
         let mut pares = Vec::new();
         for (k, v) in mapa {
             let val_str = self.convertir_a_texto(v).await;
@@ -1668,13 +2431,13 @@ impl Interpretador {
         format!("{{{}}}", pares.join(", "))
     }
 
-    async fn procesar_texto(&mut self, texto: String) -> String {
+    fn procesar_texto(&self, texto: String) -> String {
         if self.es_texto_multilinea(&texto) {
-            return self.procesar_texto_multilinea(texto).await;
+            return self.procesar_texto_multilinea(texto);
         }
 
         if self.es_texto_con_comillas_dobles(&texto) {
-            return self.procesar_texto_comillas_dobles(texto).await;
+            return self.procesar_texto_comillas_dobles(texto);
         }
 
         texto.trim_matches('\'').to_string()
@@ -1688,15 +2451,13 @@ impl Interpretador {
         texto.starts_with('"') && texto.ends_with('"')
     }
 
-    async fn procesar_texto_multilinea(&mut self, texto: String) -> String {
+    fn procesar_texto_multilinea(&self, texto: String) -> String {
         let contenido = texto.trim_matches('\'').to_string();
-        let normalizado = self.normalizar_multilinea(contenido);
-        self.procesar_interpolaciones(normalizado).await
+        self.normalizar_multilinea(contenido)
     }
 
-    async fn procesar_texto_comillas_dobles(&mut self, texto: String) -> String {
-        let contenido = texto.trim_matches('"').to_string();
-        self.procesar_interpolaciones(contenido).await
+    fn procesar_texto_comillas_dobles(&self, texto: String) -> String {
+        texto.trim_matches('"').to_string()
     }
 
     fn normalizar_multilinea(&self, texto: String) -> String {
@@ -1715,231 +2476,13 @@ impl Interpretador {
             .join("\n")
     }
 
-    #[async_recursion]
-    async fn procesar_interpolaciones(&mut self, texto: String) -> String {
-        let mut salida = String::new();
-        let mut chars = texto.chars().peekable();
-
-        while let Some(c) = chars.next() {
-            self.procesar_caracter_interpolacion(c, &mut chars, &mut salida)
-                .await;
-        }
-
-        salida
-    }
-
-    #[async_recursion]
-    async fn procesar_caracter_interpolacion(
-        &mut self,
-        caracter: char,
-        chars: &mut std::iter::Peekable<std::str::Chars>,
-        salida: &mut String,
-    ) {
-        if self.es_escape_interpolacion(caracter, chars) {
-            self.agregar_escape_interpolacion(chars, salida);
-            return;
-        }
-
-        if caracter != '&' {
-            salida.push(caracter);
-            return;
-        }
-
-        self.evaluar_y_agregar_interpolacion(chars, salida).await;
-    }
-
-    fn es_escape_interpolacion(
-        &self,
-        caracter: char,
-        chars: &mut std::iter::Peekable<std::str::Chars>,
-    ) -> bool {
-        caracter == '\\' && chars.peek() == Some(&'&')
-    }
-
-    fn agregar_escape_interpolacion(
-        &self,
-        chars: &mut std::iter::Peekable<std::str::Chars>,
-        salida: &mut String,
-    ) {
-        chars.next();
-        salida.push('&');
-    }
-
-    #[async_recursion]
-    async fn evaluar_y_agregar_interpolacion(
-        &mut self,
-        chars: &mut std::iter::Peekable<std::str::Chars>,
-        salida: &mut String,
-    ) {
-        let expr = self.leer_expresion_interpolacion(chars);
-
-        if expr.is_empty() {
-            salida.push('&');
-            return;
-        }
-
-        let valor = self.evaluar_interpolacion(expr).await;
-        salida.push_str(&valor);
-    }
-
-    fn leer_expresion_interpolacion(
-        &self,
-        chars: &mut std::iter::Peekable<std::str::Chars>,
-    ) -> String {
-        let mut expr = String::new();
-        let mut nivel_parentesis = 0;
-        let mut nivel_brackets = 0;
-
-        while let Some(&ch) = chars.peek() {
-            if self.procesar_parentesis_apertura(ch, &mut nivel_parentesis, &mut expr, chars) {
-                continue;
-            }
-
-            if ch == ')' {
-                if self.procesar_parentesis_cierre(ch, &mut nivel_parentesis, &mut expr, chars) {
-                    if nivel_brackets == 0 {
-                        break;
-                    }
-                }
-                continue;
-            }
-
-            if ch == '[' {
-                nivel_brackets += 1;
-                expr.push(chars.next().unwrap());
-                continue;
-            }
-
-            if ch == ']' {
-                if nivel_brackets > 0 {
-                    nivel_brackets -= 1;
-                    expr.push(chars.next().unwrap());
-                    continue;
-                }
-                break;
-            }
-
-            if nivel_parentesis > 0 {
-                expr.push(chars.next().unwrap());
-                continue;
-            }
-
-            if self.procesar_punto_acceso(ch, chars, &mut expr) {
-                continue;
-            }
-
-            if !self.es_caracter_expresion_basico(ch) {
-                break;
-            }
-
-            expr.push(chars.next().unwrap());
-        }
-        expr
-    }
-
-    fn procesar_parentesis_apertura(
-        &self,
-        caracter: char,
-        nivel: &mut i32,
-        expr: &mut String,
-        chars: &mut std::iter::Peekable<std::str::Chars>,
-    ) -> bool {
-        if caracter != '(' {
-            return false;
-        }
-
-        *nivel += 1;
-        expr.push(chars.next().unwrap());
-        true
-    }
-
-    fn procesar_parentesis_cierre(
-        &self,
-        caracter: char,
-        nivel: &mut i32,
-        expr: &mut String,
-        chars: &mut std::iter::Peekable<std::str::Chars>,
-    ) -> bool {
-        if caracter != ')' {
-            return false;
-        }
-
-        if *nivel == 0 {
-            return true;
-        }
-
-        *nivel -= 1;
-        expr.push(chars.next().unwrap());
-
-        if *nivel == 0 && chars.peek() != Some(&'.') {
-            return true;
-        }
-
-        false
-    }
-
-    fn procesar_punto_acceso(
-        &self,
-        caracter: char,
-        chars: &mut std::iter::Peekable<std::str::Chars>,
-        expr: &mut String,
-    ) -> bool {
-        if caracter != '.' {
-            return false;
-        }
-
-        let mut temp_chars = chars.clone();
-        temp_chars.next();
-
-        let es_acceso_valido = temp_chars
-            .peek()
-            .map(|&ch| ch.is_alphanumeric() || ch == '_')
-            .unwrap_or(false);
-
-        if !es_acceso_valido {
-            return false;
-        }
-
-        expr.push(chars.next().unwrap());
-        true
-    }
-
-    fn es_caracter_expresion_basico(&self, c: char) -> bool {
-        c.is_alphanumeric() || c == '_'
-    }
-
-    #[async_recursion]
-    async fn evaluar_interpolacion(&mut self, expr: String) -> String {
-        let valor = self.parsear_expresion_interpolacion(expr).await;
-        self.convertir_a_texto(valor).await
-    }
-
-    async fn parsear_expresion_interpolacion(&mut self, expr: String) -> Valor {
-        if self.es_literal_numerico(&expr) {
-            return self.parsear_entero(&expr);
-        }
-
-        if expr.contains('(') {
-            return self.resolver_llamada_metodo_interpolacion(&expr).await;
-        }
-
-        if expr.contains('.') || expr.contains('[') {
-            return self.resolver_acceso_encadenado(&expr).await;
-        }
-
-        let nombre_variable = if expr == "th" { "__this__" } else { &expr };
-
-        self.entorno_actual
-            .obtener(nombre_variable)
-            .unwrap_or(Valor::Nulo)
-    }
-
     #[async_recursion]
     async fn ejecutar_try_catch(&mut self, stmt: TryCatch) -> Option<Valor> {
         self.ejecutar_bloque(stmt.bloque_try).await;
 
         if let Some(error) = self.estado_excepcion.take() {
-            if let Some(catch) = stmt.bloque_catch {
+            let capturable = stdlib::errores::codigo_salida(&error).is_none();
+            if let Some(catch) = stmt.bloque_catch.filter(|_| capturable) {
                 let coincide = if let Some(ref tipo_error) = catch.tipo {
                     match &error {
                         Valor::Objeto(inst) => inst.clase == *tipo_error,
@@ -2008,320 +2551,4 @@ impl Interpretador {
         self.estado_excepcion = Some(valor);
         None
     }
-
-    fn es_literal_numerico(&self, expr: &str) -> bool {
-        expr.chars().all(|c| c.is_digit(10))
-    }
-
-    fn parsear_entero(&self, expr: &str) -> Valor {
-        Valor::Entero(expr.parse::<i64>().unwrap_or(0))
-    }
-
-    #[async_recursion]
-    async fn resolver_llamada_metodo_interpolacion(&mut self, expr: &str) -> Valor {
-        let partes: Vec<&str> = expr.split('.').collect();
-        let primer_parte = partes[0];
-
-        let mut valor_actual = if primer_parte.contains('(') {
-            let (nombre_func, args_str) = self.extraer_metodo_argumentos(primer_parte);
-            let args = self.parsear_argumentos_interpolacion(args_str);
-
-            if let Some(res) = self.intentar_ejecutar_como_funcion(nombre_func, args).await {
-                res
-            } else {
-                return Valor::Nulo;
-            }
-        } else {
-            let primer_elemento = self.obtener_nombre_inicial(primer_parte);
-            match self.entorno_actual.obtener(primer_elemento) {
-                Some(v) => v,
-                None => return Valor::Nulo,
-            }
-        };
-
-        for &parte in &partes[1..] {
-            valor_actual = self.procesar_parte_cadena(valor_actual, parte).await;
-
-            if matches!(valor_actual, Valor::Nulo) {
-                break;
-            }
-        }
-
-        valor_actual
-    }
-
-    fn obtener_nombre_inicial<'a>(&self, parte: &'a str) -> &'a str {
-        if parte == "th" {
-            "__this__"
-        } else {
-            parte
-        }
-    }
-
-    #[async_recursion]
-    async fn procesar_parte_cadena(&mut self, valor: Valor, parte: &str) -> Valor {
-        if !parte.contains('(') {
-            return self.navegar_propiedad(valor, parte);
-        }
-
-        let (metodo, args_str) = self.extraer_metodo_argumentos(parte);
-        self.ejecutar_metodo_interpolacion(valor, metodo, args_str)
-            .await
-    }
-
-    fn extraer_metodo_argumentos<'a>(&self, parte: &'a str) -> (&'a str, &'a str) {
-        let metodo_fin = parte.find('(').unwrap_or(parte.len());
-        let metodo = &parte[..metodo_fin];
-
-        let args_str = if parte.contains('(') && parte.contains(')') {
-            let inicio = parte.find('(').unwrap() + 1;
-            let fin = parte.rfind(')').unwrap();
-            &parte[inicio..fin]
-        } else {
-            ""
-        };
-
-        (metodo, args_str)
-    }
-
-    #[async_recursion]
-    async fn ejecutar_metodo_interpolacion(
-        &mut self,
-        valor: Valor,
-        metodo: &str,
-        args_str: &str,
-    ) -> Valor {
-        match valor {
-            Valor::Lista(ref items) => self.ejecutar_metodo_lista(items, metodo, args_str),
-            Valor::Objeto(ref instancia) => {
-                let argumentos = self.parsear_argumentos_interpolacion(args_str);
-                self.ejecutar_metodo_objeto(instancia, metodo, argumentos)
-                    .await
-            }
-            _ => Valor::Nulo,
-        }
-    }
-
-    fn ejecutar_metodo_lista(&mut self, items: &[Valor], metodo: &str, args_str: &str) -> Valor {
-        match metodo {
-            "len" => Valor::Entero(items.len() as i64),
-            "push" => self.ejecutar_push_lista(items, args_str),
-            "pop" => self.ejecutar_pop_lista(items),
-            _ => Valor::Nulo,
-        }
-    }
-
-    fn ejecutar_push_lista(&mut self, items: &[Valor], args_str: &str) -> Valor {
-        if args_str.is_empty() {
-            return Valor::Lista(items.to_vec());
-        }
-
-        let mut nueva_lista = items.to_vec();
-        let arg_valor = self.parsear_argumento_simple(args_str);
-        nueva_lista.push(arg_valor);
-        Valor::Lista(nueva_lista)
-    }
-
-    fn ejecutar_pop_lista(&self, items: &[Valor]) -> Valor {
-        if items.is_empty() {
-            return Valor::Lista(vec![]);
-        }
-
-        let mut nueva_lista = items.to_vec();
-        nueva_lista.pop();
-        Valor::Lista(nueva_lista)
-    }
-
-    fn parsear_argumentos_interpolacion(&mut self, args_str: &str) -> Vec<Valor> {
-        if args_str.is_empty() {
-            return vec![];
-        }
-
-        args_str
-            .split(',')
-            .map(|s| self.parsear_argumento_simple(s.trim()))
-            .collect()
-    }
-
-    #[async_recursion]
-    async fn ejecutar_metodo_objeto(
-        &mut self,
-        instancia: &crate::runtime::valores::Instancia,
-        metodo: &str,
-        args: Vec<Valor>,
-    ) -> Valor {
-        let clase = match self.gestor_clases.obtener_clase(&instancia.clase) {
-            Some(c) => c,
-            None => return Valor::Nulo,
-        };
-
-        let metodo_def = match clase.obtener_metodo(metodo) {
-            Some(m) => m.clone(),
-            None => return Valor::Nulo,
-        };
-
-        let anterior = std::mem::replace(&mut self.entorno_actual, Entorno::nuevo(None));
-        self.entorno_actual = Entorno::nuevo(Some(anterior));
-
-        self.entorno_actual
-            .definir_variable("__this__".to_string(), Valor::Objeto(instancia.clone()));
-
-        for (i, param) in metodo_def.parametros.iter().enumerate() {
-            if let Some(valor) = args.get(i) {
-                self.entorno_actual
-                    .definir_variable(param.nombre.clone(), valor.clone());
-            }
-        }
-
-        self.valor_retorno = None;
-        for sentencia in metodo_def.cuerpo {
-            if let Some(valor) = self.ejecutar_sentencia(sentencia).await {
-                if let Some(parent) = self.entorno_actual.parent.take() {
-                    self.entorno_actual = *parent;
-                }
-                self.valor_retorno = None;
-                return valor;
-            }
-        }
-
-        if let Some(parent) = self.entorno_actual.parent.take() {
-            self.entorno_actual = *parent;
-        }
-        self.valor_retorno = None;
-        Valor::Nulo
-    }
-
-    fn parsear_argumento_simple(&self, arg: &str) -> Valor {
-        let arg_limpio = arg.trim();
-
-        if let Ok(n) = arg_limpio.parse::<i64>() {
-            return Valor::Entero(n);
-        }
-
-        if let Ok(f) = arg_limpio.parse::<f64>() {
-            return Valor::Flotante(f);
-        }
-
-        if (arg_limpio.starts_with('"') && arg_limpio.ends_with('"'))
-            || (arg_limpio.starts_with('\'') && arg_limpio.ends_with('\''))
-        {
-            return Valor::Texto(arg_limpio[1..arg_limpio.len() - 1].to_string());
-        }
-
-        if arg_limpio == "true" {
-            return Valor::Booleano(true);
-        }
-        if arg_limpio == "false" {
-            return Valor::Booleano(false);
-        }
-
-        self.entorno_actual
-            .obtener(arg_limpio)
-            .unwrap_or(Valor::Nulo)
-    }
-
-    async fn resolver_acceso_encadenado(&mut self, expr: &str) -> Valor {
-        let partes = self.tokenizar_encadenamiento(expr);
-        if partes.is_empty() {
-            return Valor::Nulo;
-        }
-
-        let primer_elemento = if partes[0] == "th" {
-            "__this__"
-        } else {
-            partes[0].as_str()
-        };
-
-        let Some(mut valor_actual) = self.entorno_actual.obtener(primer_elemento) else {
-            return Valor::Nulo;
-        };
-
-        for parte in &partes[1..] {
-            valor_actual = if parte.starts_with('[') && parte.ends_with(']') {
-                self.evaluar_acceso_array_interpolacion(valor_actual, parte)
-            } else {
-                self.navegar_propiedad(valor_actual, parte)
-            };
-
-            if matches!(valor_actual, Valor::Nulo) {
-                break;
-            }
-        }
-
-        valor_actual
-    }
-
-    fn tokenizar_encadenamiento(&self, expr: &str) -> Vec<String> {
-        let mut partes = Vec::new();
-        let mut parte_actual = String::new();
-        let mut chars = expr.chars().peekable();
-
-        while let Some(c) = chars.next() {
-            if c == '.' {
-                if !parte_actual.is_empty() {
-                    partes.push(parte_actual.clone());
-                    parte_actual.clear();
-                }
-                continue;
-            }
-
-            if c == '[' {
-                if !parte_actual.is_empty() {
-                    partes.push(parte_actual.clone());
-                    parte_actual.clear();
-                }
-
-                let mut indice = String::new();
-                indice.push('[');
-                while let Some(&ic) = chars.peek() {
-                    if ic == ']' {
-                        chars.next();
-                        indice.push(']');
-                        break;
-                    }
-                    indice.push(chars.next().unwrap());
-                }
-                partes.push(indice);
-                continue;
-            }
-
-            parte_actual.push(c);
-        }
-
-        if !parte_actual.is_empty() {
-            partes.push(parte_actual);
-        }
-
-        partes
-    }
-
-    fn evaluar_acceso_array_interpolacion(&self, valor_actual: Valor, parte: &str) -> Valor {
-        let indice_str = &parte[1..parte.len() - 1];
-        let indice_valor = self.parsear_argumento_simple(indice_str);
-
-        let Valor::Lista(items) = valor_actual else {
-            return Valor::Nulo;
-        };
-
-        if let Valor::Entero(idx) = indice_valor {
-            return self.acceder_elemento_lista(items, idx);
-        }
-
-        Valor::Nulo
-    }
-
-    fn navegar_propiedad(&self, valor: Valor, propiedad: &str) -> Valor {
-        match valor {
-            Valor::Objeto(ref inst) => {
-                if let Ok(props) = inst.propiedades.lock() {
-                    props.get(propiedad).cloned().unwrap_or(Valor::Nulo)
-                } else {
-                    Valor::Nulo
-                }
-            }
-            Valor::Diccionario(ref mapa) => mapa.get(propiedad).cloned().unwrap_or(Valor::Nulo),
-            Valor::Lista(ref items) if propiedad == "length" => Valor::Entero(items.len() as i64),
-            _ => Valor::Nulo,
-        }
-    }
 }
@@ -1,14 +1,21 @@
 pub mod clases;
+pub mod depuracion;
 pub mod entorno;
 pub mod enums;
 pub mod errores;
+pub mod exportaciones;
 pub mod funciones;
 pub mod interfaces;
 pub mod interpretador;
+pub mod modulos;
+pub mod paquetes;
+pub mod perfil;
 pub mod stdlib;
 pub mod valores;
 
+use crate::runtime::funciones::GestorFunciones;
 use crate::runtime::interpretador::Interpretador;
+use crate::runtime::valores::Valor;
 use std::path::PathBuf;
 use umbral_parser::ast::Programa;
 
@@ -27,7 +34,16 @@ impl Runtime {
         self.interpretador.establecer_directorio_base(ruta);
     }
 
-    pub async fn ejecutar(&mut self, programa: Programa) {
+    pub fn establecer_archivo(&mut self, ruta: PathBuf) {
+        self.interpretador.establecer_archivo_actual(ruta);
+    }
+
+    pub fn establecer_argumentos(&mut self, argumentos: Vec<String>) {
+        self.interpretador.establecer_argumentos(argumentos);
+    }
+
+    /// Ejecuta el programa y devuelve la excepción que quedó sin capturar.
+    pub async fn ejecutar_programa(&mut self, programa: Programa) -> Option<Valor> {
         for sentencia in programa.sentencias {
             if self
                 .interpretador
@@ -37,6 +53,40 @@ impl Runtime {
             {
                 break;
             }
+
+            if let Some(excepcion) = self.interpretador.estado_excepcion.take() {
+                return Some(excepcion);
+            }
+        }
+        None
+    }
+
+    /// Llama sin argumentos a la función global `nombre`. Las asíncronas se
+    /// ejecutan en la misma tarea para no perder sus excepciones.
+    pub async fn llamar_funcion(&mut self, nombre: &str) -> Result<Valor, Valor> {
+        let Some(Valor::Funcion(funcion)) = self.interpretador.entorno_actual.obtener(nombre)
+        else {
+            let mensaje = format!("'{}' no es una función", nombre);
+            return Err(stdlib::errores::crear_error(&mensaje, Valor::Nulo));
+        };
+
+        self.interpretador.valor_retorno = None;
+        let resultado =
+            GestorFunciones::ejecutar_funcion(&funcion, vec![], &mut self.interpretador).await;
+        self.interpretador.valor_retorno = None;
+
+        match self.interpretador.estado_excepcion.take() {
+            Some(excepcion) => Err(excepcion),
+            None => Ok(resultado),
+        }
+    }
+}
+
+pub fn describir_excepcion(excepcion: &Valor) -> String {
+    if let Valor::Objeto(instancia) = excepcion {
+        if let Some(mensaje) = instancia.propiedades.lock().unwrap().get("mensaje") {
+            return format!("{}: {}", instancia.clase, mensaje);
         }
     }
+    excepcion.to_string()
 }
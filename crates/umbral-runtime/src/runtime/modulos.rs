@@ -0,0 +1,120 @@
+use crate::runtime::paquetes::{self, Proyecto};
+use std::path::{Path, PathBuf};
+
+/// Motivo por el que no se encontró el archivo de un `origin`.
+#[derive(Debug, Clone)]
+pub enum ErrorResolucion {
+    /// Ruta de archivo: se probaron estas rutas y ninguna existe.
+    RutaNoEncontrada(Vec<PathBuf>),
+    /// Nombre de paquete que no está instalado en ningún `modules_ump`.
+    PaqueteNoEncontrado,
+    /// El manifiesto del proyecto no se pudo leer o no resuelve la dependencia.
+    Manifiesto(String),
+}
+
+/// Busca el archivo al que apunta `origin 'ruta'` desde un módulo situado en
+/// `directorio_modulo`. Las rutas con `./` o `../` son relativas al módulo;
+/// el resto de rutas de archivo, al directorio base del programa. Un nombre
+/// sin barras ni extensión es un paquete UMP: primero se consulta el
+/// manifiesto y después los `modules_ump` de los directorios superiores.
+pub fn resolver(
+    ruta: &str,
+    directorio_modulo: &Path,
+    directorio_base: &Path,
+) -> Result<PathBuf, ErrorResolucion> {
+    if !es_ruta_relativa(ruta) {
+        return resolver_paquete(ruta, directorio_modulo);
+    }
+
+    let ruta_original = PathBuf::from(ruta);
+    let rutas_posibles = if es_ruta_explicita(ruta) {
+        rutas_explicitas(directorio_modulo, &ruta_original)
+    } else {
+        rutas_relativas(directorio_base, &ruta_original)
+    };
+
+    primera_existente(rutas_posibles)
+}
+
+pub fn es_ruta_relativa(ruta: &str) -> bool {
+    ruta.contains('/') || ruta.starts_with("./") || ruta.starts_with("../") || ruta.ends_with(".um")
+}
+
+fn es_ruta_explicita(ruta: &str) -> bool {
+    ruta.starts_with("./") || ruta.starts_with("../")
+}
+
+fn rutas_explicitas(directorio: &Path, ruta: &Path) -> Vec<PathBuf> {
+    vec![
+        directorio.join(ruta),
+        directorio.join(ruta).join("main.um"),
+        directorio.join(ruta).join("index.um"),
+    ]
+}
+
+fn rutas_relativas(directorio_base: &Path, ruta: &Path) -> Vec<PathBuf> {
+    let modulos = directorio_base.join(paquetes::DIRECTORIO_MODULOS);
+    vec![
+        directorio_base.join(ruta),
+        modulos.join(ruta),
+        modulos.join(ruta).join("main.um"),
+        modulos.join(ruta).join("index.um"),
+    ]
+}
+
+fn primera_existente(rutas: Vec<PathBuf>) -> Result<PathBuf, ErrorResolucion> {
+    match rutas.iter().find(|ruta| ruta.is_file()) {
+        Some(ruta) => Ok(ruta.clone()),
+        None => Err(ErrorResolucion::RutaNoEncontrada(rutas)),
+    }
+}
+
+fn resolver_paquete(nombre: &str, directorio_modulo: &Path) -> Result<PathBuf, ErrorResolucion> {
+    if let Some(ruta) = resolver_con_manifiesto(nombre, directorio_modulo)? {
+        return primera_existente(vec![ruta]);
+    }
+
+    let mut directorio = directorio_modulo.to_path_buf();
+    loop {
+        if let Some(ruta) = entrada_de_paquete(&directorio, nombre) {
+            return Ok(ruta);
+        }
+
+        if !directorio.pop() {
+            return Err(ErrorResolucion::PaqueteNoEncontrado);
+        }
+    }
+}
+
+fn resolver_con_manifiesto(
+    nombre: &str,
+    directorio_modulo: &Path,
+) -> Result<Option<PathBuf>, ErrorResolucion> {
+    let proyecto =
+        Proyecto::buscar_raiz(directorio_modulo).map_err(ErrorResolucion::Manifiesto)?;
+    let Some(proyecto) = proyecto else {
+        return Ok(None);
+    };
+
+    if !proyecto.declara(nombre, directorio_modulo) {
+        return Ok(None);
+    }
+
+    proyecto
+        .resolver(nombre, directorio_modulo)
+        .map(Some)
+        .map_err(ErrorResolucion::Manifiesto)
+}
+
+fn entrada_de_paquete(directorio: &Path, nombre: &str) -> Option<PathBuf> {
+    let directorio_paquete = directorio.join(paquetes::DIRECTORIO_MODULOS).join(nombre);
+
+    if !directorio_paquete.is_dir() {
+        return None;
+    }
+
+    let manifiesto = paquetes::Manifiesto::leer(&directorio_paquete)
+        .ok()
+        .flatten();
+    paquetes::buscar_entrada(&directorio_paquete, manifiesto.as_ref())
+}
@@ -0,0 +1,611 @@
+use semver::{Version, VersionReq};
+use std::collections::{BTreeMap, HashSet};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+pub const ARCHIVO_MANIFIESTO: &str = "ump.json";
+pub const ARCHIVO_BLOQUEO: &str = "ump.lock";
+pub const DIRECTORIO_MODULOS: &str = "modules_ump";
+
+const ENTRADAS_POR_DEFECTO: [&str; 3] = ["src/main.um", "main.um", "index.um"];
+
+#[derive(Debug, Clone)]
+pub struct Manifiesto {
+    pub nombre: String,
+    pub version: Version,
+    pub entrada: Option<String>,
+    pub dependencias: BTreeMap<String, VersionReq>,
+    /// Nivel de cada regla de `umbral lint` (`"off"`, `"warn"` o `"error"`).
+    pub lint: BTreeMap<String, String>,
+}
+
+impl Manifiesto {
+    pub fn leer(directorio: &Path) -> Result<Option<Self>, String> {
+        let ruta = directorio.join(ARCHIVO_MANIFIESTO);
+        let Some(texto) = leer_si_existe(&ruta)? else {
+            return Ok(None);
+        };
+
+        Self::desde_texto(&texto)
+            .map(Some)
+            .map_err(|e| format!("{}: {}", ruta.display(), e))
+    }
+
+    pub fn desde_texto(texto: &str) -> Result<Self, String> {
+        let json = parsear_objeto_json(texto)?;
+
+        let nombre =
+            obtener_texto(&json, "name")?.ok_or_else(|| "falta el campo 'name'".to_string())?;
+        let version = obtener_texto(&json, "version")?
+            .ok_or_else(|| "falta el campo 'version'".to_string())?;
+        let version = parsear_version(&version)?;
+        let entrada = obtener_texto(&json, "main")?;
+
+        let mut dependencias = BTreeMap::new();
+        for (dependencia, restriccion) in obtener_tabla(&json, "dependencies", "la versión")? {
+            dependencias.insert(dependencia, parsear_restriccion(&restriccion)?);
+        }
+
+        let lint = obtener_tabla(&json, "lint", "el nivel")?
+            .into_iter()
+            .collect();
+
+        Ok(Self {
+            nombre,
+            version,
+            entrada,
+            dependencias,
+            lint,
+        })
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct PaqueteBloqueado {
+    pub version: Version,
+    pub dependencias: BTreeMap<String, Version>,
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct ArchivoBloqueo {
+    pub paquetes: BTreeMap<String, PaqueteBloqueado>,
+}
+
+impl ArchivoBloqueo {
+    pub fn leer(directorio: &Path) -> Result<Option<Self>, String> {
+        let ruta = directorio.join(ARCHIVO_BLOQUEO);
+        let Some(texto) = leer_si_existe(&ruta)? else {
+            return Ok(None);
+        };
+
+        Self::desde_texto(&texto)
+            .map(Some)
+            .map_err(|e| format!("{}: {}", ruta.display(), e))
+    }
+
+    pub fn desde_texto(texto: &str) -> Result<Self, String> {
+        let json = parsear_objeto_json(texto)?;
+        let mut paquetes = BTreeMap::new();
+
+        let Some(entradas) = json.get("packages") else {
+            return Ok(Self { paquetes });
+        };
+
+        let serde_json::Value::Object(entradas) = entradas else {
+            return Err("el campo 'packages' debe ser un objeto".to_string());
+        };
+
+        for (nombre, entrada) in entradas {
+            let serde_json::Value::Object(entrada) = entrada else {
+                return Err(format!("la entrada '{}' debe ser un objeto", nombre));
+            };
+
+            let version = obtener_texto(entrada, "version")?
+                .ok_or_else(|| format!("falta 'version' en la entrada '{}'", nombre))?;
+
+            let mut dependencias = BTreeMap::new();
+            for (dependencia, version_exacta) in obtener_tabla(entrada, "dependencies", "la versión")? {
+                dependencias.insert(dependencia, parsear_version(&version_exacta)?);
+            }
+
+            paquetes.insert(
+                nombre.clone(),
+                PaqueteBloqueado {
+                    version: parsear_version(&version)?,
+                    dependencias,
+                },
+            );
+        }
+
+        Ok(Self { paquetes })
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct Proyecto {
+    pub raiz: PathBuf,
+    pub manifiesto: Manifiesto,
+    pub bloqueo: Option<ArchivoBloqueo>,
+}
+
+#[derive(Debug, Clone)]
+pub struct NodoDependencia {
+    pub nombre: String,
+    pub requerida: VersionReq,
+    pub instalada: Option<Version>,
+    pub bloqueada: Option<Version>,
+    pub conflicto: Option<String>,
+    pub dependencias: Vec<NodoDependencia>,
+}
+
+impl Proyecto {
+    pub fn buscar(desde: &Path) -> Result<Option<Self>, String> {
+        let mut directorio = desde.to_path_buf();
+
+        loop {
+            if let Some(manifiesto) = Manifiesto::leer(&directorio)? {
+                let bloqueo = ArchivoBloqueo::leer(&directorio)?;
+                return Ok(Some(Self {
+                    raiz: directorio,
+                    manifiesto,
+                    bloqueo,
+                }));
+            }
+
+            if !directorio.pop() {
+                return Ok(None);
+            }
+        }
+    }
+
+    /// Proyecto al que pertenece `desde`. Dentro de un paquete instalado es
+    /// el que contiene el `modules_ump` más externo, no el propio paquete:
+    /// todas las importaciones se comprueban con el `ump.lock` de la raíz.
+    pub fn buscar_raiz(desde: &Path) -> Result<Option<Self>, String> {
+        let inicio = desde
+            .ancestors()
+            .filter(|directorio| directorio.file_name() == Some(DIRECTORIO_MODULOS.as_ref()))
+            .last()
+            .and_then(Path::parent)
+            .unwrap_or(desde);
+        Self::buscar(inicio)
+    }
+
+    /// Si `nombre` es una dependencia conocida para un módulo situado en
+    /// `desde`: la declara el proyecto, su `ump.lock` o el paquete que
+    /// importa.
+    pub fn declara(&self, nombre: &str, desde: &Path) -> bool {
+        self.manifiesto.dependencias.contains_key(nombre)
+            || self
+                .bloqueo
+                .as_ref()
+                .is_some_and(|b| b.paquetes.contains_key(nombre))
+            || paquete_contenedor(desde).is_some_and(|(_, manifiesto)| {
+                manifiesto.is_some_and(|m| m.dependencias.contains_key(nombre))
+            })
+    }
+
+    /// Entrada del paquete `nombre` importado desde un módulo situado en
+    /// `desde`, tras comprobar su versión contra el manifiesto de quien lo
+    /// importa y el `ump.lock` del proyecto.
+    pub fn resolver(&self, nombre: &str, desde: &Path) -> Result<PathBuf, String> {
+        let directorio = ubicar_instalacion(desde, nombre).ok_or_else(|| {
+            format!(
+                "La dependencia '{}' está declarada en {} pero no está instalada en {}.",
+                nombre, ARCHIVO_MANIFIESTO, DIRECTORIO_MODULOS
+            )
+        })?;
+
+        let manifiesto = Manifiesto::leer(&directorio)?;
+        let instalada = manifiesto.as_ref().map(|m| m.version.clone());
+
+        let importador = paquete_contenedor(desde);
+        let (importador, requerida) = match &importador {
+            Some((paquete, manifiesto)) => (
+                Some(paquete.as_str()),
+                manifiesto.as_ref().and_then(|m| m.dependencias.get(nombre)),
+            ),
+            None => (None, self.manifiesto.dependencias.get(nombre)),
+        };
+
+        if let Some(conflicto) =
+            self.verificar_version(nombre, requerida, importador, instalada.as_ref())
+        {
+            return Err(conflicto);
+        }
+
+        buscar_entrada(&directorio, manifiesto.as_ref()).ok_or_else(|| {
+            format!(
+                "El paquete '{}' no tiene punto de entrada en {}.",
+                nombre,
+                directorio.display()
+            )
+        })
+    }
+
+    fn version_bloqueada(&self, nombre: &str) -> Option<&Version> {
+        self.bloqueo
+            .as_ref()
+            .and_then(|b| b.paquetes.get(nombre))
+            .map(|p| &p.version)
+    }
+
+    // Versión de `nombre` que el `ump.lock` anota para las dependencias de
+    // `importador`.
+    fn version_bloqueada_para(&self, importador: &str, nombre: &str) -> Option<&Version> {
+        self.bloqueo
+            .as_ref()
+            .and_then(|b| b.paquetes.get(importador))
+            .and_then(|p| p.dependencias.get(nombre))
+    }
+
+    /// Comprueba la versión de `nombre` que requiere quien lo importa (el
+    /// proyecto o el paquete `importador`) contra la instalada y el
+    /// `ump.lock` de la raíz. Lo usan tanto el intérprete como `deps`.
+    fn verificar_version(
+        &self,
+        nombre: &str,
+        requerida: Option<&VersionReq>,
+        importador: Option<&str>,
+        instalada: Option<&Version>,
+    ) -> Option<String> {
+        let bloqueada = self.version_bloqueada(nombre);
+
+        let esperada = importador.and_then(|importador| {
+            self.version_bloqueada_para(importador, nombre)
+                .map(|version| (importador, version))
+        });
+        if let Some((importador, esperada)) = esperada {
+            if bloqueada != Some(esperada) {
+                return Some(format!(
+                    "Conflicto de versiones en '{}': {} anota {} para '{}' pero fija {}.",
+                    nombre,
+                    ARCHIVO_BLOQUEO,
+                    esperada,
+                    importador,
+                    bloqueada.map_or("ninguna".to_string(), Version::to_string)
+                ));
+            }
+        }
+
+        if let (Some(bloqueada), Some(requerida)) = (bloqueada, requerida) {
+            if !requerida.matches(bloqueada) {
+                return Some(format!(
+                    "Conflicto de versiones en '{}': {} fija {} pero {} requiere {}.",
+                    nombre, ARCHIVO_BLOQUEO, bloqueada, ARCHIVO_MANIFIESTO, requerida
+                ));
+            }
+        }
+
+        let Some(instalada) = instalada else {
+            if bloqueada.is_none() && requerida.is_none() {
+                return None;
+            }
+            return Some(format!(
+                "No se puede comprobar la versión de '{}': el paquete instalado no tiene {}.",
+                nombre, ARCHIVO_MANIFIESTO
+            ));
+        };
+
+        if let Some(bloqueada) = bloqueada {
+            if instalada != bloqueada {
+                return Some(format!(
+                    "Conflicto de versiones en '{}': instalada {} pero {} fija {}.",
+                    nombre, instalada, ARCHIVO_BLOQUEO, bloqueada
+                ));
+            }
+            return None;
+        }
+
+        match requerida {
+            Some(requerida) if !requerida.matches(instalada) => Some(format!(
+                "Conflicto de versiones en '{}': instalada {} pero {} requiere {}.",
+                nombre, instalada, ARCHIVO_MANIFIESTO, requerida
+            )),
+            _ => None,
+        }
+    }
+
+    pub fn arbol_dependencias(&self) -> Vec<NodoDependencia> {
+        let mut visitados = HashSet::new();
+        visitados.insert(self.manifiesto.nombre.clone());
+        self.construir_nodos(&self.raiz, &self.manifiesto, None, &mut visitados)
+    }
+
+    fn construir_nodos(
+        &self,
+        directorio: &Path,
+        manifiesto: &Manifiesto,
+        importador: Option<&str>,
+        visitados: &mut HashSet<String>,
+    ) -> Vec<NodoDependencia> {
+        let mut nodos = Vec::new();
+
+        for (nombre, requerida) in &manifiesto.dependencias {
+            let instalacion = ubicar_instalacion(directorio, nombre);
+            let manifiesto_dep = instalacion
+                .as_ref()
+                .and_then(|dir| Manifiesto::leer(dir).ok().flatten());
+            let instalada = manifiesto_dep.as_ref().map(|m| m.version.clone());
+            let bloqueada = self.version_bloqueada(nombre).cloned();
+
+            let conflicto = match &instalacion {
+                None => Some("no instalada".to_string()),
+                Some(_) => self.verificar_version(
+                    nombre,
+                    Some(requerida),
+                    importador,
+                    instalada.as_ref(),
+                ),
+            };
+
+            let mut dependencias = Vec::new();
+            if let (Some(dir), Some(dep)) = (&instalacion, &manifiesto_dep) {
+                if visitados.insert(nombre.clone()) {
+                    dependencias = self.construir_nodos(dir, dep, Some(nombre), visitados);
+                    visitados.remove(nombre);
+                }
+            }
+
+            nodos.push(NodoDependencia {
+                nombre: nombre.clone(),
+                requerida: requerida.clone(),
+                instalada,
+                bloqueada,
+                conflicto,
+                dependencias,
+            });
+        }
+
+        nodos
+    }
+}
+
+pub fn ubicar_instalacion(desde: &Path, nombre: &str) -> Option<PathBuf> {
+    let mut directorio = desde.to_path_buf();
+
+    loop {
+        let candidato = directorio.join(DIRECTORIO_MODULOS).join(nombre);
+        if candidato.is_dir() {
+            return Some(candidato);
+        }
+
+        if !directorio.pop() {
+            return None;
+        }
+    }
+}
+
+// Paquete instalado que contiene `desde` (el directorio más cercano que
+// está directamente dentro de un `modules_ump`) y su manifiesto, si tiene.
+fn paquete_contenedor(desde: &Path) -> Option<(String, Option<Manifiesto>)> {
+    let directorio = desde.ancestors().find(|directorio| {
+        directorio
+            .parent()
+            .and_then(Path::file_name)
+            .is_some_and(|nombre| nombre == DIRECTORIO_MODULOS)
+    })?;
+    let nombre = directorio.file_name()?.to_string_lossy().to_string();
+    Some((nombre, Manifiesto::leer(directorio).ok().flatten()))
+}
+
+pub fn buscar_entrada(directorio: &Path, manifiesto: Option<&Manifiesto>) -> Option<PathBuf> {
+    if let Some(entrada) = manifiesto.and_then(|m| m.entrada.as_ref()) {
+        let ruta = directorio.join(entrada);
+        return ruta.is_file().then_some(ruta);
+    }
+
+    ENTRADAS_POR_DEFECTO
+        .iter()
+        .map(|entrada| directorio.join(entrada))
+        .find(|ruta| ruta.is_file())
+}
+
+fn leer_si_existe(ruta: &Path) -> Result<Option<String>, String> {
+    if !ruta.is_file() {
+        return Ok(None);
+    }
+
+    fs::read_to_string(ruta)
+        .map(Some)
+        .map_err(|e| format!("No se pudo leer {}: {}", ruta.display(), e))
+}
+
+fn parsear_objeto_json(texto: &str) -> Result<serde_json::Map<String, serde_json::Value>, String> {
+    match serde_json::from_str::<serde_json::Value>(texto) {
+        Ok(serde_json::Value::Object(objeto)) => Ok(objeto),
+        Ok(_) => Err("se esperaba un objeto JSON".to_string()),
+        Err(e) => Err(format!("JSON inválido: {}", e)),
+    }
+}
+
+fn obtener_texto(
+    objeto: &serde_json::Map<String, serde_json::Value>,
+    campo: &str,
+) -> Result<Option<String>, String> {
+    match objeto.get(campo) {
+        None | Some(serde_json::Value::Null) => Ok(None),
+        Some(serde_json::Value::String(texto)) => Ok(Some(texto.clone())),
+        Some(_) => Err(format!("el campo '{}' debe ser texto", campo)),
+    }
+}
+
+fn obtener_tabla(
+    objeto: &serde_json::Map<String, serde_json::Value>,
+    campo: &str,
+    valor_esperado: &str,
+) -> Result<Vec<(String, String)>, String> {
+    let Some(valor) = objeto.get(campo) else {
+        return Ok(Vec::new());
+    };
+
+    let serde_json::Value::Object(tabla) = valor else {
+        return Err(format!("el campo '{}' debe ser un objeto", campo));
+    };
+
+    tabla
+        .iter()
+        .map(|(clave, valor)| match valor {
+            serde_json::Value::String(texto) => Ok((clave.clone(), texto.clone())),
+            _ => Err(format!("{} de '{}' debe ser texto", valor_esperado, clave)),
+        })
+        .collect()
+}
+
+fn parsear_version(texto: &str) -> Result<Version, String> {
+    Version::parse(texto).map_err(|e| format!("versión '{}' inválida: {}", texto, e))
+}
+
+fn parsear_restriccion(texto: &str) -> Result<VersionReq, String> {
+    VersionReq::parse(texto).map_err(|e| format!("restricción '{}' inválida: {}", texto, e))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    fn proyecto(manifiesto: &str, bloqueo: Option<&str>) -> Proyecto {
+        Proyecto {
+            raiz: PathBuf::from("."),
+            manifiesto: Manifiesto::desde_texto(manifiesto).unwrap(),
+            bloqueo: bloqueo.map(|b| ArchivoBloqueo::desde_texto(b).unwrap()),
+        }
+    }
+
+    #[test]
+    fn test_manifiesto_con_dependencias() {
+        let manifiesto = Manifiesto::desde_texto(
+            r#"{ "name": "app", "version": "1.0.0", "main": "src/app.um",
+                 "dependencies": { "http": "^1.2" }, "lint": { "sombreado": "off" } }"#,
+        )
+        .unwrap();
+
+        assert_eq!(manifiesto.nombre, "app");
+        assert_eq!(manifiesto.entrada.as_deref(), Some("src/app.um"));
+        assert!(manifiesto.dependencias["http"].matches(&Version::new(1, 4, 0)));
+        assert_eq!(manifiesto.lint["sombreado"], "off");
+        assert!(Manifiesto::desde_texto(r#"{ "name": "app" }"#).is_err());
+    }
+
+    #[test]
+    fn test_conflicto_entre_bloqueo_e_instalada() {
+        let p = proyecto(
+            r#"{ "name": "app", "version": "1.0.0", "dependencies": { "log": "^2" } }"#,
+            Some(r#"{ "packages": { "log": { "version": "2.1.0" } } }"#),
+        );
+
+        let requerida = p.manifiesto.dependencias.get("log");
+        assert!(p
+            .verificar_version("log", requerida, None, Some(&Version::new(2, 1, 0)))
+            .is_none());
+        assert!(p
+            .verificar_version("log", requerida, None, Some(&Version::new(2, 0, 0)))
+            .is_some());
+    }
+
+    #[test]
+    fn test_conflicto_entre_restriccion_y_bloqueo() {
+        let p = proyecto(
+            r#"{ "name": "app", "version": "1.0.0", "dependencies": { "log": "^3" } }"#,
+            Some(r#"{ "packages": { "log": { "version": "2.1.0" } } }"#),
+        );
+
+        let requerida = p.manifiesto.dependencias.get("log");
+        assert!(p.verificar_version("log", requerida, None, None).is_some());
+    }
+
+    fn escribir(raiz: &TempDir, archivo: &str, contenido: &str) {
+        let ruta = raiz.path().join(archivo);
+        fs::create_dir_all(ruta.parent().unwrap()).unwrap();
+        fs::write(ruta, contenido).unwrap();
+    }
+
+    // Proyecto `app` que depende de `http`, que a su vez depende de `json`.
+    fn proyecto_con_transitiva(json_instalada: &str) -> TempDir {
+        let raiz = tempfile::tempdir().unwrap();
+        escribir(
+            &raiz,
+            "ump.json",
+            r#"{ "name": "app", "version": "1.0.0", "dependencies": { "http": "^1" } }"#,
+        );
+        escribir(
+            &raiz,
+            "ump.lock",
+            r#"{ "packages": {
+                "http": { "version": "1.2.4", "dependencies": { "json": "0.3.1" } },
+                "json": { "version": "0.3.1" } } }"#,
+        );
+        escribir(
+            &raiz,
+            "modules_ump/http/ump.json",
+            r#"{ "name": "http", "version": "1.2.4", "dependencies": { "json": "~0.3" } }"#,
+        );
+        // Su propio bloqueo no cuenta: manda el de la raíz.
+        escribir(
+            &raiz,
+            "modules_ump/http/ump.lock",
+            &format!(r#"{{ "packages": {{ "json": {{ "version": "{}" }} }} }}"#, json_instalada),
+        );
+        escribir(&raiz, "modules_ump/http/main.um", "");
+        escribir(
+            &raiz,
+            "modules_ump/json/ump.json",
+            &format!(r#"{{ "name": "json", "version": "{}" }}"#, json_instalada),
+        );
+        escribir(&raiz, "modules_ump/json/main.um", "");
+        raiz
+    }
+
+    #[test]
+    fn test_transitiva_se_comprueba_con_el_bloqueo_de_la_raiz() {
+        let raiz = proyecto_con_transitiva("0.3.0");
+        let desde = raiz.path().join("modules_ump/http");
+        let proyecto = Proyecto::buscar_raiz(&desde).unwrap().unwrap();
+        assert_eq!(proyecto.raiz, raiz.path());
+        assert!(proyecto.declara("json", &desde));
+
+        let error = proyecto.resolver("json", &desde).unwrap_err();
+        assert!(error.contains("ump.lock fija 0.3.1"), "{}", error);
+
+        let arbol = proyecto.arbol_dependencias();
+        let json = &arbol[0].dependencias[0];
+        assert_eq!(json.nombre, "json");
+        assert!(json.conflicto.as_ref().unwrap().contains("ump.lock fija 0.3.1"));
+
+        let raiz = proyecto_con_transitiva("0.3.1");
+        let desde = raiz.path().join("modules_ump/http");
+        let proyecto = Proyecto::buscar_raiz(&desde).unwrap().unwrap();
+        let entrada = proyecto.resolver("json", &desde).unwrap();
+        assert_eq!(entrada, raiz.path().join("modules_ump/json/main.um"));
+        assert!(proyecto.arbol_dependencias()[0].dependencias[0].conflicto.is_none());
+    }
+
+    #[test]
+    fn test_paquete_fijado_sin_manifiesto() {
+        let raiz = proyecto_con_transitiva("0.3.1");
+        fs::remove_file(raiz.path().join("modules_ump/json/ump.json")).unwrap();
+        let desde = raiz.path().join("modules_ump/http");
+        let proyecto = Proyecto::buscar_raiz(&desde).unwrap().unwrap();
+
+        let error = proyecto.resolver("json", &desde).unwrap_err();
+        assert!(error.contains("no tiene ump.json"), "{}", error);
+    }
+
+    #[test]
+    fn test_dependencias_del_bloqueo_deben_coincidir() {
+        let p = proyecto(
+            r#"{ "name": "app", "version": "1.0.0", "dependencies": { "http": "^1" } }"#,
+            Some(
+                r#"{ "packages": {
+                    "http": { "version": "1.2.4", "dependencies": { "json": "0.3.0" } },
+                    "json": { "version": "0.3.1" } } }"#,
+            ),
+        );
+
+        let conflicto = p
+            .verificar_version("json", None, Some("http"), Some(&Version::new(0, 3, 1)))
+            .unwrap();
+        assert!(conflicto.contains("anota 0.3.0 para 'http'"), "{}", conflicto);
+    }
+}
@@ -0,0 +1,284 @@
+//! Perfilador y cobertura.
+//!
+//! Con un `Perfil` activo el intérprete mide cada llamada a una función, a
+//! un método o a una función nativa de `Std`, y cada sentencia anotada con
+//! `Sentencia::Ubicada`. Los datos se comparten entre todos los intérpretes
+//! que reciben un clon del perfil (tareas asíncronas, módulos importados y,
+//! en `umbral test`, cada prueba); la pila de llamadas es propia de cada uno.
+
+use std::collections::{BTreeMap, HashMap};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+use umbral_parser::ast::Ubicacion;
+
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct EstadisticaFuncion {
+    pub llamadas: u64,
+    /// Tiempo desde que empieza hasta que vuelve, contado una sola vez en
+    /// las llamadas recursivas.
+    pub total: Duration,
+    /// Tiempo sin contar el de las funciones a las que llama.
+    pub propio: Duration,
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct EstadisticaLinea {
+    pub veces: u64,
+    /// Incluye lo que ejecutan los bloques y llamadas de la sentencia,
+    /// contado una sola vez cuando la línea vuelve a ejecutarse dentro de sí
+    /// misma (recursión o bloques en la misma línea).
+    pub tiempo: Duration,
+}
+
+#[derive(Debug, Clone)]
+pub struct Archivo {
+    pub ruta: PathBuf,
+    /// Todas las líneas con alguna sentencia, se hayan ejecutado o no.
+    pub lineas: BTreeMap<usize, EstadisticaLinea>,
+}
+
+#[derive(Default)]
+struct Datos {
+    archivos: Vec<Archivo>,
+    funciones: HashMap<String, EstadisticaFuncion>,
+    // Tiempo propio de cada pila, con los nombres separados por `;`.
+    pilas: HashMap<String, Duration>,
+}
+
+#[derive(Clone)]
+struct MarcoPerfil {
+    nombre: String,
+    inicio: Instant,
+    hijos: Duration,
+    // Líneas abiertas al entrar, para distinguir las de esta llamada.
+    lineas: usize,
+}
+
+#[derive(Clone, Default)]
+pub struct Perfil {
+    datos: Arc<Mutex<Datos>>,
+    pila: Vec<MarcoPerfil>,
+    lineas_activas: Vec<Ubicacion>,
+}
+
+impl Perfil {
+    pub fn nuevo() -> Self {
+        Self::default()
+    }
+
+    /// Identificador de `ruta` para `Parser::anotar_lineas`; el mismo
+    /// archivo recibe siempre el mismo, aunque se llegue a él por rutas
+    /// distintas.
+    pub fn registrar_archivo(&self, ruta: &Path) -> usize {
+        let ruta = fs::canonicalize(ruta).unwrap_or_else(|_| ruta.to_path_buf());
+        let mut datos = self.datos.lock().unwrap();
+        if let Some(indice) = datos.archivos.iter().position(|a| a.ruta == ruta) {
+            return indice;
+        }
+        datos.archivos.push(Archivo {
+            ruta,
+            lineas: BTreeMap::new(),
+        });
+        datos.archivos.len() - 1
+    }
+
+    /// Añade las líneas ejecutables del archivo, aunque nunca se ejecuten.
+    pub fn registrar_lineas(&self, archivo: usize, lineas: &[usize]) {
+        let mut datos = self.datos.lock().unwrap();
+        if let Some(archivo) = datos.archivos.get_mut(archivo) {
+            for &linea in lineas {
+                archivo.lineas.entry(linea).or_default();
+            }
+        }
+    }
+
+    /// Una sentencia anidada en la misma línea que la que la contiene, dentro
+    /// de la misma llamada, no cuenta como otra ejecución de la línea.
+    pub fn entrar_linea(&mut self, ubicacion: Ubicacion) {
+        let base = self.pila.last().map_or(0, |marco| marco.lineas);
+        let anidada =
+            self.lineas_activas.len() > base && self.lineas_activas.last() == Some(&ubicacion);
+        self.lineas_activas.push(ubicacion);
+        if anidada {
+            return;
+        }
+        let mut datos = self.datos.lock().unwrap();
+        if let Some(archivo) = datos.archivos.get_mut(ubicacion.archivo) {
+            archivo.lineas.entry(ubicacion.linea).or_default().veces += 1;
+        }
+    }
+
+    /// Cierra la última línea abierta con `entrar_linea`.
+    pub fn salir_linea(&mut self, tiempo: Duration) {
+        let Some(ubicacion) = self.lineas_activas.pop() else {
+            return;
+        };
+        if self.lineas_activas.contains(&ubicacion) {
+            return;
+        }
+        let mut datos = self.datos.lock().unwrap();
+        if let Some(archivo) = datos.archivos.get_mut(ubicacion.archivo) {
+            archivo.lineas.entry(ubicacion.linea).or_default().tiempo += tiempo;
+        }
+    }
+
+    pub fn entrar(&mut self, nombre: String) {
+        self.pila.push(MarcoPerfil {
+            nombre,
+            inicio: Instant::now(),
+            hijos: Duration::ZERO,
+            lineas: self.lineas_activas.len(),
+        });
+    }
+
+    pub fn salir(&mut self) {
+        let Some(marco) = self.pila.pop() else {
+            return;
+        };
+        let total = marco.inicio.elapsed();
+        let propio = total.saturating_sub(marco.hijos);
+        if let Some(padre) = self.pila.last_mut() {
+            padre.hijos += total;
+        }
+
+        let recursiva = self.pila.iter().any(|otro| otro.nombre == marco.nombre);
+        let mut ruta: Vec<&str> = self.pila.iter().map(|m| m.nombre.as_str()).collect();
+        ruta.push(&marco.nombre);
+        let ruta = ruta.join(";");
+
+        let mut datos = self.datos.lock().unwrap();
+        *datos.pilas.entry(ruta).or_default() += propio;
+        let estadistica = datos.funciones.entry(marco.nombre).or_default();
+        estadistica.llamadas += 1;
+        estadistica.propio += propio;
+        if !recursiva {
+            estadistica.total += total;
+        }
+    }
+
+    /// Cierra las llamadas que siguen abiertas, incluida la principal.
+    pub fn salir_de_todo(&mut self) {
+        while !self.pila.is_empty() {
+            self.salir();
+        }
+    }
+
+    /// Funciones ordenadas de mayor a menor tiempo propio.
+    pub fn funciones(&self) -> Vec<(String, EstadisticaFuncion)> {
+        let datos = self.datos.lock().unwrap();
+        let mut funciones: Vec<_> = datos
+            .funciones
+            .iter()
+            .map(|(nombre, estadistica)| (nombre.clone(), *estadistica))
+            .collect();
+        funciones.sort_by(|a, b| b.1.propio.cmp(&a.1.propio).then_with(|| a.0.cmp(&b.0)));
+        funciones
+    }
+
+    pub fn archivos(&self) -> Vec<Archivo> {
+        self.datos.lock().unwrap().archivos.clone()
+    }
+
+    /// Pilas en el formato plegado de los flame graphs: una línea por pila
+    /// con su tiempo propio en microsegundos.
+    pub fn pilas_plegadas(&self) -> String {
+        let datos = self.datos.lock().unwrap();
+        let mut pilas: Vec<_> = datos.pilas.iter().collect();
+        pilas.sort();
+
+        let mut texto = String::new();
+        for (pila, tiempo) in pilas {
+            texto.push_str(&format!("{} {}\n", pila, tiempo.as_micros()));
+        }
+        texto
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::thread;
+
+    // Pila plegada y microsegundos de cada línea de `pilas_plegadas`.
+    fn leer_pilas(texto: &str) -> Vec<(String, u128)> {
+        texto
+            .lines()
+            .map(|linea| {
+                let (pila, tiempo) = linea.rsplit_once(' ').expect("falta el tiempo");
+                (
+                    pila.to_string(),
+                    tiempo.parse().expect("tiempo no numérico"),
+                )
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_pilas_plegadas_con_tiempo_propio() {
+        let mut perfil = Perfil::nuevo();
+        perfil.entrar("<principal>".to_string());
+        perfil.entrar("fib".to_string());
+        perfil.entrar("fib".to_string());
+        thread::sleep(Duration::from_millis(5));
+        perfil.salir();
+        perfil.salir();
+        perfil.entrar("Std.time.sleep".to_string());
+        perfil.salir();
+        perfil.salir_de_todo();
+
+        let texto = perfil.pilas_plegadas();
+        assert!(texto.ends_with('\n'));
+        let pilas = leer_pilas(&texto);
+        let nombres: Vec<&str> = pilas.iter().map(|(pila, _)| pila.as_str()).collect();
+        assert_eq!(
+            nombres,
+            [
+                "<principal>",
+                "<principal>;Std.time.sleep",
+                "<principal>;fib",
+                "<principal>;fib;fib"
+            ]
+        );
+        // El tiempo de la llamada interna no se suma a la externa.
+        assert!(pilas[3].1 >= 5_000);
+        assert!(pilas[2].1 < pilas[3].1);
+
+        let funciones = perfil.funciones();
+        assert_eq!(funciones[0].0, "fib");
+        assert_eq!(funciones[0].1.llamadas, 2);
+        assert!(funciones[0].1.total >= Duration::from_millis(5));
+    }
+
+    #[test]
+    fn test_lineas_anidadas_cuentan_una_vez() {
+        let mut perfil = Perfil::nuevo();
+        let archivo = perfil.registrar_archivo(Path::new("no_existe.um"));
+        assert_eq!(perfil.registrar_archivo(Path::new("no_existe.um")), archivo);
+        perfil.registrar_lineas(archivo, &[1, 2, 3]);
+
+        let linea = |linea| Ubicacion { archivo, linea };
+        perfil.entrar_linea(linea(1));
+        perfil.entrar_linea(linea(1));
+        perfil.salir_linea(Duration::from_millis(1));
+        perfil.salir_linea(Duration::from_millis(3));
+        perfil.entrar_linea(linea(2));
+        perfil.salir_linea(Duration::from_millis(2));
+
+        let archivos = perfil.archivos();
+        let lineas: Vec<(usize, u64, Duration)> = archivos[0]
+            .lineas
+            .iter()
+            .map(|(linea, e)| (*linea, e.veces, e.tiempo))
+            .collect();
+        assert_eq!(
+            lineas,
+            [
+                (1, 1, Duration::from_millis(3)),
+                (2, 1, Duration::from_millis(2)),
+                (3, 0, Duration::ZERO)
+            ]
+        );
+    }
+}
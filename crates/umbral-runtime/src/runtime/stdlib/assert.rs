@@ -0,0 +1,333 @@
+use crate::runtime::stdlib::errores;
+use crate::runtime::valores::{NativeFn, Valor};
+use indexmap::IndexMap;
+use rust_decimal::prelude::ToPrimitive;
+use rust_decimal::Decimal;
+use std::sync::Arc;
+
+// Las aserciones fallidas lanzan un `Error` con este `data.kind`.
+pub const TIPO_FALLO: &str = "AssertionError";
+
+const TOLERANCIA_POR_DEFECTO: f64 = 1e-9;
+
+fn registrar_funcion(mapa: &mut IndexMap<String, Valor>, nombre: &str, funcion: NativeFn) {
+    mapa.insert(
+        nombre.to_string(),
+        Valor::FuncionNativa(nombre.to_string(), funcion),
+    );
+}
+
+pub fn crear_modulo() -> Valor {
+    let mut mapa = IndexMap::new();
+
+    registrar_funcion(&mut mapa, "eq", igual);
+    registrar_funcion(&mut mapa, "ne", distinto);
+    registrar_funcion(&mut mapa, "ok", verdadero);
+    registrar_funcion(&mut mapa, "fail", fallar);
+    registrar_funcion(&mut mapa, "approx", aproximado);
+    registrar_funcion(&mut mapa, "throws", lanza);
+
+    Valor::Diccionario(mapa)
+}
+
+fn fallo(mensaje: &str) -> Valor {
+    errores::lanzar_mensaje(mensaje, TIPO_FALLO)
+}
+
+// El mensaje opcional del usuario sustituye al encabezado por defecto; el
+// detalle de la diferencia se añade siempre debajo.
+fn encabezado(argumentos: &[Valor], indice: usize, por_defecto: &str) -> String {
+    match argumentos.get(indice) {
+        Some(Valor::Texto(mensaje)) => mensaje.clone(),
+        _ => por_defecto.to_string(),
+    }
+}
+
+// eq(obtenido, esperado, mensaje?)
+fn igual(argumentos: Vec<Valor>) -> Valor {
+    let (Some(obtenido), Some(esperado)) = (argumentos.first(), argumentos.get(1)) else {
+        return errores::lanzar_argumento_invalido("assert.eq", "dos valores");
+    };
+
+    let Some(diferencia) = primera_diferencia(obtenido, esperado, String::new()) else {
+        return Valor::Nulo;
+    };
+
+    let mut mensaje = encabezado(&argumentos, 2, "assert.eq: los valores no son iguales");
+    if diferencia.ruta.is_empty() {
+        mensaje.push_str(&format!("\n  {}", diferencia.detalle));
+    } else {
+        mensaje.push_str(&format!(
+            "\n  en {}: {}\n  esperado: {}\n  obtenido: {}",
+            diferencia.ruta,
+            diferencia.detalle,
+            mostrar(esperado),
+            mostrar(obtenido)
+        ));
+    }
+    fallo(&mensaje)
+}
+
+// ne(obtenido, no_esperado, mensaje?)
+fn distinto(argumentos: Vec<Valor>) -> Valor {
+    let (Some(obtenido), Some(otro)) = (argumentos.first(), argumentos.get(1)) else {
+        return errores::lanzar_argumento_invalido("assert.ne", "dos valores");
+    };
+
+    if primera_diferencia(obtenido, otro, String::new()).is_some() {
+        return Valor::Nulo;
+    }
+
+    let mensaje = encabezado(&argumentos, 2, "assert.ne: los valores son iguales");
+    fallo(&format!("{}\n  valor: {}", mensaje, mostrar(obtenido)))
+}
+
+// ok(valor, mensaje?): el valor debe ser exactamente `true`.
+fn verdadero(argumentos: Vec<Valor>) -> Valor {
+    match argumentos.first() {
+        Some(Valor::Booleano(true)) => Valor::Nulo,
+        valor => {
+            let mensaje = encabezado(&argumentos, 1, "assert.ok: la condición no se cumple");
+            let obtenido = valor.map_or_else(|| "nada".to_string(), mostrar);
+            fallo(&format!("{}\n  se esperaba true, se obtuvo {}", mensaje, obtenido))
+        }
+    }
+}
+
+fn fallar(argumentos: Vec<Valor>) -> Valor {
+    fallo(&encabezado(&argumentos, 0, "assert.fail: fallo explícito"))
+}
+
+fn numero(valor: Option<&Valor>) -> Option<f64> {
+    match valor {
+        Some(Valor::Entero(entero)) => Some(*entero as f64),
+        Some(Valor::Flotante(flotante)) => Some(*flotante),
+        Some(Valor::Decimal(decimal)) => decimal.to_f64(),
+        _ => None,
+    }
+}
+
+// approx(obtenido, esperado, tolerancia?, mensaje?)
+fn aproximado(argumentos: Vec<Valor>) -> Valor {
+    let (Some(obtenido), Some(esperado)) = (numero(argumentos.first()), numero(argumentos.get(1)))
+    else {
+        return errores::lanzar_argumento_invalido("assert.approx", "dos números");
+    };
+
+    let tolerancia = match argumentos.get(2) {
+        None | Some(Valor::Nulo) => TOLERANCIA_POR_DEFECTO,
+        valor => match numero(valor) {
+            Some(tolerancia) if tolerancia >= 0.0 => tolerancia,
+            _ => {
+                return errores::lanzar_argumento_invalido(
+                    "assert.approx",
+                    "una tolerancia no negativa",
+                )
+            }
+        },
+    };
+
+    let diferencia = (obtenido - esperado).abs();
+    if diferencia <= tolerancia {
+        return Valor::Nulo;
+    }
+
+    let mensaje = encabezado(&argumentos, 3, "assert.approx: los números no son próximos");
+    fallo(&format!(
+        "{}\n  se esperaba {} ± {}, se obtuvo {} (diferencia {})",
+        mensaje, esperado, tolerancia, obtenido, diferencia
+    ))
+}
+
+// Solo se llega aquí si el primer argumento no es una función de Umbral:
+// con una función, el intérprete resuelve la llamada.
+pub(crate) fn lanza(_argumentos: Vec<Valor>) -> Valor {
+    errores::lanzar_argumento_invalido("throws", "una función")
+}
+
+/// Comprueba la excepción que lanzó la función de `throws`. Con un texto
+/// esperado, el mensaje de la excepción debe contenerlo. Devuelve la
+/// excepción para que la prueba pueda inspeccionarla.
+pub fn comprobar_excepcion(excepcion: Option<Valor>, esperado: Option<&Valor>) -> Valor {
+    let Some(excepcion) = excepcion else {
+        return fallo("assert.throws: la función no lanzó ninguna excepción");
+    };
+
+    if let Some(Valor::Texto(esperado)) = esperado {
+        let mensaje = match &excepcion {
+            Valor::Objeto(instancia) => instancia
+                .propiedades
+                .lock()
+                .unwrap()
+                .get("mensaje")
+                .map(Valor::to_string)
+                .unwrap_or_default(),
+            otro => otro.to_string(),
+        };
+        if !mensaje.contains(esperado.as_str()) {
+            return fallo(&format!(
+                "assert.throws: el mensaje de la excepción no contiene {:?}\n  mensaje: {:?}",
+                esperado, mensaje
+            ));
+        }
+    }
+
+    excepcion
+}
+
+struct Diferencia {
+    ruta: String,
+    detalle: String,
+}
+
+// Igualdad estructural: las listas se comparan elemento a elemento, los
+// diccionarios por clave sin importar el orden y las instancias por clase y
+// propiedades. Devuelve dónde está la primera diferencia.
+fn primera_diferencia(obtenido: &Valor, esperado: &Valor, ruta: String) -> Option<Diferencia> {
+    let distinto = |ruta: String| {
+        Some(Diferencia {
+            ruta,
+            detalle: format!(
+                "se esperaba {}, se obtuvo {}",
+                mostrar(esperado),
+                mostrar(obtenido)
+            ),
+        })
+    };
+
+    match (obtenido, esperado) {
+        (Valor::Lista(a), Valor::Lista(b)) => {
+            for (indice, (x, y)) in a.iter().zip(b).enumerate() {
+                let diferencia = primera_diferencia(x, y, format!("{}[{}]", ruta, indice));
+                if diferencia.is_some() {
+                    return diferencia;
+                }
+            }
+            let detalle = match a.len().cmp(&b.len()) {
+                std::cmp::Ordering::Equal => return None,
+                std::cmp::Ordering::Less => {
+                    format!("falta el elemento [{}] = {}", a.len(), mostrar(&b[a.len()]))
+                }
+                std::cmp::Ordering::Greater => {
+                    format!("sobra el elemento [{}] = {}", b.len(), mostrar(&a[b.len()]))
+                }
+            };
+            Some(Diferencia { ruta, detalle })
+        }
+        (Valor::Diccionario(a), Valor::Diccionario(b)) => {
+            diferencia_mapas(a, b, &ruta, |clave| format!("[{:?}]", clave), "la clave")
+        }
+        (Valor::Objeto(a), Valor::Objeto(b)) => {
+            if Arc::ptr_eq(&a.propiedades, &b.propiedades) {
+                return None;
+            }
+            if a.clase != b.clase {
+                return Some(Diferencia {
+                    ruta,
+                    detalle: format!(
+                        "se esperaba una instancia de {}, se obtuvo una de {}",
+                        b.clase, a.clase
+                    ),
+                });
+            }
+            // Se copian para no mantener dos bloqueos durante la comparación.
+            let propiedades_a = a.propiedades.lock().unwrap().clone();
+            let propiedades_b = b.propiedades.lock().unwrap().clone();
+            diferencia_mapas(
+                &propiedades_a,
+                &propiedades_b,
+                &ruta,
+                |clave| format!(".{}", clave),
+                "la propiedad",
+            )
+        }
+        (Valor::Lista(_) | Valor::Diccionario(_) | Valor::Objeto(_), _)
+        | (_, Valor::Lista(_) | Valor::Diccionario(_) | Valor::Objeto(_)) => distinto(ruta),
+        _ if escalares_iguales(obtenido, esperado) => None,
+        _ => distinto(ruta),
+    }
+}
+
+fn diferencia_mapas(
+    obtenido: &IndexMap<String, Valor>,
+    esperado: &IndexMap<String, Valor>,
+    ruta: &str,
+    segmento: fn(&str) -> String,
+    que: &str,
+) -> Option<Diferencia> {
+    for (clave, valor_esperado) in esperado {
+        let Some(valor_obtenido) = obtenido.get(clave) else {
+            return Some(Diferencia {
+                ruta: ruta.to_string(),
+                detalle: format!("falta {} {:?}", que, clave),
+            });
+        };
+        let diferencia = primera_diferencia(
+            valor_obtenido,
+            valor_esperado,
+            format!("{}{}", ruta, segmento(clave)),
+        );
+        if diferencia.is_some() {
+            return diferencia;
+        }
+    }
+
+    obtenido
+        .keys()
+        .find(|clave| !esperado.contains_key(*clave))
+        .map(|clave| Diferencia {
+            ruta: ruta.to_string(),
+            detalle: format!("sobra {} {:?}", que, clave),
+        })
+}
+
+// Mismas reglas que `==` para los valores simples.
+fn escalares_iguales(a: &Valor, b: &Valor) -> bool {
+    match (a, b) {
+        (Valor::Entero(x), Valor::Entero(y)) => x == y,
+        (Valor::Flotante(x), Valor::Flotante(y)) => (x - y).abs() < f64::EPSILON,
+        (Valor::Entero(x), Valor::Flotante(y)) => (*x as f64 - y).abs() < f64::EPSILON,
+        (Valor::Flotante(x), Valor::Entero(y)) => (x - *y as f64).abs() < f64::EPSILON,
+        (Valor::Decimal(x), Valor::Decimal(y)) => x == y,
+        (Valor::Decimal(x), Valor::Entero(y)) | (Valor::Entero(y), Valor::Decimal(x)) => {
+            *x == Decimal::from(*y)
+        }
+        (Valor::Booleano(x), Valor::Booleano(y)) => x == y,
+        (Valor::Texto(x), Valor::Texto(y)) => x == y,
+        (Valor::FechaHora(x), Valor::FechaHora(y)) => x.instante == y.instante,
+        (Valor::Duracion(x), Valor::Duracion(y)) => x == y,
+        (Valor::Nulo, Valor::Nulo) => true,
+        (Valor::Clase(x), Valor::Clase(y)) => x == y,
+        (Valor::Funcion(x), Valor::Funcion(y)) => x.nombre == y.nombre,
+        (Valor::FuncionNativa(x, _), Valor::FuncionNativa(y, _)) => x == y,
+        _ => false,
+    }
+}
+
+// Como `Display`, pero con los textos entre comillas para distinguir `"1"`
+// de `1` en los mensajes.
+fn mostrar(valor: &Valor) -> String {
+    match valor {
+        Valor::Texto(texto) => format!("{:?}", texto),
+        Valor::Lista(items) => {
+            let items: Vec<String> = items.iter().map(mostrar).collect();
+            format!("[{}]", items.join(", "))
+        }
+        Valor::Diccionario(mapa) => {
+            let pares: Vec<String> = mapa
+                .iter()
+                .map(|(clave, valor)| format!("{:?}: {}", clave, mostrar(valor)))
+                .collect();
+            format!("{{{}}}", pares.join(", "))
+        }
+        Valor::Objeto(instancia) => {
+            let propiedades = instancia.propiedades.lock().unwrap().clone();
+            let pares: Vec<String> = propiedades
+                .iter()
+                .map(|(clave, valor)| format!("{}: {}", clave, mostrar(valor)))
+                .collect();
+            format!("{} {{ {} }}", instancia.clase, pares.join(", "))
+        }
+        otro => otro.to_string(),
+    }
+}
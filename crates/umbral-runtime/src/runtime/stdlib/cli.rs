@@ -0,0 +1,500 @@
+use crate::runtime::stdlib::{errores, sesion};
+use crate::runtime::valores::Valor;
+use indexmap::IndexMap;
+
+fn registrar_funcion(
+    mapa: &mut IndexMap<String, Valor>,
+    nombre: &str,
+    funcion: fn(Vec<Valor>) -> Valor,
+) {
+    mapa.insert(
+        nombre.to_string(),
+        Valor::FuncionNativa(nombre.to_string(), funcion),
+    );
+}
+
+pub fn crear_modulo() -> Valor {
+    let mut mapa = IndexMap::new();
+
+    registrar_funcion(&mut mapa, "parse", parsear);
+    registrar_funcion(&mut mapa, "help", ayuda);
+
+    Valor::Diccionario(mapa)
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum TipoArgumento {
+    Texto,
+    Entero,
+    Flotante,
+    Booleano,
+}
+
+impl TipoArgumento {
+    fn desde_nombre(nombre: &str) -> Option<Self> {
+        match nombre {
+            "str" => Some(Self::Texto),
+            "int" => Some(Self::Entero),
+            "float" => Some(Self::Flotante),
+            "bool" => Some(Self::Booleano),
+            _ => None,
+        }
+    }
+
+    fn nombre(&self) -> &'static str {
+        match self {
+            Self::Texto => "str",
+            Self::Entero => "int",
+            Self::Flotante => "float",
+            Self::Booleano => "bool",
+        }
+    }
+
+    fn convertir(&self, texto: &str) -> Option<Valor> {
+        match self {
+            Self::Texto => Some(Valor::Texto(texto.to_string())),
+            Self::Entero => texto.parse().ok().map(Valor::Entero),
+            Self::Flotante => texto.parse().ok().map(Valor::Flotante),
+            Self::Booleano => match texto {
+                "true" | "1" | "yes" | "si" => Some(Valor::Booleano(true)),
+                "false" | "0" | "no" => Some(Valor::Booleano(false)),
+                _ => None,
+            },
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+struct Argumento {
+    nombre: String,
+    corto: Option<String>,
+    tipo: TipoArgumento,
+    requerido: bool,
+    multiple: bool,
+    por_defecto: Option<Valor>,
+    ayuda: String,
+}
+
+impl Argumento {
+    fn desde_valor(valor: &Valor) -> Result<Self, String> {
+        let Valor::Diccionario(mapa) = valor else {
+            return Err("cada argumento debe ser un objeto con 'name'".to_string());
+        };
+
+        let texto = |clave: &str| match mapa.get(clave) {
+            Some(Valor::Texto(texto)) => Some(texto.clone()),
+            _ => None,
+        };
+
+        let Some(nombre) = texto("name") else {
+            return Err("falta 'name' en la definición de un argumento".to_string());
+        };
+
+        let tipo = match texto("type") {
+            Some(nombre_tipo) => TipoArgumento::desde_nombre(&nombre_tipo)
+                .ok_or_else(|| format!("tipo '{}' no soportado en '{}'", nombre_tipo, nombre))?,
+            None => TipoArgumento::Texto,
+        };
+
+        Ok(Argumento {
+            corto: texto("short"),
+            tipo,
+            requerido: mapa.get("required").is_some_and(Valor::es_verdadero),
+            multiple: mapa.get("multiple").is_some_and(Valor::es_verdadero),
+            por_defecto: mapa.get("default").cloned(),
+            ayuda: texto("help").unwrap_or_default(),
+            nombre,
+        })
+    }
+
+    fn valor_inicial(&self) -> Valor {
+        if let Some(valor) = &self.por_defecto {
+            return valor.clone();
+        }
+
+        if self.multiple {
+            return Valor::Lista(Vec::new());
+        }
+
+        if self.tipo == TipoArgumento::Booleano {
+            return Valor::Booleano(false);
+        }
+
+        Valor::Nulo
+    }
+}
+
+struct Especificacion {
+    nombre: String,
+    descripcion: String,
+    opciones: Vec<Argumento>,
+    posicionales: Vec<Argumento>,
+}
+
+impl Especificacion {
+    fn desde_valor(valor: Option<&Valor>) -> Result<Self, String> {
+        let Some(Valor::Diccionario(mapa)) = valor else {
+            return Err("se esperaba un objeto con la especificación".to_string());
+        };
+
+        let texto = |clave: &str| match mapa.get(clave) {
+            Some(Valor::Texto(texto)) => texto.clone(),
+            _ => String::new(),
+        };
+
+        let lista = |clave: &str| -> Result<Vec<Argumento>, String> {
+            match mapa.get(clave) {
+                Some(Valor::Lista(items)) => items.iter().map(Argumento::desde_valor).collect(),
+                None => Ok(Vec::new()),
+                Some(_) => Err(format!("'{}' debe ser una lista", clave)),
+            }
+        };
+
+        Ok(Especificacion {
+            nombre: texto("name"),
+            descripcion: texto("description"),
+            opciones: lista("options")?,
+            posicionales: lista("positionals")?,
+        })
+    }
+
+    fn buscar_opcion(&self, bandera: &str) -> Option<&Argumento> {
+        if let Some(largo) = bandera.strip_prefix("--") {
+            return self.opciones.iter().find(|o| o.nombre == largo);
+        }
+
+        let corto = bandera.strip_prefix('-')?;
+        self.opciones
+            .iter()
+            .find(|o| o.corto.as_deref() == Some(corto))
+    }
+
+    fn uso(&self) -> String {
+        let nombre = if self.nombre.is_empty() {
+            "script"
+        } else {
+            &self.nombre
+        };
+
+        let mut uso = format!("Uso: {} [opciones]", nombre);
+        for posicional in &self.posicionales {
+            let puntos = if posicional.multiple { "..." } else { "" };
+            if posicional.requerido {
+                uso.push_str(&format!(" <{}{}>", posicional.nombre, puntos));
+            } else {
+                uso.push_str(&format!(" [{}{}]", posicional.nombre, puntos));
+            }
+        }
+        uso
+    }
+
+    fn ayuda(&self) -> String {
+        let mut lineas = vec![self.uso()];
+
+        if !self.descripcion.is_empty() {
+            lineas.push(String::new());
+            lineas.push(self.descripcion.clone());
+        }
+
+        if !self.posicionales.is_empty() {
+            lineas.push(String::new());
+            lineas.push("Argumentos:".to_string());
+            for posicional in &self.posicionales {
+                lineas.push(formatear_linea_ayuda(&posicional.nombre, posicional));
+            }
+        }
+
+        lineas.push(String::new());
+        lineas.push("Opciones:".to_string());
+        for opcion in &self.opciones {
+            let mut firma = match &opcion.corto {
+                Some(corto) => format!("-{}, --{}", corto, opcion.nombre),
+                None => format!("    --{}", opcion.nombre),
+            };
+            if opcion.tipo != TipoArgumento::Booleano {
+                firma.push_str(&format!(" <{}>", opcion.tipo.nombre()));
+            }
+            lineas.push(formatear_linea_ayuda(&firma, opcion));
+        }
+        lineas.push(format!("  {:<24} Muestra esta ayuda", "-h, --help"));
+
+        lineas.join("\n")
+    }
+}
+
+fn formatear_linea_ayuda(firma: &str, argumento: &Argumento) -> String {
+    let mut descripcion = argumento.ayuda.clone();
+
+    if argumento.requerido {
+        descripcion.push_str(" (requerido)");
+    } else if let Some(valor) = &argumento.por_defecto {
+        descripcion.push_str(&format!(" (por defecto: {})", valor));
+    }
+
+    format!("  {:<24} {}", firma, descripcion.trim())
+        .trim_end()
+        .to_string()
+}
+
+fn asignar(
+    resultado: &mut IndexMap<String, Valor>,
+    argumento: &Argumento,
+    texto: &str,
+) -> Result<(), String> {
+    let Some(valor) = argumento.tipo.convertir(texto) else {
+        return Err(format!(
+            "valor inválido para '{}': se esperaba {} y se recibió '{}'",
+            argumento.nombre,
+            argumento.tipo.nombre(),
+            texto
+        ));
+    };
+
+    if !argumento.multiple {
+        resultado.insert(argumento.nombre.clone(), valor);
+        return Ok(());
+    }
+
+    match resultado.get_mut(&argumento.nombre) {
+        Some(Valor::Lista(items)) => items.push(valor),
+        _ => {
+            resultado.insert(argumento.nombre.clone(), Valor::Lista(vec![valor]));
+        }
+    }
+    Ok(())
+}
+
+enum Resultado {
+    Valores(IndexMap<String, Valor>),
+    Ayuda,
+}
+
+fn analizar(espec: &Especificacion, argv: &[String]) -> Result<Resultado, String> {
+    let mut resultado: IndexMap<String, Valor> = espec
+        .opciones
+        .iter()
+        .chain(&espec.posicionales)
+        .map(|a| (a.nombre.clone(), a.valor_inicial()))
+        .collect();
+
+    let mut recibidos: Vec<String> = Vec::new();
+    let mut posicionales = Vec::new();
+    let mut solo_posicionales = false;
+    let mut i = 0;
+
+    while i < argv.len() {
+        let actual = &argv[i];
+        i += 1;
+
+        if solo_posicionales
+            || !actual.starts_with('-')
+            || actual == "-"
+            || es_numero_negativo(espec, actual)
+        {
+            posicionales.push(actual.clone());
+            continue;
+        }
+
+        if actual == "--" {
+            solo_posicionales = true;
+            continue;
+        }
+
+        if actual == "-h" || actual == "--help" {
+            return Ok(Resultado::Ayuda);
+        }
+
+        let (bandera, valor_en_linea) = match actual.split_once('=') {
+            Some((bandera, valor)) => (bandera, Some(valor.to_string())),
+            None => (actual.as_str(), None),
+        };
+
+        let Some(opcion) = espec.buscar_opcion(bandera) else {
+            return Err(format!("opción desconocida '{}'", bandera));
+        };
+
+        let valor = match valor_en_linea {
+            Some(valor) => valor,
+            None if opcion.tipo == TipoArgumento::Booleano => "true".to_string(),
+            None => {
+                let Some(siguiente) = argv.get(i) else {
+                    return Err(format!("falta el valor de '{}'", bandera));
+                };
+                i += 1;
+                siguiente.clone()
+            }
+        };
+
+        asignar(&mut resultado, opcion, &valor)?;
+        recibidos.push(opcion.nombre.clone());
+    }
+
+    let mut restantes = posicionales.into_iter();
+    for posicional in &espec.posicionales {
+        let valores: Vec<String> = if posicional.multiple {
+            restantes.by_ref().collect()
+        } else {
+            restantes.next().into_iter().collect()
+        };
+
+        for valor in &valores {
+            asignar(&mut resultado, posicional, valor)?;
+        }
+        if !valores.is_empty() {
+            recibidos.push(posicional.nombre.clone());
+        }
+    }
+
+    if let Some(sobrante) = restantes.next() {
+        return Err(format!("argumento inesperado '{}'", sobrante));
+    }
+
+    let faltante = espec
+        .opciones
+        .iter()
+        .chain(&espec.posicionales)
+        .find(|a| a.requerido && !recibidos.contains(&a.nombre));
+    if let Some(argumento) = faltante {
+        return Err(format!(
+            "falta el argumento requerido '{}'",
+            argumento.nombre
+        ));
+    }
+
+    Ok(Resultado::Valores(resultado))
+}
+
+// `-5` es un posicional salvo que haya una opción corta con ese nombre.
+fn es_numero_negativo(espec: &Especificacion, texto: &str) -> bool {
+    let empieza_por_cifra = texto[1..].starts_with(|c: char| c.is_ascii_digit());
+    empieza_por_cifra && texto.parse::<f64>().is_ok() && espec.buscar_opcion(texto).is_none()
+}
+
+fn obtener_argv(valor: Option<&Valor>) -> Vec<String> {
+    match valor {
+        Some(Valor::Lista(items)) => items.iter().map(|item| item.to_string()).collect(),
+        _ => sesion::actual().argumentos.lock().unwrap().clone(),
+    }
+}
+
+// parse(especificacion, [argv]): sin argv se usan los argumentos del script.
+// Con -h/--help imprime la ayuda y termina el programa con código 0.
+fn parsear(argumentos: Vec<Valor>) -> Valor {
+    let espec = match Especificacion::desde_valor(argumentos.first()) {
+        Ok(espec) => espec,
+        Err(mensaje) => {
+            return errores::lanzar_mensaje(&format!("parse: {}", mensaje), "InvalidInput")
+        }
+    };
+
+    match analizar(&espec, &obtener_argv(argumentos.get(1))) {
+        Ok(Resultado::Valores(valores)) => Valor::Diccionario(valores),
+        Ok(Resultado::Ayuda) => {
+            println!("{}", espec.ayuda());
+            errores::lanzar_salida(0)
+        }
+        Err(mensaje) => {
+            let mut data = IndexMap::new();
+            data.insert("kind".to_string(), Valor::Texto("InvalidInput".to_string()));
+            data.insert("usage".to_string(), Valor::Texto(espec.uso()));
+            errores::lanzar(errores::crear_error(&mensaje, Valor::Diccionario(data)))
+        }
+    }
+}
+
+fn ayuda(argumentos: Vec<Valor>) -> Valor {
+    match Especificacion::desde_valor(argumentos.first()) {
+        Ok(espec) => Valor::Texto(espec.ayuda()),
+        Err(mensaje) => errores::lanzar_mensaje(&format!("help: {}", mensaje), "InvalidInput"),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::runtime::stdlib::sesion::Sesion;
+
+    fn diccionario(pares: &[(&str, Valor)]) -> Valor {
+        Valor::Diccionario(
+            pares
+                .iter()
+                .map(|(clave, valor)| (clave.to_string(), valor.clone()))
+                .collect(),
+        )
+    }
+
+    fn texto(valor: &str) -> Valor {
+        Valor::Texto(valor.to_string())
+    }
+
+    fn especificacion() -> Valor {
+        let opciones = vec![
+            diccionario(&[("name", texto("limite")), ("type", texto("int"))]),
+            diccionario(&[
+                ("name", texto("verbose")),
+                ("short", texto("v")),
+                ("type", texto("bool")),
+            ]),
+        ];
+        let posicionales = vec![diccionario(&[
+            ("name", texto("desplazamiento")),
+            ("type", texto("float")),
+        ])];
+        diccionario(&[
+            ("options", Valor::Lista(opciones)),
+            ("positionals", Valor::Lista(posicionales)),
+        ])
+    }
+
+    fn analizar_textos(argv: &[&str]) -> Result<IndexMap<String, String>, String> {
+        let espec = Especificacion::desde_valor(Some(&especificacion()))?;
+        let argv: Vec<String> = argv.iter().map(|a| a.to_string()).collect();
+        match analizar(&espec, &argv)? {
+            Resultado::Valores(valores) => Ok(valores
+                .into_iter()
+                .map(|(clave, valor)| (clave, valor.to_string()))
+                .collect()),
+            Resultado::Ayuda => Err("ayuda".to_string()),
+        }
+    }
+
+    #[test]
+    fn test_numeros_negativos_como_posicionales_y_valores() {
+        let valores = analizar_textos(&["-2.5", "--limite", "-3", "-v"]).unwrap();
+        assert_eq!(valores["desplazamiento"], "-2.5");
+        assert_eq!(valores["limite"], "-3");
+        assert_eq!(valores["verbose"], "true");
+
+        assert_eq!(
+            analizar_textos(&["-x"]).unwrap_err(),
+            "opción desconocida '-x'"
+        );
+        assert_eq!(
+            analizar_textos(&["-inf"]).unwrap_err(),
+            "opción desconocida '-inf'"
+        );
+        assert_eq!(analizar_textos(&["-h"]).unwrap_err(), "ayuda");
+    }
+
+    #[test]
+    fn test_ayuda_termina_el_programa_sin_salir_del_proceso() {
+        let resultado = parsear(vec![especificacion(), Valor::Lista(vec![texto("--help")])]);
+
+        assert_eq!(resultado.to_string(), "null");
+        let salida = errores::tomar_error_pendiente().expect("se esperaba la salida");
+        assert_eq!(errores::codigo_salida(&salida), Some(0));
+    }
+
+    #[test]
+    fn test_sin_argv_usa_los_argumentos_de_la_sesion() {
+        let sesion = Sesion::nueva();
+        *sesion.argumentos.lock().unwrap() = vec!["-7".to_string()];
+
+        let resultado = sesion::con_sesion(&sesion, || parsear(vec![especificacion()]));
+        let Valor::Diccionario(valores) = resultado else {
+            panic!("se esperaba un diccionario");
+        };
+        assert_eq!(valores["desplazamiento"].to_string(), "-7");
+        assert!(errores::tomar_error_pendiente().is_none());
+    }
+}
@@ -1,7 +1,7 @@
 use crate::runtime::valores::Valor;
-use std::collections::HashMap;
+use indexmap::IndexMap;
 
-fn registrar_funcion(mapa: &mut HashMap<String, Valor>, nombre: &str, funcion: fn(Vec<Valor>) -> Valor) {
+fn registrar_funcion(mapa: &mut IndexMap<String, Valor>, nombre: &str, funcion: fn(Vec<Valor>) -> Valor) {
     mapa.insert(
         nombre.to_string(),
         Valor::FuncionNativa(nombre.to_string(), funcion),
@@ -9,7 +9,7 @@ fn registrar_funcion(mapa: &mut HashMap<String, Valor>, nombre: &str, funcion: f
 }
 
 pub fn crear_modulo() -> Valor {
-    let mut mapa = HashMap::new();
+    let mut mapa = IndexMap::new();
 
     registrar_funcion(&mut mapa, "len", len);
     registrar_funcion(&mut mapa, "push", push);
@@ -27,15 +27,15 @@ fn obtener_longitud_lista(lista: &[Valor]) -> Valor {
 }
 
 fn obtener_longitud_texto(texto: &str) -> Valor {
-    Valor::Entero(texto.len() as i64)
+    Valor::Entero(texto.chars().count() as i64)
 }
 
-fn obtener_longitud_diccionario(diccionario: &HashMap<String, Valor>) -> Valor {
+fn obtener_longitud_diccionario(diccionario: &IndexMap<String, Valor>) -> Valor {
     Valor::Entero(diccionario.len() as i64)
 }
 
 fn len(argumentos: Vec<Valor>) -> Valor {
-    match argumentos.get(0) {
+    match argumentos.first() {
         Some(Valor::Lista(lista)) => obtener_longitud_lista(lista),
         Some(Valor::Texto(texto)) => obtener_longitud_texto(texto),
         Some(Valor::Diccionario(diccionario)) => obtener_longitud_diccionario(diccionario),
@@ -43,27 +43,22 @@ fn len(argumentos: Vec<Valor>) -> Valor {
     }
 }
 
-fn agregar_elemento_lista(mut lista: Vec<Valor>, elemento: Valor) -> Valor {
-    lista.push(elemento);
+fn agregar_elementos_lista(mut lista: Vec<Valor>, elementos: &[Valor]) -> Valor {
+    lista.extend_from_slice(elementos);
     Valor::Lista(lista)
 }
 
 fn push(argumentos: Vec<Valor>) -> Valor {
-    let lista = match argumentos.get(0).cloned() {
+    let lista = match argumentos.first().cloned() {
         Some(Valor::Lista(l)) => l,
         _ => return Valor::Nulo,
     };
 
-    let elemento = match argumentos.get(1) {
-        Some(valor) => valor.clone(),
-        None => return Valor::Lista(lista),
-    };
-
-    agregar_elemento_lista(lista, elemento)
+    agregar_elementos_lista(lista, &argumentos[1..])
 }
 
 fn pop(argumentos: Vec<Valor>) -> Valor {
-    let mut lista = match argumentos.get(0).cloned() {
+    let mut lista = match argumentos.first().cloned() {
         Some(Valor::Lista(l)) => l,
         _ => return Valor::Nulo,
     };
@@ -72,25 +67,25 @@ fn pop(argumentos: Vec<Valor>) -> Valor {
     Valor::Lista(lista)
 }
 
-fn extraer_claves(diccionario: &HashMap<String, Valor>) -> Valor {
+fn extraer_claves(diccionario: &IndexMap<String, Valor>) -> Valor {
     let claves: Vec<Valor> = diccionario.keys().map(|c| Valor::Texto(c.clone())).collect();
     Valor::Lista(claves)
 }
 
 fn keys(argumentos: Vec<Valor>) -> Valor {
-    match argumentos.get(0) {
+    match argumentos.first() {
         Some(Valor::Diccionario(diccionario)) => extraer_claves(diccionario),
         _ => Valor::Lista(vec![]),
     }
 }
 
-fn extraer_valores(diccionario: &HashMap<String, Valor>) -> Valor {
+fn extraer_valores(diccionario: &IndexMap<String, Valor>) -> Valor {
     let valores: Vec<Valor> = diccionario.values().cloned().collect();
     Valor::Lista(valores)
 }
 
 fn values(argumentos: Vec<Valor>) -> Valor {
-    match argumentos.get(0) {
+    match argumentos.first() {
         Some(Valor::Diccionario(diccionario)) => extraer_valores(diccionario),
         _ => Valor::Lista(vec![]),
     }
@@ -108,7 +103,7 @@ fn comparar_valores(a: &Valor, b: &Valor) -> std::cmp::Ordering {
 }
 
 fn sort(argumentos: Vec<Valor>) -> Valor {
-    let mut lista = match argumentos.get(0).cloned() {
+    let mut lista = match argumentos.first().cloned() {
         Some(Valor::Lista(l)) => l,
         _ => return Valor::Nulo,
     };
@@ -118,7 +113,7 @@ fn sort(argumentos: Vec<Valor>) -> Valor {
 }
 
 fn reverse(argumentos: Vec<Valor>) -> Valor {
-    let mut lista = match argumentos.get(0).cloned() {
+    let mut lista = match argumentos.first().cloned() {
         Some(Valor::Lista(l)) => l,
         _ => return Valor::Nulo,
     };
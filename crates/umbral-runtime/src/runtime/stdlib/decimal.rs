@@ -0,0 +1,300 @@
+use crate::runtime::stdlib::errores;
+use crate::runtime::stdlib::sesion::{self, Sesion};
+use crate::runtime::valores::{NativeFn, Valor};
+use indexmap::IndexMap;
+use rust_decimal::prelude::{FromPrimitive, ToPrimitive};
+use rust_decimal::{Decimal, RoundingStrategy};
+use std::str::FromStr;
+
+/// Contexto de las divisiones decimales: número de decimales del resultado
+/// y modo de redondeo. Sumas, restas y productos son siempre exactos. Cada
+/// intérprete tiene el suyo, en su sesión.
+#[derive(Clone, Copy)]
+pub struct Contexto {
+    precision: u32,
+    redondeo: RoundingStrategy,
+}
+
+impl Default for Contexto {
+    fn default() -> Self {
+        Contexto {
+            precision: 28,
+            redondeo: RoundingStrategy::MidpointNearestEven,
+        }
+    }
+}
+
+fn contexto_actual() -> Contexto {
+    *sesion::actual().decimal.lock().unwrap()
+}
+
+const MODOS: [(&str, RoundingStrategy); 7] = [
+    ("half_even", RoundingStrategy::MidpointNearestEven),
+    ("half_up", RoundingStrategy::MidpointAwayFromZero),
+    ("half_down", RoundingStrategy::MidpointTowardZero),
+    ("down", RoundingStrategy::ToZero),
+    ("up", RoundingStrategy::AwayFromZero),
+    ("floor", RoundingStrategy::ToNegativeInfinity),
+    ("ceil", RoundingStrategy::ToPositiveInfinity),
+];
+
+fn registrar_funcion(mapa: &mut IndexMap<String, Valor>, nombre: &str, funcion: NativeFn) {
+    mapa.insert(
+        nombre.to_string(),
+        Valor::FuncionNativa(nombre.to_string(), funcion),
+    );
+}
+
+pub fn crear_modulo() -> Valor {
+    let mut mapa = IndexMap::new();
+
+    registrar_funcion(&mut mapa, "new", nuevo);
+    registrar_funcion(&mut mapa, "round", redondear);
+    registrar_funcion(&mut mapa, "scale", escala);
+    registrar_funcion(&mut mapa, "to_float", a_flotante);
+    registrar_funcion(&mut mapa, "to_int", a_entero);
+    registrar_funcion(&mut mapa, "set_context", fijar_contexto);
+    registrar_funcion(&mut mapa, "context", contexto);
+
+    Valor::Diccionario(mapa)
+}
+
+/// Literal `1.25d` del código fuente.
+pub fn desde_literal(texto: &str) -> Option<Decimal> {
+    Decimal::from_str_exact(texto).ok()
+}
+
+/// División redondeada según el contexto de la sesión.
+pub fn dividir(sesion: &Sesion, a: Decimal, b: Decimal) -> Option<Decimal> {
+    let contexto = *sesion.decimal.lock().unwrap();
+    a.checked_div(b)
+        .map(|cociente| cociente.round_dp_with_strategy(contexto.precision, contexto.redondeo))
+}
+
+fn modo_redondeo(nombre: &str) -> Option<RoundingStrategy> {
+    MODOS
+        .iter()
+        .find(|(modo, _)| *modo == nombre)
+        .map(|(_, estrategia)| *estrategia)
+}
+
+fn nombre_modo(estrategia: RoundingStrategy) -> &'static str {
+    MODOS
+        .iter()
+        .find(|(_, otra)| *otra == estrategia)
+        .map_or("half_even", |(modo, _)| modo)
+}
+
+fn lanzar_modo_invalido(funcion: &str) -> Valor {
+    let modos: Vec<&str> = MODOS.iter().map(|(modo, _)| *modo).collect();
+    errores::lanzar_argumento_invalido(
+        funcion,
+        &format!("un modo de redondeo ({})", modos.join(", ")),
+    )
+}
+
+// new(valor): acepta texto, enteros, flotantes y decimales.
+fn nuevo(argumentos: Vec<Valor>) -> Valor {
+    let decimal = match argumentos.first() {
+        Some(Valor::Decimal(decimal)) => Some(*decimal),
+        Some(Valor::Entero(entero)) => Some(Decimal::from(*entero)),
+        Some(Valor::Flotante(flotante)) => Decimal::from_f64(*flotante),
+        Some(Valor::Texto(texto)) => Decimal::from_str_exact(texto.trim())
+            .or_else(|_| Decimal::from_scientific(texto.trim()))
+            .or_else(|_| Decimal::from_str(texto.trim()))
+            .ok(),
+        _ => return errores::lanzar_argumento_invalido("new", "un número o un texto"),
+    };
+
+    match decimal {
+        Some(decimal) => Valor::Decimal(decimal),
+        None => errores::lanzar_mensaje(
+            &format!(
+                "new: no se puede representar {} como decimal",
+                argumentos[0]
+            ),
+            "InvalidInput",
+        ),
+    }
+}
+
+// round(decimal, [decimales], [modo])
+fn redondear(argumentos: Vec<Valor>) -> Valor {
+    let Some(Valor::Decimal(decimal)) = argumentos.first() else {
+        return errores::lanzar_argumento_invalido("round", "un decimal");
+    };
+
+    let decimales = match argumentos.get(1) {
+        Some(Valor::Entero(n)) if *n >= 0 => *n as u32,
+        None | Some(Valor::Nulo) => 0,
+        _ => return errores::lanzar_argumento_invalido("round", "un número de decimales"),
+    };
+
+    let estrategia = match argumentos.get(2) {
+        Some(Valor::Texto(modo)) => match modo_redondeo(modo) {
+            Some(estrategia) => estrategia,
+            None => return lanzar_modo_invalido("round"),
+        },
+        None => contexto_actual().redondeo,
+        _ => return lanzar_modo_invalido("round"),
+    };
+
+    Valor::Decimal(decimal.round_dp_with_strategy(decimales, estrategia))
+}
+
+fn escala(argumentos: Vec<Valor>) -> Valor {
+    match argumentos.first() {
+        Some(Valor::Decimal(decimal)) => Valor::Entero(decimal.scale() as i64),
+        _ => errores::lanzar_argumento_invalido("scale", "un decimal"),
+    }
+}
+
+fn a_flotante(argumentos: Vec<Valor>) -> Valor {
+    match argumentos.first() {
+        Some(Valor::Decimal(decimal)) => decimal.to_f64().map_or(Valor::Nulo, Valor::Flotante),
+        _ => errores::lanzar_argumento_invalido("to_float", "un decimal"),
+    }
+}
+
+// to_int(decimal): descarta la parte fraccionaria.
+fn a_entero(argumentos: Vec<Valor>) -> Valor {
+    let Some(Valor::Decimal(decimal)) = argumentos.first() else {
+        return errores::lanzar_argumento_invalido("to_int", "un decimal");
+    };
+
+    match decimal.trunc().to_i64() {
+        Some(entero) => Valor::Entero(entero),
+        None => errores::lanzar_mensaje(
+            &format!("to_int: {} no cabe en un entero", decimal),
+            "Overflow",
+        ),
+    }
+}
+
+// set_context(precision, [modo]): afecta a las divisiones posteriores.
+fn fijar_contexto(argumentos: Vec<Valor>) -> Valor {
+    let precision = match argumentos.first() {
+        Some(Valor::Entero(n)) if (0..=28).contains(n) => *n as u32,
+        _ => {
+            return errores::lanzar_argumento_invalido("set_context", "una precisión entre 0 y 28")
+        }
+    };
+
+    let sesion = sesion::actual();
+    let mut contexto = sesion.decimal.lock().unwrap();
+    if let Some(valor) = argumentos.get(1) {
+        let Valor::Texto(modo) = valor else {
+            return lanzar_modo_invalido("set_context");
+        };
+        let Some(estrategia) = modo_redondeo(modo) else {
+            return lanzar_modo_invalido("set_context");
+        };
+        contexto.redondeo = estrategia;
+    }
+    contexto.precision = precision;
+    Valor::Nulo
+}
+
+fn contexto(_: Vec<Valor>) -> Valor {
+    let contexto = contexto_actual();
+    let mut mapa = IndexMap::new();
+    mapa.insert(
+        "precision".to_string(),
+        Valor::Entero(contexto.precision as i64),
+    );
+    mapa.insert(
+        "rounding".to_string(),
+        Valor::Texto(nombre_modo(contexto.redondeo).to_string()),
+    );
+    Valor::Diccionario(mapa)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn decimal(texto: &str) -> Decimal {
+        Decimal::from_str_exact(texto).unwrap()
+    }
+
+    #[test]
+    fn test_contexto_de_division_por_sesion() {
+        let primera = Sesion::nueva();
+        let segunda = Sesion::nueva();
+
+        sesion::con_sesion(&primera, || {
+            fijar_contexto(vec![Valor::Entero(4), Valor::Texto("half_up".to_string())])
+        });
+        let Valor::Diccionario(ajustado) = sesion::con_sesion(&primera, || contexto(vec![])) else {
+            panic!("context debería devolver un diccionario");
+        };
+        assert_eq!(ajustado["precision"].to_string(), "4");
+        assert_eq!(ajustado["rounding"].to_string(), "half_up");
+
+        let tercio = |sesion: &Sesion| dividir(sesion, Decimal::from(2), Decimal::from(3));
+        assert_eq!(tercio(&primera).unwrap().to_string(), "0.6667");
+        assert_eq!(
+            tercio(&segunda).unwrap().to_string(),
+            "0.6666666666666666666666666667"
+        );
+        assert!(dividir(&segunda, Decimal::ONE, Decimal::ZERO).is_none());
+    }
+
+    #[test]
+    fn test_redondeo_con_el_modo_de_la_sesion() {
+        let sesion = Sesion::nueva();
+        let redondeado = |argumentos: Vec<Valor>| {
+            sesion::con_sesion(&sesion, || redondear(argumentos)).to_string()
+        };
+
+        let valor = || Valor::Decimal(decimal("2.345"));
+        assert_eq!(redondeado(vec![valor(), Valor::Entero(2)]), "2.34");
+        assert_eq!(
+            redondeado(vec![
+                valor(),
+                Valor::Entero(2),
+                Valor::Texto("up".to_string())
+            ]),
+            "2.35"
+        );
+
+        sesion::con_sesion(&sesion, || {
+            fijar_contexto(vec![Valor::Entero(28), Valor::Texto("floor".to_string())])
+        });
+        assert_eq!(
+            redondeado(vec![Valor::Decimal(decimal("-2.341")), Valor::Entero(2)]),
+            "-2.35"
+        );
+    }
+
+    #[test]
+    fn test_conversiones_y_errores() {
+        assert_eq!(
+            nuevo(vec![Valor::Texto(" 1.10 ".to_string())]).to_string(),
+            "1.10"
+        );
+        assert_eq!(
+            nuevo(vec![Valor::Texto("1e3".to_string())]).to_string(),
+            "1000"
+        );
+        assert_eq!(nuevo(vec![Valor::Entero(-7)]).to_string(), "-7");
+        assert_eq!(
+            escala(vec![Valor::Decimal(decimal("1.50"))]).to_string(),
+            "2"
+        );
+        assert_eq!(
+            a_entero(vec![Valor::Decimal(decimal("-7.9"))]).to_string(),
+            "-7"
+        );
+
+        assert_eq!(
+            a_entero(vec![Valor::Decimal(Decimal::MAX)]).to_string(),
+            "null"
+        );
+        assert!(errores::tomar_error_pendiente().is_some());
+        assert_eq!(nuevo(vec![Valor::Flotante(f64::NAN)]).to_string(), "null");
+        assert!(errores::tomar_error_pendiente().is_some());
+        assert_eq!(fijar_contexto(vec![Valor::Entero(29)]).to_string(), "null");
+        assert!(errores::tomar_error_pendiente().is_some());
+    }
+}
@@ -1,10 +1,14 @@
 use crate::runtime::clases::Clase;
-use crate::runtime::valores::Valor;
+use crate::runtime::valores::{Instancia, SharedPromesa, Valor};
+use std::cell::RefCell;
+use indexmap::IndexMap;
 use std::collections::HashMap;
+use std::io;
+use std::sync::{Arc, LazyLock, Mutex};
 use umbral_parser::ast::{Asignacion, Expresion, Metodo, ObjetivoAsignacion, Parametro, Sentencia};
 
 pub fn crear_clase_error() -> Clase {
-    let mut propiedades = HashMap::new();
+    let mut propiedades = IndexMap::new();
     propiedades.insert("mensaje".to_string(), Valor::Texto("".to_string()));
     propiedades.insert("data".to_string(), Valor::Nulo);
 
@@ -34,3 +38,112 @@ pub fn crear_clase_error() -> Clase {
         constructor: Some(constructor),
     }
 }
+
+thread_local! {
+    static ERROR_PENDIENTE: RefCell<Option<Valor>> = const { RefCell::new(None) };
+}
+
+pub fn crear_error(mensaje: &str, data: Valor) -> Valor {
+    let mut propiedades = IndexMap::new();
+    propiedades.insert("mensaje".to_string(), Valor::Texto(mensaje.to_string()));
+    propiedades.insert("data".to_string(), data);
+
+    Valor::Objeto(Instancia {
+        clase: "Error".to_string(),
+        propiedades: Arc::new(Mutex::new(propiedades)),
+    })
+}
+
+// Las funciones nativas no tienen acceso al intérprete: dejan el error
+// pendiente y el intérprete lo lanza como excepción al terminar la llamada.
+pub fn lanzar(error: Valor) -> Valor {
+    ERROR_PENDIENTE.with(|pendiente| *pendiente.borrow_mut() = Some(error));
+    Valor::Nulo
+}
+
+pub fn lanzar_mensaje(mensaje: &str, tipo: &str) -> Valor {
+    let mut data = IndexMap::new();
+    data.insert("kind".to_string(), Valor::Texto(tipo.to_string()));
+    lanzar(crear_error(mensaje, Valor::Diccionario(data)))
+}
+
+// Terminar el programa también es una excepción, para que lo decida quien
+// lo ejecuta (la CLI sale con el código; `umbral test` sigue con el
+// siguiente test). Los `ct:` no la capturan. El nombre no es un
+// identificador válido, así que ninguna clase del usuario puede hacerse pasar
+// por ella.
+pub const CLASE_SALIDA: &str = "<salida>";
+
+pub fn lanzar_salida(codigo: i32) -> Valor {
+    let mut propiedades = IndexMap::new();
+    propiedades.insert("codigo".to_string(), Valor::Entero(codigo as i64));
+
+    lanzar(Valor::Objeto(Instancia {
+        clase: CLASE_SALIDA.to_string(),
+        propiedades: Arc::new(Mutex::new(propiedades)),
+    }))
+}
+
+pub fn codigo_salida(excepcion: &Valor) -> Option<i32> {
+    let Valor::Objeto(instancia) = excepcion else {
+        return None;
+    };
+    if instancia.clase != CLASE_SALIDA {
+        return None;
+    }
+    match instancia.propiedades.lock().unwrap().get("codigo") {
+        Some(Valor::Entero(codigo)) => Some(*codigo as i32),
+        _ => Some(0),
+    }
+}
+
+pub fn tomar_error_pendiente() -> Option<Valor> {
+    ERROR_PENDIENTE.with(|pendiente| pendiente.borrow_mut().take())
+}
+
+// Una operación en segundo plano termina en otro hilo: su error se guarda con
+// el id de la tarea y lo recoge el `awa` que la espera.
+static ERRORES_DE_TAREAS: LazyLock<Mutex<HashMap<tokio::task::Id, Valor>>> =
+    LazyLock::new(|| Mutex::new(HashMap::new()));
+
+/// Ejecuta `operacion` en un hilo de `spawn_blocking` y devuelve la promesa
+/// de su resultado, para que una nativa que bloquea no detenga el runtime.
+pub fn promesa_bloqueante(operacion: impl FnOnce() -> Valor + Send + 'static) -> Valor {
+    let tarea = tokio::spawn(async move {
+        let (valor, error) =
+            tokio::task::spawn_blocking(move || (operacion(), tomar_error_pendiente()))
+                .await
+                .unwrap_or((Valor::Nulo, None));
+        if let Some(error) = error {
+            ERRORES_DE_TAREAS
+                .lock()
+                .unwrap()
+                .insert(tokio::task::id(), error);
+        }
+        valor
+    });
+    Valor::Promesa(SharedPromesa(Arc::new(Mutex::new(Some(tarea)))))
+}
+
+pub fn tomar_error_de_tarea(id: tokio::task::Id) -> Option<Valor> {
+    ERRORES_DE_TAREAS.lock().unwrap().remove(&id)
+}
+
+pub fn lanzar_argumento_invalido(funcion: &str, esperado: &str) -> Valor {
+    lanzar_mensaje(
+        &format!("{}: se esperaba {}", funcion, esperado),
+        "InvalidInput",
+    )
+}
+
+pub fn datos_error_io(error: &io::Error) -> IndexMap<String, Valor> {
+    let mut data = IndexMap::new();
+    data.insert(
+        "kind".to_string(),
+        Valor::Texto(format!("{:?}", error.kind())),
+    );
+    if let Some(codigo) = error.raw_os_error() {
+        data.insert("code".to_string(), Valor::Entero(codigo as i64));
+    }
+    data
+}
@@ -1,9 +1,17 @@
+use crate::runtime::stdlib::{errores, sesion};
 use crate::runtime::valores::Valor;
-use std::collections::HashMap;
-use std::fs;
-use std::path::Path;
+use indexmap::IndexMap;
+use std::fs::{self, File, OpenOptions};
+use std::io::{self, BufRead, BufReader, BufWriter, Write};
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicI64, Ordering};
+use std::time::UNIX_EPOCH;
 
-fn registrar_funcion(mapa: &mut HashMap<String, Valor>, nombre: &str, funcion: fn(Vec<Valor>) -> Valor) {
+fn registrar_funcion(
+    mapa: &mut IndexMap<String, Valor>,
+    nombre: &str,
+    funcion: fn(Vec<Valor>) -> Valor,
+) {
     mapa.insert(
         nombre.to_string(),
         Valor::FuncionNativa(nombre.to_string(), funcion),
@@ -11,71 +19,651 @@ fn registrar_funcion(mapa: &mut HashMap<String, Valor>, nombre: &str, funcion: f
 }
 
 pub fn crear_modulo() -> Valor {
-    let mut mapa = HashMap::new();
+    let mut mapa = IndexMap::new();
 
     registrar_funcion(&mut mapa, "read_file", leer_archivo);
     registrar_funcion(&mut mapa, "write_file", escribir_archivo);
+    registrar_funcion(&mut mapa, "append_file", anexar_archivo);
     registrar_funcion(&mut mapa, "exists", existe);
+    registrar_funcion(&mut mapa, "read_lines", leer_lineas);
+    registrar_funcion(&mut mapa, "read_bytes", leer_bytes);
+    registrar_funcion(&mut mapa, "write_bytes", escribir_bytes);
+    registrar_funcion(&mut mapa, "list_dir", listar_directorio);
+    registrar_funcion(&mut mapa, "walk", recorrer);
+    registrar_funcion(&mut mapa, "mkdir", crear_directorio);
+    registrar_funcion(&mut mapa, "remove", eliminar);
+    registrar_funcion(&mut mapa, "remove_dir_all", eliminar_directorio_completo);
+    registrar_funcion(&mut mapa, "copy", copiar);
+    registrar_funcion(&mut mapa, "rename", renombrar);
+    registrar_funcion(&mut mapa, "metadata", metadatos);
+    registrar_funcion(&mut mapa, "temp_dir", directorio_temporal);
+    registrar_funcion(&mut mapa, "temp_file", archivo_temporal);
+    registrar_funcion(&mut mapa, "open", abrir);
+    registrar_funcion(&mut mapa, "read_line", leer_linea);
+    registrar_funcion(&mut mapa, "write", escribir);
+    registrar_funcion(&mut mapa, "close", cerrar);
 
     Valor::Diccionario(mapa)
 }
 
-fn obtener_ruta(argumentos: &[Valor]) -> Option<String> {
-    match argumentos.get(0) {
-        Some(Valor::Texto(ruta)) => Some(ruta.clone()),
+fn lanzar_error_io(operacion: &str, ruta: &str, error: io::Error) -> Valor {
+    let mut data = errores::datos_error_io(&error);
+    data.insert("path".to_string(), Valor::Texto(ruta.to_string()));
+
+    let mensaje = format!("{}: '{}': {}", operacion, ruta, error);
+    errores::lanzar(errores::crear_error(&mensaje, Valor::Diccionario(data)))
+}
+
+fn obtener_texto(argumentos: &[Valor], indice: usize) -> Option<String> {
+    match argumentos.get(indice) {
+        Some(Valor::Texto(texto)) => Some(texto.clone()),
         _ => None,
     }
 }
 
-fn leer_contenido_archivo(ruta: &str) -> Valor {
-    match fs::read_to_string(ruta) {
+fn obtener_ruta_y_contenido(argumentos: &[Valor]) -> Option<(String, String)> {
+    Some((obtener_texto(argumentos, 0)?, obtener_texto(argumentos, 1)?))
+}
+
+// Las rutas relativas parten del directorio de trabajo del programa, que
+// `Std.proc.chdir` cambia sin tocar el del proceso.
+fn resolver(ruta: &str) -> PathBuf {
+    sesion::actual().resolver_ruta(ruta)
+}
+
+fn ruta_a_valor(ruta: &Path) -> Valor {
+    Valor::Texto(ruta.to_string_lossy().to_string())
+}
+
+fn leer_archivo(argumentos: Vec<Valor>) -> Valor {
+    let Some(ruta) = obtener_texto(&argumentos, 0) else {
+        return errores::lanzar_argumento_invalido("read_file", "una ruta");
+    };
+
+    match fs::read_to_string(resolver(&ruta)) {
         Ok(contenido) => Valor::Texto(contenido),
-        Err(_) => Valor::Nulo,
+        Err(error) => lanzar_error_io("read_file", &ruta, error),
     }
 }
 
-fn leer_archivo(argumentos: Vec<Valor>) -> Valor {
-    match obtener_ruta(&argumentos) {
-        Some(ruta) => leer_contenido_archivo(&ruta),
-        None => Valor::Nulo,
+fn escribir_archivo(argumentos: Vec<Valor>) -> Valor {
+    let Some((ruta, contenido)) = obtener_ruta_y_contenido(&argumentos) else {
+        return errores::lanzar_argumento_invalido("write_file", "una ruta y un texto");
+    };
+
+    match fs::write(resolver(&ruta), contenido) {
+        Ok(_) => Valor::Booleano(true),
+        Err(error) => lanzar_error_io("write_file", &ruta, error),
     }
 }
 
-fn obtener_ruta_y_contenido(argumentos: &[Valor]) -> Option<(String, String)> {
-    let ruta = match argumentos.get(0) {
-        Some(Valor::Texto(r)) => r.clone(),
-        _ => return None,
+fn anexar_archivo(argumentos: Vec<Valor>) -> Valor {
+    let Some((ruta, contenido)) = obtener_ruta_y_contenido(&argumentos) else {
+        return errores::lanzar_argumento_invalido("append_file", "una ruta y un texto");
     };
 
-    let contenido = match argumentos.get(1) {
-        Some(Valor::Texto(c)) => c.clone(),
-        _ => return None,
+    let resultado = OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(resolver(&ruta))
+        .and_then(|mut archivo| archivo.write_all(contenido.as_bytes()));
+
+    match resultado {
+        Ok(_) => Valor::Booleano(true),
+        Err(error) => lanzar_error_io("append_file", &ruta, error),
+    }
+}
+
+fn existe(argumentos: Vec<Valor>) -> Valor {
+    match obtener_texto(&argumentos, 0) {
+        Some(ruta) => Valor::Booleano(resolver(&ruta).exists()),
+        None => Valor::Booleano(false),
+    }
+}
+
+fn leer_lineas(argumentos: Vec<Valor>) -> Valor {
+    let Some(ruta) = obtener_texto(&argumentos, 0) else {
+        return errores::lanzar_argumento_invalido("read_lines", "una ruta");
     };
 
-    Some((ruta, contenido))
+    match fs::read_to_string(resolver(&ruta)) {
+        Ok(contenido) => Valor::Lista(
+            contenido
+                .lines()
+                .map(|linea| Valor::Texto(linea.to_string()))
+                .collect(),
+        ),
+        Err(error) => lanzar_error_io("read_lines", &ruta, error),
+    }
+}
+
+fn leer_bytes(argumentos: Vec<Valor>) -> Valor {
+    let Some(ruta) = obtener_texto(&argumentos, 0) else {
+        return errores::lanzar_argumento_invalido("read_bytes", "una ruta");
+    };
+
+    match fs::read(resolver(&ruta)) {
+        Ok(bytes) => Valor::Lista(bytes.into_iter().map(|b| Valor::Entero(b as i64)).collect()),
+        Err(error) => lanzar_error_io("read_bytes", &ruta, error),
+    }
+}
+
+fn convertir_bytes(valores: &[Valor]) -> Option<Vec<u8>> {
+    valores
+        .iter()
+        .map(|valor| match valor {
+            Valor::Entero(n) => u8::try_from(*n).ok(),
+            _ => None,
+        })
+        .collect()
 }
 
-fn guardar_contenido(ruta: &str, contenido: &str) -> Valor {
-    match fs::write(ruta, contenido) {
+fn escribir_bytes(argumentos: Vec<Valor>) -> Valor {
+    let (Some(ruta), Some(Valor::Lista(valores))) =
+        (obtener_texto(&argumentos, 0), argumentos.get(1))
+    else {
+        return errores::lanzar_argumento_invalido("write_bytes", "una ruta y una lista de bytes");
+    };
+
+    let Some(bytes) = convertir_bytes(valores) else {
+        return errores::lanzar_argumento_invalido("write_bytes", "enteros entre 0 y 255");
+    };
+
+    match fs::write(resolver(&ruta), bytes) {
         Ok(_) => Valor::Booleano(true),
-        Err(_) => Valor::Booleano(false),
+        Err(error) => lanzar_error_io("write_bytes", &ruta, error),
     }
 }
 
-fn escribir_archivo(argumentos: Vec<Valor>) -> Valor {
-    match obtener_ruta_y_contenido(&argumentos) {
-        Some((ruta, contenido)) => guardar_contenido(&ruta, &contenido),
-        None => Valor::Booleano(false),
+fn listar_directorio(argumentos: Vec<Valor>) -> Valor {
+    let Some(ruta) = obtener_texto(&argumentos, 0) else {
+        return errores::lanzar_argumento_invalido("list_dir", "una ruta");
+    };
+
+    let entradas = fs::read_dir(resolver(&ruta)).and_then(|lector| {
+        lector
+            .map(|entrada| entrada.map(|e| e.file_name().to_string_lossy().to_string()))
+            .collect::<io::Result<Vec<String>>>()
+    });
+
+    match entradas {
+        Ok(mut nombres) => {
+            nombres.sort();
+            Valor::Lista(nombres.into_iter().map(Valor::Texto).collect())
+        }
+        Err(error) => lanzar_error_io("list_dir", &ruta, error),
     }
 }
 
-fn verificar_existencia(ruta: &str) -> Valor {
-    Valor::Booleano(Path::new(ruta).exists())
+fn coincide_patron(patron: &glob::Pattern, raiz: &Path, ruta: &Path) -> bool {
+    let relativa = ruta.strip_prefix(raiz).unwrap_or(ruta);
+
+    if patron.as_str().contains('/') {
+        return patron.matches_path(relativa);
+    }
+
+    ruta.file_name()
+        .is_some_and(|nombre| patron.matches(&nombre.to_string_lossy()))
 }
 
-fn existe(argumentos: Vec<Valor>) -> Valor {
-    match obtener_ruta(&argumentos) {
-        Some(ruta) => verificar_existencia(&ruta),
+fn recorrer_directorio(
+    directorio: &Path,
+    raiz: &Path,
+    patron: Option<&glob::Pattern>,
+    resultado: &mut Vec<PathBuf>,
+) -> io::Result<()> {
+    let mut entradas = fs::read_dir(directorio)?.collect::<io::Result<Vec<_>>>()?;
+    entradas.sort_by_key(|entrada| entrada.file_name());
+
+    for entrada in entradas {
+        let ruta = entrada.path();
+
+        if entrada.file_type()?.is_dir() {
+            recorrer_directorio(&ruta, raiz, patron, resultado)?;
+            continue;
+        }
+
+        if patron.is_none_or(|p| coincide_patron(p, raiz, &ruta)) {
+            resultado.push(ruta);
+        }
+    }
+
+    Ok(())
+}
+
+fn recorrer(argumentos: Vec<Valor>) -> Valor {
+    let Some(ruta) = obtener_texto(&argumentos, 0) else {
+        return errores::lanzar_argumento_invalido("walk", "una ruta");
+    };
+
+    let patron = match obtener_texto(&argumentos, 1) {
+        Some(texto) => match glob::Pattern::new(&texto) {
+            Ok(patron) => Some(patron),
+            Err(error) => {
+                return errores::lanzar_mensaje(
+                    &format!("walk: patrón inválido '{}': {}", texto, error),
+                    "InvalidInput",
+                )
+            }
+        },
+        None => None,
+    };
+
+    // Los resultados empiezan por `ruta` tal como se escribió.
+    let raiz = resolver(&ruta);
+    let mut archivos = Vec::new();
+
+    match recorrer_directorio(&raiz, &raiz, patron.as_ref(), &mut archivos) {
+        Ok(_) => Valor::Lista(
+            archivos
+                .iter()
+                .map(|r| ruta_a_valor(&Path::new(&ruta).join(r.strip_prefix(&raiz).unwrap_or(r))))
+                .collect(),
+        ),
+        Err(error) => lanzar_error_io("walk", &ruta, error),
+    }
+}
+
+fn crear_directorio(argumentos: Vec<Valor>) -> Valor {
+    let Some(ruta) = obtener_texto(&argumentos, 0) else {
+        return errores::lanzar_argumento_invalido("mkdir", "una ruta");
+    };
+
+    match fs::create_dir_all(resolver(&ruta)) {
+        Ok(_) => Valor::Booleano(true),
+        Err(error) => lanzar_error_io("mkdir", &ruta, error),
+    }
+}
+
+fn eliminar(argumentos: Vec<Valor>) -> Valor {
+    let Some(ruta) = obtener_texto(&argumentos, 0) else {
+        return errores::lanzar_argumento_invalido("remove", "una ruta");
+    };
+
+    let destino = resolver(&ruta);
+    let resultado = if destino.is_dir() {
+        fs::remove_dir(&destino)
+    } else {
+        fs::remove_file(&destino)
+    };
+
+    match resultado {
+        Ok(_) => Valor::Booleano(true),
+        Err(error) => lanzar_error_io("remove", &ruta, error),
+    }
+}
+
+fn eliminar_directorio_completo(argumentos: Vec<Valor>) -> Valor {
+    let Some(ruta) = obtener_texto(&argumentos, 0) else {
+        return errores::lanzar_argumento_invalido("remove_dir_all", "una ruta");
+    };
+
+    match fs::remove_dir_all(resolver(&ruta)) {
+        Ok(_) => Valor::Booleano(true),
+        Err(error) => lanzar_error_io("remove_dir_all", &ruta, error),
+    }
+}
+
+fn copiar(argumentos: Vec<Valor>) -> Valor {
+    let Some((origen, destino)) = obtener_ruta_y_contenido(&argumentos) else {
+        return errores::lanzar_argumento_invalido("copy", "una ruta de origen y una de destino");
+    };
+
+    match fs::copy(resolver(&origen), resolver(&destino)) {
+        Ok(bytes) => Valor::Entero(bytes as i64),
+        Err(error) => lanzar_error_io("copy", &origen, error),
+    }
+}
+
+fn renombrar(argumentos: Vec<Valor>) -> Valor {
+    let Some((origen, destino)) = obtener_ruta_y_contenido(&argumentos) else {
+        return errores::lanzar_argumento_invalido("rename", "una ruta de origen y una de destino");
+    };
+
+    match fs::rename(resolver(&origen), resolver(&destino)) {
+        Ok(_) => Valor::Booleano(true),
+        Err(error) => lanzar_error_io("rename", &origen, error),
+    }
+}
+
+#[cfg(unix)]
+fn modo_permisos(permisos: &fs::Permissions) -> Valor {
+    use std::os::unix::fs::PermissionsExt;
+    Valor::Entero((permisos.mode() & 0o7777) as i64)
+}
+
+#[cfg(not(unix))]
+fn modo_permisos(_permisos: &fs::Permissions) -> Valor {
+    Valor::Nulo
+}
+
+fn convertir_metadatos(datos: &fs::Metadata) -> Valor {
+    let mut mapa = IndexMap::new();
+
+    let modificado = datos
+        .modified()
+        .ok()
+        .and_then(|fecha| fecha.duration_since(UNIX_EPOCH).ok())
+        .map(|duracion| Valor::Entero(duracion.as_secs() as i64))
+        .unwrap_or(Valor::Nulo);
+
+    mapa.insert("size".to_string(), Valor::Entero(datos.len() as i64));
+    mapa.insert("mtime".to_string(), modificado);
+    mapa.insert("is_dir".to_string(), Valor::Booleano(datos.is_dir()));
+    mapa.insert("is_file".to_string(), Valor::Booleano(datos.is_file()));
+    mapa.insert(
+        "is_symlink".to_string(),
+        Valor::Booleano(datos.file_type().is_symlink()),
+    );
+    mapa.insert(
+        "readonly".to_string(),
+        Valor::Booleano(datos.permissions().readonly()),
+    );
+    mapa.insert(
+        "permissions".to_string(),
+        modo_permisos(&datos.permissions()),
+    );
+
+    Valor::Diccionario(mapa)
+}
+
+fn metadatos(argumentos: Vec<Valor>) -> Valor {
+    let Some(ruta) = obtener_texto(&argumentos, 0) else {
+        return errores::lanzar_argumento_invalido("metadata", "una ruta");
+    };
+
+    match fs::metadata(resolver(&ruta)) {
+        Ok(datos) => convertir_metadatos(&datos),
+        Err(error) => lanzar_error_io("metadata", &ruta, error),
+    }
+}
+
+fn directorio_temporal(_argumentos: Vec<Valor>) -> Valor {
+    ruta_a_valor(&std::env::temp_dir())
+}
+
+static CONTADOR_TEMPORALES: AtomicI64 = AtomicI64::new(0);
+
+fn archivo_temporal(argumentos: Vec<Valor>) -> Valor {
+    let prefijo = obtener_texto(&argumentos, 0).unwrap_or_else(|| "umbral".to_string());
+
+    loop {
+        let nombre = format!(
+            "{}-{}-{}",
+            prefijo,
+            std::process::id(),
+            CONTADOR_TEMPORALES.fetch_add(1, Ordering::Relaxed)
+        );
+        let ruta = std::env::temp_dir().join(nombre);
+
+        match OpenOptions::new().write(true).create_new(true).open(&ruta) {
+            Ok(_) => return ruta_a_valor(&ruta),
+            Err(error) if error.kind() == io::ErrorKind::AlreadyExists => continue,
+            Err(error) => return lanzar_error_io("temp_file", &ruta.to_string_lossy(), error),
+        }
+    }
+}
+
+// Los manejadores viven en la sesión del intérprete que los abrió: los que
+// no se cierran con `close` se liberan cuando esa sesión termina.
+pub(crate) enum ArchivoAbierto {
+    Lectura(BufReader<File>),
+    Escritura(BufWriter<File>),
+}
+
+static SIGUIENTE_MANEJADOR: AtomicI64 = AtomicI64::new(1);
+
+fn abrir_con_modo(ruta: &Path, modo: &str) -> io::Result<ArchivoAbierto> {
+    match modo {
+        "r" => Ok(ArchivoAbierto::Lectura(BufReader::new(File::open(ruta)?))),
+        "w" => Ok(ArchivoAbierto::Escritura(BufWriter::new(File::create(
+            ruta,
+        )?))),
+        "a" => {
+            let archivo = OpenOptions::new().create(true).append(true).open(ruta)?;
+            Ok(ArchivoAbierto::Escritura(BufWriter::new(archivo)))
+        }
+        _ => Err(io::Error::new(
+            io::ErrorKind::InvalidInput,
+            format!("modo '{}' no soportado (usa 'r', 'w' o 'a')", modo),
+        )),
+    }
+}
+
+fn abrir(argumentos: Vec<Valor>) -> Valor {
+    let Some(ruta) = obtener_texto(&argumentos, 0) else {
+        return errores::lanzar_argumento_invalido("open", "una ruta");
+    };
+    let modo = obtener_texto(&argumentos, 1).unwrap_or_else(|| "r".to_string());
+
+    let archivo = match abrir_con_modo(&resolver(&ruta), &modo) {
+        Ok(archivo) => archivo,
+        Err(error) => return lanzar_error_io("open", &ruta, error),
+    };
+
+    let id = SIGUIENTE_MANEJADOR.fetch_add(1, Ordering::Relaxed);
+    sesion::actual()
+        .archivos
+        .lock()
+        .unwrap()
+        .insert(id, archivo);
+
+    let mut manejador = IndexMap::new();
+    manejador.insert("id".to_string(), Valor::Entero(id));
+    manejador.insert("path".to_string(), Valor::Texto(ruta));
+    manejador.insert("mode".to_string(), Valor::Texto(modo));
+    Valor::Diccionario(manejador)
+}
+
+fn obtener_manejador(argumentos: &[Valor]) -> Option<(i64, String)> {
+    let Some(Valor::Diccionario(manejador)) = argumentos.first() else {
+        return None;
+    };
+
+    match (manejador.get("id"), manejador.get("path")) {
+        (Some(Valor::Entero(id)), Some(Valor::Texto(ruta))) => Some((*id, ruta.clone())),
+        _ => None,
+    }
+}
+
+fn con_archivo_abierto(
+    funcion: &str,
+    argumentos: &[Valor],
+    operacion: impl FnOnce(&mut ArchivoAbierto) -> io::Result<Valor>,
+) -> Valor {
+    let Some((id, ruta)) = obtener_manejador(argumentos) else {
+        return errores::lanzar_argumento_invalido(funcion, "un archivo abierto con open");
+    };
+
+    let sesion = sesion::actual();
+    let mut archivos = sesion.archivos.lock().unwrap();
+    let Some(archivo) = archivos.get_mut(&id) else {
+        return lanzar_error_io(
+            funcion,
+            &ruta,
+            io::Error::other("el archivo ya está cerrado"),
+        );
+    };
+
+    match operacion(archivo) {
+        Ok(valor) => valor,
+        Err(error) => lanzar_error_io(funcion, &ruta, error),
+    }
+}
+
+fn leer_linea(argumentos: Vec<Valor>) -> Valor {
+    con_archivo_abierto("read_line", &argumentos, |archivo| {
+        let ArchivoAbierto::Lectura(lector) = archivo else {
+            return Err(io::Error::new(
+                io::ErrorKind::Unsupported,
+                "el archivo no está abierto para lectura",
+            ));
+        };
+
+        let mut linea = String::new();
+        if lector.read_line(&mut linea)? == 0 {
+            return Ok(Valor::Nulo);
+        }
+
+        let sin_salto = linea.strip_suffix('\n').unwrap_or(&linea);
+        let sin_salto = sin_salto.strip_suffix('\r').unwrap_or(sin_salto);
+        Ok(Valor::Texto(sin_salto.to_string()))
+    })
+}
+
+fn escribir(argumentos: Vec<Valor>) -> Valor {
+    let contenido = match argumentos.get(1) {
+        Some(Valor::Texto(texto)) => texto.clone(),
+        Some(otro) => otro.to_string(),
+        None => return errores::lanzar_argumento_invalido("write", "un archivo y un texto"),
+    };
+
+    con_archivo_abierto("write", &argumentos, |archivo| {
+        let ArchivoAbierto::Escritura(escritor) = archivo else {
+            return Err(io::Error::new(
+                io::ErrorKind::Unsupported,
+                "el archivo no está abierto para escritura",
+            ));
+        };
+
+        escritor.write_all(contenido.as_bytes())?;
+        Ok(Valor::Booleano(true))
+    })
+}
+
+fn cerrar(argumentos: Vec<Valor>) -> Valor {
+    let Some((id, ruta)) = obtener_manejador(&argumentos) else {
+        return errores::lanzar_argumento_invalido("close", "un archivo abierto con open");
+    };
+
+    let archivo = sesion::actual().archivos.lock().unwrap().remove(&id);
+
+    match archivo {
+        Some(ArchivoAbierto::Escritura(mut escritor)) => match escritor.flush() {
+            Ok(_) => Valor::Booleano(true),
+            Err(error) => lanzar_error_io("close", &ruta, error),
+        },
+        Some(ArchivoAbierto::Lectura(_)) => Valor::Booleano(true),
         None => Valor::Booleano(false),
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::runtime::stdlib::sesion::{self, Sesion};
+    use tempfile::TempDir;
+
+    fn ruta_en(directorio: &TempDir, archivo: &str) -> String {
+        directorio.path().join(archivo).to_string_lossy().into_owned()
+    }
+
+    fn texto(valor: &str) -> Valor {
+        Valor::Texto(valor.to_string())
+    }
+
+    fn dato_del_error(clave: &str) -> Option<String> {
+        let Some(Valor::Objeto(error)) = errores::tomar_error_pendiente() else {
+            return None;
+        };
+        let propiedades = error.propiedades.lock().unwrap();
+        match propiedades.get("data") {
+            Some(Valor::Diccionario(data)) => data.get(clave).map(Valor::to_string),
+            _ => None,
+        }
+    }
+
+    #[test]
+    fn test_error_de_archivo_inexistente_indica_el_tipo() {
+        let directorio = tempfile::tempdir().unwrap();
+        let ruta = ruta_en(&directorio, "falta.txt");
+
+        leer_archivo(vec![texto(&ruta)]);
+
+        assert_eq!(dato_del_error("kind"), Some("NotFound".to_string()));
+        leer_archivo(vec![texto(&ruta)]);
+        assert_eq!(dato_del_error("path"), Some(ruta));
+    }
+
+    #[test]
+    fn test_recorrer_ordena_y_filtra_por_patron() {
+        let directorio = tempfile::tempdir().unwrap();
+        for archivo in ["b.um", "a.txt", "sub/c.um", "sub/d.txt"] {
+            let ruta = directorio.path().join(archivo);
+            fs::create_dir_all(ruta.parent().unwrap()).unwrap();
+            fs::write(ruta, "").unwrap();
+        }
+        let raiz = texto(&ruta_en(&directorio, ""));
+        let relativas = |resultado: Valor| match resultado {
+            Valor::Lista(rutas) => rutas
+                .iter()
+                .map(|ruta| ruta.to_string().replace(&ruta_en(&directorio, ""), ""))
+                .collect::<Vec<_>>(),
+            otro => panic!("walk devolvió {}", otro),
+        };
+
+        assert_eq!(
+            relativas(recorrer(vec![raiz.clone()])),
+            ["a.txt", "b.um", "sub/c.um", "sub/d.txt"]
+        );
+        assert_eq!(
+            relativas(recorrer(vec![raiz.clone(), texto("*.um")])),
+            ["b.um", "sub/c.um"]
+        );
+        assert_eq!(
+            relativas(recorrer(vec![raiz, texto("sub/*.txt")])),
+            ["sub/d.txt"]
+        );
+        assert!(errores::tomar_error_pendiente().is_none());
+    }
+
+    #[test]
+    fn test_abrir_escribir_y_cerrar() {
+        let directorio = tempfile::tempdir().unwrap();
+        let ruta = ruta_en(&directorio, "datos.txt");
+        let sesion = Sesion::nueva();
+
+        sesion::con_sesion(&sesion, || {
+            let manejador = abrir(vec![texto(&ruta), texto("w")]);
+            assert_eq!(
+                escribir(vec![manejador.clone(), texto("uno\r\n")]).to_string(),
+                "true"
+            );
+            assert_eq!(
+                escribir(vec![manejador.clone(), texto("dos")]).to_string(),
+                "true"
+            );
+            assert_eq!(leer_linea(vec![manejador.clone()]).to_string(), "null");
+            assert_eq!(dato_del_error("kind"), Some("Unsupported".to_string()));
+            assert_eq!(cerrar(vec![manejador.clone()]).to_string(), "true");
+            assert_eq!(cerrar(vec![manejador.clone()]).to_string(), "false");
+
+            escribir(vec![manejador, texto("tres")]);
+            assert!(dato_del_error("kind").is_some());
+
+            let lector = abrir(vec![texto(&ruta)]);
+            assert_eq!(leer_linea(vec![lector.clone()]).to_string(), "uno");
+            assert_eq!(leer_linea(vec![lector.clone()]).to_string(), "dos");
+            assert_eq!(leer_linea(vec![lector]).to_string(), "null");
+        });
+
+        assert!(errores::tomar_error_pendiente().is_none());
+        assert_eq!(sesion.archivos.lock().unwrap().len(), 1);
+        assert!(sesion::actual().archivos.lock().unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_archivos_sin_cerrar_se_vuelcan_al_terminar_la_sesion() {
+        let directorio = tempfile::tempdir().unwrap();
+        let ruta = ruta_en(&directorio, "pendiente.txt");
+        let sesion = Sesion::nueva();
+
+        sesion::con_sesion(&sesion, || {
+            let manejador = abrir(vec![texto(&ruta), texto("w")]);
+            escribir(vec![manejador, texto("sin close")]);
+        });
+        drop(sesion);
+
+        assert_eq!(fs::read_to_string(&ruta).unwrap(), "sin close");
+    }
+}
@@ -1,50 +1,109 @@
+use crate::runtime::stdlib::errores;
 use crate::runtime::valores::Valor;
-use std::collections::HashMap;
+use indexmap::IndexMap;
+use serde::Serialize;
+
+pub fn crear_modulo() -> Valor {
+    let mut mapa = IndexMap::new();
 
-fn registrar_funcion(mapa: &mut HashMap<String, Valor>, nombre: &str, funcion: fn(Vec<Valor>) -> Valor) {
     mapa.insert(
-        nombre.to_string(),
-        Valor::FuncionNativa(nombre.to_string(), funcion),
+        "parse".to_string(),
+        Valor::FuncionNativa("parse".to_string(), parsear),
+    );
+    mapa.insert(
+        "stringify".to_string(),
+        Valor::FuncionNativa("stringify".to_string(), convertir_texto),
     );
-}
 
-pub fn crear_modulo() -> Valor {
-    let mut mapa = HashMap::new();
+    Valor::Diccionario(mapa)
+}
 
-    registrar_funcion(&mut mapa, "parse", parsear);
-    registrar_funcion(&mut mapa, "stringify", convertir_texto);
+fn lanzar_error_sintaxis(error: &serde_json::Error) -> Valor {
+    let mut data = IndexMap::new();
+    data.insert("kind".to_string(), Valor::Texto("SyntaxError".to_string()));
+    data.insert("line".to_string(), Valor::Entero(error.line() as i64));
+    data.insert("column".to_string(), Valor::Entero(error.column() as i64));
 
-    Valor::Diccionario(mapa)
+    let mensaje = format!(
+        "JSON inválido en línea {}, columna {}: {}",
+        error.line(),
+        error.column(),
+        error
+    );
+    errores::lanzar(errores::crear_error(&mensaje, Valor::Diccionario(data)))
 }
 
-fn parsear_texto_json(texto: &str) -> Valor {
+pub(crate) fn parsear(argumentos: Vec<Valor>) -> Valor {
+    let Some(Valor::Texto(texto)) = argumentos.first() else {
+        return errores::lanzar_argumento_invalido("parse", "un texto JSON");
+    };
+
     match serde_json::from_str::<serde_json::Value>(texto) {
         Ok(valor_json) => json_a_valor(&valor_json),
-        Err(_) => Valor::Nulo,
+        Err(error) => lanzar_error_sintaxis(&error),
     }
 }
 
-fn parsear(argumentos: Vec<Valor>) -> Valor {
-    match argumentos.get(0) {
-        Some(Valor::Texto(texto)) => parsear_texto_json(texto),
-        _ => Valor::Nulo,
-    }
+struct OpcionesSalida {
+    sangria: Option<usize>,
+    ordenar_claves: bool,
 }
 
-fn serializar_a_texto(valor_json: &serde_json::Value) -> Valor {
-    match serde_json::to_string(valor_json) {
-        Ok(texto) => Valor::Texto(texto),
-        Err(_) => Valor::Nulo,
+impl OpcionesSalida {
+    // stringify(valor, [sangria], [opciones]); las opciones aceptan
+    // "indent" y "sort_keys".
+    fn desde_argumentos(argumentos: &[Valor]) -> Self {
+        let mut opciones = OpcionesSalida {
+            sangria: None,
+            ordenar_claves: false,
+        };
+
+        for argumento in argumentos.iter().skip(1) {
+            match argumento {
+                Valor::Entero(n) if *n > 0 => opciones.sangria = Some(*n as usize),
+                Valor::Diccionario(mapa) => {
+                    if let Some(Valor::Entero(n)) = mapa.get("indent") {
+                        opciones.sangria = (*n > 0).then_some(*n as usize);
+                    }
+                    opciones.ordenar_claves =
+                        mapa.get("sort_keys").is_some_and(Valor::es_verdadero);
+                }
+                _ => {}
+            }
+        }
+
+        opciones
     }
 }
 
-fn convertir_texto(argumentos: Vec<Valor>) -> Valor {
-    match argumentos.get(0) {
-        Some(valor) => {
-            let valor_json = valor_a_json(valor);
-            serializar_a_texto(&valor_json)
-        }
-        None => Valor::Nulo,
+fn serializar(
+    valor_json: &serde_json::Value,
+    sangria: Option<usize>,
+) -> serde_json::Result<String> {
+    let Some(sangria) = sangria else {
+        return serde_json::to_string(valor_json);
+    };
+
+    let espacios = vec![b' '; sangria];
+    let formato = serde_json::ser::PrettyFormatter::with_indent(&espacios);
+    let mut salida = Vec::new();
+    let mut serializador = serde_json::Serializer::with_formatter(&mut salida, formato);
+    valor_json.serialize(&mut serializador)?;
+
+    Ok(String::from_utf8_lossy(&salida).to_string())
+}
+
+pub(crate) fn convertir_texto(argumentos: Vec<Valor>) -> Valor {
+    let Some(valor) = argumentos.first() else {
+        return errores::lanzar_argumento_invalido("stringify", "un valor");
+    };
+
+    let opciones = OpcionesSalida::desde_argumentos(&argumentos);
+    let valor_json = valor_a_json(valor, opciones.ordenar_claves);
+
+    match serializar(&valor_json, opciones.sangria) {
+        Ok(texto) => Valor::Texto(texto),
+        Err(error) => errores::lanzar_mensaje(&format!("stringify: {}", error), "InvalidData"),
     }
 }
 
@@ -52,11 +111,11 @@ fn convertir_numero_json(numero: &serde_json::Number) -> Valor {
     if let Some(entero) = numero.as_i64() {
         return Valor::Entero(entero);
     }
-    
+
     if let Some(flotante) = numero.as_f64() {
         return Valor::Flotante(flotante);
     }
-    
+
     Valor::Nulo
 }
 
@@ -66,7 +125,7 @@ fn convertir_array_json(array: &[serde_json::Value]) -> Valor {
 }
 
 fn convertir_objeto_json(objeto: &serde_json::Map<String, serde_json::Value>) -> Valor {
-    let mut mapa = HashMap::new();
+    let mut mapa = IndexMap::new();
     for (clave, valor) in objeto {
         mapa.insert(clave.clone(), json_a_valor(valor));
     }
@@ -90,28 +149,140 @@ fn convertir_flotante_json(flotante: f64) -> serde_json::Value {
         .unwrap_or(serde_json::Value::Null)
 }
 
-fn convertir_lista_json(lista: &[Valor]) -> serde_json::Value {
-    let array: Vec<serde_json::Value> = lista.iter().map(valor_a_json).collect();
+fn convertir_lista_json(lista: &[Valor], ordenar_claves: bool) -> serde_json::Value {
+    let array: Vec<serde_json::Value> = lista
+        .iter()
+        .map(|valor| valor_a_json(valor, ordenar_claves))
+        .collect();
     serde_json::Value::Array(array)
 }
 
-fn convertir_diccionario_json(diccionario: &HashMap<String, Valor>) -> serde_json::Value {
+fn convertir_diccionario_json(
+    diccionario: &IndexMap<String, Valor>,
+    ordenar_claves: bool,
+) -> serde_json::Value {
+    let mut claves: Vec<&String> = diccionario.keys().collect();
+    if ordenar_claves {
+        claves.sort();
+    }
+
     let mut objeto = serde_json::Map::new();
-    for (clave, valor) in diccionario {
-        objeto.insert(clave.clone(), valor_a_json(valor));
+    for clave in claves {
+        objeto.insert(
+            clave.clone(),
+            valor_a_json(&diccionario[clave], ordenar_claves),
+        );
     }
     serde_json::Value::Object(objeto)
 }
 
-fn valor_a_json(valor: &Valor) -> serde_json::Value {
+fn valor_a_json(valor: &Valor, ordenar_claves: bool) -> serde_json::Value {
     match valor {
         Valor::Nulo => serde_json::Value::Null,
         Valor::Booleano(booleano) => serde_json::Value::Bool(*booleano),
         Valor::Entero(entero) => serde_json::Value::Number((*entero).into()),
         Valor::Flotante(flotante) => convertir_flotante_json(*flotante),
+        // Como texto, para no perder precisión al pasar por un f64.
+        Valor::Decimal(decimal) => serde_json::Value::String(decimal.to_string()),
         Valor::Texto(texto) => serde_json::Value::String(texto.clone()),
-        Valor::Lista(lista) => convertir_lista_json(lista),
-        Valor::Diccionario(diccionario) => convertir_diccionario_json(diccionario),
+        Valor::Lista(lista) => convertir_lista_json(lista, ordenar_claves),
+        Valor::Diccionario(diccionario) => convertir_diccionario_json(diccionario, ordenar_claves),
+        Valor::Objeto(instancia) => {
+            let propiedades = instancia.propiedades.lock().unwrap().clone();
+            convertir_diccionario_json(&propiedades, ordenar_claves)
+        }
+        Valor::FechaHora(fecha) => serde_json::Value::String(fecha.to_string()),
+        Valor::Duracion(duracion) => serde_json::Value::Number(duracion.num_milliseconds().into()),
         _ => serde_json::Value::Null,
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn texto(valor: &str) -> Valor {
+        Valor::Texto(valor.to_string())
+    }
+
+    fn stringify(argumentos: Vec<Valor>) -> String {
+        convertir_texto(argumentos).to_string()
+    }
+
+    #[test]
+    fn test_error_de_sintaxis_con_linea_y_columna() {
+        assert_eq!(
+            parsear(vec![texto("{\n  \"a\": 1,\n  x\n}")]).to_string(),
+            "null"
+        );
+
+        let Some(Valor::Objeto(error)) = errores::tomar_error_pendiente() else {
+            panic!("se esperaba un Error");
+        };
+        let propiedades = error.propiedades.lock().unwrap();
+        let Some(Valor::Diccionario(data)) = propiedades.get("data") else {
+            panic!("el error no tiene data");
+        };
+        assert_eq!(data["kind"].to_string(), "SyntaxError");
+        assert_eq!(data["line"].to_string(), "3");
+        assert_eq!(data["column"].to_string(), "3");
+        assert!(propiedades["mensaje"]
+            .to_string()
+            .starts_with("JSON inválido en línea 3, columna 3"));
+    }
+
+    #[test]
+    fn test_null_literal_no_es_un_error() {
+        assert_eq!(parsear(vec![texto("null")]).to_string(), "null");
+        assert!(errores::tomar_error_pendiente().is_none());
+    }
+
+    #[test]
+    fn test_parse_conserva_el_orden_del_texto() {
+        let Valor::Diccionario(datos) = parsear(vec![texto(r#"{"b": 1, "a": 2.5, "c": [true]}"#)])
+        else {
+            panic!("se esperaba un diccionario");
+        };
+        assert_eq!(datos.keys().collect::<Vec<_>>(), ["b", "a", "c"]);
+        assert!(matches!(datos["b"], Valor::Entero(1)));
+        assert!(matches!(datos["a"], Valor::Flotante(f) if f == 2.5));
+    }
+
+    #[test]
+    fn test_stringify_con_sangria_y_claves_ordenadas() {
+        let mut interior = IndexMap::new();
+        interior.insert("z".to_string(), Valor::Entero(1));
+        interior.insert("y".to_string(), Valor::Nulo);
+        let mut datos = IndexMap::new();
+        datos.insert("b".to_string(), Valor::Diccionario(interior));
+        datos.insert("a".to_string(), Valor::Lista(vec![Valor::Booleano(true)]));
+        let datos = Valor::Diccionario(datos);
+
+        assert_eq!(
+            stringify(vec![datos.clone()]),
+            r#"{"b":{"z":1,"y":null},"a":[true]}"#
+        );
+        assert_eq!(
+            stringify(vec![datos.clone(), Valor::Entero(2)]),
+            "{\n  \"b\": {\n    \"z\": 1,\n    \"y\": null\n  },\n  \"a\": [\n    true\n  ]\n}"
+        );
+
+        let mut opciones = IndexMap::new();
+        opciones.insert("sort_keys".to_string(), Valor::Booleano(true));
+        assert_eq!(
+            stringify(vec![datos, Valor::Diccionario(opciones)]),
+            r#"{"a":[true],"b":{"y":null,"z":1}}"#
+        );
+    }
+
+    #[test]
+    fn test_stringify_de_valores_sin_equivalente_json() {
+        let decimal = "0.1".parse().unwrap();
+        let lista = Valor::Lista(vec![
+            Valor::Decimal(decimal),
+            Valor::Flotante(f64::NAN),
+            Valor::Duracion(chrono::TimeDelta::seconds(2)),
+        ]);
+        assert_eq!(stringify(vec![lista]), r#"["0.1",null,2000]"#);
+    }
+}
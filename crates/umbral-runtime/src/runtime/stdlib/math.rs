@@ -0,0 +1,619 @@
+use crate::runtime::clases::Clase;
+use crate::runtime::stdlib::{errores, num};
+use crate::runtime::valores::{Instancia, NativeFn, Valor};
+use indexmap::IndexMap;
+use rand::seq::SliceRandom;
+use rand::{Rng, RngCore};
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+
+pub const CLASE_RANDOM: &str = "Random";
+
+fn registrar_funcion(mapa: &mut IndexMap<String, Valor>, nombre: &str, funcion: NativeFn) {
+    mapa.insert(
+        nombre.to_string(),
+        Valor::FuncionNativa(nombre.to_string(), funcion),
+    );
+}
+
+pub fn crear_modulo() -> Valor {
+    let mut mapa = IndexMap::new();
+
+    mapa.insert("PI".to_string(), Valor::Flotante(std::f64::consts::PI));
+    mapa.insert("E".to_string(), Valor::Flotante(std::f64::consts::E));
+    mapa.insert("INF".to_string(), Valor::Flotante(f64::INFINITY));
+    mapa.insert("NAN".to_string(), Valor::Flotante(f64::NAN));
+
+    registrar_funcion(&mut mapa, "sqrt", raiz);
+    registrar_funcion(&mut mapa, "cbrt", raiz_cubica);
+    registrar_funcion(&mut mapa, "pow", potencia);
+    registrar_funcion(&mut mapa, "exp", exponencial);
+    registrar_funcion(&mut mapa, "log", logaritmo);
+    registrar_funcion(&mut mapa, "log10", logaritmo_10);
+    registrar_funcion(&mut mapa, "log2", logaritmo_2);
+    registrar_funcion(&mut mapa, "sin", seno);
+    registrar_funcion(&mut mapa, "cos", coseno);
+    registrar_funcion(&mut mapa, "tan", tangente);
+    registrar_funcion(&mut mapa, "asin", arcoseno);
+    registrar_funcion(&mut mapa, "acos", arcocoseno);
+    registrar_funcion(&mut mapa, "atan", arcotangente);
+    registrar_funcion(&mut mapa, "atan2", arcotangente_2);
+    registrar_funcion(&mut mapa, "hypot", hipotenusa);
+    registrar_funcion(&mut mapa, "floor", num::piso);
+    registrar_funcion(&mut mapa, "ceil", num::techo);
+    registrar_funcion(&mut mapa, "round", num::redondear);
+    registrar_funcion(&mut mapa, "trunc", truncar);
+    registrar_funcion(&mut mapa, "sign", signo);
+    registrar_funcion(&mut mapa, "gcd", mcd);
+    registrar_funcion(&mut mapa, "lcm", mcm);
+    registrar_funcion(&mut mapa, "is_nan", es_nan);
+    registrar_funcion(&mut mapa, "is_infinite", es_infinito);
+    registrar_funcion(&mut mapa, "is_finite", es_finito);
+    registrar_funcion(&mut mapa, "Random", crear_random);
+
+    Valor::Diccionario(mapa)
+}
+
+pub fn crear_clase_random() -> Clase {
+    let mut propiedades = IndexMap::new();
+    propiedades.insert("seed".to_string(), Valor::Entero(0));
+    propiedades.insert("state".to_string(), Valor::Entero(0));
+
+    Clase {
+        nombre: CLASE_RANDOM.to_string(),
+        propiedades,
+        metodos: HashMap::new(),
+        constructor: None,
+    }
+}
+
+/// Métodos de las instancias de `Random`; reciben la instancia como primer
+/// argumento.
+pub fn metodo(nombre: &str) -> Option<Valor> {
+    let funcion: NativeFn = match nombre {
+        "int_range" => rango_entero,
+        "float" => flotante_aleatorio,
+        "choice" => elegir,
+        "shuffle" => barajar,
+        "sample" => muestra,
+        _ => return None,
+    };
+    Some(Valor::FuncionNativa(nombre.to_string(), funcion))
+}
+
+fn numero(argumentos: &[Valor], indice: usize) -> Option<f64> {
+    match argumentos.get(indice) {
+        Some(Valor::Entero(entero)) => Some(*entero as f64),
+        Some(Valor::Flotante(flotante)) => Some(*flotante),
+        _ => None,
+    }
+}
+
+fn aplicar(argumentos: &[Valor], funcion: &str, operacion: fn(f64) -> f64) -> Valor {
+    match numero(argumentos, 0) {
+        Some(x) => Valor::Flotante(operacion(x)),
+        None => errores::lanzar_argumento_invalido(funcion, "un número"),
+    }
+}
+
+fn aplicar_dos(argumentos: &[Valor], funcion: &str, operacion: fn(f64, f64) -> f64) -> Valor {
+    match (numero(argumentos, 0), numero(argumentos, 1)) {
+        (Some(x), Some(y)) => Valor::Flotante(operacion(x, y)),
+        _ => errores::lanzar_argumento_invalido(funcion, "dos números"),
+    }
+}
+
+fn raiz(argumentos: Vec<Valor>) -> Valor {
+    aplicar(&argumentos, "sqrt", f64::sqrt)
+}
+
+fn raiz_cubica(argumentos: Vec<Valor>) -> Valor {
+    aplicar(&argumentos, "cbrt", f64::cbrt)
+}
+
+// pow(base, exponente): entero si ambos son enteros y el exponente no es
+// negativo; el desbordamiento lanza un error igual que la aritmética.
+fn potencia(argumentos: Vec<Valor>) -> Valor {
+    if let (Some(Valor::Entero(base)), Some(Valor::Entero(exponente))) =
+        (argumentos.first(), argumentos.get(1))
+    {
+        if let Ok(exponente) = u32::try_from(*exponente) {
+            return match base.checked_pow(exponente) {
+                Some(resultado) => Valor::Entero(resultado),
+                None => errores::lanzar_mensaje(
+                    &format!("pow: desbordamiento de entero en {}^{}", base, exponente),
+                    "Overflow",
+                ),
+            };
+        }
+    }
+    aplicar_dos(&argumentos, "pow", f64::powf)
+}
+
+fn exponencial(argumentos: Vec<Valor>) -> Valor {
+    aplicar(&argumentos, "exp", f64::exp)
+}
+
+// log(x, [base]): sin base es el logaritmo natural.
+fn logaritmo(argumentos: Vec<Valor>) -> Valor {
+    if argumentos.len() > 1 {
+        return aplicar_dos(&argumentos, "log", f64::log);
+    }
+    aplicar(&argumentos, "log", f64::ln)
+}
+
+fn logaritmo_10(argumentos: Vec<Valor>) -> Valor {
+    aplicar(&argumentos, "log10", f64::log10)
+}
+
+fn logaritmo_2(argumentos: Vec<Valor>) -> Valor {
+    aplicar(&argumentos, "log2", f64::log2)
+}
+
+fn seno(argumentos: Vec<Valor>) -> Valor {
+    aplicar(&argumentos, "sin", f64::sin)
+}
+
+fn coseno(argumentos: Vec<Valor>) -> Valor {
+    aplicar(&argumentos, "cos", f64::cos)
+}
+
+fn tangente(argumentos: Vec<Valor>) -> Valor {
+    aplicar(&argumentos, "tan", f64::tan)
+}
+
+fn arcoseno(argumentos: Vec<Valor>) -> Valor {
+    aplicar(&argumentos, "asin", f64::asin)
+}
+
+fn arcocoseno(argumentos: Vec<Valor>) -> Valor {
+    aplicar(&argumentos, "acos", f64::acos)
+}
+
+fn arcotangente(argumentos: Vec<Valor>) -> Valor {
+    aplicar(&argumentos, "atan", f64::atan)
+}
+
+fn arcotangente_2(argumentos: Vec<Valor>) -> Valor {
+    aplicar_dos(&argumentos, "atan2", f64::atan2)
+}
+
+fn hipotenusa(argumentos: Vec<Valor>) -> Valor {
+    aplicar_dos(&argumentos, "hypot", f64::hypot)
+}
+
+fn truncar(argumentos: Vec<Valor>) -> Valor {
+    match argumentos.first() {
+        Some(Valor::Entero(numero)) => Valor::Entero(*numero),
+        Some(Valor::Flotante(numero)) => Valor::Entero(numero.trunc() as i64),
+        _ => Valor::Nulo,
+    }
+}
+
+fn signo(argumentos: Vec<Valor>) -> Valor {
+    match argumentos.first() {
+        Some(Valor::Entero(numero)) => Valor::Entero(numero.signum()),
+        Some(Valor::Flotante(numero)) if *numero == 0.0 || numero.is_nan() => {
+            Valor::Flotante(*numero)
+        }
+        Some(Valor::Flotante(numero)) => Valor::Flotante(numero.signum()),
+        _ => Valor::Nulo,
+    }
+}
+
+fn dos_enteros(argumentos: &[Valor], funcion: &str) -> Result<(i64, i64), Valor> {
+    match (argumentos.first(), argumentos.get(1)) {
+        (Some(Valor::Entero(a)), Some(Valor::Entero(b))) => Ok((*a, *b)),
+        _ => Err(errores::lanzar_argumento_invalido(funcion, "dos enteros")),
+    }
+}
+
+fn calcular_mcd(a: i64, b: i64) -> u64 {
+    let (mut a, mut b) = (a.unsigned_abs(), b.unsigned_abs());
+    while b != 0 {
+        (a, b) = (b, a % b);
+    }
+    a
+}
+
+fn lanzar_desbordamiento(funcion: &str, a: i64, b: i64) -> Valor {
+    errores::lanzar_mensaje(
+        &format!("{}: desbordamiento de entero con {} y {}", funcion, a, b),
+        "Overflow",
+    )
+}
+
+fn mcd(argumentos: Vec<Valor>) -> Valor {
+    let (a, b) = match dos_enteros(&argumentos, "gcd") {
+        Ok(enteros) => enteros,
+        Err(error) => return error,
+    };
+
+    match i64::try_from(calcular_mcd(a, b)) {
+        Ok(resultado) => Valor::Entero(resultado),
+        Err(_) => lanzar_desbordamiento("gcd", a, b),
+    }
+}
+
+fn mcm(argumentos: Vec<Valor>) -> Valor {
+    let (a, b) = match dos_enteros(&argumentos, "lcm") {
+        Ok(enteros) => enteros,
+        Err(error) => return error,
+    };
+
+    if a == 0 || b == 0 {
+        return Valor::Entero(0);
+    }
+
+    let resultado = (a.unsigned_abs() / calcular_mcd(a, b))
+        .checked_mul(b.unsigned_abs())
+        .and_then(|resultado| i64::try_from(resultado).ok());
+    match resultado {
+        Some(resultado) => Valor::Entero(resultado),
+        None => lanzar_desbordamiento("lcm", a, b),
+    }
+}
+
+fn es_nan(argumentos: Vec<Valor>) -> Valor {
+    Valor::Booleano(matches!(argumentos.first(), Some(Valor::Flotante(f)) if f.is_nan()))
+}
+
+fn es_infinito(argumentos: Vec<Valor>) -> Valor {
+    Valor::Booleano(matches!(argumentos.first(), Some(Valor::Flotante(f)) if f.is_infinite()))
+}
+
+fn es_finito(argumentos: Vec<Valor>) -> Valor {
+    Valor::Booleano(match argumentos.first() {
+        Some(Valor::Entero(_)) => true,
+        Some(Valor::Flotante(f)) => f.is_finite(),
+        _ => false,
+    })
+}
+
+/// Generador SplitMix64. Su estado cabe en un entero, así que vive en la
+/// propia instancia de `Random` y la misma semilla produce siempre la misma
+/// secuencia, sin depender del algoritmo por defecto de `rand`.
+struct Generador(u64);
+
+impl RngCore for Generador {
+    fn next_u32(&mut self) -> u32 {
+        (self.next_u64() >> 32) as u32
+    }
+
+    fn next_u64(&mut self) -> u64 {
+        self.0 = self.0.wrapping_add(0x9E37_79B9_7F4A_7C15);
+        let mut z = self.0;
+        z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+        z ^ (z >> 31)
+    }
+
+    fn fill_bytes(&mut self, destino: &mut [u8]) {
+        for bloque in destino.chunks_mut(8) {
+            let bytes = self.next_u64().to_le_bytes();
+            bloque.copy_from_slice(&bytes[..bloque.len()]);
+        }
+    }
+
+    fn try_fill_bytes(&mut self, destino: &mut [u8]) -> Result<(), rand::Error> {
+        self.fill_bytes(destino);
+        Ok(())
+    }
+}
+
+// Random([semilla]): sin semilla se elige una al azar.
+fn crear_random(argumentos: Vec<Valor>) -> Valor {
+    let semilla = match argumentos.first() {
+        Some(Valor::Entero(semilla)) => *semilla,
+        None | Some(Valor::Nulo) => rand::thread_rng().gen(),
+        _ => return errores::lanzar_argumento_invalido("Random", "una semilla entera"),
+    };
+
+    let mut propiedades = IndexMap::new();
+    propiedades.insert("seed".to_string(), Valor::Entero(semilla));
+    propiedades.insert("state".to_string(), Valor::Entero(semilla));
+
+    Valor::Objeto(Instancia {
+        clase: CLASE_RANDOM.to_string(),
+        propiedades: Arc::new(Mutex::new(propiedades)),
+    })
+}
+
+/// Ejecuta `operacion` con el generador de la instancia y guarda el estado
+/// resultante para la siguiente llamada.
+fn con_generador(
+    argumentos: &[Valor],
+    funcion: &str,
+    operacion: impl FnOnce(&mut Generador, &[Valor]) -> Valor,
+) -> Valor {
+    let Some(Valor::Objeto(instancia)) = argumentos.first() else {
+        return errores::lanzar_argumento_invalido(funcion, "una instancia de Random");
+    };
+    if instancia.clase != CLASE_RANDOM {
+        return errores::lanzar_argumento_invalido(funcion, "una instancia de Random");
+    }
+
+    let mut propiedades = instancia.propiedades.lock().unwrap();
+    let estado = match propiedades.get("state") {
+        Some(Valor::Entero(estado)) => *estado as u64,
+        _ => 0,
+    };
+
+    let mut generador = Generador(estado);
+    let resultado = operacion(&mut generador, &argumentos[1..]);
+    propiedades.insert("state".to_string(), Valor::Entero(generador.0 as i64));
+    resultado
+}
+
+// int_range(min, max): entero entre ambos extremos, incluidos.
+fn rango_entero(argumentos: Vec<Valor>) -> Valor {
+    con_generador(&argumentos, "int_range", |generador, resto| {
+        match (resto.first(), resto.get(1)) {
+            (Some(Valor::Entero(minimo)), Some(Valor::Entero(maximo))) if minimo <= maximo => {
+                Valor::Entero(generador.gen_range(*minimo..=*maximo))
+            }
+            _ => errores::lanzar_argumento_invalido("int_range", "dos enteros min <= max"),
+        }
+    })
+}
+
+// float([min, max]): sin extremos devuelve un valor en [0, 1). Los extremos
+// y su distancia tienen que ser finitos o `gen_range` no sabe muestrear.
+fn flotante_aleatorio(argumentos: Vec<Valor>) -> Valor {
+    con_generador(&argumentos, "float", |generador, resto| {
+        if resto.is_empty() {
+            return Valor::Flotante(generador.gen::<f64>());
+        }
+        match (numero(resto, 0), numero(resto, 1)) {
+            (Some(minimo), Some(maximo))
+                if minimo.is_finite()
+                    && maximo.is_finite()
+                    && (maximo - minimo).is_finite()
+                    && minimo < maximo =>
+            {
+                Valor::Flotante(generador.gen_range(minimo..maximo))
+            }
+            _ => errores::lanzar_argumento_invalido("float", "dos números finitos min < max"),
+        }
+    })
+}
+
+fn elegir(argumentos: Vec<Valor>) -> Valor {
+    con_generador(&argumentos, "choice", |generador, resto| {
+        match resto.first() {
+            Some(Valor::Lista(lista)) => lista.choose(generador).cloned().unwrap_or(Valor::Nulo),
+            _ => errores::lanzar_argumento_invalido("choice", "una lista"),
+        }
+    })
+}
+
+// shuffle(lista): devuelve una copia barajada.
+fn barajar(argumentos: Vec<Valor>) -> Valor {
+    con_generador(&argumentos, "shuffle", |generador, resto| {
+        match resto.first() {
+            Some(Valor::Lista(lista)) => {
+                let mut barajada = lista.clone();
+                barajada.shuffle(generador);
+                Valor::Lista(barajada)
+            }
+            _ => errores::lanzar_argumento_invalido("shuffle", "una lista"),
+        }
+    })
+}
+
+// sample(lista, k): k elementos distintos sin reemplazo.
+fn muestra(argumentos: Vec<Valor>) -> Valor {
+    con_generador(&argumentos, "sample", |generador, resto| {
+        let (Some(Valor::Lista(lista)), Some(Valor::Entero(cantidad))) =
+            (resto.first(), resto.get(1))
+        else {
+            return errores::lanzar_argumento_invalido("sample", "una lista y un entero");
+        };
+
+        match usize::try_from(*cantidad) {
+            Ok(cantidad) if cantidad <= lista.len() => Valor::Lista(
+                lista
+                    .choose_multiple(generador, cantidad)
+                    .cloned()
+                    .collect(),
+            ),
+            _ => errores::lanzar_mensaje(
+                &format!(
+                    "sample: no se pueden tomar {} elementos de una lista de {}",
+                    cantidad,
+                    lista.len()
+                ),
+                "InvalidInput",
+            ),
+        }
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn lista(valores: &[i64]) -> Valor {
+        Valor::Lista(valores.iter().map(|n| Valor::Entero(*n)).collect())
+    }
+
+    fn enteros(valor: Valor) -> Vec<i64> {
+        let Valor::Lista(lista) = valor else {
+            panic!("se esperaba una lista, no {}", valor);
+        };
+        lista
+            .iter()
+            .map(|valor| match valor {
+                Valor::Entero(n) => *n,
+                otro => panic!("se esperaba un entero, no {}", otro),
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_splitmix64_coincide_con_la_referencia() {
+        let mut generador = Generador(1234567);
+        let salida: Vec<u64> = (0..3).map(|_| generador.next_u64()).collect();
+        assert_eq!(
+            salida,
+            [
+                6457827717110365317,
+                3203168211198807973,
+                9817491932198370423
+            ]
+        );
+    }
+
+    #[test]
+    fn test_misma_semilla_misma_secuencia() {
+        let secuencia = |semilla: i64| {
+            let random = crear_random(vec![Valor::Entero(semilla)]);
+            let tiradas: Vec<String> = (0..5)
+                .map(|_| {
+                    rango_entero(vec![random.clone(), Valor::Entero(1), Valor::Entero(6)])
+                        .to_string()
+                })
+                .collect();
+            let barajada = barajar(vec![random.clone(), lista(&[1, 2, 3, 4, 5])]);
+            (tiradas, barajada.to_string())
+        };
+
+        assert_eq!(secuencia(42), secuencia(42));
+        assert_ne!(secuencia(42), secuencia(43));
+    }
+
+    #[test]
+    fn test_rangos_y_muestras() {
+        let random = crear_random(vec![Valor::Entero(7)]);
+
+        let mut vistos = std::collections::HashSet::new();
+        for _ in 0..200 {
+            let Valor::Entero(n) =
+                rango_entero(vec![random.clone(), Valor::Entero(-2), Valor::Entero(2)])
+            else {
+                panic!("int_range debería devolver un entero");
+            };
+            assert!((-2..=2).contains(&n));
+            vistos.insert(n);
+
+            let Valor::Flotante(x) = flotante_aleatorio(vec![random.clone()]) else {
+                panic!("float debería devolver un flotante");
+            };
+            assert!((0.0..1.0).contains(&x));
+        }
+        assert_eq!(vistos.len(), 5, "los dos extremos están incluidos");
+
+        let mut barajada = enteros(barajar(vec![random.clone(), lista(&[1, 2, 3, 4, 5])]));
+        barajada.sort();
+        assert_eq!(barajada, [1, 2, 3, 4, 5]);
+
+        let mut tomados = enteros(muestra(vec![
+            random.clone(),
+            lista(&[1, 2, 3, 4, 5]),
+            Valor::Entero(3),
+        ]));
+        tomados.sort();
+        tomados.dedup();
+        assert_eq!(tomados.len(), 3);
+
+        assert_eq!(elegir(vec![random.clone(), lista(&[])]).to_string(), "null");
+        assert_eq!(
+            muestra(vec![random.clone(), lista(&[1]), Valor::Entero(2)]).to_string(),
+            "null"
+        );
+        assert!(errores::tomar_error_pendiente().is_some());
+        assert_eq!(
+            rango_entero(vec![random, Valor::Entero(3), Valor::Entero(1)]).to_string(),
+            "null"
+        );
+        assert!(errores::tomar_error_pendiente().is_some());
+    }
+
+    #[test]
+    fn test_float_con_extremos_no_finitos() {
+        let random = crear_random(vec![Valor::Entero(1)]);
+        let flotante = |minimo: f64, maximo: f64| {
+            flotante_aleatorio(vec![
+                random.clone(),
+                Valor::Flotante(minimo),
+                Valor::Flotante(maximo),
+            ])
+        };
+
+        let Valor::Flotante(x) = flotante(-1.5, 1.5) else {
+            panic!("float debería devolver un flotante");
+        };
+        assert!((-1.5..1.5).contains(&x));
+
+        for (minimo, maximo) in [
+            (0.0, f64::INFINITY),
+            (f64::NEG_INFINITY, 0.0),
+            (0.0, f64::NAN),
+            (-f64::MAX, f64::MAX),
+        ] {
+            assert_eq!(flotante(minimo, maximo).to_string(), "null");
+            assert!(errores::tomar_error_pendiente().is_some());
+        }
+    }
+
+    #[test]
+    fn test_potencias_logaritmos_y_redondeos() {
+        assert!(matches!(
+            potencia(vec![Valor::Entero(2), Valor::Entero(10)]),
+            Valor::Entero(1024)
+        ));
+        assert!(matches!(
+            potencia(vec![Valor::Entero(2), Valor::Entero(-1)]),
+            Valor::Flotante(x) if x == 0.5
+        ));
+        assert_eq!(
+            potencia(vec![Valor::Entero(10), Valor::Entero(19)]).to_string(),
+            "null"
+        );
+        assert!(errores::tomar_error_pendiente().is_some());
+
+        assert_eq!(
+            logaritmo(vec![Valor::Entero(8), Valor::Entero(2)]).to_string(),
+            "3"
+        );
+        assert_eq!(logaritmo_10(vec![Valor::Entero(1000)]).to_string(), "3");
+        assert_eq!(
+            hipotenusa(vec![Valor::Entero(3), Valor::Entero(4)]).to_string(),
+            "5"
+        );
+        assert_eq!(truncar(vec![Valor::Flotante(-2.7)]).to_string(), "-2");
+        assert_eq!(signo(vec![Valor::Entero(-9)]).to_string(), "-1");
+        assert_eq!(
+            raiz(vec![Valor::Texto("4".to_string())]).to_string(),
+            "null"
+        );
+        assert!(errores::tomar_error_pendiente().is_some());
+    }
+
+    #[test]
+    fn test_mcd_mcm_y_desbordamiento() {
+        let dos = |a: i64, b: i64| vec![Valor::Entero(a), Valor::Entero(b)];
+        assert_eq!(mcd(dos(-12, 18)).to_string(), "6");
+        assert_eq!(mcd(dos(0, 0)).to_string(), "0");
+        assert_eq!(mcm(dos(4, -6)).to_string(), "12");
+        assert_eq!(mcm(dos(0, 5)).to_string(), "0");
+
+        assert_eq!(mcd(dos(i64::MIN, 0)).to_string(), "null");
+        assert!(errores::tomar_error_pendiente().is_some());
+        assert_eq!(mcm(dos(i64::MAX, i64::MAX - 1)).to_string(), "null");
+        assert!(errores::tomar_error_pendiente().is_some());
+    }
+
+    #[test]
+    fn test_comprobaciones_de_flotantes() {
+        let nan = || vec![Valor::Flotante(f64::NAN)];
+        assert_eq!(es_nan(nan()).to_string(), "true");
+        assert_eq!(es_finito(nan()).to_string(), "false");
+        assert_eq!(
+            es_infinito(vec![Valor::Flotante(f64::NEG_INFINITY)]).to_string(),
+            "true"
+        );
+        assert_eq!(es_finito(vec![Valor::Entero(i64::MAX)]).to_string(), "true");
+        assert_eq!(es_nan(vec![Valor::Entero(1)]).to_string(), "false");
+    }
+}
@@ -1,56 +1,400 @@
 use crate::runtime::interpretador::Interpretador;
-use crate::runtime::valores::Valor;
+use crate::runtime::valores::{NativeFn, Valor};
+use indexmap::IndexMap;
 use std::collections::HashMap;
+use std::sync::LazyLock;
 
+pub mod assert;
+pub mod cli;
 pub mod collections;
+pub mod decimal;
 pub mod errores;
 pub mod fs;
 pub mod json;
+pub mod math;
 pub mod num;
 pub mod path;
 pub mod proc;
+pub mod regex;
+pub mod sesion;
 pub mod str;
 pub mod time;
 
+pub const PREFIJO_IMPORTACION: &str = "std:";
+
+pub const MODULOS: [&str; 13] = [
+    "collections",
+    "str",
+    "num",
+    "fs",
+    "path",
+    "time",
+    "json",
+    "proc",
+    "cli",
+    "regex",
+    "decimal",
+    "math",
+    "assert",
+];
+
+// La capa plana `Std.xxx` conserva solo los nombres que existían antes de
+// `Std.modulo`; lo que se añade a un módulo se usa como `Std.modulo.xxx`.
+pub const CAPA_PLANA: [(&str, &[&str]); 8] = [
+    (
+        "collections",
+        &["len", "push", "pop", "keys", "values", "sort", "reverse"],
+    ),
+    (
+        "str",
+        &[
+            "trim",
+            "split",
+            "replace",
+            "to_upper",
+            "to_lower",
+            "search",
+            "contains",
+            "starts_with",
+            "ends_with",
+        ],
+    ),
+    (
+        "num",
+        &[
+            "parse_int",
+            "parse_float",
+            "to_string",
+            "random",
+            "abs",
+            "clamp",
+            "min",
+            "max",
+        ],
+    ),
+    ("fs", &["read_file", "write_file", "exists"]),
+    ("path", &["join", "basename", "dirname", "extension"]),
+    ("time", &["now", "timestamp"]),
+    ("json", &["parse", "stringify"]),
+    ("proc", &["exec"]),
+];
+
+/// Nombres de `modulo` que también están en la capa plana `Std.xxx`.
+pub fn nombres_planos(modulo: &str) -> &'static [&'static str] {
+    CAPA_PLANA
+        .iter()
+        .find(|(nombre, _)| *nombre == modulo)
+        .map_or(&[], |(_, nombres)| nombres)
+}
+
+pub fn crear_modulo(nombre: &str) -> Option<Valor> {
+    match nombre {
+        "collections" => Some(collections::crear_modulo()),
+        "str" => Some(str::crear_modulo()),
+        "num" => Some(num::crear_modulo()),
+        "fs" => Some(fs::crear_modulo()),
+        "path" => Some(path::crear_modulo()),
+        "time" => Some(time::crear_modulo()),
+        "json" => Some(json::crear_modulo()),
+        "proc" => Some(proc::crear_modulo()),
+        "cli" => Some(cli::crear_modulo()),
+        "regex" => Some(regex::crear_modulo()),
+        "decimal" => Some(decimal::crear_modulo()),
+        "math" => Some(math::crear_modulo()),
+        "assert" => Some(assert::crear_modulo()),
+        _ => None,
+    }
+}
+
 pub fn registrar_stdlib(interpretador: &mut Interpretador) {
-    let mut std_map = HashMap::new();
+    let mut std_map = IndexMap::new();
+    let mut espacios = IndexMap::new();
+
+    for nombre in MODULOS {
+        let Some(Valor::Diccionario(funciones)) = crear_modulo(nombre) else {
+            continue;
+        };
 
-    if let Valor::Diccionario(str_funcs) = str::crear_modulo() {
-        std_map.extend(str_funcs);
+        for clave in nombres_planos(nombre) {
+            if let Some(funcion) = funciones.get(*clave) {
+                std_map.insert(clave.to_string(), funcion.clone());
+            }
+        }
+
+        espacios.insert(nombre.to_string(), Valor::Diccionario(funciones));
     }
 
-    if let Valor::Diccionario(num_funcs) = num::crear_modulo() {
-        std_map.extend(num_funcs);
+    std_map.extend(espacios);
+    std_map.insert(
+        FUNCION_EXTENDER.to_string(),
+        Valor::FuncionNativa(FUNCION_EXTENDER.to_string(), extender),
+    );
+
+    interpretador
+        .entorno_actual
+        .definir_variable("Std".to_string(), Valor::Diccionario(std_map));
+
+    let error_class = errores::crear_clase_error();
+    interpretador.gestor_clases.registrar_clase(error_class);
+    interpretador
+        .gestor_clases
+        .registrar_clase(regex::crear_clase_regex());
+    interpretador
+        .gestor_clases
+        .registrar_clase(math::crear_clase_random());
+}
+
+// Std.extend(tipo, nombre, funcion) no pertenece a ningún módulo: está
+// directamente en `Std`.
+pub const FUNCION_EXTENDER: &str = "extend";
+
+pub const TIPOS_CON_METODOS: [&str; 7] = ["Str", "Int", "Flo", "Dec", "Bool", "List", "Dict"];
+
+/// Nombre del tipo de un valor tal como se escribe en las anotaciones.
+pub fn nombre_tipo(valor: &Valor) -> Option<&'static str> {
+    match valor {
+        Valor::Texto(_) => Some("Str"),
+        Valor::Entero(_) => Some("Int"),
+        Valor::Flotante(_) => Some("Flo"),
+        Valor::Decimal(_) => Some("Dec"),
+        Valor::Booleano(_) => Some("Bool"),
+        Valor::Lista(_) => Some("List"),
+        Valor::Diccionario(_) => Some("Dict"),
+        _ => None,
     }
+}
 
-    if let Valor::Diccionario(fs_funcs) = fs::crear_modulo() {
-        std_map.extend(fs_funcs);
+// Módulos que forman la tabla de métodos de cada tipo, por orden de
+// precedencia. `"abc".to_upper()` equivale a `Std.str.to_upper("abc")`.
+fn modulos_de_tipo(tipo: &str) -> &'static [&'static str] {
+    match tipo {
+        "Str" => &["str", "num"],
+        "Int" | "Flo" => &["num", "math"],
+        "Dec" => &["decimal"],
+        "List" | "Dict" => &["collections"],
+        _ => &[],
     }
+}
+
+// Las tablas se construyen una sola vez; `to_string` está en todas.
+static METODOS_DE_TIPO: LazyLock<HashMap<&'static str, IndexMap<String, Valor>>> =
+    LazyLock::new(|| {
+        TIPOS_CON_METODOS
+            .iter()
+            .map(|tipo| {
+                let mut metodos = IndexMap::new();
+                for nombre in modulos_de_tipo(tipo) {
+                    if let Some(Valor::Diccionario(funciones)) = crear_modulo(nombre) {
+                        for (clave, funcion) in funciones {
+                            metodos.entry(clave).or_insert(funcion);
+                        }
+                    }
+                }
+                metodos
+                    .entry("to_string".to_string())
+                    .or_insert_with(|| Valor::FuncionNativa("to_string".to_string(), a_texto));
+                (*tipo, metodos)
+            })
+            .collect()
+    });
 
-    if let Valor::Diccionario(coll_funcs) = collections::crear_modulo() {
-        std_map.extend(coll_funcs);
+/// Método de la tabla de un tipo.
+pub fn metodo_de_tipo(tipo: &str, metodo: &str) -> Option<Valor> {
+    METODOS_DE_TIPO.get(tipo)?.get(metodo).cloned()
+}
+
+fn a_texto(argumentos: Vec<Valor>) -> Valor {
+    match argumentos.first() {
+        Some(valor) => Valor::Texto(valor.to_string()),
+        None => Valor::Nulo,
+    }
+}
+
+fn extender(argumentos: Vec<Valor>) -> Valor {
+    let (Some(Valor::Texto(tipo)), Some(Valor::Texto(_)), Some(funcion)) =
+        (argumentos.first(), argumentos.get(1), argumentos.get(2))
+    else {
+        return errores::lanzar_argumento_invalido(
+            FUNCION_EXTENDER,
+            "un tipo, un nombre y una función",
+        );
+    };
+
+    if !TIPOS_CON_METODOS.contains(&tipo.as_str()) {
+        let mensaje = format!(
+            "extend: tipo '{}' desconocido; disponibles: {}",
+            tipo,
+            TIPOS_CON_METODOS.join(", ")
+        );
+        return errores::lanzar_mensaje(&mensaje, "InvalidInput");
+    }
+
+    if !matches!(funcion, Valor::Funcion(_) | Valor::FuncionNativa(..)) {
+        return errores::lanzar_argumento_invalido(FUNCION_EXTENDER, "una función");
     }
 
-    if let Valor::Diccionario(path_funcs) = path::crear_modulo() {
-        std_map.extend(path_funcs);
+    Valor::Nulo
+}
+
+/// Nativas que el intérprete completa porque necesitan ejecutar código
+/// Umbral o cambiar su estado.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum NativaDelInterprete {
+    Extender,
+    JsonParse,
+    JsonStringify,
+    RegexReplace,
+    AssertThrows,
+}
+
+// Se reconocen por la función y no por el nombre, que puede repetirse
+// (`Std.replace` es la de `str`) o cambiar al copiarla a otra tabla.
+pub fn nativa_del_interprete(funcion: NativeFn) -> Option<NativaDelInterprete> {
+    let nativas: [(NativeFn, NativaDelInterprete); 5] = [
+        (extender, NativaDelInterprete::Extender),
+        (json::parsear, NativaDelInterprete::JsonParse),
+        (json::convertir_texto, NativaDelInterprete::JsonStringify),
+        (regex::reemplazar, NativaDelInterprete::RegexReplace),
+        (assert::lanza, NativaDelInterprete::AssertThrows),
+    ];
+
+    nativas
+        .into_iter()
+        .find(|(nativa, _)| std::ptr::fn_addr_eq(*nativa, funcion))
+        .map(|(_, especial)| especial)
+}
+
+// Métodos de las clases de la stdlib implementados en Rust: reciben la
+// instancia como primer argumento.
+pub fn metodo_nativo(clase: &str, metodo: &str) -> Option<Valor> {
+    match clase {
+        regex::CLASE_REGEX => regex::metodo(metodo),
+        math::CLASE_RANDOM => math::metodo(metodo),
+        _ => None,
     }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_capa_plana_solo_tiene_los_nombres_originales() {
+        let interpretador = Interpretador::nuevo();
+        let Some(Valor::Diccionario(std_map)) = interpretador.entorno_actual.obtener("Std") else {
+            panic!("Std no está definido");
+        };
 
-    if let Valor::Diccionario(time_funcs) = time::crear_modulo() {
-        std_map.extend(time_funcs);
+        let ganadores = [
+            ("len", "collections"),
+            ("reverse", "collections"),
+            ("split", "str"),
+            ("replace", "str"),
+            ("abs", "num"),
+            ("parse", "json"),
+        ];
+        for (nombre, modulo) in ganadores {
+            let Some(Valor::Diccionario(funciones)) = crear_modulo(modulo) else {
+                panic!("no existe el módulo {}", modulo);
+            };
+            match (&std_map[nombre], &funciones[nombre]) {
+                (Valor::FuncionNativa(_, plana), Valor::FuncionNativa(_, propia)) => assert!(
+                    std::ptr::fn_addr_eq(*plana, *propia),
+                    "Std.{} debería ser Std.{}.{}",
+                    nombre,
+                    modulo,
+                    nombre
+                ),
+                otro => panic!("Std.{} no es nativa: {:?}", nombre, otro),
+            }
+        }
+
+        for nombre in ["eq", "compile", "exit", "new", "PI", "write", "chdir"] {
+            assert!(
+                !std_map.contains_key(nombre),
+                "Std.{} no debería estar en la capa plana",
+                nombre
+            );
+        }
+        for (modulo, nombres) in CAPA_PLANA {
+            let Some(Valor::Diccionario(funciones)) = crear_modulo(modulo) else {
+                panic!("no existe el módulo {}", modulo);
+            };
+            for nombre in nombres {
+                assert!(
+                    funciones.contains_key(*nombre),
+                    "falta Std.{}.{}",
+                    modulo,
+                    nombre
+                );
+                assert!(std_map.contains_key(*nombre), "falta Std.{}", nombre);
+            }
+        }
     }
 
-    if let Valor::Diccionario(json_funcs) = json::crear_modulo() {
-        std_map.extend(json_funcs);
+    fn funcion_de(valor: Option<Valor>) -> NativeFn {
+        match valor {
+            Some(Valor::FuncionNativa(_, funcion)) => funcion,
+            otro => panic!("se esperaba una nativa, no {:?}", otro),
+        }
     }
 
-    if let Valor::Diccionario(proc_funcs) = proc::crear_modulo() {
-        std_map.extend(proc_funcs);
+    #[test]
+    fn test_tablas_de_metodos_por_tipo() {
+        // En los textos, `str` tiene precedencia sobre `num`.
+        let Some(Valor::Diccionario(cadenas)) = crear_modulo("str") else {
+            unreachable!()
+        };
+        assert!(std::ptr::fn_addr_eq(
+            funcion_de(metodo_de_tipo("Str", "replace")),
+            funcion_de(cadenas.get("replace").cloned())
+        ));
+        assert!(metodo_de_tipo("Int", "round").is_some());
+        assert!(metodo_de_tipo("Flo", "sqrt").is_some());
+        assert!(metodo_de_tipo("Dict", "keys").is_some());
+        assert!(metodo_de_tipo("Str", "keys").is_none());
+        assert!(metodo_de_tipo("Objeto", "to_string").is_none());
+
+        for tipo in TIPOS_CON_METODOS {
+            let a_texto = funcion_de(metodo_de_tipo(tipo, "to_string"));
+            assert_eq!(a_texto(vec![Valor::Booleano(true)]).to_string(), "true");
+        }
     }
 
-    interpretador
-        .entorno_actual
-        .definir_variable("Std".to_string(), Valor::Diccionario(std_map));
+    #[test]
+    fn test_nativas_del_interprete_se_reconocen_por_la_funcion() {
+        let Some(Valor::Diccionario(json)) = crear_modulo("json") else {
+            unreachable!()
+        };
+        let parse = funcion_de(json.get("parse").cloned());
+        assert_eq!(
+            nativa_del_interprete(parse),
+            Some(NativaDelInterprete::JsonParse)
+        );
 
-    let error_class = errores::crear_clase_error();
-    interpretador.gestor_clases.registrar_clase(error_class);
+        // El método de las instancias de Regex es la misma función.
+        assert_eq!(
+            nativa_del_interprete(funcion_de(regex::metodo("replace"))),
+            Some(NativaDelInterprete::RegexReplace)
+        );
+
+        // `Std.replace` y `"a".replace(...)` son los de `str`, aunque se llamen igual.
+        let Some(Valor::Diccionario(std_map)) =
+            Interpretador::nuevo().entorno_actual.obtener("Std")
+        else {
+            panic!("Std no está definido");
+        };
+        assert_eq!(
+            nativa_del_interprete(funcion_de(std_map.get("replace").cloned())),
+            None
+        );
+        assert_eq!(
+            nativa_del_interprete(funcion_de(metodo_de_tipo("Str", "replace"))),
+            None
+        );
+        assert_eq!(
+            nativa_del_interprete(funcion_de(std_map.get(FUNCION_EXTENDER).cloned())),
+            Some(NativaDelInterprete::Extender)
+        );
+    }
 }
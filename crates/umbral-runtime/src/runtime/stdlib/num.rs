@@ -1,8 +1,8 @@
 use crate::runtime::valores::Valor;
 use rand::Rng;
-use std::collections::HashMap;
+use indexmap::IndexMap;
 
-fn registrar_funcion(mapa: &mut HashMap<String, Valor>, nombre: &str, funcion: fn(Vec<Valor>) -> Valor) {
+fn registrar_funcion(mapa: &mut IndexMap<String, Valor>, nombre: &str, funcion: fn(Vec<Valor>) -> Valor) {
     mapa.insert(
         nombre.to_string(),
         Valor::FuncionNativa(nombre.to_string(), funcion),
@@ -10,7 +10,7 @@ fn registrar_funcion(mapa: &mut HashMap<String, Valor>, nombre: &str, funcion: f
 }
 
 pub fn crear_modulo() -> Valor {
-    let mut mapa = HashMap::new();
+    let mut mapa = IndexMap::new();
 
     registrar_funcion(&mut mapa, "parse_int", parsear_entero);
     registrar_funcion(&mut mapa, "parse_float", parsear_flotante);
@@ -20,6 +20,9 @@ pub fn crear_modulo() -> Valor {
     registrar_funcion(&mut mapa, "clamp", limitar);
     registrar_funcion(&mut mapa, "min", minimo);
     registrar_funcion(&mut mapa, "max", maximo);
+    registrar_funcion(&mut mapa, "round", redondear);
+    registrar_funcion(&mut mapa, "floor", piso);
+    registrar_funcion(&mut mapa, "ceil", techo);
 
     Valor::Diccionario(mapa)
 }
@@ -32,7 +35,7 @@ fn parsear_texto_entero(texto: &str) -> Valor {
 }
 
 fn parsear_entero(argumentos: Vec<Valor>) -> Valor {
-    match argumentos.get(0) {
+    match argumentos.first() {
         Some(Valor::Texto(texto)) => parsear_texto_entero(texto),
         _ => Valor::Nulo,
     }
@@ -46,14 +49,14 @@ fn parsear_texto_flotante(texto: &str) -> Valor {
 }
 
 fn parsear_flotante(argumentos: Vec<Valor>) -> Valor {
-    match argumentos.get(0) {
+    match argumentos.first() {
         Some(Valor::Texto(texto)) => parsear_texto_flotante(texto),
         _ => Valor::Nulo,
     }
 }
 
 fn convertir_texto(argumentos: Vec<Valor>) -> Valor {
-    match argumentos.get(0) {
+    match argumentos.first() {
         Some(valor) => Valor::Texto(format!("{}", valor)),
         None => Valor::Nulo,
     }
@@ -65,7 +68,7 @@ fn aleatorio(_argumentos: Vec<Valor>) -> Valor {
 }
 
 fn absoluto(argumentos: Vec<Valor>) -> Valor {
-    match argumentos.get(0) {
+    match argumentos.first() {
         Some(Valor::Entero(numero)) => Valor::Entero(numero.abs()),
         Some(Valor::Flotante(numero)) => Valor::Flotante(numero.abs()),
         _ => Valor::Nulo,
@@ -73,7 +76,7 @@ fn absoluto(argumentos: Vec<Valor>) -> Valor {
 }
 
 fn obtener_tres_enteros(argumentos: &[Valor]) -> Option<(i64, i64, i64)> {
-    let valor = match argumentos.get(0) {
+    let valor = match argumentos.first() {
         Some(Valor::Entero(v)) => *v,
         _ => return None,
     };
@@ -92,7 +95,7 @@ fn obtener_tres_enteros(argumentos: &[Valor]) -> Option<(i64, i64, i64)> {
 }
 
 fn obtener_tres_flotantes(argumentos: &[Valor]) -> Option<(f64, f64, f64)> {
-    let valor = match argumentos.get(0) {
+    let valor = match argumentos.first() {
         Some(Valor::Flotante(v)) => *v,
         _ => return None,
     };
@@ -123,7 +126,7 @@ fn limitar(argumentos: Vec<Valor>) -> Valor {
 }
 
 fn obtener_dos_enteros(argumentos: &[Valor]) -> Option<(i64, i64)> {
-    let primero = match argumentos.get(0) {
+    let primero = match argumentos.first() {
         Some(Valor::Entero(a)) => *a,
         _ => return None,
     };
@@ -137,7 +140,7 @@ fn obtener_dos_enteros(argumentos: &[Valor]) -> Option<(i64, i64)> {
 }
 
 fn obtener_dos_flotantes(argumentos: &[Valor]) -> Option<(f64, f64)> {
-    let primero = match argumentos.get(0) {
+    let primero = match argumentos.first() {
         Some(Valor::Flotante(a)) => *a,
         _ => return None,
     };
@@ -173,3 +176,36 @@ fn maximo(argumentos: Vec<Valor>) -> Valor {
 
     Valor::Nulo
 }
+
+// round(numero, [decimales]): sin decimales devuelve un entero.
+pub fn redondear(argumentos: Vec<Valor>) -> Valor {
+    let numero = match argumentos.first() {
+        Some(Valor::Entero(numero)) => return Valor::Entero(*numero),
+        Some(Valor::Flotante(numero)) => *numero,
+        _ => return Valor::Nulo,
+    };
+
+    match argumentos.get(1) {
+        Some(Valor::Entero(decimales)) => {
+            let factor = 10f64.powi((*decimales).clamp(0, 15) as i32);
+            Valor::Flotante((numero * factor).round() / factor)
+        }
+        _ => Valor::Entero(numero.round() as i64),
+    }
+}
+
+pub fn piso(argumentos: Vec<Valor>) -> Valor {
+    match argumentos.first() {
+        Some(Valor::Entero(numero)) => Valor::Entero(*numero),
+        Some(Valor::Flotante(numero)) => Valor::Entero(numero.floor() as i64),
+        _ => Valor::Nulo,
+    }
+}
+
+pub fn techo(argumentos: Vec<Valor>) -> Valor {
+    match argumentos.first() {
+        Some(Valor::Entero(numero)) => Valor::Entero(*numero),
+        Some(Valor::Flotante(numero)) => Valor::Entero(numero.ceil() as i64),
+        _ => Valor::Nulo,
+    }
+}
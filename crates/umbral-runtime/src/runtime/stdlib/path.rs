@@ -1,8 +1,8 @@
 use crate::runtime::valores::Valor;
-use std::collections::HashMap;
+use indexmap::IndexMap;
 use std::path::Path;
 
-fn registrar_funcion(mapa: &mut HashMap<String, Valor>, nombre: &str, funcion: fn(Vec<Valor>) -> Valor) {
+fn registrar_funcion(mapa: &mut IndexMap<String, Valor>, nombre: &str, funcion: fn(Vec<Valor>) -> Valor) {
     mapa.insert(
         nombre.to_string(),
         Valor::FuncionNativa(nombre.to_string(), funcion),
@@ -10,7 +10,7 @@ fn registrar_funcion(mapa: &mut HashMap<String, Valor>, nombre: &str, funcion: f
 }
 
 pub fn crear_modulo() -> Valor {
-    let mut mapa = HashMap::new();
+    let mut mapa = IndexMap::new();
 
     registrar_funcion(&mut mapa, "join", unir);
     registrar_funcion(&mut mapa, "basename", nombre_archivo);
@@ -33,7 +33,7 @@ fn unir(argumentos: Vec<Valor>) -> Valor {
 }
 
 fn obtener_ruta(argumentos: &[Valor]) -> Option<String> {
-    match argumentos.get(0) {
+    match argumentos.first() {
         Some(Valor::Texto(ruta)) => Some(ruta.clone()),
         _ => None,
     }
@@ -1,8 +1,19 @@
+use crate::runtime::stdlib::{errores, sesion};
 use crate::runtime::valores::Valor;
-use std::collections::HashMap;
-use std::process::Command;
+use indexmap::IndexMap;
+use std::io::{self, BufRead, BufReader, Read, Write};
+use std::process::{Child, ChildStderr, ChildStdin, ChildStdout, Command, ExitStatus, Stdio};
+use std::sync::atomic::{AtomicI64, Ordering};
+use std::sync::mpsc::{self, Sender};
+use std::sync::{Arc, Mutex};
+use std::thread::{self, JoinHandle};
+use std::time::{Duration, Instant};
 
-fn registrar_funcion(mapa: &mut HashMap<String, Valor>, nombre: &str, funcion: fn(Vec<Valor>) -> Valor) {
+fn registrar_funcion(
+    mapa: &mut IndexMap<String, Valor>,
+    nombre: &str,
+    funcion: fn(Vec<Valor>) -> Valor,
+) {
     mapa.insert(
         nombre.to_string(),
         Valor::FuncionNativa(nombre.to_string(), funcion),
@@ -10,60 +21,817 @@ fn registrar_funcion(mapa: &mut HashMap<String, Valor>, nombre: &str, funcion: f
 }
 
 pub fn crear_modulo() -> Valor {
-    let mut mapa = HashMap::new();
+    let mut mapa = IndexMap::new();
 
     registrar_funcion(&mut mapa, "exec", ejecutar);
+    registrar_funcion(&mut mapa, "spawn", lanzar_proceso);
+    registrar_funcion(&mut mapa, "read_line", leer_linea);
+    registrar_funcion(&mut mapa, "write", escribir_entrada);
+    registrar_funcion(&mut mapa, "close_stdin", cerrar_entrada);
+    registrar_funcion(&mut mapa, "kill", matar);
+    registrar_funcion(&mut mapa, "wait", esperar);
+    registrar_funcion(&mut mapa, "args", argumentos_script);
+    registrar_funcion(&mut mapa, "env", variables_entorno);
+    registrar_funcion(&mut mapa, "set_env", establecer_variable_entorno);
+    registrar_funcion(&mut mapa, "cwd", directorio_actual);
+    registrar_funcion(&mut mapa, "chdir", cambiar_directorio);
+    registrar_funcion(&mut mapa, "pid", identificador_proceso);
+    registrar_funcion(&mut mapa, "exit", salir);
 
     Valor::Diccionario(mapa)
 }
 
-fn obtener_comando(argumentos: &[Valor]) -> Option<String> {
-    match argumentos.get(0) {
-        Some(Valor::Texto(comando)) => Some(comando.clone()),
-        _ => None,
+fn lanzar_error_io(operacion: &str, comando: &str, error: io::Error) -> Valor {
+    let mut data = errores::datos_error_io(&error);
+    data.insert("command".to_string(), Valor::Texto(comando.to_string()));
+
+    let mensaje = format!("{}: '{}': {}", operacion, comando, error);
+    errores::lanzar(errores::crear_error(&mensaje, Valor::Diccionario(data)))
+}
+
+#[derive(Default)]
+struct Opciones {
+    directorio: Option<String>,
+    entorno: Vec<(String, String)>,
+    entrada: Option<String>,
+    limite: Option<Duration>,
+    heredar: bool,
+    shell: bool,
+}
+
+impl Opciones {
+    fn desde_diccionario(mapa: &IndexMap<String, Valor>) -> Self {
+        let mut opciones = Opciones::default();
+
+        if let Some(Valor::Texto(directorio)) = mapa.get("cwd") {
+            opciones.directorio = Some(directorio.clone());
+        }
+
+        if let Some(Valor::Diccionario(variables)) = mapa.get("env") {
+            opciones.entorno = variables
+                .iter()
+                .map(|(clave, valor)| (clave.clone(), valor.to_string()))
+                .collect();
+        }
+
+        if let Some(Valor::Texto(entrada)) = mapa.get("stdin") {
+            opciones.entrada = Some(entrada.clone());
+        }
+
+        opciones.limite = match mapa.get("timeout") {
+            Some(Valor::Entero(ms)) if *ms > 0 => Some(Duration::from_millis(*ms as u64)),
+            Some(Valor::Flotante(ms)) if *ms > 0.0 => Some(Duration::from_secs_f64(ms / 1000.0)),
+            _ => None,
+        };
+
+        opciones.heredar = mapa.get("inherit").is_some_and(Valor::es_verdadero);
+        opciones.shell = mapa.get("shell").is_some_and(Valor::es_verdadero);
+
+        opciones
     }
 }
 
-fn extraer_argumentos_texto(argumentos: &[Valor]) -> Vec<String> {
-    argumentos
-        .iter()
-        .skip(1)
-        .filter_map(|valor| {
-            if let Valor::Texto(texto) = valor {
-                Some(texto.clone())
+struct Invocacion {
+    comando: String,
+    argumentos: Vec<String>,
+    opciones: Opciones,
+}
+
+impl Invocacion {
+    // exec(comando, arg1, arg2, ..., [opciones]); los argumentos también
+    // pueden pasarse como una lista.
+    fn desde_argumentos(argumentos: &[Valor]) -> Option<Self> {
+        let Some(Valor::Texto(comando)) = argumentos.first() else {
+            return None;
+        };
+
+        let mut invocacion = Invocacion {
+            comando: comando.clone(),
+            argumentos: Vec::new(),
+            opciones: Opciones::default(),
+        };
+
+        for valor in &argumentos[1..] {
+            match valor {
+                Valor::Texto(texto) => invocacion.argumentos.push(texto.clone()),
+                Valor::Lista(items) => invocacion
+                    .argumentos
+                    .extend(items.iter().map(|item| item.to_string())),
+                Valor::Diccionario(mapa) => invocacion.opciones = Opciones::desde_diccionario(mapa),
+                otro => invocacion.argumentos.push(otro.to_string()),
+            }
+        }
+
+        Some(invocacion)
+    }
+
+    fn construir_comando(&self) -> Command {
+        let mut comando = if self.opciones.shell {
+            let linea = std::iter::once(&self.comando)
+                .chain(&self.argumentos)
+                .cloned()
+                .collect::<Vec<_>>()
+                .join(" ");
+            crear_comando_shell(&linea)
+        } else {
+            let mut comando = Command::new(&self.comando);
+            comando.args(&self.argumentos);
+            comando
+        };
+
+        let sesion = sesion::actual();
+        match &self.opciones.directorio {
+            Some(directorio) => {
+                comando.current_dir(sesion.resolver_ruta(directorio));
+            }
+            None => {
+                if let Some(directorio) = &*sesion.directorio.lock().unwrap() {
+                    comando.current_dir(directorio);
+                }
+            }
+        }
+        for (nombre, valor) in sesion.entorno.lock().unwrap().iter() {
+            match valor {
+                Some(valor) => comando.env(nombre, valor),
+                None => comando.env_remove(nombre),
+            };
+        }
+        comando.envs(self.opciones.entorno.iter().cloned());
+
+        let salida = || {
+            if self.opciones.heredar {
+                Stdio::inherit()
             } else {
-                None
+                Stdio::piped()
             }
-        })
-        .collect()
+        };
+
+        let entrada = if self.opciones.entrada.is_some() {
+            Stdio::piped()
+        } else if self.opciones.heredar {
+            Stdio::inherit()
+        } else {
+            Stdio::null()
+        };
+
+        comando.stdin(entrada).stdout(salida()).stderr(salida());
+        comando
+    }
+}
+
+#[cfg(windows)]
+fn crear_comando_shell(linea: &str) -> Command {
+    let mut comando = Command::new("cmd");
+    comando.args(["/C", linea]);
+    comando
 }
 
-fn crear_diccionario_salida(salida: &std::process::Output) -> Valor {
-    let stdout = String::from_utf8_lossy(&salida.stdout).to_string();
-    let stderr = String::from_utf8_lossy(&salida.stderr).to_string();
-    let codigo = salida.status.code().unwrap_or(-1) as i64;
+#[cfg(not(windows))]
+fn crear_comando_shell(linea: &str) -> Command {
+    let mut comando = Command::new("sh");
+    comando.args(["-c", linea]);
+    comando
+}
+
+fn leer_en_segundo_plano(fuente: Option<impl Read + Send + 'static>) -> JoinHandle<String> {
+    thread::spawn(move || {
+        let mut contenido = Vec::new();
+        if let Some(mut fuente) = fuente {
+            let _ = fuente.read_to_end(&mut contenido);
+        }
+        String::from_utf8_lossy(&contenido).to_string()
+    })
+}
+
+fn escribir_en_segundo_plano(destino: Option<ChildStdin>, contenido: Option<String>) {
+    if let (Some(mut destino), Some(contenido)) = (destino, contenido) {
+        thread::spawn(move || {
+            let _ = destino.write_all(contenido.as_bytes());
+        });
+    }
+}
 
-    let mut resultado = HashMap::new();
+// La entrada de `spawn` se escribe desde un hilo propio, en el orden en que
+// llega: un hijo que no lee su stdin no bloquea al intérprete.
+fn escritor_de_entrada(mut destino: ChildStdin) -> Sender<Vec<u8>> {
+    let (emisor, receptor) = mpsc::channel::<Vec<u8>>();
+    thread::spawn(move || {
+        for datos in receptor {
+            if destino
+                .write_all(&datos)
+                .and_then(|_| destino.flush())
+                .is_err()
+            {
+                break;
+            }
+        }
+    });
+    emisor
+}
+
+// Se llama desde `promesa_bloqueante`: el hilo que espera no es el del
+// intérprete.
+fn esperar_con_limite(
+    hijo: &mut Child,
+    limite: Option<Duration>,
+) -> io::Result<Option<ExitStatus>> {
+    let Some(limite) = limite else {
+        return hijo.wait().map(Some);
+    };
+
+    let inicio = Instant::now();
+    loop {
+        if let Some(estado) = hijo.try_wait()? {
+            return Ok(Some(estado));
+        }
+
+        if inicio.elapsed() >= limite {
+            hijo.kill()?;
+            hijo.wait()?;
+            return Ok(None);
+        }
+
+        thread::sleep(Duration::from_millis(10));
+    }
+}
+
+fn crear_diccionario_salida(estado: ExitStatus, stdout: String, stderr: String) -> Valor {
+    let mut resultado = IndexMap::new();
     resultado.insert("stdout".to_string(), Valor::Texto(stdout));
     resultado.insert("stderr".to_string(), Valor::Texto(stderr));
-    resultado.insert("code".to_string(), Valor::Entero(codigo));
+    resultado.insert(
+        "code".to_string(),
+        Valor::Entero(estado.code().unwrap_or(-1) as i64),
+    );
+    resultado.insert("success".to_string(), Valor::Booleano(estado.success()));
 
     Valor::Diccionario(resultado)
 }
 
-fn ejecutar_comando(comando: &str, argumentos_comando: &[String]) -> Valor {
-    match Command::new(comando).args(argumentos_comando).output() {
-        Ok(salida) => crear_diccionario_salida(&salida),
-        Err(_) => Valor::Nulo,
+fn ejecutar(argumentos: Vec<Valor>) -> Valor {
+    let Some(invocacion) = Invocacion::desde_argumentos(&argumentos) else {
+        return errores::lanzar_argumento_invalido("exec", "un comando");
+    };
+
+    let mut hijo = match invocacion.construir_comando().spawn() {
+        Ok(hijo) => hijo,
+        Err(error) => return lanzar_error_io("exec", &invocacion.comando, error),
+    };
+
+    escribir_en_segundo_plano(hijo.stdin.take(), invocacion.opciones.entrada.clone());
+    let lector_stdout = leer_en_segundo_plano(hijo.stdout.take());
+    let lector_stderr = leer_en_segundo_plano(hijo.stderr.take());
+
+    // El comando se lanza ahora; su salida se espera con `awa`.
+    errores::promesa_bloqueante(move || {
+        let estado = match esperar_con_limite(&mut hijo, invocacion.opciones.limite) {
+            Ok(Some(estado)) => estado,
+            Ok(None) => {
+                let error = io::Error::new(
+                    io::ErrorKind::TimedOut,
+                    "se superó el tiempo límite de ejecución",
+                );
+                return lanzar_error_io("exec", &invocacion.comando, error);
+            }
+            Err(error) => return lanzar_error_io("exec", &invocacion.comando, error),
+        };
+
+        let stdout = lector_stdout.join().unwrap_or_default();
+        let stderr = lector_stderr.join().unwrap_or_default();
+        crear_diccionario_salida(estado, stdout, stderr)
+    })
+}
+
+// Cada flujo tiene su propio cerrojo: un `read_line` bloqueado no impide
+// escribir en el proceso ni matarlo.
+pub(crate) struct ProcesoEnCurso {
+    comando: String,
+    hijo: Mutex<Child>,
+    stdin: Mutex<Option<Sender<Vec<u8>>>>,
+    stdout: Mutex<Option<BufReader<ChildStdout>>>,
+    stderr: Mutex<Option<BufReader<ChildStderr>>>,
+}
+
+// Un proceso que nadie esperó no sobrevive a la sesión que lo lanzó.
+impl Drop for ProcesoEnCurso {
+    fn drop(&mut self) {
+        if let Ok(hijo) = self.hijo.get_mut() {
+            if let Ok(None) = hijo.try_wait() {
+                let _ = hijo.kill();
+                let _ = hijo.wait();
+            }
+        }
     }
 }
 
-fn ejecutar(argumentos: Vec<Valor>) -> Valor {
-    let comando = match obtener_comando(&argumentos) {
-        Some(cmd) => cmd,
-        None => return Valor::Nulo,
+static SIGUIENTE_PROCESO: AtomicI64 = AtomicI64::new(1);
+
+fn lanzar_proceso(argumentos: Vec<Valor>) -> Valor {
+    let Some(invocacion) = Invocacion::desde_argumentos(&argumentos) else {
+        return errores::lanzar_argumento_invalido("spawn", "un comando");
+    };
+
+    let mut comando = invocacion.construir_comando();
+    if !invocacion.opciones.heredar {
+        comando.stdin(Stdio::piped());
+    }
+
+    let mut hijo = match comando.spawn() {
+        Ok(hijo) => hijo,
+        Err(error) => return lanzar_error_io("spawn", &invocacion.comando, error),
+    };
+
+    let stdin = hijo.stdin.take().map(escritor_de_entrada);
+    if let (Some(emisor), Some(contenido)) = (&stdin, &invocacion.opciones.entrada) {
+        let _ = emisor.send(contenido.clone().into_bytes());
+    }
+
+    let id = SIGUIENTE_PROCESO.fetch_add(1, Ordering::Relaxed);
+    let pid = hijo.id();
+
+    let proceso = ProcesoEnCurso {
+        comando: invocacion.comando.clone(),
+        stdout: Mutex::new(hijo.stdout.take().map(BufReader::new)),
+        stderr: Mutex::new(hijo.stderr.take().map(BufReader::new)),
+        stdin: Mutex::new(stdin),
+        hijo: Mutex::new(hijo),
+    };
+    sesion::actual()
+        .procesos
+        .lock()
+        .unwrap()
+        .insert(id, Arc::new(proceso));
+
+    let mut manejador = IndexMap::new();
+    manejador.insert("id".to_string(), Valor::Entero(id));
+    manejador.insert("pid".to_string(), Valor::Entero(pid as i64));
+    manejador.insert("command".to_string(), Valor::Texto(invocacion.comando));
+    Valor::Diccionario(manejador)
+}
+
+fn obtener_id_proceso(argumentos: &[Valor]) -> Option<i64> {
+    let Some(Valor::Diccionario(manejador)) = argumentos.first() else {
+        return None;
+    };
+
+    match manejador.get("id") {
+        Some(Valor::Entero(id)) => Some(*id),
+        _ => None,
+    }
+}
+
+// Busca el proceso del manejador; con `quitar` lo saca de la tabla de la
+// sesión. El error ya lanzado se devuelve como `Err`.
+fn buscar_proceso(
+    funcion: &str,
+    argumentos: &[Valor],
+    quitar: bool,
+) -> Result<Arc<ProcesoEnCurso>, Valor> {
+    let Some(id) = obtener_id_proceso(argumentos) else {
+        return Err(errores::lanzar_argumento_invalido(
+            funcion,
+            "un proceso creado con spawn",
+        ));
+    };
+
+    let sesion = sesion::actual();
+    let mut procesos = sesion.procesos.lock().unwrap();
+    let proceso = match quitar {
+        true => procesos.remove(&id),
+        false => procesos.get(&id).cloned(),
+    };
+    proceso.ok_or_else(|| {
+        errores::lanzar_mensaje(&format!("{}: el proceso ya terminó", funcion), "NotFound")
+    })
+}
+
+fn con_proceso(
+    funcion: &str,
+    argumentos: &[Valor],
+    operacion: impl FnOnce(&ProcesoEnCurso) -> io::Result<Valor>,
+) -> Valor {
+    // La tabla se suelta antes de operar.
+    let proceso = match buscar_proceso(funcion, argumentos, false) {
+        Ok(proceso) => proceso,
+        Err(error) => return error,
+    };
+
+    match operacion(&proceso) {
+        Ok(valor) => valor,
+        Err(error) => lanzar_error_io(funcion, &proceso.comando, error),
+    }
+}
+
+fn leer_linea_de(fuente: Option<&mut impl BufRead>) -> io::Result<Valor> {
+    let Some(fuente) = fuente else {
+        return Ok(Valor::Nulo);
+    };
+
+    let mut linea = String::new();
+    if fuente.read_line(&mut linea)? == 0 {
+        return Ok(Valor::Nulo);
+    }
+
+    let sin_salto = linea.strip_suffix('\n').unwrap_or(&linea);
+    let sin_salto = sin_salto.strip_suffix('\r').unwrap_or(sin_salto);
+    Ok(Valor::Texto(sin_salto.to_string()))
+}
+
+// read_line(proceso) lee de stdout; read_line(proceso, "stderr") de stderr.
+// Devuelve una promesa: la línea puede tardar en llegar.
+fn leer_linea(argumentos: Vec<Valor>) -> Valor {
+    let desde_stderr = matches!(argumentos.get(1), Some(Valor::Texto(flujo)) if flujo == "stderr");
+    let proceso = match buscar_proceso("read_line", &argumentos, false) {
+        Ok(proceso) => proceso,
+        Err(error) => return error,
+    };
+
+    errores::promesa_bloqueante(move || {
+        let linea = if desde_stderr {
+            leer_linea_de(proceso.stderr.lock().unwrap().as_mut())
+        } else {
+            leer_linea_de(proceso.stdout.lock().unwrap().as_mut())
+        };
+        linea.unwrap_or_else(|error| lanzar_error_io("read_line", &proceso.comando, error))
+    })
+}
+
+fn escribir_entrada(argumentos: Vec<Valor>) -> Valor {
+    let contenido = match argumentos.get(1) {
+        Some(Valor::Texto(texto)) => texto.clone(),
+        Some(otro) => otro.to_string(),
+        None => return errores::lanzar_argumento_invalido("write", "un proceso y un texto"),
+    };
+
+    con_proceso("write", &argumentos, |proceso| {
+        let enviado = proceso
+            .stdin
+            .lock()
+            .unwrap()
+            .as_ref()
+            .is_some_and(|stdin| stdin.send(contenido.into_bytes()).is_ok());
+
+        if !enviado {
+            return Err(io::Error::new(
+                io::ErrorKind::BrokenPipe,
+                "la entrada del proceso está cerrada",
+            ));
+        }
+        Ok(Valor::Booleano(true))
+    })
+}
+
+fn cerrar_entrada(argumentos: Vec<Valor>) -> Valor {
+    con_proceso("close_stdin", &argumentos, |proceso| {
+        Ok(Valor::Booleano(
+            proceso.stdin.lock().unwrap().take().is_some(),
+        ))
+    })
+}
+
+fn matar(argumentos: Vec<Valor>) -> Valor {
+    con_proceso("kill", &argumentos, |proceso| {
+        proceso.hijo.lock().unwrap().kill()?;
+        Ok(Valor::Booleano(true))
+    })
+}
+
+fn esperar(argumentos: Vec<Valor>) -> Valor {
+    let proceso = match buscar_proceso("wait", &argumentos, true) {
+        Ok(proceso) => proceso,
+        Err(error) => return error,
+    };
+
+    errores::promesa_bloqueante(move || {
+        drop(proceso.stdin.lock().unwrap().take());
+        let lector_stdout = leer_en_segundo_plano(proceso.stdout.lock().unwrap().take());
+        let lector_stderr = leer_en_segundo_plano(proceso.stderr.lock().unwrap().take());
+
+        let estado = proceso.hijo.lock().unwrap().wait();
+        match estado {
+            Ok(estado) => crear_diccionario_salida(
+                estado,
+                lector_stdout.join().unwrap_or_default(),
+                lector_stderr.join().unwrap_or_default(),
+            ),
+            Err(error) => lanzar_error_io("wait", &proceso.comando, error),
+        }
+    })
+}
+
+fn argumentos_script(_argumentos: Vec<Valor>) -> Valor {
+    let argumentos = sesion::actual().argumentos.lock().unwrap().clone();
+    Valor::Lista(argumentos.into_iter().map(Valor::Texto).collect())
+}
+
+fn variables_entorno(argumentos: Vec<Valor>) -> Valor {
+    let sesion = sesion::actual();
+    let cambios = sesion.entorno.lock().unwrap();
+
+    if let Some(Valor::Texto(nombre)) = argumentos.first() {
+        let valor = match cambios.get(nombre) {
+            Some(valor) => valor.clone(),
+            None => std::env::var(nombre).ok(),
+        };
+        return valor.map(Valor::Texto).unwrap_or(Valor::Nulo);
+    }
+
+    let mut variables: IndexMap<String, Valor> = std::env::vars()
+        .map(|(clave, valor)| (clave, Valor::Texto(valor)))
+        .collect();
+    for (nombre, valor) in cambios.iter() {
+        match valor {
+            Some(valor) => variables.insert(nombre.clone(), Valor::Texto(valor.clone())),
+            None => variables.shift_remove(nombre),
+        };
+    }
+    Valor::Diccionario(variables)
+}
+
+fn establecer_variable_entorno(argumentos: Vec<Valor>) -> Valor {
+    let Some(Valor::Texto(nombre)) = argumentos.first() else {
+        return errores::lanzar_argumento_invalido("set_env", "un nombre y un valor");
+    };
+
+    if nombre.is_empty() || nombre.contains(['=', '\0']) {
+        return errores::lanzar_mensaje(
+            &format!("set_env: nombre de variable inválido '{}'", nombre),
+            "InvalidInput",
+        );
+    }
+
+    // El entorno del proceso lo comparten todos los hilos: el cambio queda en
+    // la sesión del intérprete y se aplica a los procesos hijos que lance.
+    let valor = match argumentos.get(1) {
+        Some(Valor::Nulo) | None => None,
+        Some(valor) => Some(valor.to_string()),
     };
+    sesion::actual()
+        .entorno
+        .lock()
+        .unwrap()
+        .insert(nombre.clone(), valor);
+
+    Valor::Booleano(true)
+}
+
+fn directorio_actual(_argumentos: Vec<Valor>) -> Valor {
+    match sesion::actual().directorio_actual() {
+        Ok(ruta) => Valor::Texto(ruta.to_string_lossy().to_string()),
+        Err(error) => lanzar_error_io("cwd", ".", error),
+    }
+}
+
+fn cambiar_directorio(argumentos: Vec<Valor>) -> Valor {
+    let Some(Valor::Texto(ruta)) = argumentos.first() else {
+        return errores::lanzar_argumento_invalido("chdir", "una ruta");
+    };
+
+    // Como el entorno, el directorio del proceso es de todos los hilos: el
+    // cambio queda en la sesión y lo usan `Std.fs` y los procesos hijos.
+    let sesion = sesion::actual();
+    let destino = sesion
+        .resolver_ruta(ruta)
+        .canonicalize()
+        .and_then(|destino| match destino.is_dir() {
+            true => Ok(destino),
+            false => Err(io::Error::new(
+                io::ErrorKind::NotADirectory,
+                "no es un directorio",
+            )),
+        });
+
+    match destino {
+        Ok(destino) => {
+            *sesion.directorio.lock().unwrap() = Some(destino);
+            Valor::Booleano(true)
+        }
+        Err(error) => lanzar_error_io("chdir", ruta, error),
+    }
+}
 
-    let argumentos_comando = extraer_argumentos_texto(&argumentos);
-    ejecutar_comando(&comando, &argumentos_comando)
+fn identificador_proceso(_argumentos: Vec<Valor>) -> Valor {
+    Valor::Entero(std::process::id() as i64)
+}
+
+fn salir(argumentos: Vec<Valor>) -> Valor {
+    let codigo = match argumentos.first() {
+        Some(Valor::Entero(codigo)) => *codigo as i32,
+        _ => 0,
+    };
+
+    // Quien ejecuta el programa decide qué hacer con el código: la CLI
+    // termina el proceso y `umbral test` da el test por fallido.
+    errores::lanzar_salida(codigo)
+}
+
+// Las pruebas lanzan `sh`, `cat` y `sleep`.
+#[cfg(all(test, unix))]
+mod tests {
+    use super::*;
+    use crate::runtime::stdlib::sesion::{self, Sesion};
+    use crate::runtime::valores::SharedPromesa;
+
+    fn texto(valor: &str) -> Valor {
+        Valor::Texto(valor.to_string())
+    }
+
+    fn opciones(pares: &[(&str, Valor)]) -> Valor {
+        Valor::Diccionario(
+            pares
+                .iter()
+                .map(|(clave, valor)| (clave.to_string(), valor.clone()))
+                .collect(),
+        )
+    }
+
+    fn campo(valor: &Valor, clave: &str) -> String {
+        match valor {
+            Valor::Diccionario(mapa) => mapa.get(clave).map(Valor::to_string).unwrap_or_default(),
+            otro => panic!("se esperaba un diccionario, se obtuvo {}", otro),
+        }
+    }
+
+    // Espera la promesa de una nativa y deja pendiente el error de su tarea,
+    // como hace `awa`.
+    async fn cumplir(valor: Valor) -> Valor {
+        let Valor::Promesa(SharedPromesa(tarea)) = valor else {
+            return valor;
+        };
+        let tarea = tarea.lock().unwrap().take().unwrap();
+        let id = tarea.id();
+        let resultado = tarea.await.unwrap();
+        if let Some(error) = errores::tomar_error_de_tarea(id) {
+            errores::lanzar(error);
+        }
+        resultado
+    }
+
+    #[tokio::test]
+    async fn test_set_env_queda_en_la_sesion_y_llega_a_los_hijos() {
+        let sesion = Sesion::nueva();
+        let nombre = "UMBRAL_PRUEBA_SET_ENV";
+
+        let resultado = sesion::con_sesion(&sesion, || {
+            establecer_variable_entorno(vec![texto(nombre), texto("valor")]);
+            assert_eq!(variables_entorno(vec![texto(nombre)]).to_string(), "valor");
+            assert_eq!(campo(&variables_entorno(vec![]), nombre), "valor");
+
+            ejecutar(vec![
+                texto("sh"),
+                texto("-c"),
+                texto(&format!("cat; printf \" ${}\"", nombre)),
+                opciones(&[("stdin", texto("hola"))]),
+            ])
+        });
+        let resultado = cumplir(resultado).await;
+        assert_eq!(campo(&resultado, "stdout"), "hola valor");
+        assert_eq!(campo(&resultado, "code"), "0");
+        assert_eq!(campo(&resultado, "success"), "true");
+
+        sesion::con_sesion(&sesion, || {
+            establecer_variable_entorno(vec![texto(nombre), Valor::Nulo]);
+            assert_eq!(variables_entorno(vec![texto(nombre)]).to_string(), "null");
+        });
+
+        assert!(std::env::var(nombre).is_err());
+        assert_eq!(variables_entorno(vec![texto(nombre)]).to_string(), "null");
+        assert!(errores::tomar_error_pendiente().is_none());
+    }
+
+    #[tokio::test]
+    async fn test_exec_con_tiempo_limite_lanza_al_esperar() {
+        let promesa = ejecutar(vec![
+            texto("sleep"),
+            texto("30"),
+            opciones(&[("timeout", Valor::Entero(20))]),
+        ]);
+        assert!(errores::tomar_error_pendiente().is_none());
+
+        assert_eq!(cumplir(promesa).await.to_string(), "null");
+        assert!(errores::tomar_error_pendiente().is_some());
+    }
+
+    #[tokio::test]
+    async fn test_spawn_escribe_y_lee_lineas() {
+        let proceso = lanzar_proceso(vec![texto("cat")]);
+
+        assert_eq!(
+            escribir_entrada(vec![proceso.clone(), texto("uno\n")]).to_string(),
+            "true"
+        );
+        assert_eq!(
+            cumplir(leer_linea(vec![proceso.clone()])).await.to_string(),
+            "uno"
+        );
+        escribir_entrada(vec![proceso.clone(), texto("dos\r\n")]);
+        assert_eq!(cerrar_entrada(vec![proceso.clone()]).to_string(), "true");
+        assert_eq!(
+            cumplir(leer_linea(vec![proceso.clone()])).await.to_string(),
+            "dos"
+        );
+        assert_eq!(
+            cumplir(leer_linea(vec![proceso.clone()])).await.to_string(),
+            "null"
+        );
+
+        escribir_entrada(vec![proceso.clone(), texto("tres")]);
+        assert!(errores::tomar_error_pendiente().is_some());
+
+        let resultado = cumplir(esperar(vec![proceso.clone()])).await;
+        assert_eq!(campo(&resultado, "code"), "0");
+        esperar(vec![proceso]);
+        assert!(errores::tomar_error_pendiente().is_some());
+    }
+
+    #[tokio::test]
+    async fn test_spawn_con_entrada_grande_no_se_bloquea() {
+        // Más de lo que cabe en las tuberías: antes `spawn` esperaba a que
+        // `cat` leyera toda la entrada mientras nadie vaciaba su salida.
+        let entrada = "x".repeat(4 * 1024 * 1024);
+        let proceso = lanzar_proceso(vec![texto("cat"), opciones(&[("stdin", texto(&entrada))])]);
+        cerrar_entrada(vec![proceso.clone()]);
+
+        let resultado = cumplir(esperar(vec![proceso])).await;
+        assert_eq!(campo(&resultado, "stdout").len(), entrada.len());
+    }
+
+    #[tokio::test]
+    async fn test_read_line_no_bloquea_el_runtime_y_kill_no_lo_espera() {
+        let proceso = lanzar_proceso(vec![texto("sleep"), texto("30")]);
+
+        let Valor::Promesa(SharedPromesa(lector)) = leer_linea(vec![proceso.clone()]) else {
+            panic!("read_line debería devolver una promesa");
+        };
+        let mut lector = lector.lock().unwrap().take().unwrap();
+        // El runtime de la prueba tiene un solo hilo: si la lectura lo
+        // bloqueara, el temporizador no vencería nunca.
+        assert!(tokio::time::timeout(Duration::from_millis(50), &mut lector)
+            .await
+            .is_err());
+
+        assert_eq!(matar(vec![proceso.clone()]).to_string(), "true");
+        assert_eq!(lector.await.unwrap().to_string(), "null");
+        let resultado = cumplir(esperar(vec![proceso])).await;
+        assert_eq!(campo(&resultado, "success"), "false");
+        assert!(errores::tomar_error_pendiente().is_none());
+    }
+
+    #[test]
+    fn test_la_sesion_mata_los_procesos_sin_esperar() {
+        let sesion = Sesion::nueva();
+        let proceso = sesion::con_sesion(&sesion, || {
+            lanzar_proceso(vec![texto("sleep"), texto("30")])
+        });
+        let pid = campo(&proceso, "pid");
+
+        drop(sesion);
+        let vivo = Command::new("kill")
+            .args(["-0", &pid])
+            .stderr(Stdio::null())
+            .status()
+            .unwrap();
+        assert!(!vivo.success());
+    }
+
+    #[tokio::test]
+    async fn test_chdir_queda_en_la_sesion() {
+        let del_proceso = std::env::current_dir().unwrap();
+        let temporal = std::env::temp_dir().canonicalize().unwrap();
+        let temporal = temporal.to_string_lossy().to_string();
+        let sesion = Sesion::nueva();
+
+        let resultado = sesion::con_sesion(&sesion, || {
+            assert_eq!(
+                cambiar_directorio(vec![texto(&temporal)]).to_string(),
+                "true"
+            );
+            assert_eq!(directorio_actual(vec![]).to_string(), temporal);
+            ejecutar(vec![texto("pwd")])
+        });
+        let resultado = cumplir(resultado).await;
+        assert_eq!(campo(&resultado, "stdout").trim_end(), temporal);
+
+        sesion::con_sesion(&sesion, || {
+            assert_eq!(
+                cambiar_directorio(vec![texto("no_existe_en_umbral")]).to_string(),
+                "null"
+            );
+            assert!(errores::tomar_error_pendiente().is_some());
+            assert_eq!(directorio_actual(vec![]).to_string(), temporal);
+        });
+
+        assert_eq!(std::env::current_dir().unwrap(), del_proceso);
+        assert_eq!(
+            directorio_actual(vec![]).to_string(),
+            del_proceso.to_string_lossy()
+        );
+    }
+
+    #[test]
+    fn test_exit_lanza_la_salida_en_lugar_de_terminar() {
+        assert_eq!(salir(vec![Valor::Entero(3)]).to_string(), "null");
+        let excepcion = errores::tomar_error_pendiente().unwrap();
+        assert_eq!(errores::codigo_salida(&excepcion), Some(3));
+    }
 }
@@ -0,0 +1,427 @@
+use crate::runtime::clases::Clase;
+use crate::runtime::stdlib::errores;
+use crate::runtime::valores::{Instancia, NativeFn, Valor};
+use indexmap::IndexMap;
+use regex::{Captures, Regex, RegexBuilder};
+use std::collections::HashMap;
+use std::sync::{Arc, LazyLock, Mutex};
+
+pub const CLASE_REGEX: &str = "Regex";
+
+// Los patrones se compilan una sola vez por combinación de patrón y flags.
+static COMPILADAS: LazyLock<Mutex<HashMap<(String, String), Regex>>> =
+    LazyLock::new(|| Mutex::new(HashMap::new()));
+
+fn registrar_funcion(mapa: &mut IndexMap<String, Valor>, nombre: &str, funcion: NativeFn) {
+    mapa.insert(
+        nombre.to_string(),
+        Valor::FuncionNativa(nombre.to_string(), funcion),
+    );
+}
+
+pub fn crear_modulo() -> Valor {
+    let mut mapa = IndexMap::new();
+
+    registrar_funcion(&mut mapa, "compile", compilar);
+    registrar_funcion(&mut mapa, "test", probar);
+    registrar_funcion(&mut mapa, "find", buscar);
+    registrar_funcion(&mut mapa, "find_all", buscar_todas);
+    registrar_funcion(&mut mapa, "split", dividir);
+    registrar_funcion(&mut mapa, "escape", escapar);
+    registrar_funcion(&mut mapa, "replace", reemplazar);
+
+    Valor::Diccionario(mapa)
+}
+
+pub fn crear_clase_regex() -> Clase {
+    let mut propiedades = IndexMap::new();
+    propiedades.insert("pattern".to_string(), Valor::Texto(String::new()));
+    propiedades.insert("flags".to_string(), Valor::Texto(String::new()));
+
+    Clase {
+        nombre: CLASE_REGEX.to_string(),
+        propiedades,
+        metodos: HashMap::new(),
+        constructor: None,
+    }
+}
+
+/// Métodos de las instancias de `Regex`: son las funciones del módulo con
+/// la instancia como primer argumento.
+pub fn metodo(nombre: &str) -> Option<Valor> {
+    let funcion: NativeFn = match nombre {
+        "test" => probar,
+        "find" => buscar,
+        "find_all" => buscar_todas,
+        "split" => dividir,
+        "replace" => reemplazar,
+        _ => return None,
+    };
+    Some(Valor::FuncionNativa(nombre.to_string(), funcion))
+}
+
+fn construir(patron: &str, flags: &str) -> Result<Regex, String> {
+    let clave = (patron.to_string(), flags.to_string());
+    if let Some(regex) = COMPILADAS.lock().unwrap().get(&clave) {
+        return Ok(regex.clone());
+    }
+
+    let mut constructor = RegexBuilder::new(patron);
+    for flag in flags.chars() {
+        match flag {
+            'i' => constructor.case_insensitive(true),
+            'm' => constructor.multi_line(true),
+            's' => constructor.dot_matches_new_line(true),
+            'x' => constructor.ignore_whitespace(true),
+            'U' => constructor.swap_greed(true),
+            otro => return Err(format!("flag de expresión regular desconocido '{}'", otro)),
+        };
+    }
+
+    let regex = constructor.build().map_err(|error| error.to_string())?;
+    COMPILADAS.lock().unwrap().insert(clave, regex.clone());
+    Ok(regex)
+}
+
+fn lanzar_patron_invalido(patron: &str, mensaje: &str) -> Valor {
+    let mut data = IndexMap::new();
+    data.insert("kind".to_string(), Valor::Texto("SyntaxError".to_string()));
+    data.insert("pattern".to_string(), Valor::Texto(patron.to_string()));
+
+    let mensaje = format!("Expresión regular inválida: {}", mensaje);
+    errores::lanzar(errores::crear_error(&mensaje, Valor::Diccionario(data)))
+}
+
+// compile(patron, [flags]); flags: i, m, s, x, U.
+fn compilar(argumentos: Vec<Valor>) -> Valor {
+    let Some(Valor::Texto(patron)) = argumentos.first() else {
+        return errores::lanzar_argumento_invalido("compile", "un patrón de texto");
+    };
+
+    let flags = match argumentos.get(1) {
+        Some(Valor::Texto(flags)) => flags.clone(),
+        _ => String::new(),
+    };
+
+    if let Err(mensaje) = construir(patron, &flags) {
+        return lanzar_patron_invalido(patron, &mensaje);
+    }
+
+    let mut propiedades = IndexMap::new();
+    propiedades.insert("pattern".to_string(), Valor::Texto(patron.clone()));
+    propiedades.insert("flags".to_string(), Valor::Texto(flags));
+
+    Valor::Objeto(Instancia {
+        clase: CLASE_REGEX.to_string(),
+        propiedades: Arc::new(Mutex::new(propiedades)),
+    })
+}
+
+// Todas las funciones aceptan una instancia de `Regex` o directamente un
+// patrón de texto sin flags.
+fn regex_argumento(valor: Option<&Valor>, funcion: &str) -> Result<Regex, Valor> {
+    let (patron, flags) = match valor {
+        Some(Valor::Texto(patron)) => (patron.clone(), String::new()),
+        Some(Valor::Objeto(instancia)) if instancia.clase == CLASE_REGEX => {
+            let propiedades = instancia.propiedades.lock().unwrap();
+            let texto = |clave: &str| match propiedades.get(clave) {
+                Some(Valor::Texto(texto)) => texto.clone(),
+                _ => String::new(),
+            };
+            (texto("pattern"), texto("flags"))
+        }
+        _ => {
+            return Err(errores::lanzar_argumento_invalido(
+                funcion,
+                "una expresión regular",
+            ))
+        }
+    };
+
+    construir(&patron, &flags).map_err(|mensaje| lanzar_patron_invalido(&patron, &mensaje))
+}
+
+fn argumentos_busqueda<'a>(
+    argumentos: &'a [Valor],
+    funcion: &str,
+) -> Result<(Regex, &'a str), Valor> {
+    let regex = regex_argumento(argumentos.first(), funcion)?;
+    match argumentos.get(1) {
+        Some(Valor::Texto(texto)) => Ok((regex, texto)),
+        _ => Err(errores::lanzar_argumento_invalido(
+            funcion,
+            "una expresión regular y un texto",
+        )),
+    }
+}
+
+fn posicion_caracter(texto: &str, byte: usize) -> Valor {
+    Valor::Entero(texto[..byte].chars().count() as i64)
+}
+
+/// Coincidencia como diccionario: `text`, `start` y `end` (en caracteres),
+/// `groups` con los grupos por posición y `named` con los grupos con nombre.
+fn coincidencia_a_valor(regex: &Regex, texto: &str, capturas: &Captures) -> Valor {
+    let completa = capturas.get(0).expect("el grupo 0 siempre existe");
+    let texto_grupo = |grupo: Option<regex::Match>| {
+        grupo.map_or(Valor::Nulo, |g| Valor::Texto(g.as_str().to_string()))
+    };
+
+    let grupos = capturas.iter().skip(1).map(texto_grupo).collect();
+
+    let mut nombrados = IndexMap::new();
+    for nombre in regex.capture_names().flatten() {
+        nombrados.insert(nombre.to_string(), texto_grupo(capturas.name(nombre)));
+    }
+
+    let mut mapa = IndexMap::new();
+    mapa.insert(
+        "text".to_string(),
+        Valor::Texto(completa.as_str().to_string()),
+    );
+    mapa.insert(
+        "start".to_string(),
+        posicion_caracter(texto, completa.start()),
+    );
+    mapa.insert("end".to_string(), posicion_caracter(texto, completa.end()));
+    mapa.insert("groups".to_string(), Valor::Lista(grupos));
+    mapa.insert("named".to_string(), Valor::Diccionario(nombrados));
+    Valor::Diccionario(mapa)
+}
+
+fn probar(argumentos: Vec<Valor>) -> Valor {
+    match argumentos_busqueda(&argumentos, "test") {
+        Ok((regex, texto)) => Valor::Booleano(regex.is_match(texto)),
+        Err(error) => error,
+    }
+}
+
+fn buscar(argumentos: Vec<Valor>) -> Valor {
+    let (regex, texto) = match argumentos_busqueda(&argumentos, "find") {
+        Ok(resultado) => resultado,
+        Err(error) => return error,
+    };
+
+    regex.captures(texto).map_or(Valor::Nulo, |capturas| {
+        coincidencia_a_valor(&regex, texto, &capturas)
+    })
+}
+
+fn buscar_todas(argumentos: Vec<Valor>) -> Valor {
+    let (regex, texto) = match argumentos_busqueda(&argumentos, "find_all") {
+        Ok(resultado) => resultado,
+        Err(error) => return error,
+    };
+
+    let coincidencias = regex
+        .captures_iter(texto)
+        .map(|capturas| coincidencia_a_valor(&regex, texto, &capturas))
+        .collect();
+    Valor::Lista(coincidencias)
+}
+
+/// Coincidencias para un reemplazo con función: rango en bytes y el
+/// diccionario que recibe la función. Lanza el error si los argumentos no
+/// son válidos.
+pub fn coincidencias(argumentos: &[Valor]) -> Option<Vec<(usize, usize, Valor)>> {
+    let (regex, texto) = argumentos_busqueda(argumentos, "replace").ok()?;
+
+    let coincidencias = regex
+        .captures_iter(texto)
+        .take(match limite_reemplazos(argumentos) {
+            0 => usize::MAX,
+            limite => limite,
+        })
+        .map(|capturas| {
+            let completa = capturas.get(0).expect("el grupo 0 siempre existe");
+            let datos = coincidencia_a_valor(&regex, texto, &capturas);
+            (completa.start(), completa.end(), datos)
+        })
+        .collect();
+    Some(coincidencias)
+}
+
+// Cuarto argumento de `replace`; 0 reemplaza todas las coincidencias.
+fn limite_reemplazos(argumentos: &[Valor]) -> usize {
+    match argumentos.get(3) {
+        Some(Valor::Entero(n)) if *n > 0 => *n as usize,
+        _ => 0,
+    }
+}
+
+// replace(regex, texto, reemplazo, [limite]); el reemplazo admite $1, $2 y
+// ${nombre}. Si es una función, el intérprete la llama con cada coincidencia.
+pub(crate) fn reemplazar(argumentos: Vec<Valor>) -> Valor {
+    let (regex, texto) = match argumentos_busqueda(&argumentos, "replace") {
+        Ok(resultado) => resultado,
+        Err(error) => return error,
+    };
+
+    let Some(Valor::Texto(reemplazo)) = argumentos.get(2) else {
+        return errores::lanzar_argumento_invalido(
+            "replace",
+            "un texto o una función de reemplazo",
+        );
+    };
+
+    Valor::Texto(
+        regex
+            .replacen(texto, limite_reemplazos(&argumentos), reemplazo.as_str())
+            .into_owned(),
+    )
+}
+
+// split(regex, texto, [limite])
+fn dividir(argumentos: Vec<Valor>) -> Valor {
+    let (regex, texto) = match argumentos_busqueda(&argumentos, "split") {
+        Ok(resultado) => resultado,
+        Err(error) => return error,
+    };
+
+    let partes: Vec<Valor> = match argumentos.get(2) {
+        Some(Valor::Entero(limite)) if *limite > 0 => regex
+            .splitn(texto, *limite as usize)
+            .map(|parte| Valor::Texto(parte.to_string()))
+            .collect(),
+        _ => regex
+            .split(texto)
+            .map(|parte| Valor::Texto(parte.to_string()))
+            .collect(),
+    };
+    Valor::Lista(partes)
+}
+
+fn escapar(argumentos: Vec<Valor>) -> Valor {
+    match argumentos.first() {
+        Some(Valor::Texto(texto)) => Valor::Texto(regex::escape(texto)),
+        _ => errores::lanzar_argumento_invalido("escape", "un texto"),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn texto(valor: &str) -> Valor {
+        Valor::Texto(valor.to_string())
+    }
+
+    fn dividir_esperado(partes: &[&str]) -> String {
+        Valor::Lista(partes.iter().map(|parte| texto(parte)).collect()).to_string()
+    }
+
+    fn campo(coincidencia: &Valor, clave: &str) -> String {
+        let Valor::Diccionario(mapa) = coincidencia else {
+            panic!("se esperaba una coincidencia, no {}", coincidencia);
+        };
+        mapa[clave].to_string()
+    }
+
+    #[test]
+    fn test_compilar_con_flags_y_probar() {
+        let regex = compilar(vec![texto("^hola$"), texto("im")]);
+        assert!(matches!(&regex, Valor::Objeto(instancia) if instancia.clase == CLASE_REGEX));
+
+        assert_eq!(
+            probar(vec![regex.clone(), texto("adiós\nHOLA")]).to_string(),
+            "true"
+        );
+        assert_eq!(
+            probar(vec![texto("^hola$"), texto("adiós\nHOLA")]).to_string(),
+            "false"
+        );
+    }
+
+    #[test]
+    fn test_patron_o_flag_invalido_lanza_syntax_error() {
+        for argumentos in [vec![texto("(sin cerrar")], vec![texto("a"), texto("q")]] {
+            assert_eq!(compilar(argumentos).to_string(), "null");
+            let Some(Valor::Objeto(error)) = errores::tomar_error_pendiente() else {
+                panic!("se esperaba un Error");
+            };
+            let propiedades = error.propiedades.lock().unwrap();
+            let Some(Valor::Diccionario(data)) = propiedades.get("data") else {
+                panic!("el error no tiene data");
+            };
+            assert_eq!(data["kind"].to_string(), "SyntaxError");
+        }
+    }
+
+    #[test]
+    fn test_buscar_con_grupos_y_posiciones_en_caracteres() {
+        let coincidencia = buscar(vec![
+            texto(r"(?P<clave>\w+)=(\d+)?"),
+            texto("año: ñandú=42"),
+        ]);
+        assert_eq!(campo(&coincidencia, "text"), "ñandú=42");
+        assert_eq!(campo(&coincidencia, "start"), "5");
+        assert_eq!(campo(&coincidencia, "end"), "13");
+
+        let Valor::Diccionario(mapa) = &coincidencia else {
+            unreachable!()
+        };
+        let Valor::Lista(grupos) = &mapa["groups"] else {
+            panic!("groups debería ser una lista");
+        };
+        assert_eq!(grupos[0].to_string(), "ñandú");
+        assert_eq!(grupos[1].to_string(), "42");
+        assert_eq!(campo(&mapa["named"], "clave"), "ñandú");
+
+        let sin_grupo = buscar(vec![texto(r"(\w+)=(\d+)?"), texto("x=")]);
+        let Valor::Diccionario(mapa) = &sin_grupo else {
+            panic!("se esperaba una coincidencia");
+        };
+        assert!(matches!(&mapa["groups"], Valor::Lista(g) if matches!(g[1], Valor::Nulo)));
+
+        assert_eq!(buscar(vec![texto(r"\d"), texto("abc")]).to_string(), "null");
+    }
+
+    #[test]
+    fn test_buscar_todas_dividir_y_escapar() {
+        let Valor::Lista(todas) = buscar_todas(vec![texto(r"\d+"), texto("a1b22c333")]) else {
+            panic!("find_all debería devolver una lista");
+        };
+        let textos: Vec<String> = todas.iter().map(|c| campo(c, "text")).collect();
+        assert_eq!(textos, ["1", "22", "333"]);
+
+        let partes = dividir(vec![texto(r"\s*,\s*"), texto("a , b,c ,d")]);
+        assert_eq!(partes.to_string(), dividir_esperado(&["a", "b", "c", "d"]));
+        let limitadas = dividir(vec![texto(","), texto("a,b,c"), Valor::Entero(2)]);
+        assert_eq!(limitadas.to_string(), dividir_esperado(&["a", "b,c"]));
+
+        assert_eq!(escapar(vec![texto("1+1=2?")]).to_string(), r"1\+1=2\?");
+    }
+
+    #[test]
+    fn test_reemplazar_con_grupos_y_limite() {
+        let fecha = texto(r"(?P<d>\d{2})/(?P<m>\d{2})/(\d{4})");
+        assert_eq!(
+            reemplazar(vec![fecha, texto("hoy 05/10/2024"), texto("$3-${m}-$d")]).to_string(),
+            "hoy 2024-10-05"
+        );
+        assert_eq!(
+            reemplazar(vec![
+                texto("a"),
+                texto("banana"),
+                texto("o"),
+                Valor::Entero(2)
+            ])
+            .to_string(),
+            "bonona"
+        );
+    }
+
+    #[test]
+    fn test_coincidencias_para_reemplazo_con_funcion() {
+        let rangos = coincidencias(&[texto(r"\d+"), texto("é1-22")]).unwrap();
+        let posiciones: Vec<(usize, usize)> = rangos.iter().map(|(i, f, _)| (*i, *f)).collect();
+        // Rangos en bytes para recortar el texto; las posiciones del
+        // diccionario van en caracteres.
+        assert_eq!(posiciones, [(2, 3), (4, 6)]);
+        assert_eq!(campo(&rangos[1].2, "start"), "3");
+
+        let limitadas =
+            coincidencias(&[texto(r"\d+"), texto("1 2 3"), Valor::Nulo, Valor::Entero(2)]);
+        assert_eq!(limitadas.unwrap().len(), 2);
+    }
+}
@@ -0,0 +1,73 @@
+use crate::runtime::stdlib::decimal;
+use crate::runtime::stdlib::fs::ArchivoAbierto;
+use crate::runtime::stdlib::proc::ProcesoEnCurso;
+use indexmap::IndexMap;
+use std::cell::RefCell;
+use std::collections::HashMap;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, LazyLock, Mutex};
+
+/// Estado de la biblioteca estándar que pertenece a un intérprete y no al
+/// proceso. Lo comparten el intérprete, sus módulos y las tareas que lanza;
+/// se libera (y con él los archivos abiertos y los procesos que sigan en
+/// marcha) cuando todos terminan.
+#[derive(Default)]
+pub struct Sesion {
+    pub(crate) archivos: Mutex<HashMap<i64, ArchivoAbierto>>,
+    // Procesos de `Std.proc.spawn` a los que aún no se ha hecho `wait`.
+    pub(crate) procesos: Mutex<HashMap<i64, Arc<ProcesoEnCurso>>>,
+    // Variables cambiadas con `Std.proc.set_env`; `None` si se eliminó.
+    pub(crate) entorno: Mutex<IndexMap<String, Option<String>>>,
+    // Argumentos del script: `ARGS`, `Std.proc.args()` y `Std.cli.parse`.
+    pub(crate) argumentos: Mutex<Vec<String>>,
+    // Precisión y redondeo de `Std.decimal.set_context`.
+    pub(crate) decimal: Mutex<decimal::Contexto>,
+    // Directorio de `Std.proc.chdir`; `None` mientras sea el del proceso,
+    // que comparten todos los intérpretes.
+    pub(crate) directorio: Mutex<Option<PathBuf>>,
+}
+
+impl Sesion {
+    pub fn nueva() -> Arc<Self> {
+        Arc::new(Self::default())
+    }
+
+    pub(crate) fn directorio_actual(&self) -> io::Result<PathBuf> {
+        match &*self.directorio.lock().unwrap() {
+            Some(directorio) => Ok(directorio.clone()),
+            None => std::env::current_dir(),
+        }
+    }
+
+    /// `ruta` tal cual si es absoluta; si no, dentro del directorio de
+    /// trabajo de la sesión.
+    pub(crate) fn resolver_ruta(&self, ruta: impl AsRef<Path>) -> PathBuf {
+        match &*self.directorio.lock().unwrap() {
+            Some(directorio) => directorio.join(ruta),
+            None => ruta.as_ref().to_path_buf(),
+        }
+    }
+}
+
+thread_local! {
+    static SESION_ACTUAL: RefCell<Option<Arc<Sesion>>> = const { RefCell::new(None) };
+}
+
+// Sesión de las nativas que se llaman fuera de un intérprete.
+static SESION_GLOBAL: LazyLock<Arc<Sesion>> = LazyLock::new(Sesion::nueva);
+
+// Las funciones nativas no reciben el intérprete: este instala su sesión
+// mientras dura la llamada, que es síncrona y no cambia de hilo.
+pub fn con_sesion<R>(sesion: &Arc<Sesion>, funcion: impl FnOnce() -> R) -> R {
+    let anterior = SESION_ACTUAL.with(|actual| actual.replace(Some(sesion.clone())));
+    let resultado = funcion();
+    SESION_ACTUAL.with(|actual| *actual.borrow_mut() = anterior);
+    resultado
+}
+
+pub fn actual() -> Arc<Sesion> {
+    SESION_ACTUAL
+        .with(|actual| actual.borrow().clone())
+        .unwrap_or_else(|| SESION_GLOBAL.clone())
+}
@@ -1,7 +1,14 @@
+use crate::runtime::stdlib::errores;
 use crate::runtime::valores::Valor;
-use std::collections::HashMap;
+use indexmap::IndexMap;
+use unicode_normalization::UnicodeNormalization;
+use unicode_segmentation::UnicodeSegmentation;
 
-fn registrar_funcion(mapa: &mut HashMap<String, Valor>, nombre: &str, funcion: fn(Vec<Valor>) -> Valor) {
+// Tope de `repeat` en bytes, para que un contador enorme sea un error del
+// programa y no agote la memoria del proceso.
+const MAXIMO_REPETICION: usize = 1 << 30;
+
+fn registrar_funcion(mapa: &mut IndexMap<String, Valor>, nombre: &str, funcion: fn(Vec<Valor>) -> Valor) {
     mapa.insert(
         nombre.to_string(),
         Valor::FuncionNativa(nombre.to_string(), funcion),
@@ -9,7 +16,7 @@ fn registrar_funcion(mapa: &mut HashMap<String, Valor>, nombre: &str, funcion: f
 }
 
 pub fn crear_modulo() -> Valor {
-    let mut mapa = HashMap::new();
+    let mut mapa = IndexMap::new();
 
     registrar_funcion(&mut mapa, "trim", recortar);
     registrar_funcion(&mut mapa, "split", dividir);
@@ -20,12 +27,31 @@ pub fn crear_modulo() -> Valor {
     registrar_funcion(&mut mapa, "contains", contiene);
     registrar_funcion(&mut mapa, "starts_with", inicia_con);
     registrar_funcion(&mut mapa, "ends_with", termina_con);
+    registrar_funcion(&mut mapa, "len", longitud);
+    registrar_funcion(&mut mapa, "grapheme_len", longitud_grafemas);
+    registrar_funcion(&mut mapa, "chars", caracteres);
+    registrar_funcion(&mut mapa, "graphemes", grafemas);
+    registrar_funcion(&mut mapa, "lines", lineas);
+    registrar_funcion(&mut mapa, "char_at", caracter_en);
+    registrar_funcion(&mut mapa, "substring", subcadena);
+    registrar_funcion(&mut mapa, "index_of", indice_de);
+    registrar_funcion(&mut mapa, "last_index_of", ultimo_indice_de);
+    registrar_funcion(&mut mapa, "count", contar);
+    registrar_funcion(&mut mapa, "pad_start", rellenar_inicio);
+    registrar_funcion(&mut mapa, "pad_end", rellenar_final);
+    registrar_funcion(&mut mapa, "repeat", repetir);
+    registrar_funcion(&mut mapa, "reverse", invertir);
+    registrar_funcion(&mut mapa, "capitalize", capitalizar);
+    registrar_funcion(&mut mapa, "trim_start", recortar_inicio);
+    registrar_funcion(&mut mapa, "trim_end", recortar_final);
+    registrar_funcion(&mut mapa, "is_empty", esta_vacio);
+    registrar_funcion(&mut mapa, "normalize", normalizar);
 
     Valor::Diccionario(mapa)
 }
 
 fn obtener_texto(argumentos: &[Valor]) -> Option<String> {
-    match argumentos.get(0) {
+    match argumentos.first() {
         Some(Valor::Texto(texto)) => Some(texto.clone()),
         _ => None,
     }
@@ -98,7 +124,7 @@ fn minusculas(argumentos: Vec<Valor>) -> Valor {
 }
 
 fn obtener_texto_y_patron(argumentos: &[Valor]) -> Option<(String, String)> {
-    let texto = match argumentos.get(0) {
+    let texto = match argumentos.first() {
         Some(Valor::Texto(t)) => t.clone(),
         _ => return None,
     };
@@ -112,15 +138,7 @@ fn obtener_texto_y_patron(argumentos: &[Valor]) -> Option<(String, String)> {
 }
 
 fn buscar(argumentos: Vec<Valor>) -> Valor {
-    match obtener_texto_y_patron(&argumentos) {
-        Some((texto, patron)) => {
-            match texto.find(&patron) {
-                Some(indice) => Valor::Entero(indice as i64),
-                None => Valor::Entero(-1),
-            }
-        }
-        None => Valor::Entero(-1),
-    }
+    indice_de(argumentos)
 }
 
 fn contiene(argumentos: Vec<Valor>) -> Valor {
@@ -143,3 +161,363 @@ fn termina_con(argumentos: Vec<Valor>) -> Valor {
         None => Valor::Booleano(false),
     }
 }
+
+// Las posiciones de texto se cuentan en caracteres Unicode, no en bytes.
+fn indice_caracter(texto: &str, byte: usize) -> i64 {
+    texto[..byte].chars().count() as i64
+}
+
+fn byte_de_caracter(texto: &str, caracter: usize) -> usize {
+    texto
+        .char_indices()
+        .nth(caracter)
+        .map_or(texto.len(), |(byte, _)| byte)
+}
+
+fn obtener_entero(argumentos: &[Valor], posicion: usize) -> Option<i64> {
+    match argumentos.get(posicion) {
+        Some(Valor::Entero(n)) => Some(*n),
+        _ => None,
+    }
+}
+
+/// Posición de `indice` en una secuencia de `longitud` elementos; los
+/// negativos cuentan desde el final (`-1` es el último).
+pub fn indice_absoluto(indice: i64, longitud: usize) -> Option<usize> {
+    let absoluto = if indice < 0 {
+        longitud as i64 + indice
+    } else {
+        indice
+    };
+    usize::try_from(absoluto)
+        .ok()
+        .filter(|absoluto| *absoluto < longitud)
+}
+
+pub fn caracter_de_texto(texto: &str, indice: i64) -> Option<char> {
+    let indice = indice_absoluto(indice, texto.chars().count())?;
+    texto.chars().nth(indice)
+}
+
+/// Límites de una rebanada `[inicio:fin]` sobre una secuencia de `longitud`
+/// elementos. Los índices negativos cuentan desde el final y los que quedan
+/// fuera de rango se ajustan a los extremos.
+pub fn limites_rebanada(longitud: usize, inicio: Option<i64>, fin: Option<i64>) -> (usize, usize) {
+    let ajustar = |indice: i64| {
+        let absoluto = if indice < 0 {
+            longitud as i64 + indice
+        } else {
+            indice
+        };
+        absoluto.clamp(0, longitud as i64) as usize
+    };
+
+    let inicio = inicio.map_or(0, ajustar);
+    let fin = fin.map_or(longitud, ajustar);
+    (inicio, fin.max(inicio))
+}
+
+pub fn rebanar_texto(texto: &str, inicio: Option<i64>, fin: Option<i64>) -> String {
+    let longitud = texto.chars().count();
+    let (inicio, fin) = limites_rebanada(longitud, inicio, fin);
+    texto.chars().skip(inicio).take(fin - inicio).collect()
+}
+
+fn longitud(argumentos: Vec<Valor>) -> Valor {
+    match obtener_texto(&argumentos) {
+        Some(texto) => Valor::Entero(texto.chars().count() as i64),
+        None => Valor::Entero(0),
+    }
+}
+
+fn longitud_grafemas(argumentos: Vec<Valor>) -> Valor {
+    match obtener_texto(&argumentos) {
+        Some(texto) => Valor::Entero(texto.graphemes(true).count() as i64),
+        None => Valor::Entero(0),
+    }
+}
+
+fn caracteres(argumentos: Vec<Valor>) -> Valor {
+    match obtener_texto(&argumentos) {
+        Some(texto) => Valor::Lista(
+            texto
+                .chars()
+                .map(|caracter| Valor::Texto(caracter.to_string()))
+                .collect(),
+        ),
+        None => Valor::Nulo,
+    }
+}
+
+fn grafemas(argumentos: Vec<Valor>) -> Valor {
+    match obtener_texto(&argumentos) {
+        Some(texto) => Valor::Lista(
+            texto
+                .graphemes(true)
+                .map(|grafema| Valor::Texto(grafema.to_string()))
+                .collect(),
+        ),
+        None => Valor::Nulo,
+    }
+}
+
+fn lineas(argumentos: Vec<Valor>) -> Valor {
+    match obtener_texto(&argumentos) {
+        Some(texto) => Valor::Lista(
+            texto
+                .lines()
+                .map(|linea| Valor::Texto(linea.to_string()))
+                .collect(),
+        ),
+        None => Valor::Nulo,
+    }
+}
+
+fn caracter_en(argumentos: Vec<Valor>) -> Valor {
+    let (Some(texto), Some(indice)) = (obtener_texto(&argumentos), obtener_entero(&argumentos, 1))
+    else {
+        return Valor::Nulo;
+    };
+
+    caracter_de_texto(&texto, indice)
+        .map_or(Valor::Nulo, |caracter| Valor::Texto(caracter.to_string()))
+}
+
+// substring(texto, inicio, [fin])
+fn subcadena(argumentos: Vec<Valor>) -> Valor {
+    match obtener_texto(&argumentos) {
+        Some(texto) => Valor::Texto(rebanar_texto(
+            &texto,
+            obtener_entero(&argumentos, 1),
+            obtener_entero(&argumentos, 2),
+        )),
+        None => Valor::Nulo,
+    }
+}
+
+// index_of(texto, patron, [desde])
+fn indice_de(argumentos: Vec<Valor>) -> Valor {
+    let Some((texto, patron)) = obtener_texto_y_patron(&argumentos) else {
+        return Valor::Entero(-1);
+    };
+
+    let desde = obtener_entero(&argumentos, 2).unwrap_or(0).max(0) as usize;
+    let inicio = byte_de_caracter(&texto, desde);
+
+    match texto[inicio..].find(&patron) {
+        Some(byte) => Valor::Entero(indice_caracter(&texto, inicio + byte)),
+        None => Valor::Entero(-1),
+    }
+}
+
+fn ultimo_indice_de(argumentos: Vec<Valor>) -> Valor {
+    match obtener_texto_y_patron(&argumentos) {
+        Some((texto, patron)) => match texto.rfind(&patron) {
+            Some(byte) => Valor::Entero(indice_caracter(&texto, byte)),
+            None => Valor::Entero(-1),
+        },
+        None => Valor::Entero(-1),
+    }
+}
+
+fn contar(argumentos: Vec<Valor>) -> Valor {
+    match obtener_texto_y_patron(&argumentos) {
+        Some((texto, patron)) if !patron.is_empty() => {
+            Valor::Entero(texto.matches(&patron).count() as i64)
+        }
+        _ => Valor::Entero(0),
+    }
+}
+
+// pad_start(texto, ancho, [relleno]); el ancho se mide en caracteres.
+fn obtener_relleno(argumentos: &[Valor]) -> Option<(String, String)> {
+    let texto = obtener_texto(argumentos)?;
+    let ancho = obtener_entero(argumentos, 1)?.max(0) as usize;
+    let relleno = match argumentos.get(2) {
+        Some(Valor::Texto(relleno)) if !relleno.is_empty() => relleno.clone(),
+        _ => " ".to_string(),
+    };
+
+    let faltan = ancho.saturating_sub(texto.chars().count());
+    let relleno: String = relleno.chars().cycle().take(faltan).collect();
+    Some((texto, relleno))
+}
+
+fn rellenar_inicio(argumentos: Vec<Valor>) -> Valor {
+    match obtener_relleno(&argumentos) {
+        Some((texto, relleno)) => Valor::Texto(relleno + &texto),
+        None => Valor::Nulo,
+    }
+}
+
+fn rellenar_final(argumentos: Vec<Valor>) -> Valor {
+    match obtener_relleno(&argumentos) {
+        Some((texto, relleno)) => Valor::Texto(texto + &relleno),
+        None => Valor::Nulo,
+    }
+}
+
+fn repetir(argumentos: Vec<Valor>) -> Valor {
+    match (obtener_texto(&argumentos), obtener_entero(&argumentos, 1)) {
+        (Some(texto), Some(veces)) => {
+            let veces = usize::try_from(veces.max(0)).unwrap_or(usize::MAX);
+            match texto.len().checked_mul(veces) {
+                Some(tamano) if tamano <= MAXIMO_REPETICION => Valor::Texto(texto.repeat(veces)),
+                _ => errores::lanzar_mensaje(
+                    &format!("repeat: el resultado superaría {} bytes", MAXIMO_REPETICION),
+                    "Overflow",
+                ),
+            }
+        }
+        _ => Valor::Nulo,
+    }
+}
+
+// Invierte por grafemas para no separar acentos combinados ni emojis.
+fn invertir(argumentos: Vec<Valor>) -> Valor {
+    match obtener_texto(&argumentos) {
+        Some(texto) => Valor::Texto(texto.graphemes(true).rev().collect()),
+        None => Valor::Nulo,
+    }
+}
+
+fn capitalizar(argumentos: Vec<Valor>) -> Valor {
+    let Some(texto) = obtener_texto(&argumentos) else {
+        return Valor::Nulo;
+    };
+
+    let mut caracteres = texto.chars();
+    match caracteres.next() {
+        Some(primero) => Valor::Texto(primero.to_uppercase().chain(caracteres).collect()),
+        None => Valor::Texto(texto),
+    }
+}
+
+fn recortar_inicio(argumentos: Vec<Valor>) -> Valor {
+    match obtener_texto(&argumentos) {
+        Some(texto) => Valor::Texto(texto.trim_start().to_string()),
+        None => Valor::Nulo,
+    }
+}
+
+fn recortar_final(argumentos: Vec<Valor>) -> Valor {
+    match obtener_texto(&argumentos) {
+        Some(texto) => Valor::Texto(texto.trim_end().to_string()),
+        None => Valor::Nulo,
+    }
+}
+
+fn esta_vacio(argumentos: Vec<Valor>) -> Valor {
+    match obtener_texto(&argumentos) {
+        Some(texto) => Valor::Booleano(texto.is_empty()),
+        None => Valor::Booleano(true),
+    }
+}
+
+// normalize(texto, [forma]); formas: NFC (por defecto), NFD, NFKC, NFKD.
+fn normalizar(argumentos: Vec<Valor>) -> Valor {
+    let Some(texto) = obtener_texto(&argumentos) else {
+        return Valor::Nulo;
+    };
+
+    let forma = match argumentos.get(1) {
+        Some(Valor::Texto(forma)) => forma.to_uppercase(),
+        _ => "NFC".to_string(),
+    };
+
+    match forma.as_str() {
+        "NFC" => Valor::Texto(texto.nfc().collect()),
+        "NFD" => Valor::Texto(texto.nfd().collect()),
+        "NFKC" => Valor::Texto(texto.nfkc().collect()),
+        "NFKD" => Valor::Texto(texto.nfkd().collect()),
+        _ => Valor::Nulo,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn texto(valor: &str) -> Valor {
+        Valor::Texto(valor.to_string())
+    }
+
+    #[test]
+    fn test_repeat_con_contador_enorme() {
+        let repetir_ab = |veces| repetir(vec![texto("ab"), Valor::Entero(veces)]).to_string();
+        assert_eq!(repetir_ab(3), "ababab");
+        assert_eq!(repetir_ab(-1), "");
+        assert!(errores::tomar_error_pendiente().is_none());
+
+        assert_eq!(repetir_ab(i64::MAX), "null");
+        assert!(errores::tomar_error_pendiente().is_some());
+        assert_eq!(repetir_ab(1 << 40), "null");
+        assert!(errores::tomar_error_pendiente().is_some());
+    }
+
+    #[test]
+    fn test_limites_rebanada_negativos_y_fuera_de_rango() {
+        assert_eq!(limites_rebanada(5, Some(1), Some(3)), (1, 3));
+        assert_eq!(limites_rebanada(5, Some(-2), None), (3, 5));
+        assert_eq!(limites_rebanada(5, None, Some(-1)), (0, 4));
+        assert_eq!(limites_rebanada(5, Some(-10), Some(10)), (0, 5));
+        assert_eq!(limites_rebanada(5, Some(4), Some(2)), (4, 4));
+        assert_eq!(limites_rebanada(0, Some(1), Some(-1)), (0, 0));
+    }
+
+    #[test]
+    fn test_indices_por_caracter_y_desde_el_final() {
+        assert_eq!(indice_absoluto(0, 3), Some(0));
+        assert_eq!(indice_absoluto(-1, 3), Some(2));
+        assert_eq!(indice_absoluto(-3, 3), Some(0));
+        assert_eq!(indice_absoluto(-4, 3), None);
+        assert_eq!(indice_absoluto(3, 3), None);
+        assert_eq!(indice_absoluto(-1, 0), None);
+
+        assert_eq!(caracter_de_texto("Ñandú", 0), Some('Ñ'));
+        assert_eq!(caracter_de_texto("Ñandú", -1), Some('ú'));
+        assert_eq!(caracter_de_texto("Ñandú", 5), None);
+        assert_eq!(rebanar_texto("Ñandú", Some(1), Some(-1)), "and");
+        assert_eq!(rebanar_texto("日本語テキスト", Some(-4), None), "テキスト");
+    }
+
+    #[test]
+    fn test_longitudes_en_caracteres_y_grafemas() {
+        // "e" + acento combinado y una familia con ZWJ: 2 grafemas, 7 caracteres.
+        let compuesto = "e\u{301}👨\u{200d}👩\u{200d}👧";
+        assert_eq!(longitud(vec![texto(compuesto)]).to_string(), "7");
+        assert_eq!(longitud_grafemas(vec![texto(compuesto)]).to_string(), "2");
+        assert_eq!(longitud(vec![texto("año")]).to_string(), "3");
+        assert_eq!(invertir(vec![texto("ae\u{301}")]).to_string(), "e\u{301}a");
+    }
+
+    #[test]
+    fn test_metodos_con_posiciones_en_caracteres() {
+        let banana = || texto("bañana");
+        assert_eq!(indice_de(vec![banana(), texto("an")]).to_string(), "3");
+        assert_eq!(
+            ultimo_indice_de(vec![banana(), texto("a")]).to_string(),
+            "5"
+        );
+        assert_eq!(
+            subcadena(vec![banana(), Valor::Entero(2), Valor::Entero(4)]).to_string(),
+            "ña"
+        );
+        assert_eq!(
+            caracter_en(vec![banana(), Valor::Entero(-4)]).to_string(),
+            "ñ"
+        );
+        assert_eq!(
+            caracter_en(vec![banana(), Valor::Entero(10)]).to_string(),
+            "null"
+        );
+        assert_eq!(
+            rellenar_inicio(vec![texto("7"), Valor::Entero(3), texto("0")]).to_string(),
+            "007"
+        );
+        assert_eq!(
+            rellenar_final(vec![texto("ñ"), Valor::Entero(3), texto("·")]).to_string(),
+            "ñ··"
+        );
+    }
+}
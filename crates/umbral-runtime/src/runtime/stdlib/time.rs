@@ -1,18 +1,275 @@
-use crate::runtime::valores::Valor;
-use std::collections::HashMap;
-use std::time::{SystemTime, UNIX_EPOCH};
+use crate::runtime::stdlib::errores;
+use crate::runtime::valores::{SharedPromesa, Valor};
+use chrono::format::{Item, StrftimeItems};
+use chrono::{
+    DateTime, Datelike, FixedOffset, Local, NaiveDate, NaiveDateTime, Offset, SecondsFormat,
+    TimeDelta, TimeZone, Timelike, Utc,
+};
+use chrono_tz::Tz;
+use indexmap::IndexMap;
+use std::fmt;
+use std::sync::{Arc, LazyLock, Mutex};
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
 
-pub fn crear_modulo() -> Valor {
-    let mut mapa = HashMap::new();
+// Referencia del reloj monotónico: `monotonic()` mide desde el primer uso.
+static INICIO_MONOTONICO: LazyLock<Instant> = LazyLock::new(Instant::now);
 
+/// Zona horaria asociada a una fecha: solo afecta a cómo se leen sus
+/// componentes y a cómo se formatea, nunca al instante que representa.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Zona {
+    Utc,
+    Local,
+    Fija(FixedOffset),
+    Iana(Tz),
+}
+
+impl Zona {
+    /// Acepta "UTC"/"Z", "local", desplazamientos como "+02:00" o "-0530"
+    /// y nombres IANA como "Europe/Madrid".
+    pub fn desde_texto(texto: &str) -> Option<Zona> {
+        match texto {
+            "UTC" | "utc" | "Z" => return Some(Zona::Utc),
+            "local" => return Some(Zona::Local),
+            _ => {}
+        }
+
+        if texto.starts_with('+') || texto.starts_with('-') {
+            return parsear_desplazamiento(texto).map(Zona::Fija);
+        }
+
+        texto.parse::<Tz>().ok().map(Zona::Iana)
+    }
+
+    pub fn nombre(&self) -> String {
+        match self {
+            Zona::Utc => "UTC".to_string(),
+            Zona::Local => "local".to_string(),
+            Zona::Fija(desplazamiento) => desplazamiento.to_string(),
+            Zona::Iana(zona) => zona.name().to_string(),
+        }
+    }
+
+    fn desplazamiento(&self, instante: &DateTime<Utc>) -> FixedOffset {
+        let ingenua = instante.naive_utc();
+        match self {
+            Zona::Utc => Utc.fix(),
+            Zona::Local => Local.offset_from_utc_datetime(&ingenua).fix(),
+            Zona::Fija(desplazamiento) => *desplazamiento,
+            Zona::Iana(zona) => zona.offset_from_utc_datetime(&ingenua).fix(),
+        }
+    }
+
+    // En los cambios de horario una hora local puede repetirse; se toma la
+    // primera. Las horas que no existen en la zona devuelven None.
+    fn instante_desde_local(&self, local: &NaiveDateTime) -> Option<DateTime<Utc>> {
+        match self {
+            Zona::Utc => Some(Utc.from_utc_datetime(local)),
+            Zona::Local => Local
+                .from_local_datetime(local)
+                .earliest()
+                .map(|f| f.to_utc()),
+            Zona::Fija(desplazamiento) => desplazamiento
+                .from_local_datetime(local)
+                .earliest()
+                .map(|f| f.to_utc()),
+            Zona::Iana(zona) => zona
+                .from_local_datetime(local)
+                .earliest()
+                .map(|f| f.to_utc()),
+        }
+    }
+}
+
+fn parsear_desplazamiento(texto: &str) -> Option<FixedOffset> {
+    let (signo, resto) = texto.split_at(1);
+    let digitos: String = resto.chars().filter(|c| *c != ':').collect();
+    if !digitos.chars().all(|c| c.is_ascii_digit()) {
+        return None;
+    }
+
+    let (horas, minutos) = match digitos.len() {
+        2 => (digitos.parse::<i32>().ok()?, 0),
+        4 => (
+            digitos[..2].parse::<i32>().ok()?,
+            digitos[2..].parse::<i32>().ok()?,
+        ),
+        _ => return None,
+    };
+
+    let segundos = horas * 3600 + minutos * 60;
+    if signo == "-" {
+        FixedOffset::west_opt(segundos)
+    } else {
+        FixedOffset::east_opt(segundos)
+    }
+}
+
+/// Instante en el tiempo junto con la zona en la que se presenta.
+#[derive(Debug, Clone, Copy)]
+pub struct FechaHora {
+    pub instante: DateTime<Utc>,
+    pub zona: Zona,
+}
+
+impl FechaHora {
+    pub fn nueva(instante: DateTime<Utc>, zona: Zona) -> Self {
+        FechaHora { instante, zona }
+    }
+
+    pub fn en_zona(&self) -> DateTime<FixedOffset> {
+        self.instante
+            .with_timezone(&self.zona.desplazamiento(&self.instante))
+    }
+
+    pub fn desplazar(&self, duracion: TimeDelta) -> Option<FechaHora> {
+        self.instante
+            .checked_add_signed(duracion)
+            .map(|instante| FechaHora::nueva(instante, self.zona))
+    }
+
+    /// Componentes accesibles como propiedades (`fecha.year`, `fecha.zone`, ...).
+    pub fn componente(&self, nombre: &str) -> Option<Valor> {
+        let local = self.en_zona();
+        let entero = |n: i64| Some(Valor::Entero(n));
+
+        match nombre {
+            "year" => entero(local.year() as i64),
+            "month" => entero(local.month() as i64),
+            "day" => entero(local.day() as i64),
+            "hour" => entero(local.hour() as i64),
+            "minute" => entero(local.minute() as i64),
+            "second" => entero(local.second() as i64),
+            "millisecond" => entero(local.timestamp_subsec_millis() as i64),
+            "nanosecond" => entero(local.timestamp_subsec_nanos() as i64),
+            "weekday" => entero(local.weekday().number_from_monday() as i64),
+            "yearday" => entero(local.ordinal() as i64),
+            "timestamp" => entero(self.instante.timestamp()),
+            "timestamp_ms" => entero(self.instante.timestamp_millis()),
+            "offset" => entero(local.offset().local_minus_utc() as i64),
+            "zone" => Some(Valor::Texto(self.zona.nombre())),
+            _ => None,
+        }
+    }
+}
+
+impl fmt::Display for FechaHora {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let texto = self.en_zona().to_rfc3339_opts(SecondsFormat::AutoSi, true);
+        write!(f, "{}", texto)
+    }
+}
+
+/// Componentes de una duración; todos son totales truncados salvo
+/// `total_seconds`, que conserva la fracción.
+pub fn componente_duracion(duracion: &TimeDelta, nombre: &str) -> Option<Valor> {
+    match nombre {
+        "days" => Some(Valor::Entero(duracion.num_days())),
+        "hours" => Some(Valor::Entero(duracion.num_hours())),
+        "minutes" => Some(Valor::Entero(duracion.num_minutes())),
+        "seconds" => Some(Valor::Entero(duracion.num_seconds())),
+        "millis" => Some(Valor::Entero(duracion.num_milliseconds())),
+        "micros" => duracion.num_microseconds().map(Valor::Entero),
+        "nanos" => duracion.num_nanoseconds().map(Valor::Entero),
+        "total_seconds" => Some(Valor::Flotante(segundos_flotantes(duracion))),
+        _ => None,
+    }
+}
+
+pub fn segundos_flotantes(duracion: &TimeDelta) -> f64 {
+    duracion.num_seconds() as f64 + duracion.subsec_nanos() as f64 / 1e9
+}
+
+pub fn duracion_desde_segundos(segundos: f64) -> Option<TimeDelta> {
+    let nanos = segundos * 1e9;
+    if !nanos.is_finite() || nanos.abs() >= i64::MAX as f64 {
+        return None;
+    }
+    Some(TimeDelta::nanoseconds(nanos.round() as i64))
+}
+
+fn quitar_ceros(texto: String) -> String {
+    if !texto.contains('.') {
+        return texto;
+    }
+    texto
+        .trim_end_matches('0')
+        .trim_end_matches('.')
+        .to_string()
+}
+
+/// Formato compacto de duraciones: "1h30m5.25s", "2m0s", "250ms".
+pub fn formatear_duracion(duracion: &TimeDelta) -> String {
+    if duracion.is_zero() {
+        return "0s".to_string();
+    }
+
+    let signo = if *duracion < TimeDelta::zero() {
+        "-"
+    } else {
+        ""
+    };
+    let absoluta = duracion.abs();
+    let segundos = absoluta.num_seconds();
+    let nanos = absoluta.subsec_nanos();
+
+    if segundos == 0 {
+        let milis = quitar_ceros(format!("{:.6}", nanos as f64 / 1e6));
+        return format!("{}{}ms", signo, milis);
+    }
+
+    let horas = segundos / 3600;
+    let minutos = (segundos % 3600) / 60;
+    let resto = quitar_ceros(format!("{}.{:09}", segundos % 60, nanos));
+
+    let mut texto = signo.to_string();
+    if horas > 0 {
+        texto.push_str(&format!("{}h", horas));
+    }
+    if horas > 0 || minutos > 0 {
+        texto.push_str(&format!("{}m", minutos));
+    }
+    texto.push_str(&format!("{}s", resto));
+    texto
+}
+
+fn registrar_funcion(
+    mapa: &mut IndexMap<String, Valor>,
+    nombre: &str,
+    funcion: fn(Vec<Valor>) -> Valor,
+) {
     mapa.insert(
-        "now".to_string(),
-        Valor::FuncionNativa("now".to_string(), now),
-    );
-    mapa.insert(
-        "timestamp".to_string(),
-        Valor::FuncionNativa("timestamp".to_string(), timestamp),
+        nombre.to_string(),
+        Valor::FuncionNativa(nombre.to_string(), funcion),
     );
+}
+
+pub fn crear_modulo() -> Valor {
+    let mut mapa = IndexMap::new();
+
+    registrar_funcion(&mut mapa, "now", now);
+    registrar_funcion(&mut mapa, "timestamp", timestamp);
+
+    registrar_funcion(&mut mapa, "current", actual);
+    registrar_funcion(&mut mapa, "date", fecha);
+    registrar_funcion(&mut mapa, "from_timestamp", desde_timestamp);
+    registrar_funcion(&mut mapa, "from_millis", desde_milisegundos);
+    registrar_funcion(&mut mapa, "parse_iso", parsear_iso);
+    registrar_funcion(&mut mapa, "strptime", strptime);
+    registrar_funcion(&mut mapa, "iso", iso);
+    registrar_funcion(&mut mapa, "strftime", strftime);
+    registrar_funcion(&mut mapa, "to_zone", a_zona);
+
+    registrar_funcion(&mut mapa, "millis", milisegundos);
+    registrar_funcion(&mut mapa, "seconds", segundos);
+    registrar_funcion(&mut mapa, "minutes", minutos);
+    registrar_funcion(&mut mapa, "hours", horas);
+    registrar_funcion(&mut mapa, "days", dias);
+    registrar_funcion(&mut mapa, "weeks", semanas);
+
+    registrar_funcion(&mut mapa, "monotonic", monotonico);
+    registrar_funcion(&mut mapa, "elapsed", transcurrido);
+    registrar_funcion(&mut mapa, "sleep", dormir);
 
     Valor::Diccionario(mapa)
 }
@@ -30,3 +287,483 @@ fn timestamp(_args: Vec<Valor>) -> Valor {
         Err(_) => Valor::Nulo,
     }
 }
+
+fn lanzar_fecha_invalida(mensaje: &str) -> Valor {
+    errores::lanzar_mensaje(mensaje, "InvalidData")
+}
+
+// La zona es opcional en casi todas las funciones; None significa que el
+// argumento no es texto y se usa UTC.
+fn zona_argumento(valor: Option<&Valor>) -> Result<Zona, Valor> {
+    match valor {
+        Some(Valor::Texto(texto)) => Zona::desde_texto(texto).ok_or_else(|| {
+            errores::lanzar_mensaje(
+                &format!("zona horaria desconocida '{}'", texto),
+                "InvalidInput",
+            )
+        }),
+        _ => Ok(Zona::Utc),
+    }
+}
+
+fn fecha_argumento(valor: Option<&Valor>, funcion: &str) -> Result<FechaHora, Valor> {
+    match valor {
+        Some(Valor::FechaHora(fecha)) => Ok(*fecha),
+        _ => Err(errores::lanzar_argumento_invalido(funcion, "una fecha")),
+    }
+}
+
+fn actual(argumentos: Vec<Valor>) -> Valor {
+    match zona_argumento(argumentos.first()) {
+        Ok(zona) => Valor::FechaHora(FechaHora::nueva(Utc::now(), zona)),
+        Err(error) => error,
+    }
+}
+
+// date(año, mes, día, [hora, minuto, segundo, milisegundo], [zona])
+fn fecha(argumentos: Vec<Valor>) -> Valor {
+    let componentes: Vec<i64> = argumentos
+        .iter()
+        .map_while(|valor| match valor {
+            Valor::Entero(n) => Some(*n),
+            _ => None,
+        })
+        .take(7)
+        .collect();
+
+    if componentes.len() < 3 {
+        return errores::lanzar_argumento_invalido("date", "año, mes y día enteros");
+    }
+
+    let zona = match zona_argumento(argumentos.get(componentes.len())) {
+        Ok(zona) => zona,
+        Err(error) => return error,
+    };
+
+    let parte = |i: usize| componentes.get(i).copied().unwrap_or(0);
+    let local = i32::try_from(parte(0)).ok().and_then(|año| {
+        NaiveDate::from_ymd_opt(año, parte(1) as u32, parte(2) as u32)?.and_hms_milli_opt(
+            parte(3) as u32,
+            parte(4) as u32,
+            parte(5) as u32,
+            parte(6) as u32,
+        )
+    });
+
+    match local.and_then(|local| zona.instante_desde_local(&local)) {
+        Some(instante) => Valor::FechaHora(FechaHora::nueva(instante, zona)),
+        None => lanzar_fecha_invalida(&format!("date: fecha inválida {:?}", componentes)),
+    }
+}
+
+fn desde_timestamp(argumentos: Vec<Valor>) -> Valor {
+    let Some(segundos) = argumentos.first().and_then(Valor::a_numero) else {
+        return errores::lanzar_argumento_invalido("from_timestamp", "segundos desde 1970");
+    };
+
+    let zona = match zona_argumento(argumentos.get(1)) {
+        Ok(zona) => zona,
+        Err(error) => return error,
+    };
+
+    let instante = duracion_desde_segundos(segundos)
+        .and_then(|duracion| DateTime::UNIX_EPOCH.checked_add_signed(duracion));
+    match instante {
+        Some(instante) => Valor::FechaHora(FechaHora::nueva(instante, zona)),
+        None => lanzar_fecha_invalida("from_timestamp: fuera de rango"),
+    }
+}
+
+fn desde_milisegundos(argumentos: Vec<Valor>) -> Valor {
+    let Some(Valor::Entero(milisegundos)) = argumentos.first() else {
+        return errores::lanzar_argumento_invalido("from_millis", "milisegundos desde 1970");
+    };
+
+    let zona = match zona_argumento(argumentos.get(1)) {
+        Ok(zona) => zona,
+        Err(error) => return error,
+    };
+
+    match DateTime::from_timestamp_millis(*milisegundos) {
+        Some(instante) => Valor::FechaHora(FechaHora::nueva(instante, zona)),
+        None => lanzar_fecha_invalida("from_millis: fuera de rango"),
+    }
+}
+
+const FORMATOS_ISO_SIN_ZONA: [&str; 4] = [
+    "%Y-%m-%dT%H:%M:%S%.f",
+    "%Y-%m-%d %H:%M:%S%.f",
+    "%Y-%m-%dT%H:%M",
+    "%Y-%m-%d %H:%M",
+];
+
+// Con desplazamiento explícito se conserva el de la cadena, salvo que se pida
+// otra zona; sin él, la hora se interpreta en la zona indicada (UTC por defecto).
+fn parsear_iso(argumentos: Vec<Valor>) -> Valor {
+    let Some(Valor::Texto(texto)) = argumentos.first() else {
+        return errores::lanzar_argumento_invalido("parse_iso", "un texto ISO 8601");
+    };
+
+    let zona_pedida = match argumentos.get(1) {
+        Some(valor) => match zona_argumento(Some(valor)) {
+            Ok(zona) => Some(zona),
+            Err(error) => return error,
+        },
+        None => None,
+    };
+
+    if let Ok(con_zona) = DateTime::parse_from_rfc3339(texto) {
+        let zona = zona_pedida.unwrap_or_else(|| zona_de_desplazamiento(texto, *con_zona.offset()));
+        return Valor::FechaHora(FechaHora::nueva(con_zona.to_utc(), zona));
+    }
+
+    let local = FORMATOS_ISO_SIN_ZONA
+        .iter()
+        .find_map(|formato| NaiveDateTime::parse_from_str(texto, formato).ok())
+        .or_else(|| {
+            NaiveDate::parse_from_str(texto, "%Y-%m-%d")
+                .ok()
+                .and_then(|fecha| fecha.and_hms_opt(0, 0, 0))
+        });
+
+    construir_desde_local(local, zona_pedida.unwrap_or(Zona::Utc), || {
+        format!("parse_iso: fecha ISO 8601 inválida '{}'", texto)
+    })
+}
+
+fn zona_de_desplazamiento(texto: &str, desplazamiento: FixedOffset) -> Zona {
+    if texto.ends_with('Z') || texto.ends_with('z') {
+        Zona::Utc
+    } else {
+        Zona::Fija(desplazamiento)
+    }
+}
+
+fn construir_desde_local(
+    local: Option<NaiveDateTime>,
+    zona: Zona,
+    mensaje: impl FnOnce() -> String,
+) -> Valor {
+    match local.and_then(|local| zona.instante_desde_local(&local)) {
+        Some(instante) => Valor::FechaHora(FechaHora::nueva(instante, zona)),
+        None => lanzar_fecha_invalida(&mensaje()),
+    }
+}
+
+fn formato_valido(formato: &str) -> bool {
+    !StrftimeItems::new(formato).any(|item| matches!(item, Item::Error))
+}
+
+// strptime(texto, formato, [zona]): si el formato incluye %z se usa ese
+// desplazamiento; si no, la hora es local a la zona indicada.
+fn strptime(argumentos: Vec<Valor>) -> Valor {
+    let (Some(Valor::Texto(texto)), Some(Valor::Texto(formato))) =
+        (argumentos.first(), argumentos.get(1))
+    else {
+        return errores::lanzar_argumento_invalido("strptime", "un texto y un formato");
+    };
+
+    if !formato_valido(formato) {
+        return errores::lanzar_mensaje(
+            &format!("strptime: formato inválido '{}'", formato),
+            "InvalidInput",
+        );
+    }
+
+    let zona_pedida = match argumentos.get(2) {
+        Some(valor) => match zona_argumento(Some(valor)) {
+            Ok(zona) => Some(zona),
+            Err(error) => return error,
+        },
+        None => None,
+    };
+
+    if let Ok(con_zona) = DateTime::parse_from_str(texto, formato) {
+        let zona = zona_pedida.unwrap_or(Zona::Fija(*con_zona.offset()));
+        return Valor::FechaHora(FechaHora::nueva(con_zona.to_utc(), zona));
+    }
+
+    let local = NaiveDateTime::parse_from_str(texto, formato).or_else(|error| {
+        NaiveDate::parse_from_str(texto, formato)
+            .map(|fecha| fecha.and_hms_opt(0, 0, 0).unwrap_or_default())
+            .map_err(|_| error)
+    });
+
+    let detalle = match &local {
+        Err(error) => error.to_string(),
+        Ok(_) => "hora inexistente en la zona".to_string(),
+    };
+
+    construir_desde_local(local.ok(), zona_pedida.unwrap_or(Zona::Utc), || {
+        format!(
+            "strptime: '{}' no coincide con '{}': {}",
+            texto, formato, detalle
+        )
+    })
+}
+
+fn iso(argumentos: Vec<Valor>) -> Valor {
+    match fecha_argumento(argumentos.first(), "iso") {
+        Ok(fecha) => Valor::Texto(fecha.to_string()),
+        Err(error) => error,
+    }
+}
+
+fn strftime(argumentos: Vec<Valor>) -> Valor {
+    let fecha = match fecha_argumento(argumentos.first(), "strftime") {
+        Ok(fecha) => fecha,
+        Err(error) => return error,
+    };
+
+    let Some(Valor::Texto(formato)) = argumentos.get(1) else {
+        return errores::lanzar_argumento_invalido("strftime", "una fecha y un formato");
+    };
+
+    if !formato_valido(formato) {
+        return errores::lanzar_mensaje(
+            &format!("strftime: formato inválido '{}'", formato),
+            "InvalidInput",
+        );
+    }
+
+    Valor::Texto(fecha.en_zona().format(formato).to_string())
+}
+
+fn a_zona(argumentos: Vec<Valor>) -> Valor {
+    let fecha = match fecha_argumento(argumentos.first(), "to_zone") {
+        Ok(fecha) => fecha,
+        Err(error) => return error,
+    };
+
+    let zona = match argumentos.get(1) {
+        Some(Valor::Entero(segundos)) => i32::try_from(*segundos)
+            .ok()
+            .and_then(FixedOffset::east_opt)
+            .map(Zona::Fija),
+        Some(Valor::Texto(texto)) => Zona::desde_texto(texto),
+        _ => return errores::lanzar_argumento_invalido("to_zone", "una fecha y una zona"),
+    };
+
+    match zona {
+        Some(zona) => Valor::FechaHora(FechaHora::nueva(fecha.instante, zona)),
+        None => errores::lanzar_mensaje(
+            &format!("to_zone: zona horaria desconocida '{}'", argumentos[1]),
+            "InvalidInput",
+        ),
+    }
+}
+
+fn crear_duracion(argumentos: &[Valor], funcion: &str, segundos_por_unidad: f64) -> Valor {
+    let Some(cantidad) = argumentos.first().and_then(Valor::a_numero) else {
+        return errores::lanzar_argumento_invalido(funcion, "un número");
+    };
+
+    match duracion_desde_segundos(cantidad * segundos_por_unidad) {
+        Some(duracion) => Valor::Duracion(duracion),
+        None => lanzar_fecha_invalida(&format!("{}: duración fuera de rango", funcion)),
+    }
+}
+
+fn milisegundos(argumentos: Vec<Valor>) -> Valor {
+    crear_duracion(&argumentos, "millis", 0.001)
+}
+
+fn segundos(argumentos: Vec<Valor>) -> Valor {
+    crear_duracion(&argumentos, "seconds", 1.0)
+}
+
+fn minutos(argumentos: Vec<Valor>) -> Valor {
+    crear_duracion(&argumentos, "minutes", 60.0)
+}
+
+fn horas(argumentos: Vec<Valor>) -> Valor {
+    crear_duracion(&argumentos, "hours", 3600.0)
+}
+
+fn dias(argumentos: Vec<Valor>) -> Valor {
+    crear_duracion(&argumentos, "days", 86400.0)
+}
+
+fn semanas(argumentos: Vec<Valor>) -> Valor {
+    crear_duracion(&argumentos, "weeks", 604800.0)
+}
+
+fn lectura_monotonica() -> TimeDelta {
+    TimeDelta::from_std(INICIO_MONOTONICO.elapsed()).unwrap_or(TimeDelta::MAX)
+}
+
+// monotonic() no se ve afectado por cambios del reloj del sistema; solo tiene
+// sentido restar dos lecturas entre sí.
+fn monotonico(_argumentos: Vec<Valor>) -> Valor {
+    Valor::Duracion(lectura_monotonica())
+}
+
+fn transcurrido(argumentos: Vec<Valor>) -> Valor {
+    match argumentos.first() {
+        Some(Valor::Duracion(inicio)) => Valor::Duracion(lectura_monotonica() - *inicio),
+        _ => errores::lanzar_argumento_invalido("elapsed", "una lectura de monotonic()"),
+    }
+}
+
+// sleep(duración | milisegundos)
+// Devuelve una promesa que se cumple (con null) pasado el tiempo, para
+// esperarla con `awa` sin bloquear el hilo del runtime.
+fn dormir(argumentos: Vec<Valor>) -> Valor {
+    let espera = match argumentos.first() {
+        Some(Valor::Duracion(duracion)) => duracion.to_std().ok(),
+        Some(valor) => valor
+            .a_numero()
+            .and_then(|milisegundos| Duration::try_from_secs_f64(milisegundos / 1000.0).ok()),
+        None => None,
+    };
+
+    let Some(espera) = espera else {
+        return errores::lanzar_argumento_invalido("sleep", "una duración no negativa");
+    };
+
+    let tarea = tokio::spawn(async move {
+        tokio::time::sleep(espera).await;
+        Valor::Nulo
+    });
+    Valor::Promesa(SharedPromesa(Arc::new(Mutex::new(Some(tarea)))))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn texto(valor: &str) -> Valor {
+        Valor::Texto(valor.to_string())
+    }
+
+    fn fecha_de(valor: Valor) -> FechaHora {
+        match valor {
+            Valor::FechaHora(fecha) => fecha,
+            otro => panic!("se esperaba una fecha, no {}", otro),
+        }
+    }
+
+    #[test]
+    fn test_sumar_duracion_cruza_el_cambio_de_hora() {
+        let antes = fecha_de(fecha(vec![
+            Valor::Entero(2024),
+            Valor::Entero(3),
+            Valor::Entero(30),
+            Valor::Entero(12),
+            texto("Europe/Madrid"),
+        ]));
+        assert_eq!(antes.to_string(), "2024-03-30T12:00:00+01:00");
+
+        // Un día son 24 horas exactas: tras el cambio el reloj marca una más.
+        let despues = antes.desplazar(TimeDelta::days(1)).unwrap();
+        assert_eq!(despues.to_string(), "2024-03-31T13:00:00+02:00");
+        assert_eq!(despues.componente("offset").unwrap().to_string(), "7200");
+        assert_eq!(despues.zona.nombre(), "Europe/Madrid");
+    }
+
+    #[test]
+    fn test_hora_inexistente_en_la_zona_es_un_error() {
+        let resultado = fecha(vec![
+            Valor::Entero(2024),
+            Valor::Entero(3),
+            Valor::Entero(31),
+            Valor::Entero(2),
+            Valor::Entero(30),
+            texto("Europe/Madrid"),
+        ]);
+        assert_eq!(resultado.to_string(), "null");
+        assert!(errores::tomar_error_pendiente().is_some());
+    }
+
+    #[test]
+    fn test_parse_iso_y_formato() {
+        let con_zona = fecha_de(parsear_iso(vec![texto("2024-05-01T10:30:00.250+02:00")]));
+        assert_eq!(con_zona.to_string(), "2024-05-01T10:30:00.250+02:00");
+        assert_eq!(con_zona.instante.timestamp_millis(), 1_714_552_200_250);
+
+        let utc = fecha_de(parsear_iso(vec![texto("2024-05-01T08:30:00Z")]));
+        assert_eq!(utc.to_string(), "2024-05-01T08:30:00Z");
+
+        let local = fecha_de(parsear_iso(vec![
+            texto("2024-05-01"),
+            texto("America/New_York"),
+        ]));
+        assert_eq!(local.to_string(), "2024-05-01T00:00:00-04:00");
+
+        assert_eq!(
+            iso(vec![Valor::FechaHora(con_zona)]).to_string(),
+            "2024-05-01T10:30:00.250+02:00"
+        );
+        assert_eq!(
+            strftime(vec![Valor::FechaHora(con_zona), texto("%d/%m/%Y %H:%M")]).to_string(),
+            "01/05/2024 10:30"
+        );
+    }
+
+    #[test]
+    fn test_strptime_con_y_sin_desplazamiento() {
+        let sin_zona = fecha_de(strptime(vec![
+            texto("01/05/2024 10:30"),
+            texto("%d/%m/%Y %H:%M"),
+            texto("Europe/Madrid"),
+        ]));
+        assert_eq!(sin_zona.to_string(), "2024-05-01T10:30:00+02:00");
+
+        let con_zona = fecha_de(strptime(vec![
+            texto("2024-05-01 10:30 -0500"),
+            texto("%Y-%m-%d %H:%M %z"),
+        ]));
+        assert_eq!(con_zona.to_string(), "2024-05-01T10:30:00-05:00");
+
+        let invalida = strptime(vec![texto("mayo"), texto("%d/%m/%Y")]);
+        assert_eq!(invalida.to_string(), "null");
+        assert!(errores::tomar_error_pendiente().is_some());
+    }
+
+    #[test]
+    fn test_formato_y_calculo_de_duraciones() {
+        let hora_y_media = TimeDelta::minutes(90);
+        assert_eq!(formatear_duracion(&hora_y_media), "1h30m0s");
+        assert_eq!(formatear_duracion(&TimeDelta::milliseconds(250)), "250ms");
+        assert_eq!(
+            formatear_duracion(&TimeDelta::milliseconds(-5250)),
+            "-5.25s"
+        );
+        assert_eq!(formatear_duracion(&TimeDelta::zero()), "0s");
+
+        let mitad = duracion_desde_segundos(segundos_flotantes(&hora_y_media) * 0.5).unwrap();
+        assert_eq!(formatear_duracion(&mitad), "45m0s");
+        assert_eq!(
+            componente_duracion(&hora_y_media, "hours")
+                .unwrap()
+                .to_string(),
+            "1"
+        );
+        assert_eq!(
+            componente_duracion(&mitad, "total_seconds")
+                .unwrap()
+                .to_string(),
+            "2700"
+        );
+        assert!(duracion_desde_segundos(f64::INFINITY).is_none());
+        assert!(duracion_desde_segundos(1e12).is_none());
+    }
+
+    #[test]
+    fn test_duracion_fuera_de_rango_es_un_error() {
+        assert_eq!(dias(vec![Valor::Flotante(1e300)]).to_string(), "null");
+        assert!(errores::tomar_error_pendiente().is_some());
+    }
+
+    #[tokio::test]
+    async fn test_sleep_devuelve_una_promesa() {
+        let Valor::Promesa(SharedPromesa(tarea)) = dormir(vec![Valor::Entero(5)]) else {
+            panic!("sleep debería devolver una promesa");
+        };
+        let tarea = tarea.lock().unwrap().take().unwrap();
+        assert_eq!(tarea.await.unwrap().to_string(), "null");
+
+        assert_eq!(dormir(vec![Valor::Entero(-1)]).to_string(), "null");
+        assert!(errores::tomar_error_pendiente().is_some());
+    }
+}
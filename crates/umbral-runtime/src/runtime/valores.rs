@@ -1,4 +1,6 @@
-use std::collections::HashMap;
+use crate::runtime::entorno::EntornoCompartido;
+use crate::runtime::stdlib::time::{self, FechaHora};
+use indexmap::IndexMap;
 use std::fmt;
 use std::future::Future;
 use std::pin::Pin;
@@ -10,15 +12,18 @@ pub type FutureValor = Pin<Box<dyn Future<Output = Valor> + Send>>;
 pub enum Valor {
     Entero(i64),
     Flotante(f64),
+    Decimal(rust_decimal::Decimal),
     Booleano(bool),
     Texto(String),
     Lista(Vec<Valor>),
-    Diccionario(HashMap<String, Valor>),
+    Diccionario(IndexMap<String, Valor>),
     Objeto(Instancia),
     Funcion(Funcion),
     FuncionNativa(String, NativeFn),
     Promesa(SharedPromesa),
     Clase(String),
+    FechaHora(FechaHora),
+    Duracion(chrono::TimeDelta),
     Nulo,
 }
 
@@ -40,6 +45,7 @@ impl Valor {
             Valor::Nulo => false,
             Valor::Entero(i) => *i != 0,
             Valor::Flotante(f) => *f != 0.0,
+            Valor::Decimal(d) => !d.is_zero(),
             Valor::Texto(s) => !s.is_empty(),
             Valor::Lista(v) => !v.is_empty(),
             _ => true,
@@ -61,6 +67,7 @@ impl fmt::Display for Valor {
         match self {
             Valor::Entero(i) => write!(f, "{}", i),
             Valor::Flotante(fl) => write!(f, "{}", fl),
+            Valor::Decimal(decimal) => write!(f, "{}", decimal),
             Valor::Booleano(b) => write!(f, "{}", b),
             Valor::Texto(s) => write!(f, "{}", s),
             Valor::Nulo => write!(f, "null"),
@@ -91,6 +98,8 @@ impl fmt::Display for Valor {
             Valor::FuncionNativa(nombre, _) => write!(f, "<función nativa {}>", nombre),
             Valor::Promesa(_) => write!(f, "<promesa>"),
             Valor::Clase(nombre) => write!(f, "<clase {}>", nombre),
+            Valor::FechaHora(fecha) => write!(f, "{}", fecha),
+            Valor::Duracion(duracion) => write!(f, "{}", time::formatear_duracion(duracion)),
         }
     }
 }
@@ -98,7 +107,7 @@ impl fmt::Display for Valor {
 #[derive(Debug, Clone)]
 pub struct Instancia {
     pub clase: String,
-    pub propiedades: Arc<Mutex<HashMap<String, Valor>>>,
+    pub propiedades: Arc<Mutex<IndexMap<String, Valor>>>,
 }
 
 impl fmt::Display for Instancia {
@@ -123,6 +132,8 @@ pub struct Funcion {
     pub parametros: Vec<String>,
     pub cuerpo: Vec<umbral_parser::ast::Sentencia>,
     pub es_async: bool,
+    /// Entorno del módulo que exportó la función, si viene de otro archivo.
+    pub contexto: Option<EntornoCompartido>,
 }
 
 impl Funcion {
@@ -137,6 +148,7 @@ impl Funcion {
             parametros,
             cuerpo,
             es_async,
+            contexto: None,
         }
     }
 }